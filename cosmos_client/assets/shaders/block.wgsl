@@ -0,0 +1,168 @@
+#import bevy_pbr::mesh_view_bindings
+#import bevy_pbr::mesh_bindings
+#import bevy_pbr::mesh_functions
+
+#import bevy_pbr::pbr_types
+#import bevy_pbr::utils
+#import bevy_pbr::clustered_forward
+#import bevy_pbr::lighting
+#import bevy_pbr::pbr_ambient
+#import bevy_pbr::shadows
+#import bevy_pbr::fog
+#import bevy_pbr::pbr_functions
+
+@group(1) @binding(0)
+var atlas_texture: texture_2d<f32>;
+@group(1) @binding(1)
+var atlas_sampler: sampler;
+
+// How bright blocks that no star light reaches still are, so the insides of structures aren't pitch black
+const MIN_STAR_LIGHT: f32 = 0.05;
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    // min x, min y, max x, max y of this block's texture in the atlas
+    @location(3) atlas_rect: vec4<f32>,
+    // rgb is the block light, a is the star light
+    @location(4) light: vec4<f32>,
+    @location(5) ambient_occlusion: f32,
+    // frame count, seconds per frame, uv distance between frames, flags (1 = interpolated, 2 = emissive)
+    @location(6) animation: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec4<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) atlas_rect: vec4<f32>,
+    @location(4) light: vec4<f32>,
+    @location(5) ambient_occlusion: f32,
+    @location(6) animation: vec4<f32>,
+};
+
+const ANIMATION_INTERPOLATE: u32 = 1u;
+const ANIMATION_EMISSIVE: u32 = 2u;
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+    out.world_position = mesh_position_local_to_world(mesh.model, vec4<f32>(vertex.position, 1.0));
+    out.clip_position = mesh_position_world_to_clip(out.world_position);
+    out.world_normal = mesh_normal_local_to_world(vertex.normal);
+    out.uv = vertex.uv;
+    out.atlas_rect = vertex.atlas_rect;
+    out.light = vertex.light;
+    out.ambient_occlusion = vertex.ambient_occlusion;
+    out.animation = vertex.animation;
+
+    return out;
+}
+
+@fragment
+fn fragment(
+    @builtin(front_facing) is_front: bool,
+    in: VertexOutput,
+) -> @location(0) vec4<f32> {
+    // Merged faces have uvs outside of [0, 1], so the texture is repeated across every block they cover
+    let rect_size = in.atlas_rect.zw - in.atlas_rect.xy;
+    let atlas_uv = in.atlas_rect.xy + fract(in.uv) * rect_size;
+
+    // The gradients are based off the unrepeated uvs, otherwise the seams between blocks would use the smallest mip level
+    let ddx = dpdx(in.uv) * rect_size;
+    let ddy = dpdy(in.uv) * rect_size;
+
+    // Every frame is stored below the previous one in the atlas, followed by the emissive mask of every frame
+    let frames = max(in.animation.x, 1.0);
+    let frame_stride = in.animation.z;
+    let flags = u32(in.animation.w + 0.5);
+
+    var frame = 0.0;
+    var next_frame = 0.0;
+    var frame_progress = 0.0;
+
+    if (frames > 1.0 && in.animation.y > 0.0) {
+        let time = globals.time / in.animation.y;
+
+        frame = floor(time % frames);
+        next_frame = (frame + 1.0) % frames;
+
+        if ((flags & ANIMATION_INTERPOLATE) != 0u) {
+            frame_progress = fract(time);
+        }
+    }
+
+    let frame_offset = vec2<f32>(0.0, frame * frame_stride);
+    let next_frame_offset = vec2<f32>(0.0, next_frame * frame_stride);
+
+    let color = mix(
+        textureSampleGrad(atlas_texture, atlas_sampler, atlas_uv + frame_offset, ddx, ddy),
+        textureSampleGrad(atlas_texture, atlas_sampler, atlas_uv + next_frame_offset, ddx, ddy),
+        frame_progress
+    );
+
+    var emission = 0.0;
+
+    if ((flags & ANIMATION_EMISSIVE) != 0u) {
+        let mask_offset = vec2<f32>(0.0, frames * frame_stride);
+
+        let mask = mix(
+            textureSampleGrad(atlas_texture, atlas_sampler, atlas_uv + mask_offset + frame_offset, ddx, ddy),
+            textureSampleGrad(atlas_texture, atlas_sampler, atlas_uv + mask_offset + next_frame_offset, ddx, ddy),
+            frame_progress
+        );
+
+        // Brighter parts of the mask glow more
+        emission = dot(mask.rgb, vec3<f32>(0.299, 0.587, 0.114)) * mask.a;
+    }
+
+    if color.a < 0.5 {
+        discard;
+    }
+
+#ifdef BLOCK_UNLIT
+    var output_color = vec4<f32>(color.rgb, 1.0);
+#else
+#ifdef BLOCK_DOUBLE_SIDED
+    let double_sided = true;
+#else
+    let double_sided = false;
+#endif
+
+    var pbr_input: PbrInput = pbr_input_new();
+
+    pbr_input.material.base_color = vec4<f32>(color.rgb, 1.0);
+    pbr_input.material.metallic = 0.0;
+    pbr_input.material.reflectance = 0.0;
+
+    pbr_input.frag_coord = in.clip_position;
+    pbr_input.world_position = in.world_position;
+    pbr_input.world_normal = prepare_world_normal(in.world_normal, double_sided, is_front);
+    pbr_input.is_orthographic = view.projection[3].w == 1.0;
+    pbr_input.N = normalize(pbr_input.world_normal);
+    pbr_input.V = calculate_view(in.world_position, pbr_input.is_orthographic);
+
+    var output_color = pbr(pbr_input);
+
+    // Star light only reaches what it isn't blocked from, and block light lights up everything around it equally
+    let star_light = mix(MIN_STAR_LIGHT, 1.0, in.light.a);
+    output_color = vec4<f32>(output_color.rgb * star_light + color.rgb * in.light.rgb, output_color.a);
+    output_color = vec4<f32>(output_color.rgb * in.ambient_occlusion, output_color.a);
+
+    // Glowing parts of a texture are shown at full brightness no matter how much light reaches them
+    output_color = vec4<f32>(mix(output_color.rgb, color.rgb, emission), output_color.a);
+#endif
+
+    if (fog.mode != FOG_MODE_OFF) {
+        output_color = apply_fog(output_color, in.world_position.xyz, view.world_position.xyz);
+    }
+
+#ifdef TONEMAP_IN_SHADER
+    output_color = tone_mapping(output_color);
+#endif
+
+    return output_color;
+}
@@ -11,14 +11,19 @@ var base_color_texture: texture_cube<f32>;
 @group(1) @binding(1)
 var base_color_sampler: sampler;
 
+// The alpha is how much of the stars this hides
+@group(1) @binding(2)
+var<uniform> sky_color: vec4<f32>;
+
 @fragment
 fn fragment(
     #import bevy_pbr::mesh_vertex_output
 ) -> @location(0) vec4<f32> {
     let fragment_position_view_lh = world_position.xyz * vec3<f32>(1.0, 1.0, -1.0);
-    return textureSample(
+    let stars = textureSample(
         base_color_texture,
         base_color_sampler,
         fragment_position_view_lh
     );
+    return vec4<f32>(mix(stars.rgb, sky_color.rgb, sky_color.a), stars.a);
 }
\ No newline at end of file
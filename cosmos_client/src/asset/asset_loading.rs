@@ -1,13 +1,32 @@
 //! Handles the loading of all texture assets.
 //!
 //! This also combines the textures into one big atlas.
-
-use std::fs;
+//!
+//! A block's json file can describe how the textures it uses are animated & which parts of them glow:
+//!
+//! ```json
+//! "texture_metadata": {
+//!     "thruster_back": {
+//!         "frame_time": 0.15,
+//!         "interpolate": true,
+//!         "emissive": "thruster_back_emissive"
+//!     }
+//! }
+//! ```
+//!
+//! - Animated textures are a vertical strip of square frames, each shown for `frame_time` seconds.
+//! - If `interpolate` is true, each frame fades into the next one.
+//! - `emissive` is a mask texture (which can also be a strip of frames) where brighter pixels glow more.
+//!
+//! The frames & emissive masks are packed next to each other in the atlas, and the `BlockMaterial` picks the frame to show.
 
 use bevy::{
     prelude::*,
-    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
-    utils::HashMap,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::{CompressedImageFormats, ImageType},
+    },
+    utils::{HashMap, HashSet},
 };
 use cosmos_core::{
     block::{Block, BlockFace},
@@ -18,26 +37,54 @@ use serde::{Deserialize, Serialize};
 
 use crate::state::game_state::GameState;
 
-#[derive(Debug)]
-enum AtlasName {
-    Main,
-}
+use super::resource_packs::{monitor_resource_packs, ResourcePacks, ResourcePacksChangedEvent};
 
-#[derive(Resource, Debug)]
-struct LoadingAsset {
-    atlas_name: AtlasName,
-    handles: Vec<Handle<Image>>,
-}
+/// The folder every block texture is in
+const BLOCK_TEXTURES_FOLDER: &str = "images/blocks";
 
-struct AssetsDoneLoadingEvent;
+/// How many pixels each texture is extended by in the atlas, which stops neighboring textures from bleeding into it
+const PADDING: u32 = 2;
 
-#[derive(Resource, Debug)]
-struct AssetsLoadingID(usize);
+/// The animation data of a texture that doesn't change, see [`MainAtlas::animation_for_index`]
+pub const NO_ANIMATION: [f32; 4] = [1.0, 0.0, 0.0, 0.0];
 
-#[derive(Resource, Debug)]
-struct AssetsLoading(Vec<LoadingAsset>);
+#[derive(Reflect, FromReflect, Debug, Clone, Copy, PartialEq)]
+/// How a texture in the atlas changes over time
+pub struct TextureAnimation {
+    /// How many frames this texture has
+    pub frames: u32,
+    /// How many seconds each frame is shown for
+    pub frame_time: f32,
+    /// If true, each frame fades into the next one
+    pub interpolate: bool,
+    /// If true, an emissive mask for each frame is stored right after the frames
+    pub emissive: bool,
+}
+
+impl Default for TextureAnimation {
+    /// A texture that never changes & doesn't glow
+    fn default() -> Self {
+        Self {
+            frames: 1,
+            frame_time: 0.0,
+            interpolate: false,
+            emissive: false,
+        }
+    }
+}
 
-#[derive(Resource, Reflect, FromReflect, Debug)]
+impl TextureAnimation {
+    /// How many frames this texture takes up in the atlas, including its emissive mask
+    fn atlas_frames(&self) -> u32 {
+        if self.emissive {
+            self.frames * 2
+        } else {
+            self.frames
+        }
+    }
+}
+
+#[derive(Resource, Reflect, FromReflect, Debug, Clone)]
 /// This stores the texture atlas for all blocks in the game.
 ///
 /// Eventually this will be redone to allow for multiple atlases, but for now this works fine.
@@ -52,6 +99,10 @@ pub struct MainAtlas {
     /// Calculate that index with the `Registry<BlockTextureIndex>`.
     pub atlas: TextureAtlas,
 
+    /// The atlas index of every texture, by its file name without the extension
+    texture_indices: HashMap<String, usize>,
+    /// The textures that are animated or glow, by their atlas index
+    animations: HashMap<usize, TextureAnimation>,
     padding: u32,
 }
 
@@ -59,6 +110,8 @@ impl MainAtlas {
     #[inline]
     /// Returns the UV coordinates for the texture atlas given the block's index
     ///
+    /// For animated textures, these are the UV coordinates of the first frame.
+    ///
     /// Get the block's index from `Registry<BlockTextureIndex>`.
     pub fn uvs_for_index(&self, index: usize) -> Rect {
         let rect = self.atlas.textures[index];
@@ -70,50 +123,349 @@ impl MainAtlas {
             rect.min.x / self.atlas.size.x + padding_x,
             rect.min.y / self.atlas.size.y + padding_y,
             rect.max.x / self.atlas.size.x - padding_x,
-            rect.max.y / self.atlas.size.y - padding_y,
+            (rect.min.y + self.frame_height(index)) / self.atlas.size.y - padding_y,
         )
     }
+
+    /// The height (in pixels) of one frame of this texture, including its padding
+    fn frame_height(&self, index: usize) -> f32 {
+        let height = self.atlas.textures[index].height();
+
+        self.animations
+            .get(&index)
+            .map(|animation| height / animation.atlas_frames() as f32)
+            .unwrap_or(height)
+    }
+
+    /// Returns the animation data the `BlockMaterial` uses for the texture at this index:
+    ///
+    /// (frame count, seconds per frame, distance between frames in uv coordinates, flags)
+    ///
+    /// where the flags are 1 if the frames are interpolated + 2 if the frames are followed by emissive masks.
+    pub fn animation_for_index(&self, index: usize) -> [f32; 4] {
+        let Some(animation) = self.animations.get(&index) else {
+            return NO_ANIMATION;
+        };
+
+        let mut flags = 0;
+        if animation.interpolate {
+            flags |= 1;
+        }
+        if animation.emissive {
+            flags |= 2;
+        }
+
+        [
+            animation.frames as f32,
+            animation.frame_time,
+            self.frame_height(index) / self.atlas.size.y,
+            flags as f32,
+        ]
+    }
+
+    #[inline]
+    /// Returns the atlas index of the block texture with this name, such as `grass_top`
+    pub fn texture_index(&self, texture_name: &str) -> Option<usize> {
+        self.texture_indices.get(texture_name).copied()
+    }
 }
 
-#[derive(Resource, Reflect, FromReflect, Debug)]
-/// This contains the material for illuminated blocks.
-pub struct IlluminatedMaterial {
-    /// The material for unlit blocks
-    pub material: Handle<StandardMaterial>,
+fn atlas_material(texture: Handle<Image>, unlit: bool) -> StandardMaterial {
+    StandardMaterial {
+        base_color_texture: Some(texture),
+        alpha_mode: AlphaMode::Mask(0.5),
+        unlit,
+        metallic: 0.0,
+        reflectance: 0.0,
+
+        ..default()
+    }
 }
 
-fn setup(
-    mut commands: Commands,
-    server: Res<AssetServer>,
-    mut loading: ResMut<AssetsLoading>,
-    mut loader: ResMut<LoadingManager>,
-    mut start_writer: EventWriter<AddLoadingEvent>,
-) {
-    loading.0.push(LoadingAsset {
-        handles: server
-            .load_folder("images/blocks/")
-            .expect("error loading blocks textures")
-            .into_iter()
-            .map(|x| x.typed::<Image>())
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+/// How a texture is animated & which parts of it glow, as described in a block's json file
+struct TextureMetadata {
+    frame_time: f32,
+    interpolate: bool,
+    emissive: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+/// The parts of a block's json file the atlas cares about
+struct BlockTextureMetadata {
+    #[serde(default)]
+    texture_metadata: HashMap<String, TextureMetadata>,
+}
+
+/// Reads the metadata of every texture from every block's json file, by texture name
+///
+/// Files that can't be read are skipped, and added to `errors`.
+fn load_texture_metadata(
+    packs: &ResourcePacks,
+    errors: &mut Vec<String>,
+) -> HashMap<String, TextureMetadata> {
+    let mut metadata = HashMap::new();
+
+    for file_name in packs.files_in("blocks") {
+        if !file_name.ends_with(".json") {
+            continue;
+        }
+
+        let json_path = format!("blocks/{file_name}");
+
+        let Some(block_info) = packs.read(&json_path) else {
+            continue;
+        };
+
+        match serde_json::from_slice::<BlockTextureMetadata>(&block_info) {
+            Ok(block_metadata) => metadata.extend(block_metadata.texture_metadata),
+            Err(e) => errors.push(format!("Error reading json data in {json_path} - {e}")),
+        }
+    }
+
+    metadata
+}
+
+fn read_image(packs: &ResourcePacks, path: &str) -> Option<Image> {
+    let image = packs.read(path).and_then(|data| {
+        Image::from_buffer(
+            &data,
+            ImageType::Extension("png"),
+            CompressedImageFormats::NONE,
+            true,
+        )
+        .ok()
+    });
+
+    if image.is_none() {
+        warn!("{path} is not a valid png image.");
+    }
+
+    image
+}
+
+/// Splits a vertical strip of square frames into each frame.
+///
+/// Images that aren't a strip of square frames are treated as a single frame.
+fn split_frames(image: &Image) -> Vec<Image> {
+    let width = image.size().x as u32;
+    let height = image.size().y as u32;
+
+    if width == 0 || height <= width || height % width != 0 {
+        return vec![image.clone()];
+    }
+
+    let frame_bytes = (width * width * 4) as usize;
+
+    image
+        .data
+        .chunks_exact(frame_bytes)
+        .map(|frame| {
+            Image::new(
+                Extent3d {
+                    width,
+                    height: width,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                frame.to_vec(),
+                TextureFormat::Rgba8UnormSrgb,
+            )
+        })
+        .collect()
+}
+
+/// Puts images of the same width on top of each other
+fn stack_images(frames: &[Image]) -> Image {
+    let width = frames[0].size().x as u32;
+    let height = frames.iter().map(|frame| frame.size().y as u32).sum();
+
+    Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        frames
+            .iter()
+            .flat_map(|frame| frame.data.iter().copied())
             .collect(),
-        atlas_name: AtlasName::Main,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+/// Creates the image that is put into the atlas for a texture: every frame (padded separately so they don't bleed into each other)
+/// followed by the emissive mask of every frame.
+fn animated_atlas_image(
+    packs: &ResourcePacks,
+    texture_name: &str,
+    image: &Image,
+    metadata: &TextureMetadata,
+) -> (Image, TextureAnimation) {
+    let texture_frames = split_frames(image);
+
+    let mask_frames = metadata.emissive.as_ref().and_then(|mask_name| {
+        read_image(packs, &format!("{BLOCK_TEXTURES_FOLDER}/{mask_name}.png"))
+            .map(|mask| split_frames(&mask))
     });
 
-    commands.insert_resource(AssetsLoadingID(loader.register_loader(&mut start_writer)));
+    let mut frames = texture_frames.len();
+
+    if let Some(mask_frames) = &mask_frames {
+        if mask_frames[0].size() != texture_frames[0].size() {
+            warn!("The emissive mask of {texture_name} is a different size than its texture.");
+            return (expand_image(image, PADDING), TextureAnimation::default());
+        }
+
+        // A static texture can still have a glow that changes, and the other way around
+        frames = frames.max(mask_frames.len());
+    }
+
+    let mut padded_frames = (0..frames)
+        .map(|i| expand_image(&texture_frames[i % texture_frames.len()], PADDING))
+        .collect::<Vec<Image>>();
+
+    if let Some(mask_frames) = &mask_frames {
+        padded_frames.extend(
+            (0..frames).map(|i| expand_image(&mask_frames[i % mask_frames.len()], PADDING)),
+        );
+    }
+
+    (
+        stack_images(&padded_frames),
+        TextureAnimation {
+            frames: frames as u32,
+            frame_time: metadata.frame_time,
+            interpolate: metadata.interpolate,
+            emissive: mask_frames.is_some(),
+        },
+    )
+}
+
+/// The atlas & everything needed to find the textures in it
+struct AtlasTextures {
+    atlas: TextureAtlas,
+    texture_indices: HashMap<String, usize>,
+    animations: HashMap<usize, TextureAnimation>,
+}
+
+/// Packs every block texture in the resource packs into one atlas.
+///
+/// Emissive masks are packed along with the texture that uses them instead of on their own.
+/// Anything that goes wrong is added to `errors`, and None is returned if the atlas couldn't be built at all.
+fn build_main_atlas(
+    packs: &ResourcePacks,
+    images: &mut Assets<Image>,
+    errors: &mut Vec<String>,
+) -> Option<AtlasTextures> {
+    let mut texture_atlas_builder = TextureAtlasBuilder::default();
+    let mut handles = Vec::new();
+
+    let metadata = load_texture_metadata(packs, errors);
+    let masks = metadata
+        .values()
+        .filter_map(|texture_metadata| texture_metadata.emissive.clone())
+        .collect::<HashSet<String>>();
+
+    for file_name in packs.files_in(BLOCK_TEXTURES_FOLDER) {
+        let Some(texture_name) = file_name.strip_suffix(".png") else {
+            continue;
+        };
+
+        if masks.contains(texture_name) {
+            continue;
+        }
+
+        let Some(image) = read_image(packs, &format!("{BLOCK_TEXTURES_FOLDER}/{file_name}")) else {
+            continue;
+        };
+
+        let (atlas_image, animation) = match metadata.get(texture_name) {
+            Some(texture_metadata) => {
+                animated_atlas_image(packs, texture_name, &image, texture_metadata)
+            }
+            None => (expand_image(&image, PADDING), TextureAnimation::default()),
+        };
+
+        let handle = images.add(atlas_image);
+
+        texture_atlas_builder.add_texture(
+            handle.clone_weak(),
+            images
+                .get(&handle)
+                .expect("This image was just added, but doesn't exist."),
+        );
+
+        handles.push((texture_name.to_owned(), handle, animation));
+    }
+
+    let atlas = match texture_atlas_builder.finish(images) {
+        Ok(atlas) => atlas,
+        Err(e) => {
+            errors.push(format!("Failed to build atlas - {e}"));
+            return None;
+        }
+    };
+
+    let mut texture_indices = HashMap::new();
+    let mut animations = HashMap::new();
+
+    // The individual images aren't needed once they're in the atlas, so they are dropped with these handles
+    for (texture_name, handle, animation) in handles {
+        let Some(index) = atlas.get_texture_index(&handle) else {
+            continue;
+        };
+
+        texture_indices.insert(texture_name, index);
+
+        if animation != TextureAnimation::default() {
+            animations.insert(index, animation);
+        }
+    }
+
+    Some(AtlasTextures {
+        atlas,
+        texture_indices,
+        animations,
+    })
 }
 
-fn assets_done_loading(
+fn setup(
     mut commands: Commands,
-    event_listener: EventReader<AssetsDoneLoadingEvent>,
-    loading_id: Option<Res<AssetsLoadingID>>,
+    packs: Res<ResourcePacks>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
     mut loader: ResMut<LoadingManager>,
+    mut start_writer: EventWriter<AddLoadingEvent>,
     mut end_writer: EventWriter<DoneLoadingEvent>,
 ) {
-    if loading_id.is_some() && !event_listener.is_empty() {
-        loader.finish_loading(loading_id.as_ref().unwrap().0, &mut end_writer);
+    let id = loader.register_loader(&mut start_writer);
 
-        commands.remove_resource::<AssetsLoadingID>();
+    let mut errors = Vec::new();
+    let atlas_textures = build_main_atlas(&packs, &mut images, &mut errors);
+
+    for error in errors {
+        warn!("{error}");
     }
+
+    let AtlasTextures {
+        atlas,
+        texture_indices,
+        animations,
+    } = atlas_textures.expect("The block texture atlas is needed to draw anything");
+
+    commands.insert_resource(MainAtlas {
+        material: materials.add(atlas_material(atlas.texture.clone(), false)),
+        unlit_material: materials.add(atlas_material(atlas.texture.clone(), true)),
+        atlas,
+        texture_indices,
+        animations,
+        padding: PADDING,
+    });
+
+    loader.finish_loading(id, &mut end_writer);
 }
 
 fn expand_image(image: &Image, padding: u32) -> Image {
@@ -160,7 +512,7 @@ fn expand_image(image: &Image, padding: u32) -> Image {
     }
 
     let height = image_size_y + padding * 2;
-    let width = image_size_y + padding * 2;
+    let width = image_size_x + padding * 2;
 
     // debug save
     // image::save_buffer(&Path::new("image.png"), data.as_slice(), width, height, image::ColorType::Rgba8);
@@ -177,123 +529,7 @@ fn expand_image(image: &Image, padding: u32) -> Image {
     )
 }
 
-fn check_assets_ready(
-    mut commands: Commands,
-    server: Res<AssetServer>,
-    loading: Option<ResMut<AssetsLoading>>,
-    mut images: ResMut<Assets<Image>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    mut event_writer: EventWriter<AssetsDoneLoadingEvent>,
-) {
-    let Some(loading) = loading else {
-        return;
-    };
-
-    use bevy::asset::LoadState;
-
-    let mut handles = Vec::new();
-    for la in loading.0.iter().map(|h| &h.handles) {
-        for handle in la.iter() {
-            handles.push(handle.id());
-        }
-    }
-
-    match server.get_group_load_state(handles) {
-        LoadState::Failed => {
-            panic!("Failed to load asset!!");
-        }
-        LoadState::Loaded => {
-            // all assets are now ready, construct texture atlas
-            // for better performance
-
-            for asset in loading.0.iter() {
-                match asset.atlas_name {
-                    AtlasName::Main => {
-                        const PADDING: u32 = 2;
-
-                        let mut texture_atlas_builder = TextureAtlasBuilder::default();
-
-                        for handle in &asset.handles {
-                            let Some(image) = images.get(handle) else {
-                                warn!("{:?} did not resolve to an `Image` asset.", server.get_handle_path(handle));
-                                continue;
-                            };
-
-                            let img = expand_image(image, PADDING);
-
-                            let handle = images.set(handle.clone(), img);
-
-                            texture_atlas_builder.add_texture(
-                                handle.clone_weak(),
-                                images
-                                    .get(&handle)
-                                    .expect("This image was just added, but doesn't exist."),
-                            );
-                        }
-
-                        let atlas = texture_atlas_builder
-                            .finish(&mut images)
-                            .expect("Failed to build atlas");
-
-                        let material_handle = materials.add(StandardMaterial {
-                            base_color_texture: Some(atlas.texture.clone()),
-                            alpha_mode: AlphaMode::Mask(0.5),
-                            unlit: false,
-                            metallic: 0.0,
-                            reflectance: 0.0,
-
-                            ..default()
-                        });
-
-                        let unlit_material_handle = materials.add(StandardMaterial {
-                            base_color_texture: Some(atlas.texture.clone()),
-                            alpha_mode: AlphaMode::Mask(0.5),
-                            unlit: true,
-                            metallic: 0.0,
-                            reflectance: 0.0,
-
-                            ..default()
-                        });
-
-                        let texture = atlas.texture.clone();
-
-                        commands.insert_resource(MainAtlas {
-                            material: material_handle,
-                            unlit_material: unlit_material_handle,
-                            atlas,
-                            padding: PADDING,
-                        });
-
-                        let illuminated_material_handle = materials.add(StandardMaterial {
-                            base_color_texture: Some(texture),
-                            alpha_mode: AlphaMode::Mask(0.5),
-                            unlit: true,
-                            double_sided: true,
-                            ..default()
-                        });
-
-                        commands.insert_resource(IlluminatedMaterial {
-                            material: illuminated_material_handle,
-                        });
-                    }
-                }
-            }
-
-            // Clear out handles to avoid continually checking
-            commands.remove_resource::<AssetsLoading>();
-
-            // (note: if you don't have any other handles to the assets
-            // elsewhere, they will get unloaded after this)
-
-            event_writer.send(AssetsDoneLoadingEvent);
-        }
-        _ => {
-            // NotLoaded/Loading: not fully ready yet
-        }
-    }
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 /// Contains information that links the block faces to their texture indices.
 ///
 /// This could also link non-face imformation to their texture indices.
@@ -311,7 +547,7 @@ impl BlockTextureIndicies {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 /// Links blocks to their correspoding atlas index.
 pub struct BlockTextureIndex {
     indices: BlockTextureIndicies,
@@ -361,16 +597,17 @@ struct BlockInfo {
     texture: HashMap<String, String>,
 }
 
-fn load_block_textxures(
-    blocks: Res<Registry<Block>>,
-    atlas: Res<MainAtlas>,
-    server: Res<AssetServer>,
-    mut registry: ResMut<Registry<BlockTextureIndex>>,
+/// Links every block to the atlas index of each of its textures.
+///
+/// Blocks whose json file can't be read use the texture with their name, and the error is added to `errors`.
+fn register_block_textures(
+    blocks: &Registry<Block>,
+    texture_indices: &HashMap<String, usize>,
+    packs: &ResourcePacks,
+    registry: &mut Registry<BlockTextureIndex>,
+    errors: &mut Vec<String>,
 ) {
-    if let Some(index) = atlas
-        .atlas
-        .get_texture_index(&server.get_handle("images/blocks/missing.png"))
-    {
+    if let Some(&index) = texture_indices.get("missing") {
         registry.register(BlockTextureIndex {
             id: 0,
             unlocalized_name: "missing".to_owned(),
@@ -385,22 +622,23 @@ fn load_block_textxures(
             .nth(1)
             .unwrap_or(unlocalized_name);
 
-        let json_path = format!("assets/blocks/{block_name}.json");
+        let json_path = format!("blocks/{block_name}.json");
 
-        let block_info = if let Ok(block_info) = fs::read(&json_path) {
+        let block_info = packs.read(&json_path).and_then(|block_info| {
             serde_json::from_slice::<BlockInfo>(&block_info)
-                .unwrap_or_else(|_| panic!("Error reading json data in {json_path}"))
-        } else {
+                .map_err(|e| errors.push(format!("Error reading json data in {json_path} - {e}")))
+                .ok()
+        });
+
+        let block_info = block_info.unwrap_or_else(|| {
             let mut hh = HashMap::new();
             hh.insert("all".into(), block_name.to_owned());
             BlockInfo { texture: hh }
-        };
+        });
 
         let mut map = HashMap::new();
         for (entry, texture_name) in block_info.texture.iter() {
-            if let Some(index) = atlas.atlas.get_texture_index(
-                &server.get_handle(&format!("images/blocks/{texture_name}.png",)),
-            ) {
+            if let Some(&index) = texture_indices.get(texture_name) {
                 map.insert(entry.to_owned(), index);
             }
         }
@@ -413,14 +651,96 @@ fn load_block_textxures(
     }
 }
 
+fn load_block_textxures(
+    blocks: Res<Registry<Block>>,
+    atlas: Res<MainAtlas>,
+    packs: Res<ResourcePacks>,
+    mut registry: ResMut<Registry<BlockTextureIndex>>,
+) {
+    let mut errors = Vec::new();
+
+    register_block_textures(
+        &blocks,
+        &atlas.texture_indices,
+        &packs,
+        &mut registry,
+        &mut errors,
+    );
+
+    for error in errors {
+        warn!("{error}");
+    }
+}
+
+/// Rebuilds the atlas & every block's texture indices whenever the resource packs change.
+///
+/// If anything in the new packs can't be read, the previous atlas & texture indices are kept.
+fn reload_block_textures(
+    mut event_reader: EventReader<ResourcePacksChangedEvent>,
+    blocks: Res<Registry<Block>>,
+    packs: Res<ResourcePacks>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut main_atlas: ResMut<MainAtlas>,
+    mut registry: ResMut<Registry<BlockTextureIndex>>,
+) {
+    if event_reader.iter().last().is_none() {
+        return;
+    }
+
+    let mut errors = Vec::new();
+
+    let atlas_textures = build_main_atlas(&packs, &mut images, &mut errors);
+
+    let mut new_registry = Registry::new();
+    if let Some(atlas_textures) = &atlas_textures {
+        register_block_textures(
+            &blocks,
+            &atlas_textures.texture_indices,
+            &packs,
+            &mut new_registry,
+            &mut errors,
+        );
+    }
+
+    let Some(atlas_textures) = atlas_textures.filter(|_| errors.is_empty()) else {
+        for error in errors {
+            warn!("{error}");
+        }
+        warn!("Keeping the previous block textures until the resource packs are fixed.");
+
+        return;
+    };
+
+    let AtlasTextures {
+        atlas,
+        texture_indices,
+        animations,
+    } = atlas_textures;
+
+    for handle in [&main_atlas.material, &main_atlas.unlit_material] {
+        if let Some(material) = materials.get_mut(handle) {
+            material.base_color_texture = Some(atlas.texture.clone());
+        }
+    }
+
+    main_atlas.atlas = atlas;
+    main_atlas.texture_indices = texture_indices;
+    main_atlas.animations = animations;
+
+    *registry = new_registry;
+}
+
 pub(super) fn register(app: &mut App) {
     registry::create_registry::<BlockTextureIndex>(app);
 
-    app.insert_resource(AssetsLoading(Vec::new()))
-        .add_event::<AssetsDoneLoadingEvent>()
-        .add_systems(
-            (check_assets_ready, assets_done_loading).in_set(OnUpdate(GameState::PostLoading)),
-        )
-        .add_system(setup.in_schedule(OnEnter(GameState::PostLoading)))
-        .add_system(load_block_textxures.in_schedule(OnExit(GameState::PostLoading)));
+    app.add_system(setup.in_schedule(OnEnter(GameState::PostLoading)))
+        .add_system(load_block_textxures.in_schedule(OnExit(GameState::PostLoading)))
+        // Everything that uses the atlas is reloaded during `Update`, so it has to be rebuilt before then
+        .add_system(
+            reload_block_textures
+                .in_base_set(CoreSet::PreUpdate)
+                .after(monitor_resource_packs)
+                .run_if(in_state(GameState::Playing)),
+        );
 }
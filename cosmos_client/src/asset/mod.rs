@@ -3,7 +3,9 @@
 use bevy::prelude::App;
 
 pub mod asset_loading;
+pub mod resource_packs;
 
 pub(super) fn register(app: &mut App) {
+    resource_packs::register(app);
     asset_loading::register(app);
 }
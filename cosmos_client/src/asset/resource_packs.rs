@@ -0,0 +1,404 @@
+//! Resource packs override or add to the block textures, block json files, skybox & lang files shipped in `assets/`.
+//!
+//! A resource pack is either a directory or a `.zip` file in the `resourcepacks/` folder, laid out the same way
+//! as the `assets/` folder. For example, `resourcepacks/my_pack/images/blocks/stone.png` replaces stone's texture.
+//!
+//! The packs listed in `resourcepacks/packs.json` are used, with the first one having the highest priority:
+//!
+//! ```json
+//! ["my_pack", "community_pack.zip"]
+//! ```
+//!
+//! If that file doesn't exist, every pack is used in alphabetical order.
+//!
+//! The packs are checked for changes every so often, and a [`ResourcePacksChangedEvent`] is sent
+//! whenever anything in them changes so everything that uses them can be reloaded.
+//! If the changed packs have a json file that can't be read, the error is logged and the previous packs are kept
+//! until it's fixed.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    ffi::OsStr,
+    fs::{self, File},
+    hash::{Hash, Hasher},
+    io::Read,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use bevy::{
+    prelude::{
+        in_state, App, CoreSet, EventWriter, IntoSystemConfig, Local, Res, ResMut, Resource,
+    },
+    tasks::{AsyncComputeTaskPool, Task},
+    time::Time,
+    utils::{HashMap, HashSet},
+};
+use futures_lite::future;
+use walkdir::WalkDir;
+
+use crate::state::game_state::GameState;
+
+/// The folder resource packs are put in
+const RESOURCE_PACKS_FOLDER: &str = "resourcepacks";
+/// The folder with the assets shipped with the game, which is used when no pack has a file
+const ASSETS_FOLDER: &str = "assets";
+/// Lists which packs are used & in what order
+const PACK_ORDER_FILE: &str = "packs.json";
+
+/// How often (in seconds) the resource packs are checked for changes
+const CHANGE_CHECK_INTERVAL: f32 = 1.0;
+
+/// Sent whenever the resource packs are changed, after [`ResourcePacks`] has been reloaded.
+///
+/// Anything loaded from the resource packs should be reloaded when this is received.
+pub struct ResourcePacksChangedEvent;
+
+#[derive(Debug)]
+enum PackSource {
+    Directory(PathBuf),
+    /// Zip files are read into memory all at once, since they're usually small & seeking through them is slow
+    Zip(HashMap<String, Vec<u8>>),
+}
+
+#[derive(Debug)]
+/// A single resource pack
+struct ResourcePack {
+    name: String,
+    source: PackSource,
+}
+
+impl ResourcePack {
+    fn load(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().into_owned();
+
+        if path.is_dir() {
+            return Some(Self {
+                name,
+                source: PackSource::Directory(path.to_owned()),
+            });
+        }
+
+        if path.extension() != Some(OsStr::new("zip")) {
+            return None;
+        }
+
+        let mut archive = match File::open(path).map(zip::ZipArchive::new) {
+            Ok(Ok(archive)) => archive,
+            _ => {
+                println!(
+                    "[Resource Packs] Unable to read zip file {}",
+                    path.display()
+                );
+                return None;
+            }
+        };
+
+        let mut files = HashMap::new();
+
+        for i in 0..archive.len() {
+            let Ok(mut file) = archive.by_index(i) else {
+                continue;
+            };
+
+            if file.is_dir() {
+                continue;
+            }
+
+            let file_name = file.name().replace('\\', "/");
+            let mut contents = Vec::with_capacity(file.size() as usize);
+
+            if file.read_to_end(&mut contents).is_ok() {
+                files.insert(file_name, contents);
+            }
+        }
+
+        Some(Self {
+            name,
+            source: PackSource::Zip(files),
+        })
+    }
+
+    /// The name of this pack's directory or zip file
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn read(&self, path: &str) -> Option<Vec<u8>> {
+        match &self.source {
+            PackSource::Directory(dir) => fs::read(dir.join(path)).ok(),
+            PackSource::Zip(files) => files.get(path).cloned(),
+        }
+    }
+
+    /// Every file in this pack, relative to the root of the pack
+    fn files(&self) -> Vec<String> {
+        match &self.source {
+            PackSource::Directory(dir) => WalkDir::new(dir)
+                .into_iter()
+                .flatten()
+                .filter(|x| x.file_type().is_file())
+                .filter_map(|x| {
+                    x.path()
+                        .strip_prefix(dir)
+                        .ok()
+                        .map(|path| path.to_string_lossy().replace('\\', "/"))
+                })
+                .collect(),
+            PackSource::Zip(files) => files.keys().cloned().collect(),
+        }
+    }
+
+    /// Makes sure every json file in this pack can be read, since everything reading them expects valid json
+    fn validate(&self) -> Result<(), String> {
+        for path in self.files() {
+            if !path.ends_with(".json") {
+                continue;
+            }
+
+            let Some(json) = self.read(&path) else {
+                continue;
+            };
+
+            if let Err(e) = serde_json::from_slice::<serde_json::Value>(&json) {
+                return Err(format!(
+                    "Error reading json data in {}/{path} - {e}",
+                    self.name()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn files_in(&self, folder: &str) -> Vec<String> {
+        match &self.source {
+            PackSource::Directory(dir) => files_in_directory(&dir.join(folder)),
+            PackSource::Zip(files) => {
+                let prefix = format!("{}/", folder.trim_end_matches('/'));
+
+                files
+                    .keys()
+                    .filter_map(|path| path.strip_prefix(&prefix))
+                    .filter(|name| !name.contains('/'))
+                    .map(|name| name.to_owned())
+                    .collect()
+            }
+        }
+    }
+}
+
+fn files_in_directory(dir: &Path) -> Vec<String> {
+    WalkDir::new(dir)
+        .max_depth(1)
+        .into_iter()
+        .flatten()
+        .filter(|x| x.file_type().is_file())
+        .map(|x| x.file_name().to_string_lossy().into_owned())
+        .collect()
+}
+
+#[derive(Resource, Debug, Default)]
+/// The resource packs currently in use, sorted from highest to lowest priority.
+///
+/// Read any asset that can be overridden through this instead of straight from the `assets/` folder.
+pub struct ResourcePacks {
+    packs: Vec<ResourcePack>,
+    fingerprint: u64,
+}
+
+impl ResourcePacks {
+    /// Loads every resource pack that should be used from the `resourcepacks/` folder.
+    ///
+    /// Returns an error if the pack order file or any json file in a pack can't be read.
+    pub fn load() -> Result<Self, String> {
+        let folder = Path::new(RESOURCE_PACKS_FOLDER);
+
+        let pack_names = match fs::read(folder.join(PACK_ORDER_FILE)) {
+            Ok(order) => serde_json::from_slice::<Vec<String>>(&order).map_err(|e| {
+                format!(
+                    "Error reading json data in {RESOURCE_PACKS_FOLDER}/{PACK_ORDER_FILE} - {e}"
+                )
+            })?,
+            Err(_) => {
+                let mut names = WalkDir::new(folder)
+                    .min_depth(1)
+                    .max_depth(1)
+                    .into_iter()
+                    .flatten()
+                    .map(|x| x.file_name().to_string_lossy().into_owned())
+                    .filter(|name| name != PACK_ORDER_FILE)
+                    .collect::<Vec<String>>();
+
+                names.sort();
+
+                names
+            }
+        };
+
+        let packs = pack_names
+            .iter()
+            .filter_map(|name| ResourcePack::load(&folder.join(name)))
+            .collect::<Vec<ResourcePack>>();
+
+        for pack in packs.iter() {
+            pack.validate()?;
+        }
+
+        for pack in packs.iter() {
+            println!("[Resource Packs] Using resource pack {}", pack.name());
+        }
+
+        Ok(Self {
+            packs,
+            fingerprint: 0,
+        })
+    }
+
+    /// Reads the file at this path (relative to the `assets/` folder) from the highest priority pack that has it.
+    ///
+    /// If no pack has it, the file from the `assets/` folder is used.
+    pub fn read(&self, path: &str) -> Option<Vec<u8>> {
+        self.packs
+            .iter()
+            .find_map(|pack| pack.read(path))
+            .or_else(|| fs::read(Path::new(ASSETS_FOLDER).join(path)).ok())
+    }
+
+    /// Reads every copy of the file at this path, from the highest priority pack to the `assets/` folder.
+    ///
+    /// Useful for files that are merged together instead of replaced, such as lang files.
+    pub fn read_all(&self, path: &str) -> Vec<Vec<u8>> {
+        self.packs
+            .iter()
+            .filter_map(|pack| pack.read(path))
+            .chain(fs::read(Path::new(ASSETS_FOLDER).join(path)).ok())
+            .collect()
+    }
+
+    /// Returns the names of all the files directly in this folder (relative to the `assets/` folder)
+    /// across every pack & the `assets/` folder, sorted alphabetically.
+    pub fn files_in(&self, folder: &str) -> Vec<String> {
+        let mut files = self
+            .packs
+            .iter()
+            .flat_map(|pack| pack.files_in(folder))
+            .chain(files_in_directory(&Path::new(ASSETS_FOLDER).join(folder)))
+            .collect::<HashSet<String>>()
+            .into_iter()
+            .collect::<Vec<String>>();
+
+        files.sort();
+
+        files
+    }
+}
+
+/// Hashes the path, size & last modified time of everything in the resource packs folder, which will be
+/// different whenever a pack is added, removed or edited.
+fn fingerprint() -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    for entry in WalkDir::new(RESOURCE_PACKS_FOLDER)
+        .sort_by_file_name()
+        .into_iter()
+        .flatten()
+    {
+        entry.path().hash(&mut hasher);
+
+        if let Ok(metadata) = entry.metadata() {
+            metadata.len().hash(&mut hasher);
+
+            if let Ok(since_epoch) = metadata
+                .modified()
+                .map(|modified| modified.duration_since(UNIX_EPOCH).unwrap_or_default())
+            {
+                since_epoch.hash(&mut hasher);
+            }
+        }
+    }
+
+    hasher.finish()
+}
+
+#[derive(Default)]
+/// Keeps track of when the resource packs were last checked for changes
+pub(super) struct ChangeCheck {
+    since_last_check: f32,
+    /// Walking the resource packs folder can be slow, so it's done in the background
+    fingerprint_task: Option<Task<u64>>,
+}
+
+pub(super) fn monitor_resource_packs(
+    time: Res<Time>,
+    mut change_check: Local<ChangeCheck>,
+    mut packs: ResMut<ResourcePacks>,
+    mut event_writer: EventWriter<ResourcePacksChangedEvent>,
+) {
+    if let Some(task) = &mut change_check.fingerprint_task {
+        let Some(new_fingerprint) = future::block_on(future::poll_once(task)) else {
+            return;
+        };
+
+        change_check.fingerprint_task = None;
+
+        if new_fingerprint == packs.fingerprint {
+            return;
+        }
+
+        println!("[Resource Packs] Resource packs changed, reloading them.");
+
+        match ResourcePacks::load() {
+            Ok(new_packs) => {
+                *packs = new_packs;
+
+                event_writer.send(ResourcePacksChangedEvent);
+            }
+            Err(e) => {
+                println!("[Resource Packs] {e}");
+                println!(
+                    "[Resource Packs] Keeping the previous resource packs until this is fixed."
+                );
+            }
+        }
+
+        // Even if the packs couldn't be loaded, they shouldn't be tried again until they change
+        packs.fingerprint = new_fingerprint;
+
+        return;
+    }
+
+    change_check.since_last_check += time.delta_seconds();
+
+    if change_check.since_last_check < CHANGE_CHECK_INTERVAL {
+        return;
+    }
+
+    change_check.since_last_check = 0.0;
+    change_check.fingerprint_task =
+        Some(AsyncComputeTaskPool::get().spawn(async { fingerprint() }));
+}
+
+fn initial_resource_packs() -> ResourcePacks {
+    let mut packs = ResourcePacks::load().unwrap_or_else(|e| {
+        println!("[Resource Packs] {e}");
+        println!("[Resource Packs] Not using any resource packs until this is fixed.");
+
+        ResourcePacks::default()
+    });
+
+    packs.fingerprint = fingerprint();
+
+    packs
+}
+
+pub(super) fn register(app: &mut App) {
+    // Everything loaded during the loading states reads from these, so they have to be ready right away
+    app.insert_resource(initial_resource_packs())
+        .add_event::<ResourcePacksChangedEvent>()
+        .add_system(
+            monitor_resource_packs
+                .in_base_set(CoreSet::PreUpdate)
+                .run_if(in_state(GameState::Playing)),
+        );
+}
@@ -17,14 +17,10 @@ use crate::state::game_state::GameState;
 pub struct BlockLightProperties {
     /// The color of that light
     pub color: Color,
-    /// How intense it should be in lumens,
+    /// How many blocks this light will reach, up to [`MAX_LIGHT_LEVEL`](crate::rendering::light_field::MAX_LIGHT_LEVEL).
     ///
-    /// See https://docs.rs/bevy/latest/bevy/pbr/struct.PointLight.html for a table of valus.
-    pub intensity: f32,
-    /// How far this light will reach
+    /// Each color channel of the light reaches `range * channel` blocks, so dimmer colors don't travel as far.
     pub range: f32,
-    /// Ignored for now due to performance issues. Shadows are currently always disabled.
-    pub shadows_disabled: bool,
 }
 
 #[derive(Debug, Reflect, FromReflect, Default, Serialize, Deserialize)]
@@ -75,9 +71,7 @@ fn register_all_lights(
     register_light(
         BlockLightProperties {
             color: Color::WHITE,
-            intensity: 500.0,
             range: 12.0,
-            ..Default::default()
         },
         &mut registry,
         &blocks,
@@ -87,9 +81,7 @@ fn register_all_lights(
     register_light(
         BlockLightProperties {
             color: Color::rgb(81.0 / 255.0, 143.0 / 255.0, 225.0 / 255.0),
-            intensity: 100.0,
             range: 6.0,
-            ..Default::default()
         },
         &mut registry,
         &blocks,
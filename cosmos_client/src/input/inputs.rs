@@ -90,6 +90,14 @@ pub enum CosmosInputs {
 
     /// For testing - disconnects you from the server
     Disconnect,
+
+    /// Opens the chat box
+    OpenChat,
+    /// Opens the chat box with a `/` already typed
+    OpenCommand,
+
+    /// Opens & closes the system map
+    ToggleMap,
 }
 
 fn init_input(mut input_handler: ResMut<CosmosInputHandler>) {
@@ -138,6 +146,11 @@ fn init_input(mut input_handler: ResMut<CosmosInputHandler>) {
 
     input_handler.set_keycode(CosmosInputs::Disconnect, KeyCode::P);
 
+    input_handler.set_keycode(CosmosInputs::OpenChat, KeyCode::T);
+    input_handler.set_keycode(CosmosInputs::OpenCommand, KeyCode::Slash);
+
+    input_handler.set_keycode(CosmosInputs::ToggleMap, KeyCode::M);
+
     input_handler.set_mouse_button(CosmosInputs::UseSelectedSystem, MouseButton::Left);
 }
 
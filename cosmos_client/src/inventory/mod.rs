@@ -17,7 +17,10 @@ use cosmos_core::{
 };
 
 use crate::{
-    asset::asset_loading::{BlockTextureIndex, MainAtlas},
+    asset::{
+        asset_loading::{BlockTextureIndex, MainAtlas},
+        resource_packs::ResourcePacksChangedEvent,
+    },
     netty::flags::LocalPlayer,
     rendering::{BlockMeshRegistry, CosmosMeshBuilder, MeshBuilder},
     state::game_state::GameState,
@@ -64,11 +67,18 @@ fn render_hotbar(
     atlas: Res<MainAtlas>,
     block_textures: Res<Registry<BlockTextureIndex>>,
     block_meshes: Res<BlockMeshRegistry>,
+
+    old_hotbars: Query<Entity, With<HotbarLocation>>,
 ) {
     let Ok(inventory) = inventory.get_single() else {
         return;
     };
 
+    // The hotbar is rendered again whenever the resource packs change, so get rid of the outdated one
+    for old_hotbar in old_hotbars.iter() {
+        commands.entity(old_hotbar).despawn_recursive();
+    }
+
     let amt = 9;
 
     let size = 0.8;
@@ -123,6 +133,22 @@ fn render_hotbar(
             mesh_builder.add_mesh_information(&mesh_info, Vec3::ZERO, uvs);
         }
 
+        // Blocks that aren't full cubes can have parts of their model inside them, such as the top of a slab
+        for face_index in 0..6 {
+            let face = BlockFace::from_index(face_index);
+
+            let mut mesh_info = block_mesh_info.inner_info_for_face(face).clone();
+            mesh_info.scale(Vec3::new(size, size, size));
+
+            let Some(image_index) = index.atlas_index_from_face(face) else {
+                continue;
+            };
+
+            let uvs = atlas.uvs_for_index(image_index);
+
+            mesh_builder.add_mesh_information(&mesh_info, Vec3::ZERO, uvs);
+        }
+
         children.push(
             commands
                 .spawn((
@@ -169,5 +195,10 @@ pub(super) fn register(app: &mut App) {
         },
     )
     .add_system(render_hotbar.in_schedule(OnEnter(GameState::Playing)))
+    .add_system(
+        render_hotbar
+            .run_if(on_event::<ResourcePacksChangedEvent>())
+            .in_set(OnUpdate(GameState::Playing)),
+    )
     .add_startup_system(ui_camera);
 }
@@ -1,7 +1,13 @@
-use bevy::prelude::{App, Commands, IntoSystemAppConfig, OnEnter, OnExit, Res, ResMut};
+use bevy::prelude::{
+    App, Commands, EventReader, IntoSystemAppConfig, IntoSystemConfig, OnEnter, OnExit, OnUpdate,
+    Res, ResMut,
+};
 use cosmos_core::{item::Item, registry::Registry};
 
-use crate::state::game_state::GameState;
+use crate::{
+    asset::resource_packs::{ResourcePacks, ResourcePacksChangedEvent},
+    state::game_state::GameState,
+};
 
 use super::Lang;
 
@@ -11,11 +17,33 @@ fn insert_langs(mut item_langs: ResMut<Lang<Item>>, items: Res<Registry<Item>>)
     }
 }
 
-fn insert_resource(mut commands: Commands) {
-    commands.insert_resource(Lang::<Item>::new("en_us", vec!["items", "blocks"]));
+fn create_item_lang(packs: &ResourcePacks) -> Lang<Item> {
+    Lang::new("en_us", vec!["items", "blocks"], packs)
+}
+
+fn insert_resource(mut commands: Commands, packs: Res<ResourcePacks>) {
+    commands.insert_resource(create_item_lang(&packs));
+}
+
+fn reload_langs(
+    mut event_reader: EventReader<ResourcePacksChangedEvent>,
+    packs: Res<ResourcePacks>,
+    mut item_langs: ResMut<Lang<Item>>,
+    items: Res<Registry<Item>>,
+) {
+    if event_reader.iter().last().is_none() {
+        return;
+    }
+
+    *item_langs = create_item_lang(&packs);
+
+    for item in items.iter() {
+        item_langs.register(item);
+    }
 }
 
 pub(super) fn register(app: &mut App) {
     app.add_system(insert_resource.in_schedule(OnEnter(GameState::PreLoading)))
-        .add_system(insert_langs.in_schedule(OnExit(GameState::PostLoading)));
+        .add_system(insert_langs.in_schedule(OnExit(GameState::PostLoading)))
+        .add_system(reload_langs.in_set(OnUpdate(GameState::Playing)));
 }
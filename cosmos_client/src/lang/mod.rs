@@ -2,7 +2,7 @@
 
 mod load_langs;
 
-use std::{fs, marker::PhantomData};
+use std::marker::PhantomData;
 
 use bevy::{
     prelude::{App, Resource},
@@ -10,6 +10,8 @@ use bevy::{
 };
 use cosmos_core::registry::identifiable::Identifiable;
 
+use crate::asset::resource_packs::ResourcePacks;
+
 #[derive(Resource)]
 /// Used to get the human-readable + localized text to display for identifiable types
 pub struct Lang<T: Identifiable + Send + Sync> {
@@ -19,24 +21,39 @@ pub struct Lang<T: Identifiable + Send + Sync> {
     _phantom: PhantomData<T>,
 }
 
-fn load_data(lang_type: &str, lang_folder: &str, map: &mut HashMap<String, String>) {
-    let path = format!("assets/lang/{lang_folder}/{lang_type}.lang");
-    let str = fs::read_to_string(path.clone())
-        .unwrap_or_else(|_| panic!("Error reading lang file @ '{path}'!"));
+fn load_data(
+    lang_type: &str,
+    lang_folder: &str,
+    packs: &ResourcePacks,
+    map: &mut HashMap<String, String>,
+) {
+    let path = format!("lang/{lang_folder}/{lang_type}.lang");
 
-    for line in str
-        .split('\n')
-        .map(|x| x.trim())
-        .filter(|x| !x.is_empty() && !x.starts_with('#'))
-    {
-        let split: Vec<&str> = line.split('=').collect();
+    // Resource packs only have to contain the entries they change, so every copy of the file is read
+    let copies = packs.read_all(&path);
 
-        if split.len() == 1 {
-            panic!("Error parsing lang file {path}. Invalid line - {line} (missing = sign)");
-        }
+    if copies.is_empty() {
+        panic!("Error reading lang file @ '{path}'!");
+    }
+
+    for contents in copies {
+        let str = String::from_utf8(contents)
+            .unwrap_or_else(|_| panic!("Error reading lang file @ '{path}'!"));
 
-        if !map.contains_key(split[0]) {
-            map.insert(split[0].to_owned(), split[1..].concat());
+        for line in str
+            .split('\n')
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty() && !x.starts_with('#'))
+        {
+            let split: Vec<&str> = line.split('=').collect();
+
+            if split.len() == 1 {
+                panic!("Error parsing lang file {path}. Invalid line - {line} (missing = sign)");
+            }
+
+            if !map.contains_key(split[0]) {
+                map.insert(split[0].to_owned(), split[1..].concat());
+            }
         }
     }
 }
@@ -46,11 +63,12 @@ impl<T: Identifiable + Send + Sync> Lang<T> {
     ///
     /// * `lang_type` The language identifier, such as en_us
     /// * `read_from` These are the files that should be read from for the language data. These should be sorted in order of importance - data found in the file N will override data found files N + X.
-    pub fn new(lang_type: &str, read_from: Vec<&str>) -> Self {
+    /// * `packs` The resource packs to read the files from. Entries in higher priority packs override the ones in lower priority packs.
+    pub fn new(lang_type: &str, read_from: Vec<&str>, packs: &ResourcePacks) -> Self {
         let mut lang_contents = HashMap::new();
 
         for fallback in read_from {
-            load_data(lang_type, fallback, &mut lang_contents);
+            load_data(lang_type, fallback, packs, &mut lang_contents);
         }
 
         Self {
@@ -0,0 +1,125 @@
+//! The material used to draw the blocks of structures.
+//!
+//! Unlike a `StandardMaterial`, this repeats a block's texture across faces that span multiple blocks,
+//! which is what lets the structure renderer merge many block faces into one.
+
+use bevy::{
+    pbr::{MaterialPipeline, MaterialPipelineKey},
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        mesh::{MeshVertexAttribute, MeshVertexBufferLayout},
+        render_resource::{
+            AsBindGroup, RenderPipelineDescriptor, ShaderDefVal, ShaderRef,
+            SpecializedMeshPipelineError, VertexFormat,
+        },
+    },
+};
+
+/// The rectangle (min x, min y, max x, max y) of the atlas this vertex's texture is in.
+///
+/// The vertex's uvs are relative to this rectangle, and are repeated if they go outside of [0.0, 1.0].
+pub const ATTRIBUTE_ATLAS_RECT: MeshVertexAttribute =
+    MeshVertexAttribute::new("BlockAtlasRect", 988_540_917, VertexFormat::Float32x4);
+
+/// How much light reaches this vertex, based off the blocks around it. 1.0 is not occluded at all.
+pub const ATTRIBUTE_AMBIENT_OCCLUSION: MeshVertexAttribute =
+    MeshVertexAttribute::new("BlockAmbientOcclusion", 988_540_918, VertexFormat::Float32);
+
+/// How this vertex's texture is animated & if it glows, see `MainAtlas::animation_for_index`.
+///
+/// The frame shown is picked in the shader based off the time, so animated blocks don't need to be re-rendered.
+pub const ATTRIBUTE_ANIMATION: MeshVertexAttribute =
+    MeshVertexAttribute::new("BlockAnimation", 988_540_919, VertexFormat::Float32x4);
+
+#[derive(AsBindGroup, Debug, Clone, TypeUuid)]
+#[uuid = "5f0b1a52-8d7e-4c4e-9a8e-3b6f1f0c2d41"]
+#[bind_group_data(BlockMaterialKey)]
+/// Draws blocks using a texture atlas
+pub struct BlockMaterial {
+    #[texture(0)]
+    #[sampler(1)]
+    /// The texture atlas every block's texture is in
+    pub atlas_texture: Handle<Image>,
+    /// If true, lighting will not affect this material
+    pub unlit: bool,
+    /// If true, both sides of every face will be drawn
+    pub double_sided: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Used to specialize the render pipeline for each variant of the `BlockMaterial`
+pub struct BlockMaterialKey {
+    unlit: bool,
+    double_sided: bool,
+}
+
+impl From<&BlockMaterial> for BlockMaterialKey {
+    fn from(material: &BlockMaterial) -> Self {
+        Self {
+            unlit: material.unlit,
+            double_sided: material.double_sided,
+        }
+    }
+}
+
+impl Material for BlockMaterial {
+    fn vertex_shader() -> ShaderRef {
+        "shaders/block.wgsl".into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        "shaders/block.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Mask(0.5)
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayout,
+        key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let vertex_layout = layout.get_layout(&[
+            Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+            Mesh::ATTRIBUTE_NORMAL.at_shader_location(1),
+            Mesh::ATTRIBUTE_UV_0.at_shader_location(2),
+            ATTRIBUTE_ATLAS_RECT.at_shader_location(3),
+            Mesh::ATTRIBUTE_COLOR.at_shader_location(4),
+            ATTRIBUTE_AMBIENT_OCCLUSION.at_shader_location(5),
+            ATTRIBUTE_ANIMATION.at_shader_location(6),
+        ])?;
+        descriptor.vertex.buffers = vec![vertex_layout];
+
+        let mut shader_defs: Vec<ShaderDefVal> = Vec::new();
+
+        if key.bind_group_data.unlit {
+            shader_defs.push("BLOCK_UNLIT".into());
+        }
+
+        if key.bind_group_data.double_sided {
+            shader_defs.push("BLOCK_DOUBLE_SIDED".into());
+            descriptor.primitive.cull_mode = None;
+        }
+
+        descriptor
+            .vertex
+            .shader_defs
+            .extend(shader_defs.iter().cloned());
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            fragment.shader_defs.extend(shader_defs);
+        }
+
+        Ok(())
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    // The prepass uses the default vertex layout, which doesn't have the atlas rect in it
+    app.add_plugin(MaterialPlugin::<BlockMaterial> {
+        prepass_enabled: false,
+        ..Default::default()
+    });
+}
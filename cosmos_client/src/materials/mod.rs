@@ -7,22 +7,27 @@ use cosmos_core::{
 };
 
 use crate::{
-    asset::asset_loading::{IlluminatedMaterial, MainAtlas},
+    asset::{asset_loading::MainAtlas, resource_packs::ResourcePacksChangedEvent},
     state::game_state::GameState,
 };
 
-/// An identifiable `StandardMaterial`
+use self::block_material::BlockMaterial;
+
+pub mod block_material;
+
+#[derive(Clone)]
+/// An identifiable `BlockMaterial`
 pub struct CosmosMaterial {
-    /// The handle to the bevy `StandardMaterial`
-    pub handle: Handle<StandardMaterial>,
+    /// The handle to the `BlockMaterial`
+    pub handle: Handle<BlockMaterial>,
 
     id: u16,
     unlocalized_name: String,
 }
 
 impl CosmosMaterial {
-    /// Creates an identifiable `StandardMaterial`
-    pub fn new(unlocalized_name: String, handle: Handle<StandardMaterial>) -> Self {
+    /// Creates an identifiable `BlockMaterial`
+    pub fn new(unlocalized_name: String, handle: Handle<BlockMaterial>) -> Self {
         Self {
             unlocalized_name,
             handle,
@@ -49,16 +54,24 @@ fn register_materials(
     blocks: Res<Registry<Block>>,
     mut registry: ResMut<ManyToOneRegistry<Block, CosmosMaterial>>,
     main_atlas: Res<MainAtlas>,
-    illum_atlas: Res<IlluminatedMaterial>,
+    mut block_materials: ResMut<Assets<BlockMaterial>>,
 ) {
     registry.insert_value(CosmosMaterial::new(
         "cosmos:main".to_owned(),
-        main_atlas.material.clone(),
+        block_materials.add(BlockMaterial {
+            atlas_texture: main_atlas.atlas.texture.clone(),
+            unlit: false,
+            double_sided: false,
+        }),
     ));
 
     registry.insert_value(CosmosMaterial::new(
         "cosmos:illuminated".to_owned(),
-        illum_atlas.material.clone(),
+        block_materials.add(BlockMaterial {
+            atlas_texture: main_atlas.atlas.texture.clone(),
+            unlit: true,
+            double_sided: true,
+        }),
     ));
 
     // TODO: Automate this in file or something
@@ -86,8 +99,25 @@ fn register_materials(
     }
 }
 
+/// The atlas is rebuilt whenever the resource packs change, so every block material has to use the new one
+fn update_block_materials(
+    mut event_reader: EventReader<ResourcePacksChangedEvent>,
+    main_atlas: Res<MainAtlas>,
+    mut block_materials: ResMut<Assets<BlockMaterial>>,
+) {
+    if event_reader.iter().last().is_none() {
+        return;
+    }
+
+    for (_, material) in block_materials.iter_mut() {
+        material.atlas_texture = main_atlas.atlas.texture.clone();
+    }
+}
+
 pub(super) fn register(app: &mut App) {
     registry::many_to_one::create_many_to_one_registry::<Block, CosmosMaterial>(app);
+    block_material::register(app);
 
-    app.add_system(register_materials.in_schedule(OnExit(GameState::PostLoading)));
+    app.add_system(register_materials.in_schedule(OnExit(GameState::PostLoading)))
+        .add_system(update_block_materials.in_set(OnUpdate(GameState::Playing)));
 }
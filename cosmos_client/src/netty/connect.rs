@@ -3,6 +3,7 @@
 //! This does not add them to the bevy systems by default, and they must be manually added when needed.
 
 use std::{
+    fs,
     net::UdpSocket,
     time::{SystemTime, UNIX_EPOCH},
 };
@@ -11,7 +12,11 @@ use bevy::prelude::*;
 use bevy_renet::renet::{ClientAuthentication, RenetClient};
 use cosmos_core::{
     entities::player::Player,
-    netty::{client_connection_config, PROTOCOL_ID},
+    netty::{
+        client_connection_config,
+        connection_info::{ConnectionInfo, PLAYER_KEY_LENGTH},
+        PROTOCOL_ID,
+    },
 };
 
 use crate::{
@@ -24,6 +29,30 @@ use crate::{
 
 use super::flags::LocalPlayer;
 
+/// Where this client's player key is kept
+const PLAYER_KEY_PATH: &str = "./player_key.dat";
+
+/// Loads the key that proves this client is the player it says it is, creating one the first time.
+///
+/// Servers tie a name to the first key it was used with, so losing this file means losing that name on every server.
+fn player_key() -> [u8; PLAYER_KEY_LENGTH] {
+    if let Ok(key) = fs::read(PLAYER_KEY_PATH) {
+        if let Ok(key) = <[u8; PLAYER_KEY_LENGTH]>::try_from(key.as_slice()) {
+            return key;
+        }
+
+        println!("Player key in {PLAYER_KEY_PATH} is invalid, creating a new one.");
+    }
+
+    let key = rand::random::<[u8; PLAYER_KEY_LENGTH]>();
+
+    if let Err(e) = fs::write(PLAYER_KEY_PATH, key) {
+        println!("Unable to save player key to {PLAYER_KEY_PATH} - {e}");
+    }
+
+    key
+}
+
 fn new_renet_client(host: &str) -> RenetClient {
     let port: u16 = 1337;
 
@@ -42,9 +71,15 @@ fn new_renet_client(host: &str) -> RenetClient {
 
     let mut token = [0; 256];
 
+    let connection_info = ConnectionInfo {
+        name: name.into(),
+        key: player_key(),
+    };
+
     // Bincode because this is stored un a u8, with a fixed length of 256
-    let serialized_name = bincode::serialize(&name).expect("Unable to serialize name");
-    for (i, byte) in serialized_name.iter().enumerate() {
+    let serialized_info =
+        bincode::serialize(&connection_info).expect("Unable to serialize connection info");
+    for (i, byte) in serialized_info.iter().enumerate() {
         token[i] = *byte;
     }
 
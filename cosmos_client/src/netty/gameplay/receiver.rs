@@ -21,13 +21,19 @@ use cosmos_core::{
     },
     persistence::LoadingDistance,
     physics::{
-        location::{add_previous_location, handle_child_syncing, Location, SYSTEM_SECTORS},
+        location::{
+            add_previous_location, handle_child_syncing, Location, SECTOR_DIMENSIONS,
+            SYSTEM_SECTORS,
+        },
         player_world::PlayerWorld,
     },
     registry::Registry,
     structure::{
         chunk::Chunk,
-        planet::{biosphere::BiosphereMarker, planet_builder::TPlanetBuilder},
+        planet::{
+            biosphere::BiosphereMarker, planet_builder::TPlanetBuilder,
+            planet_movement::move_with_planets,
+        },
         ship::{pilot::Pilot, ship_builder::TShipBuilder, Ship},
         ChunkInitEvent, Structure,
     },
@@ -146,6 +152,10 @@ fn client_sync_players(
         ),
         Without<LocalPlayer>,
     >,
+    mut query_local_player: Query<
+        (&mut Location, &mut Velocity, &mut Transform),
+        With<LocalPlayer>,
+    >,
     mut query_structure: Query<&mut Structure>,
     blocks: Res<Registry<Block>>,
     mut pilot_change_event_writer: EventWriter<ChangePilotEvent>,
@@ -303,6 +313,8 @@ fn client_sync_players(
                                     transform: Transform::from_xyz(0.0, 0.75, 0.0),
                                     projection: Projection::from(PerspectiveProjection {
                                         fov: (90.0 / 360.0) * (std::f32::consts::PI * 2.0),
+                                        // Planets have to be visible from far away
+                                        far: SECTOR_DIMENSIONS * 4.0,
                                         ..default()
                                     }),
                                     ..default()
@@ -328,23 +340,6 @@ fn client_sync_players(
                     ));
                 }
             }
-            ServerReliableMessages::PlayerRemove { id } => {
-                if let Some(PlayerInfo {
-                    client_entity,
-                    server_entity,
-                }) = lobby.players.remove(&id)
-                {
-                    if let Some(mut entity) = commands.get_entity(client_entity) {
-                        if let Ok(player) = query_player.get(client_entity) {
-                            println!("Player {} ({id}) disconnected", player.name());
-                        }
-
-                        entity.insert(NeedsDespawned);
-                    }
-
-                    network_mapping.remove_mapping_from_server_entity(&server_entity);
-                }
-            }
             ServerReliableMessages::Planet {
                 entity: server_entity,
                 length,
@@ -462,9 +457,54 @@ fn client_sync_players(
                     commands.entity(entity).insert(NeedsDespawned);
                 }
             }
+            ServerReliableMessages::EntityEnter {
+                entity: server_entity,
+            } => {
+                // The server sends this entity's data on its own, so don't request it again
+                if !network_mapping.contains_server_entity(server_entity)
+                    && !requested_entities
+                        .entities
+                        .iter()
+                        .any(|x| x.0 == server_entity)
+                {
+                    requested_entities.entities.push((server_entity, 0.0));
+                }
+            }
+            ServerReliableMessages::EntityLeave {
+                entity: server_entity,
+            } => {
+                requested_entities.entities.retain(|x| x.0 != server_entity);
+
+                if let Some(entity) = network_mapping.client_from_server(&server_entity) {
+                    if let Ok(player) = query_player.get(entity) {
+                        println!(
+                            "Player {} ({}) is no longer nearby",
+                            player.name(),
+                            player.id()
+                        );
+                    }
+
+                    // Players are removed from the lobby so they can be created again once they come back into range
+                    lobby
+                        .players
+                        .retain(|_, player_info| player_info.server_entity != server_entity);
+
+                    commands.entity(entity).insert(NeedsDespawned);
+                    network_mapping.remove_mapping_from_server_entity(&server_entity);
+                }
+            }
             ServerReliableMessages::MOTD { motd } => {
                 println!("Server MOTD: {motd}");
             }
+            ServerReliableMessages::SetPlayerBody { body } => {
+                if let Ok((mut location, mut velocity, mut transform)) =
+                    query_local_player.get_single_mut()
+                {
+                    location.set_from(&body.location);
+                    *velocity = body.create_velocity();
+                    transform.rotation = body.rotation;
+                }
+            }
             ServerReliableMessages::BlockChange {
                 x,
                 y,
@@ -503,7 +543,8 @@ fn client_sync_players(
                     None
                 };
 
-                let Some(structure_entity) = network_mapping.client_from_server(&structure_entity) else {
+                let Some(structure_entity) = network_mapping.client_from_server(&structure_entity)
+                else {
                     continue;
                 };
 
@@ -646,6 +687,8 @@ pub(super) fn register(app: &mut App) {
             (
                 fix_location.before(client_sync_players),
                 lerp_towards.after(client_sync_players),
+                // The server moves planets, but this client is in charge of where its player is
+                move_with_planets::<With<LocalPlayer>>,
                 add_previous_location,
                 sync_transforms_and_locations,
                 handle_child_syncing,
@@ -0,0 +1,90 @@
+//! Builds the meshes of blocks that aren't just full cubes from their [`BlockModel`].
+//!
+//! The models are loaded by `cosmos_core` through the [`ResourcePacks`], so packs can change or add models.
+//! Block colliders are built from the models loaded when the game starts, so a pack that changes a model's shape
+//! while playing only changes how it looks.
+
+use bevy::prelude::Vec3;
+use cosmos_core::{
+    block::{
+        block_models::{self, BlockModel, BlockModelRegistry, QuadModel},
+        Block, BlockFace,
+    },
+    registry::{identifiable::Identifiable, Registry},
+};
+
+use crate::asset::resource_packs::ResourcePacks;
+
+use super::{BlockMeshInformation, MeshInformation};
+
+/// Loads every block's model from the resource packs
+pub(super) fn load_block_models(
+    blocks: &Registry<Block>,
+    packs: &ResourcePacks,
+) -> BlockModelRegistry {
+    block_models::load_block_models(blocks, |path| packs.read(path))
+}
+
+fn quads_to_mesh_information(quads: &[QuadModel]) -> MeshInformation {
+    let mut mesh_info = MeshInformation::default();
+
+    for quad in quads {
+        let positions = quad.positions();
+        let corners = positions.len();
+
+        let [p0, p1, p2] = [0, 1, 2].map(|i| Vec3::from(positions[i]));
+        let normal = (p1 - p0).cross(p2 - p0).normalize_or_zero();
+
+        let start = mesh_info.positions.len() as u32;
+        let indices: &[u32] = if corners == 4 {
+            &[0, 1, 2, 2, 3, 0]
+        } else {
+            &[0, 1, 2]
+        };
+
+        mesh_info
+            .indices
+            .extend(indices.iter().map(|index| start + index));
+        mesh_info.positions.extend(positions.iter());
+        mesh_info.uvs.extend(quad.uvs().iter());
+        mesh_info
+            .normals
+            .extend(std::iter::repeat(<[f32; 3]>::from(normal)).take(corners));
+    }
+
+    mesh_info
+}
+
+/// Builds the mesh of a block model
+pub(super) fn block_model_mesh(model: &BlockModel) -> BlockMeshInformation {
+    let [right, left, top, bottom, front, back] = [
+        BlockFace::Right,
+        BlockFace::Left,
+        BlockFace::Top,
+        BlockFace::Bottom,
+        BlockFace::Front,
+        BlockFace::Back,
+    ]
+    .map(|face| quads_to_mesh_information(model.face(face).quads()));
+
+    let mut block_mesh_info = BlockMeshInformation::new(
+        model.unlocalized_name(),
+        right,
+        left,
+        top,
+        bottom,
+        front,
+        back,
+    );
+
+    for index in 0..6 {
+        let face = BlockFace::from_index(index);
+        let face_model = model.face(face);
+
+        block_mesh_info.set_covers_face(face, face_model.covers());
+        block_mesh_info
+            .set_inner_info_for_face(face, quads_to_mesh_information(face_model.inner()));
+    }
+
+    block_mesh_info
+}
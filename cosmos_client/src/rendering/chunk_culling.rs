@@ -0,0 +1,134 @@
+//! Hides the chunks of structures that can't be seen from the camera, see [`cosmos_core::structure::occlusion`]
+//!
+//! Chunks outside the camera's view are already skipped by bevy's frustum culling, so this only
+//! handles chunks hidden behind other chunks.
+
+use bevy::{
+    prelude::{
+        Added, App, Changed, Component, Entity, EventReader, GlobalTransform, IntoSystemConfig,
+        Local, OnUpdate, Or, Query, Visibility, With,
+    },
+    utils::{HashMap, HashSet},
+};
+use cosmos_core::structure::{
+    chunk::{ChunkEntity, CHUNK_DIMENSIONS},
+    occlusion::{visible_chunks, ChunkFaceConnectivity},
+    ChunkInitEvent, ChunkState, Structure,
+};
+
+use crate::state::game_state::GameState;
+
+use super::MainCamera;
+
+#[derive(Component, Debug, Clone, Copy)]
+/// Which faces of this chunk can see each other, which is figured out when the chunk is rendered
+pub(super) struct ChunkConnectivity(pub ChunkFaceConnectivity);
+
+fn cull_hidden_chunks(
+    camera: Query<&GlobalTransform, With<MainCamera>>,
+    structures: Query<(Entity, &Structure, &GlobalTransform)>,
+    connectivities: Query<&ChunkConnectivity>,
+    changed_chunks: Query<&ChunkEntity, Or<(Changed<ChunkConnectivity>, Added<ChunkEntity>)>>,
+    mut chunk_init_reader: EventReader<ChunkInitEvent>,
+    mut chunk_visibilities: Query<(&ChunkEntity, &mut Visibility)>,
+    mut last_camera_chunks: Local<HashMap<Entity, (i64, i64, i64)>>,
+) {
+    let Ok(camera_transform) = camera.get_single() else {
+        return;
+    };
+
+    let mut dirty_structures = changed_chunks
+        .iter()
+        .map(|x| x.structure_entity)
+        .chain(chunk_init_reader.iter().map(|x| x.structure_entity))
+        .collect::<HashSet<Entity>>();
+
+    last_camera_chunks.retain(|entity, _| structures.contains(*entity));
+
+    // Only the chunks that are actually drawn need to be looked through, which keeps this small on planets
+    let mut chunk_bounds = HashMap::<Entity, ((usize, usize, usize), (usize, usize, usize))>::new();
+
+    for (chunk_entity, _) in chunk_visibilities.iter() {
+        let (x, y, z) = chunk_entity.chunk_location;
+
+        chunk_bounds
+            .entry(chunk_entity.structure_entity)
+            .and_modify(|(min, max)| {
+                *min = (min.0.min(x), min.1.min(y), min.2.min(z));
+                *max = (max.0.max(x), max.1.max(y), max.2.max(z));
+            })
+            .or_insert(((x, y, z), (x, y, z)));
+    }
+
+    let mut visible_per_structure = HashMap::new();
+
+    for (structure_entity, structure, structure_transform) in structures.iter() {
+        let Some(&bounds) = chunk_bounds.get(&structure_entity) else {
+            continue;
+        };
+
+        let relative = structure_transform
+            .affine()
+            .inverse()
+            .transform_point3(camera_transform.translation());
+
+        let (bx, by, bz) =
+            structure.relative_coords_to_local_coords(relative.x, relative.y, relative.z);
+        let n = CHUNK_DIMENSIONS as i32;
+
+        let camera_chunk = (
+            bx.div_euclid(n) as i64,
+            by.div_euclid(n) as i64,
+            bz.div_euclid(n) as i64,
+        );
+
+        if last_camera_chunks.get(&structure_entity) != Some(&camera_chunk) {
+            last_camera_chunks.insert(structure_entity, camera_chunk);
+            dirty_structures.insert(structure_entity);
+        }
+
+        if !dirty_structures.contains(&structure_entity) {
+            continue;
+        }
+
+        let visible = visible_chunks(
+            camera_chunk,
+            bounds,
+            |(cx, cy, cz)| {
+                structure
+                    .chunk_entity(cx, cy, cz)
+                    .and_then(|entity| connectivities.get(entity).ok())
+                    .map(|x| x.0)
+                    .unwrap_or_default()
+            },
+            // Chunks that aren't loaded yet can't be seen through
+            |(cx, cy, cz)| structure.get_chunk_state(cx, cy, cz) == ChunkState::Loaded,
+        );
+
+        visible_per_structure.insert(structure_entity, visible);
+    }
+
+    if visible_per_structure.is_empty() {
+        return;
+    }
+
+    for (chunk_entity, mut visibility) in chunk_visibilities.iter_mut() {
+        let Some(visible) = visible_per_structure.get(&chunk_entity.structure_entity) else {
+            continue;
+        };
+
+        let new_visibility = if visible.contains(&chunk_entity.chunk_location) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+
+        if *visibility != new_visibility {
+            *visibility = new_visibility;
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_system(cull_hidden_chunks.in_set(OnUpdate(GameState::Playing)));
+}
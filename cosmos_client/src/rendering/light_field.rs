@@ -0,0 +1,1069 @@
+//! Voxel lighting for structures.
+//!
+//! Every loaded chunk of a structure stores the light level of each of its blocks. Block light is flood-filled
+//! out from blocks with [`BlockLighting`], and star light comes in from the open space around the structure.
+//! Star light travels through open blocks facing away from the star without losing any strength, so openings that
+//! face the star let light deep into a structure.
+//!
+//! These light levels are baked into the vertices of chunk meshes, so there is no cost to having lots of lights.
+//!
+//! When the star starts shining on a different side of a structure, all of its star light has to be recalculated.
+//! That is done in the background, since it can take a long time for large structures such as planets.
+
+use std::{collections::VecDeque, sync::Arc, time::Instant};
+
+use bevy::{
+    prelude::{
+        App, Commands, Component, Entity, EventReader, GlobalTransform, IntoSystemConfigs, Local,
+        OnUpdate, Query, Res, Vec3, With,
+    },
+    tasks::{AsyncComputeTaskPool, Task},
+    utils::{HashMap, HashSet},
+};
+use cosmos_core::{
+    block::{Block, BlockFace},
+    events::block_events::BlockChangedEvent,
+    registry::{identifiable::Identifiable, Registry},
+    structure::{
+        chunk::{Chunk, CHUNK_DIMENSIONS},
+        events::ChunkSetEvent,
+        Structure,
+    },
+    universe::star::Star,
+    utils::array_utils::{expand, flatten},
+};
+use futures_lite::future;
+
+use crate::{
+    block::lighting::{BlockLightProperties, BlockLighting},
+    state::game_state::GameState,
+};
+
+use super::structure_renderer::{
+    monitor_needs_rendered_system, ChunkNeedsRendered, ChunkRenderingBudget,
+};
+
+/// The brightest any light can be. Block light loses one level for every block it travels.
+pub const MAX_LIGHT_LEVEL: u8 = 15;
+
+/// Red, green, blue & star light
+const CHANNELS: usize = 4;
+/// How many blocks are in a chunk
+const CHUNK_BLOCKS: usize = CHUNK_DIMENSIONS * CHUNK_DIMENSIONS * CHUNK_DIMENSIONS;
+/// The channel star light is stored in
+const STAR_CHANNEL: usize = 3;
+
+/// How much brightness is lost for every level below `MAX_LIGHT_LEVEL`
+const LIGHT_FALLOFF: f32 = 0.8;
+
+/// How much more the star has to line up with a new side of a structure before star light is recalculated.
+///
+/// This stops structures that are rotating from recalculating their star light every frame.
+const STAR_FACE_HYSTERESIS: f32 = 0.1;
+
+const NEIGHBORS: [(i32, i32, i32); 6] = [
+    (1, 0, 0),
+    (-1, 0, 0),
+    (0, 1, 0),
+    (0, -1, 0),
+    (0, 0, 1),
+    (0, 0, -1),
+];
+
+type Coords = (i32, i32, i32);
+type ChunkCoords = (usize, usize, usize);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+/// The red, green & blue block light + the star light at a block
+pub struct LightLevel([u8; CHANNELS]);
+
+impl LightLevel {
+    /// Anywhere not covered by a loaded chunk - no block light, but fully lit by the star
+    pub const OPEN_SPACE: Self = Self([0, 0, 0, MAX_LIGHT_LEVEL]);
+
+    /// Converts this light level to the color stored in a chunk mesh's vertices.
+    ///
+    /// The rgb is the block light, and the alpha is the star light.
+    pub fn vertex_color(&self) -> [f32; 4] {
+        self.0.map(|level| {
+            if level == 0 {
+                0.0
+            } else {
+                LIGHT_FALLOFF.powi((MAX_LIGHT_LEVEL - level) as i32)
+            }
+        })
+    }
+}
+
+/// Returns the light level this light gives off in each color channel
+fn emitted_light(properties: &BlockLightProperties) -> [u8; 3] {
+    let range = properties.range.clamp(0.0, MAX_LIGHT_LEVEL as f32);
+    let color = properties.color;
+
+    [color.r(), color.g(), color.b()].map(|c| (range * c.clamp(0.0, 1.0)).round() as u8)
+}
+
+#[derive(Component, Debug, Default)]
+/// Stores the light level of every block in a structure's loaded chunks
+pub struct StructureLightField {
+    chunks: HashMap<ChunkCoords, Vec<LightLevel>>,
+    /// The side of the structure that is facing the star
+    star_face: Option<BlockFace>,
+}
+
+impl StructureLightField {
+    /// Gets the light level at these structure block coordinates.
+    ///
+    /// Blocks that are outside the structure or in a chunk that isn't loaded are in open space.
+    pub fn light_at(&self, x: i32, y: i32, z: i32) -> LightLevel {
+        self.cell(x, y, z)
+            .and_then(|(chunk, index)| self.chunks.get(&chunk).map(|field| field[index]))
+            .unwrap_or(LightLevel::OPEN_SPACE)
+    }
+
+    /// Copies the light levels of this chunk & every chunk around it, which is all that's needed to render this chunk
+    pub fn around_chunk(&self, (cx, cy, cz): ChunkCoords) -> Self {
+        let mut chunks = HashMap::new();
+
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let (Some(x), Some(y), Some(z)) = (
+                        cx.checked_add_signed(dx),
+                        cy.checked_add_signed(dy),
+                        cz.checked_add_signed(dz),
+                    ) else {
+                        continue;
+                    };
+
+                    if let Some(field) = self.chunks.get(&(x, y, z)) {
+                        chunks.insert((x, y, z), field.clone());
+                    }
+                }
+            }
+        }
+
+        Self {
+            chunks,
+            star_face: self.star_face,
+        }
+    }
+
+    fn cell(&self, x: i32, y: i32, z: i32) -> Option<(ChunkCoords, usize)> {
+        if x < 0 || y < 0 || z < 0 {
+            return None;
+        }
+
+        let (x, y, z) = (x as usize, y as usize, z as usize);
+
+        Some((
+            (
+                x / CHUNK_DIMENSIONS,
+                y / CHUNK_DIMENSIONS,
+                z / CHUNK_DIMENSIONS,
+            ),
+            flatten(
+                x % CHUNK_DIMENSIONS,
+                y % CHUNK_DIMENSIONS,
+                z % CHUNK_DIMENSIONS,
+                CHUNK_DIMENSIONS,
+                CHUNK_DIMENSIONS,
+            ),
+        ))
+    }
+
+    fn has_field_at(&self, (x, y, z): Coords) -> bool {
+        self.cell(x, y, z)
+            .map(|(chunk, _)| self.chunks.contains_key(&chunk))
+            .unwrap_or(false)
+    }
+
+    /// The direction star light travels in without getting weaker
+    fn star_beam(&self) -> Option<Coords> {
+        self.star_face.map(|face| {
+            let (x, y, z) = face.direction();
+            (-x, -y, -z)
+        })
+    }
+}
+
+/// What light needs to know about the blocks it travels through
+trait LightBlocks {
+    /// Returns true if there is a loaded chunk at these chunk coordinates
+    fn has_chunk(&self, chunk: ChunkCoords) -> bool;
+
+    /// Returns true if light can't travel through the block at these structure block coordinates
+    fn is_opaque(&self, coords: Coords) -> bool;
+
+    /// The light level the block at these structure block coordinates gives off in this red, green or blue channel
+    fn emission(&self, coords: Coords, channel: usize) -> u8;
+}
+
+/// The blocks of a loaded structure
+struct StructureBlocks<'a> {
+    structure: &'a Structure,
+    blocks: &'a Registry<Block>,
+    lighting: &'a Registry<BlockLighting>,
+}
+
+impl<'a> StructureBlocks<'a> {
+    fn block(&self, (x, y, z): Coords) -> Option<&'a Block> {
+        let (x, y, z) = (x as usize, y as usize, z as usize);
+
+        if self.structure.is_within_blocks(x, y, z) {
+            Some(self.structure.block_at(x, y, z, self.blocks))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> LightBlocks for StructureBlocks<'a> {
+    fn has_chunk(&self, (cx, cy, cz): ChunkCoords) -> bool {
+        self.structure
+            .chunk_from_chunk_coordinates(cx, cy, cz)
+            .is_some()
+    }
+
+    fn is_opaque(&self, coords: Coords) -> bool {
+        self.block(coords)
+            .map(|block| !block.is_see_through())
+            .unwrap_or(false)
+    }
+
+    fn emission(&self, coords: Coords, channel: usize) -> u8 {
+        self.block(coords)
+            .and_then(|block| self.lighting.from_id(block.unlocalized_name()))
+            .map(|lighting| emitted_light(&lighting.properties)[channel])
+            .unwrap_or(0)
+    }
+}
+
+/// A copy of a structure's loaded chunks, so its star light can be recalculated in the background.
+///
+/// No block gives off star light, so this doesn't keep track of any lights.
+struct ChunksSnapshot {
+    chunks: HashMap<ChunkCoords, Chunk>,
+    blocks: Arc<Registry<Block>>,
+}
+
+impl LightBlocks for ChunksSnapshot {
+    fn has_chunk(&self, chunk: ChunkCoords) -> bool {
+        self.chunks.contains_key(&chunk)
+    }
+
+    fn is_opaque(&self, (x, y, z): Coords) -> bool {
+        if x < 0 || y < 0 || z < 0 {
+            return false;
+        }
+
+        let (x, y, z) = (x as usize, y as usize, z as usize);
+
+        self.chunks
+            .get(&(
+                x / CHUNK_DIMENSIONS,
+                y / CHUNK_DIMENSIONS,
+                z / CHUNK_DIMENSIONS,
+            ))
+            .map(|chunk| {
+                !chunk.has_see_through_block_at(
+                    x % CHUNK_DIMENSIONS,
+                    y % CHUNK_DIMENSIONS,
+                    z % CHUNK_DIMENSIONS,
+                    &self.blocks,
+                )
+            })
+            .unwrap_or(false)
+    }
+
+    fn emission(&self, _: Coords, _: usize) -> u8 {
+        0
+    }
+}
+
+/// Flood fills light through a structure, keeping track of which chunks had their light changed
+struct LightPropagator<'a, B: LightBlocks> {
+    blocks: &'a B,
+    field: &'a mut StructureLightField,
+    changed_chunks: HashSet<ChunkCoords>,
+}
+
+impl<'a, B: LightBlocks> LightPropagator<'a, B> {
+    fn new(blocks: &'a B, field: &'a mut StructureLightField) -> Self {
+        Self {
+            blocks,
+            field,
+            changed_chunks: HashSet::default(),
+        }
+    }
+
+    fn get(&self, (x, y, z): Coords, channel: usize) -> u8 {
+        self.field.light_at(x, y, z).0[channel]
+    }
+
+    /// Does nothing if there is no light field at these coordinates
+    fn set(&mut self, (x, y, z): Coords, channel: usize, level: u8) {
+        let Some((chunk, index)) = self.field.cell(x, y, z) else {
+            return;
+        };
+
+        if let Some(field) = self.field.chunks.get_mut(&chunk) {
+            if field[index].0[channel] != level {
+                field[index].0[channel] = level;
+                self.changed_chunks.insert(chunk);
+            }
+        }
+    }
+
+    fn is_opaque(&self, coords: Coords) -> bool {
+        self.blocks.is_opaque(coords)
+    }
+
+    fn emission(&self, coords: Coords, channel: usize) -> u8 {
+        if channel == STAR_CHANNEL {
+            return 0;
+        }
+
+        self.blocks.emission(coords, channel)
+    }
+
+    /// The light level a block gets from a neighbor with this light level
+    fn spread(&self, level: u8, offset: Coords, channel: usize) -> u8 {
+        if channel == STAR_CHANNEL
+            && level == MAX_LIGHT_LEVEL
+            && self.field.star_beam() == Some(offset)
+        {
+            MAX_LIGHT_LEVEL
+        } else {
+            level.saturating_sub(1)
+        }
+    }
+
+    /// Spreads the light of every block in the queue to its neighbors
+    fn add(&mut self, mut queue: VecDeque<Coords>, channel: usize) {
+        while let Some(coords) = queue.pop_front() {
+            let level = self.get(coords, channel);
+            if level <= 1 {
+                continue;
+            }
+
+            for offset in NEIGHBORS {
+                let neighbor = (
+                    coords.0 + offset.0,
+                    coords.1 + offset.1,
+                    coords.2 + offset.2,
+                );
+
+                if !self.field.has_field_at(neighbor) || self.is_opaque(neighbor) {
+                    continue;
+                }
+
+                let new_level = self.spread(level, offset, channel);
+                if self.get(neighbor, channel) < new_level {
+                    self.set(neighbor, channel, new_level);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+
+    /// Removes all the light that came from the blocks in the queue, which have already been set to 0.
+    ///
+    /// Returns the blocks whose light should be spread again to fill in the space left behind.
+    fn remove(&mut self, mut queue: VecDeque<(Coords, u8)>, channel: usize) -> VecDeque<Coords> {
+        let mut readd = VecDeque::new();
+
+        while let Some((coords, old_level)) = queue.pop_front() {
+            for offset in NEIGHBORS {
+                let neighbor = (
+                    coords.0 + offset.0,
+                    coords.1 + offset.1,
+                    coords.2 + offset.2,
+                );
+
+                let level = self.get(neighbor, channel);
+                if level == 0 {
+                    continue;
+                }
+
+                if !self.field.has_field_at(neighbor) {
+                    // Open space is always lit
+                    readd.push_back(neighbor);
+                    continue;
+                }
+
+                let came_from_here = level < old_level
+                    || (channel == STAR_CHANNEL
+                        && old_level == MAX_LIGHT_LEVEL
+                        && level == MAX_LIGHT_LEVEL
+                        && self.field.star_beam() == Some(offset));
+
+                if came_from_here {
+                    self.set(neighbor, channel, 0);
+                    queue.push_back((neighbor, level));
+
+                    let emission = self.emission(neighbor, channel);
+                    if emission > 0 {
+                        self.set(neighbor, channel, emission);
+                        readd.push_back(neighbor);
+                    }
+                } else {
+                    readd.push_back(neighbor);
+                }
+            }
+        }
+
+        readd
+    }
+
+    /// Recalculates the light around a block that was just changed
+    fn block_changed(&mut self, coords: Coords) {
+        let Some((chunk, _)) = self.field.cell(coords.0, coords.1, coords.2) else {
+            return;
+        };
+
+        if !self.field.chunks.contains_key(&chunk) {
+            self.relight_chunk(chunk);
+            return;
+        }
+
+        let opaque = self.is_opaque(coords);
+
+        for channel in 0..CHANNELS {
+            let old_level = self.get(coords, channel);
+            self.set(coords, channel, 0);
+
+            let mut readd = if old_level > 0 {
+                self.remove(VecDeque::from([(coords, old_level)]), channel)
+            } else {
+                VecDeque::new()
+            };
+
+            let emission = self.emission(coords, channel);
+            if emission > 0 {
+                self.set(coords, channel, emission);
+                readd.push_back(coords);
+            }
+
+            if !opaque {
+                // Let the light around this block flow into it
+                for offset in NEIGHBORS {
+                    readd.push_back((
+                        coords.0 + offset.0,
+                        coords.1 + offset.1,
+                        coords.2 + offset.2,
+                    ));
+                }
+            }
+
+            self.add(readd, channel);
+        }
+    }
+
+    /// Iterates over the structure coordinates of every block in this chunk
+    fn chunk_blocks((cx, cy, cz): ChunkCoords) -> impl Iterator<Item = Coords> {
+        (0..CHUNK_BLOCKS).map(move |i| {
+            let (x, y, z) = expand(i, CHUNK_DIMENSIONS, CHUNK_DIMENSIONS);
+
+            (
+                (cx * CHUNK_DIMENSIONS + x) as i32,
+                (cy * CHUNK_DIMENSIONS + y) as i32,
+                (cz * CHUNK_DIMENSIONS + z) as i32,
+            )
+        })
+    }
+
+    /// Iterates over the blocks just outside of this chunk, paired with the block inside the chunk they touch
+    fn chunk_border((cx, cy, cz): ChunkCoords) -> impl Iterator<Item = (Coords, Coords)> {
+        let min = (
+            (cx * CHUNK_DIMENSIONS) as i32,
+            (cy * CHUNK_DIMENSIONS) as i32,
+            (cz * CHUNK_DIMENSIONS) as i32,
+        );
+        let max = (
+            min.0 + CHUNK_DIMENSIONS as i32 - 1,
+            min.1 + CHUNK_DIMENSIONS as i32 - 1,
+            min.2 + CHUNK_DIMENSIONS as i32 - 1,
+        );
+
+        Self::chunk_blocks((cx, cy, cz)).flat_map(move |(x, y, z)| {
+            NEIGHBORS
+                .into_iter()
+                .map(move |(dx, dy, dz)| ((x + dx, y + dy, z + dz), (x, y, z)))
+                .filter(move |((ox, oy, oz), _)| {
+                    *ox < min.0
+                        || *ox > max.0
+                        || *oy < min.1
+                        || *oy > max.1
+                        || *oz < min.2
+                        || *oz > max.2
+                })
+        })
+    }
+
+    /// Recalculates all the light in this chunk, for when it has just been loaded or replaced
+    fn relight_chunk(&mut self, chunk: ChunkCoords) {
+        if !self.blocks.has_chunk(chunk) {
+            return;
+        }
+
+        let existed = self.field.chunks.contains_key(&chunk);
+
+        if !existed {
+            self.field
+                .chunks
+                .insert(chunk, vec![LightLevel::default(); CHUNK_BLOCKS]);
+            self.changed_chunks.insert(chunk);
+        }
+
+        for channel in 0..CHANNELS {
+            let mut removed = VecDeque::new();
+
+            if existed {
+                for coords in Self::chunk_blocks(chunk) {
+                    let level = self.get(coords, channel);
+                    if level > 0 {
+                        self.set(coords, channel, 0);
+                        removed.push_back((coords, level));
+                    }
+                }
+            } else {
+                // This chunk used to be open space, so its neighbors may have been lit by it
+                let open_level = LightLevel::OPEN_SPACE.0[channel];
+                if open_level > 0 {
+                    for (_, coords) in Self::chunk_border(chunk) {
+                        removed.push_back((coords, open_level));
+                    }
+                }
+            }
+
+            let mut readd = self.remove(removed, channel);
+
+            for coords in Self::chunk_blocks(chunk) {
+                let emission = self.emission(coords, channel);
+                if emission > 0 {
+                    self.set(coords, channel, emission);
+                    readd.push_back(coords);
+                }
+            }
+
+            readd.extend(Self::chunk_border(chunk).map(|(outside, _)| outside));
+
+            self.add(readd, channel);
+        }
+    }
+
+    /// Recalculates the star light of every loaded chunk, for when the star starts shining on a different side
+    fn relight_star(&mut self) {
+        let chunks = self
+            .field
+            .chunks
+            .keys()
+            .copied()
+            .collect::<Vec<ChunkCoords>>();
+
+        for field in self.field.chunks.values_mut() {
+            for level in field.iter_mut() {
+                level.0[STAR_CHANNEL] = 0;
+            }
+        }
+
+        let mut queue = VecDeque::new();
+
+        for &chunk in chunks.iter() {
+            self.changed_chunks.insert(chunk);
+
+            queue.extend(
+                Self::chunk_border(chunk)
+                    .map(|(outside, _)| outside)
+                    .filter(|outside| !self.field.has_field_at(*outside)),
+            );
+        }
+
+        self.add(queue, STAR_CHANNEL);
+    }
+
+    /// Tells the chunks that had their light changed to be rendered again
+    ///
+    /// Returns the chunks that had their light changed.
+    fn finish(self, structure: &Structure, commands: &mut Commands) -> HashSet<ChunkCoords> {
+        for &(cx, cy, cz) in self.changed_chunks.iter() {
+            if let Some(chunk_entity) = structure.chunk_entity(cx, cy, cz) {
+                if let Some(mut ecmds) = commands.get_entity(chunk_entity) {
+                    ecmds.insert(ChunkNeedsRendered);
+                }
+            }
+        }
+
+        self.changed_chunks
+    }
+}
+
+#[derive(Component)]
+/// The star light of this structure is being recalculated in the background.
+///
+/// Removing this cancels the recalculation.
+struct RelightingStar {
+    task: Task<StructureLightField>,
+    /// Chunks that had their light changed since the recalculation started, which have to be relit once it's done
+    dirty_chunks: HashSet<ChunkCoords>,
+}
+
+fn update_light_fields(
+    mut commands: Commands,
+    mut structures: Query<(
+        &Structure,
+        Option<&mut StructureLightField>,
+        Option<&mut RelightingStar>,
+    )>,
+    mut block_changed_reader: EventReader<BlockChangedEvent>,
+    mut chunk_set_reader: EventReader<ChunkSetEvent>,
+    blocks: Res<Registry<Block>>,
+    lighting: Res<Registry<BlockLighting>>,
+) {
+    let mut chunks_set = HashMap::<Entity, Vec<ChunkCoords>>::default();
+    let mut blocks_changed = HashMap::<Entity, Vec<Coords>>::default();
+
+    for ev in chunk_set_reader.iter() {
+        chunks_set
+            .entry(ev.structure_entity)
+            .or_default()
+            .push((ev.x, ev.y, ev.z));
+    }
+
+    for ev in block_changed_reader.iter() {
+        blocks_changed
+            .entry(ev.structure_entity)
+            .or_default()
+            .push((
+                ev.block.x() as i32,
+                ev.block.y() as i32,
+                ev.block.z() as i32,
+            ));
+    }
+
+    let mut to_update = chunks_set.keys().copied().collect::<HashSet<Entity>>();
+    to_update.extend(blocks_changed.keys().copied());
+
+    for structure_entity in to_update {
+        let Ok((structure, field, relighting)) = structures.get_mut(structure_entity) else {
+            continue;
+        };
+
+        let structure_blocks = StructureBlocks {
+            structure,
+            blocks: &blocks,
+            lighting: &lighting,
+        };
+
+        match field {
+            Some(mut field) => {
+                let mut propagator = LightPropagator::new(&structure_blocks, &mut field);
+
+                for chunk in chunks_set.remove(&structure_entity).unwrap_or_default() {
+                    propagator.relight_chunk(chunk);
+                }
+
+                for coords in blocks_changed.remove(&structure_entity).unwrap_or_default() {
+                    propagator.block_changed(coords);
+                }
+
+                let changed_chunks = propagator.finish(structure, &mut commands);
+
+                if let Some(mut relighting) = relighting {
+                    relighting.dirty_chunks.extend(changed_chunks);
+                }
+            }
+            None => {
+                // The first time this structure has changed, so everything that's loaded needs lit
+                let mut field = StructureLightField::default();
+
+                let mut propagator = LightPropagator::new(&structure_blocks, &mut field);
+
+                for chunk in structure.chunks().values() {
+                    propagator.relight_chunk((
+                        chunk.structure_x(),
+                        chunk.structure_y(),
+                        chunk.structure_z(),
+                    ));
+                }
+
+                propagator.finish(structure, &mut commands);
+
+                commands.entity(structure_entity).insert(field);
+            }
+        }
+    }
+}
+
+/// Forgets the light of chunks that have been unloaded
+fn forget_unloaded_chunks(mut structures: Query<(&Structure, &mut StructureLightField)>) {
+    for (structure, mut field) in structures.iter_mut() {
+        // Every chunk with light is loaded, so if the counts match then nothing needs to be removed
+        if field.chunks.len() != structure.chunks().len() {
+            field.chunks.retain(|&(cx, cy, cz), _| {
+                structure.chunk_from_chunk_coordinates(cx, cy, cz).is_some()
+            });
+        }
+    }
+}
+
+/// Finds which side of each structure the star is shining on, & starts recalculating its star light in the background if that changed
+///
+/// Copying a structure's chunks for the background can take a while, so this is limited by the [`ChunkRenderingBudget`].
+fn update_star_faces(
+    mut commands: Commands,
+    star: Query<&GlobalTransform, With<Star>>,
+    mut structures: Query<(
+        Entity,
+        &Structure,
+        &GlobalTransform,
+        &mut StructureLightField,
+    )>,
+    blocks: Res<Registry<Block>>,
+    budget: Res<ChunkRenderingBudget>,
+    mut shared_blocks: Local<Option<Arc<Registry<Block>>>>,
+) {
+    let start = Instant::now();
+    let mut started = 0;
+
+    let star_translation = star.get_single().ok().map(|x| x.translation());
+
+    for (entity, structure, transform, mut field) in structures.iter_mut() {
+        let direction = star_translation
+            .map(|star_translation| {
+                let (_, rotation, translation) = transform.to_scale_rotation_translation();
+                (rotation.inverse() * (star_translation - translation)).normalize_or_zero()
+            })
+            .filter(|direction| *direction != Vec3::ZERO);
+
+        let star_face = direction.and_then(|direction| {
+            [
+                BlockFace::Right,
+                BlockFace::Left,
+                BlockFace::Top,
+                BlockFace::Bottom,
+                BlockFace::Front,
+                BlockFace::Back,
+            ]
+            .into_iter()
+            .map(|face| (face, face.direction_vec3().dot(direction)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+        });
+
+        let needs_relit = match (field.star_face, star_face, direction) {
+            (Some(old_face), Some((new_face, new_amount)), Some(direction)) => {
+                old_face != new_face
+                    && new_amount > old_face.direction_vec3().dot(direction) + STAR_FACE_HYSTERESIS
+            }
+            (None, None, _) => false,
+            _ => true,
+        };
+
+        if !needs_relit {
+            continue;
+        }
+
+        // Structures that don't fit in this frame will be started next frame, since their star face is left as is
+        if started != 0 && start.elapsed() > budget.frame_time {
+            break;
+        }
+
+        started += 1;
+
+        field.star_face = star_face.map(|(face, _)| face);
+
+        if shared_blocks.is_none() || blocks.is_changed() {
+            *shared_blocks = Some(Arc::new(blocks.clone()));
+        }
+
+        let snapshot = ChunksSnapshot {
+            chunks: structure
+                .chunks()
+                .values()
+                .map(|chunk| {
+                    (
+                        (
+                            chunk.structure_x(),
+                            chunk.structure_y(),
+                            chunk.structure_z(),
+                        ),
+                        chunk.clone(),
+                    )
+                })
+                .collect(),
+            blocks: shared_blocks.clone().expect("This was just set"),
+        };
+
+        let mut star_field = StructureLightField {
+            chunks: HashMap::default(),
+            star_face: field.star_face,
+        };
+
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            for &chunk in snapshot.chunks.keys() {
+                star_field
+                    .chunks
+                    .insert(chunk, vec![LightLevel::default(); CHUNK_BLOCKS]);
+            }
+
+            LightPropagator::new(&snapshot, &mut star_field).relight_star();
+
+            star_field
+        });
+
+        // Any recalculation that was already running is out of date, & is cancelled by this replacing it
+        commands.entity(entity).insert(RelightingStar {
+            task,
+            dirty_chunks: HashSet::default(),
+        });
+    }
+}
+
+/// Copies the star light that finished being recalculated in the background into each structure's light field,
+/// within the [`ChunkRenderingBudget`]
+fn finish_relighting_star(
+    mut commands: Commands,
+    mut structures: Query<(
+        Entity,
+        &Structure,
+        &mut StructureLightField,
+        &mut RelightingStar,
+    )>,
+    blocks: Res<Registry<Block>>,
+    lighting: Res<Registry<BlockLighting>>,
+    budget: Res<ChunkRenderingBudget>,
+) {
+    let start = Instant::now();
+    let mut finished = 0;
+
+    for (entity, structure, mut field, mut relighting) in structures.iter_mut() {
+        if finished != 0 && start.elapsed() > budget.frame_time {
+            break;
+        }
+
+        let Some(star_field) = future::block_on(future::poll_once(&mut relighting.task)) else {
+            continue;
+        };
+
+        finished += 1;
+
+        commands.entity(entity).remove::<RelightingStar>();
+
+        let mut changed_chunks = HashSet::default();
+
+        for (chunk, star_levels) in star_field.chunks {
+            // These were changed after the copy was made, so their light is relit below instead
+            if relighting.dirty_chunks.contains(&chunk) {
+                continue;
+            }
+
+            let Some(levels) = field.chunks.get_mut(&chunk) else {
+                continue;
+            };
+
+            for (level, star_level) in levels.iter_mut().zip(star_levels) {
+                if level.0[STAR_CHANNEL] != star_level.0[STAR_CHANNEL] {
+                    level.0[STAR_CHANNEL] = star_level.0[STAR_CHANNEL];
+                    changed_chunks.insert(chunk);
+                }
+            }
+        }
+
+        let structure_blocks = StructureBlocks {
+            structure,
+            blocks: &blocks,
+            lighting: &lighting,
+        };
+
+        let mut propagator = LightPropagator::new(&structure_blocks, &mut field);
+        propagator.changed_chunks = changed_chunks;
+
+        for chunk in std::mem::take(&mut relighting.dirty_chunks) {
+            propagator.relight_chunk(chunk);
+        }
+
+        propagator.finish(structure, &mut commands);
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems(
+        (
+            forget_unloaded_chunks,
+            update_light_fields,
+            update_star_faces,
+            finish_relighting_star,
+        )
+            .chain()
+            .in_set(OnUpdate(GameState::Playing))
+            .before(monitor_needs_rendered_system),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAST: i32 = CHUNK_DIMENSIONS as i32 - 1;
+
+    #[derive(Default)]
+    struct TestBlocks {
+        chunks: HashSet<ChunkCoords>,
+        opaque: HashSet<Coords>,
+        lights: HashMap<Coords, [u8; 3]>,
+    }
+
+    impl LightBlocks for TestBlocks {
+        fn has_chunk(&self, chunk: ChunkCoords) -> bool {
+            self.chunks.contains(&chunk)
+        }
+
+        fn is_opaque(&self, coords: Coords) -> bool {
+            self.opaque.contains(&coords)
+        }
+
+        fn emission(&self, coords: Coords, channel: usize) -> u8 {
+            self.lights
+                .get(&coords)
+                .map(|light| light[channel])
+                .unwrap_or(0)
+        }
+    }
+
+    fn one_chunk() -> TestBlocks {
+        TestBlocks {
+            chunks: HashSet::from_iter([(0, 0, 0)]),
+            ..Default::default()
+        }
+    }
+
+    /// Lights every chunk from scratch
+    fn lit_field(blocks: &TestBlocks, star_face: Option<BlockFace>) -> StructureLightField {
+        let mut field = StructureLightField {
+            chunks: HashMap::default(),
+            star_face,
+        };
+
+        let mut propagator = LightPropagator::new(blocks, &mut field);
+
+        for &chunk in blocks.chunks.iter() {
+            propagator.relight_chunk(chunk);
+        }
+
+        field
+    }
+
+    /// A chunk with opaque walls all around it, except for a hole in its top at (5, LAST, 5)
+    fn closed_chunk() -> TestBlocks {
+        let mut blocks = one_chunk();
+
+        for a in 0..=LAST {
+            for b in 0..=LAST {
+                for coords in [
+                    (0, a, b),
+                    (LAST, a, b),
+                    (a, 0, b),
+                    (a, LAST, b),
+                    (a, b, 0),
+                    (a, b, LAST),
+                ] {
+                    blocks.opaque.insert(coords);
+                }
+            }
+        }
+
+        blocks.opaque.remove(&(5, LAST, 5));
+
+        blocks
+    }
+
+    #[test]
+    fn block_light_falls_off() {
+        let mut blocks = one_chunk();
+        blocks.lights.insert((5, 5, 5), [MAX_LIGHT_LEVEL, 0, 0]);
+
+        let field = lit_field(&blocks, None);
+
+        assert_eq!(field.light_at(5, 5, 5).0[0], MAX_LIGHT_LEVEL);
+        assert_eq!(field.light_at(6, 5, 5).0[0], MAX_LIGHT_LEVEL - 1);
+        assert_eq!(field.light_at(7, 6, 5).0[0], MAX_LIGHT_LEVEL - 3);
+        assert_eq!(field.light_at(5, 5, 5).0[1], 0);
+    }
+
+    #[test]
+    fn opaque_blocks_stop_light() {
+        let mut blocks = one_chunk();
+        blocks.lights.insert((5, 5, 5), [MAX_LIGHT_LEVEL, 0, 0]);
+
+        for y in 0..=LAST {
+            for z in 0..=LAST {
+                blocks.opaque.insert((6, y, z));
+            }
+        }
+
+        let field = lit_field(&blocks, None);
+
+        assert_eq!(field.light_at(6, 5, 5).0[0], 0);
+        assert_eq!(field.light_at(7, 5, 5).0[0], 0);
+    }
+
+    #[test]
+    fn removing_a_light_removes_its_light() {
+        let mut blocks = one_chunk();
+        blocks.lights.insert((5, 5, 5), [MAX_LIGHT_LEVEL, 0, 0]);
+        blocks.lights.insert((20, 5, 5), [0, MAX_LIGHT_LEVEL, 0]);
+
+        let mut field = lit_field(&blocks, None);
+
+        blocks.lights.remove(&(5, 5, 5));
+        LightPropagator::new(&blocks, &mut field).block_changed((5, 5, 5));
+
+        assert_eq!(field.light_at(8, 5, 5).0[0], 0);
+        assert_eq!(field.chunks, lit_field(&blocks, None).chunks);
+    }
+
+    #[test]
+    fn placing_a_block_removes_the_light_it_blocks() {
+        let mut blocks = closed_chunk();
+
+        let mut field = lit_field(&blocks, Some(BlockFace::Top));
+
+        blocks.opaque.insert((5, LAST - 1, 5));
+        LightPropagator::new(&blocks, &mut field).block_changed((5, LAST - 1, 5));
+
+        assert_eq!(field.light_at(5, 1, 5).0[STAR_CHANNEL], 0);
+        assert_eq!(
+            field.chunks,
+            lit_field(&blocks, Some(BlockFace::Top)).chunks
+        );
+    }
+
+    #[test]
+    fn star_light_travels_towards_the_star_face() {
+        let blocks = closed_chunk();
+
+        let field = lit_field(&blocks, Some(BlockFace::Top));
+
+        // Straight down from the hole, star light doesn't get weaker
+        assert_eq!(field.light_at(5, 1, 5).0[STAR_CHANNEL], MAX_LIGHT_LEVEL);
+        // But it does get weaker as it spreads out from there
+        assert_eq!(field.light_at(8, 1, 5).0[STAR_CHANNEL], MAX_LIGHT_LEVEL - 3);
+    }
+
+    #[test]
+    fn relighting_star_matches_lighting_from_scratch() {
+        let blocks = closed_chunk();
+
+        let mut field = lit_field(&blocks, Some(BlockFace::Top));
+
+        field.star_face = Some(BlockFace::Bottom);
+        LightPropagator::new(&blocks, &mut field).relight_star();
+
+        assert!(field.light_at(5, 1, 5).0[STAR_CHANNEL] < MAX_LIGHT_LEVEL);
+        assert_eq!(
+            field.chunks,
+            lit_field(&blocks, Some(BlockFace::Bottom)).chunks
+        );
+    }
+}
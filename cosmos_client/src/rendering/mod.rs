@@ -5,7 +5,7 @@ use bevy::{
     render::{mesh::Indices, render_resource::PrimitiveTopology},
 };
 use cosmos_core::{
-    block::{Block, BlockFace},
+    block::{block_models::BlockModelRegistry, Block, BlockFace},
     registry::{
         identifiable::Identifiable,
         many_to_one::{self, ManyToOneRegistry},
@@ -13,8 +13,14 @@ use cosmos_core::{
     },
 };
 
-use crate::state::game_state::GameState;
+use crate::{
+    asset::resource_packs::{ResourcePacks, ResourcePacksChangedEvent},
+    state::game_state::GameState,
+};
 
+mod block_models;
+mod chunk_culling;
+pub mod light_field;
 mod structure_renderer;
 
 #[derive(Component, Debug)]
@@ -108,11 +114,15 @@ impl MeshBuilder for CosmosMeshBuilder {
     }
 }
 
-#[derive(Default, Debug, Reflect, FromReflect)]
+#[derive(Default, Debug, Reflect, FromReflect, Clone)]
 /// Stores all the mesh information for a block
 pub struct BlockMeshInformation {
     /// Make sure this is in the same order as the [`BlockFace::index`] method.
     mesh_info: [MeshInformation; 6],
+    /// Geometry that is never hidden by neighboring blocks, textured with the face at that index.
+    inner_mesh_info: [MeshInformation; 6],
+    /// If the face at that index completely covers that side of the block, hiding the face of any block next to it.
+    covers: [bool; 6],
 
     id: u16,
     unlocalized_name: String,
@@ -135,6 +145,8 @@ impl Identifiable for BlockMeshInformation {
 impl BlockMeshInformation {
     /// Creates the mesh information for a block.
     ///
+    /// Every face is assumed to cover its entire side of the block, use [`Self::set_covers_face`] to change that.
+    ///
     /// Make sure the mesh information is given in the proper order
     pub fn new(
         unlocalized_name: impl Into<String>,
@@ -164,6 +176,8 @@ impl BlockMeshInformation {
                BlockFace::Back => 5,
             */
             mesh_info: [right, left, top, bottom, front, back],
+            inner_mesh_info: Default::default(),
+            covers: [true; 6],
             id: 0,
             unlocalized_name: unlocalized_name.into(),
         }
@@ -173,11 +187,38 @@ impl BlockMeshInformation {
     pub fn info_for_face(&self, face: BlockFace) -> &MeshInformation {
         &self.mesh_info[face.index()]
     }
+
+    /// Gets the mesh information inside the block that uses this face's texture.
+    ///
+    /// Unlike [`Self::info_for_face`], this is never hidden by the blocks around it.
+    pub fn inner_info_for_face(&self, face: BlockFace) -> &MeshInformation {
+        &self.inner_mesh_info[face.index()]
+    }
+
+    /// Sets the mesh information inside the block that uses this face's texture
+    pub fn set_inner_info_for_face(&mut self, face: BlockFace, mesh_info: MeshInformation) {
+        self.inner_mesh_info[face.index()] = mesh_info;
+    }
+
+    /// Returns true if this face completely covers its side of the block, meaning the face of the block
+    /// touching it can never be seen.
+    pub fn covers_face(&self, face: BlockFace) -> bool {
+        self.covers[face.index()]
+    }
+
+    /// Sets if this face completely covers its side of the block
+    pub fn set_covers_face(&mut self, face: BlockFace, covers: bool) {
+        self.covers[face.index()] = covers;
+    }
 }
 
 fn register_meshes(mut registry: ResMut<BlockMeshRegistry>) {
-    // Model for a basic cube.
-    registry.insert_value(BlockMeshInformation::new(
+    registry.insert_value(base_block_mesh_information());
+}
+
+/// Model for a basic cube.
+fn base_block_mesh_information() -> BlockMeshInformation {
+    BlockMeshInformation::new(
         "cosmos:base_block",
         MeshInformation {
             indices: vec![0, 1, 2, 2, 3, 0],
@@ -245,18 +286,71 @@ fn register_meshes(mut registry: ResMut<BlockMeshRegistry>) {
             ],
             normals: [[0.0, 0.0, 1.0]; 4].to_vec(),
         },
-    ));
+    )
+}
+
+fn link_block_meshes(
+    blocks: &Registry<Block>,
+    models: &BlockModelRegistry,
+    model_registry: &mut BlockMeshRegistry,
+) {
+    for block in blocks.iter() {
+        // Blocks without a valid model were already warned about when loading the models, and are drawn as full cubes
+        let Some(model) = models.get_value(block) else {
+            model_registry
+                .add_link(block, "cosmos:base_block")
+                .expect("The base block mesh is always registered");
+            continue;
+        };
+
+        let model_name = model.unlocalized_name();
+
+        if model_registry.add_link(block, model_name).is_err() {
+            model_registry.insert_value(block_models::block_model_mesh(model));
+
+            model_registry
+                .add_link(block, model_name)
+                .unwrap_or_else(|_| {
+                    panic!("{model_name} model link wasn't inserted successfully!")
+                });
+        }
+    }
+}
+
+fn load_block_models(
+    blocks: Res<Registry<Block>>,
+    packs: Res<ResourcePacks>,
+    mut models: ResMut<BlockModelRegistry>,
+) {
+    *models = block_models::load_block_models(&blocks, &packs);
 }
 
 fn register_block_meshes(
     blocks: Res<Registry<Block>>,
+    models: Res<BlockModelRegistry>,
     mut model_registry: ResMut<BlockMeshRegistry>,
 ) {
-    for block in blocks.iter() {
-        model_registry
-            .add_link(block, "cosmos:base_block")
-            .expect("cosmos:base_block model link wasn't inserted successfully!");
+    link_block_meshes(&blocks, &models, &mut model_registry);
+}
+
+/// Resource packs can change which model a block uses & what those models look like
+fn reload_block_meshes(
+    mut event_reader: EventReader<ResourcePacksChangedEvent>,
+    blocks: Res<Registry<Block>>,
+    packs: Res<ResourcePacks>,
+    mut models: ResMut<BlockModelRegistry>,
+    mut model_registry: ResMut<BlockMeshRegistry>,
+) {
+    if event_reader.iter().last().is_none() {
+        return;
     }
+
+    *models = block_models::load_block_models(&blocks, &packs);
+
+    *model_registry = BlockMeshRegistry::new();
+
+    model_registry.insert_value(base_block_mesh_information());
+    link_block_meshes(&blocks, &models, &mut model_registry);
 }
 
 /// This is a `ManyToOneRegistry` mapping Blocks to `BlockMeshInformation`.
@@ -265,9 +359,13 @@ pub type BlockMeshRegistry = ManyToOneRegistry<Block, BlockMeshInformation>;
 pub(super) fn register(app: &mut App) {
     many_to_one::create_many_to_one_registry::<Block, BlockMeshInformation>(app);
     structure_renderer::register(app);
+    light_field::register(app);
+    chunk_culling::register(app);
 
     app.add_systems((
         register_meshes.in_schedule(OnEnter(GameState::Loading)),
+        load_block_models.in_schedule(OnExit(GameState::Loading)),
         register_block_meshes.in_schedule(OnExit(GameState::PostLoading)),
+        reload_block_meshes.in_set(OnUpdate(GameState::Playing)),
     ));
 }
@@ -1,48 +1,56 @@
-use crate::block::lighting::{BlockLightProperties, BlockLighting};
+use crate::materials::block_material::{
+    BlockMaterial, ATTRIBUTE_AMBIENT_OCCLUSION, ATTRIBUTE_ANIMATION, ATTRIBUTE_ATLAS_RECT,
+};
 use crate::materials::CosmosMaterial;
 use crate::netty::flags::LocalPlayer;
 use crate::state::game_state::GameState;
 use crate::structure::planet::unload_chunks_far_from_players;
 use bevy::prelude::{
     warn, App, BuildChildren, Component, DespawnRecursiveExt, EventReader, GlobalTransform,
-    IntoSystemConfigs, Mesh, OnUpdate, PbrBundle, PointLight, PointLightBundle, Quat, Rect,
-    StandardMaterial, Transform, Vec3, With,
+    IntoSystemConfig, IntoSystemConfigs, Mat2, MaterialMeshBundle, Mesh, OnUpdate, Rect, Resource,
+    Vec2, Vec3, With, Without,
 };
 use bevy::reflect::{FromReflect, Reflect};
+use bevy::render::mesh::Indices;
 use bevy::render::primitives::Aabb;
+use bevy::render::render_resource::PrimitiveTopology;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
 use bevy::utils::hashbrown::HashMap;
 use cosmos_core::block::{Block, BlockFace};
 use cosmos_core::events::block_events::BlockChangedEvent;
+use cosmos_core::physics::block_colliders::block_rotation;
 use cosmos_core::physics::location::SECTOR_DIMENSIONS;
 use cosmos_core::registry::identifiable::Identifiable;
 use cosmos_core::registry::many_to_one::ManyToOneRegistry;
 use cosmos_core::registry::Registry;
 use cosmos_core::structure::chunk::{Chunk, ChunkEntity, CHUNK_DIMENSIONS, CHUNK_DIMENSIONSF};
 use cosmos_core::structure::events::ChunkSetEvent;
-use cosmos_core::structure::structure_block::StructureBlock;
+use cosmos_core::structure::occlusion::ChunkFaceConnectivity;
 use cosmos_core::structure::Structure;
 use cosmos_core::utils::array_utils::expand;
-use cosmos_core::utils::timer::UtilsTimer;
-use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use cosmos_core::utils::greedy_meshing::merge_rectangles;
+use futures_lite::future;
 use std::collections::HashSet;
-use std::f32::consts::PI;
-use std::sync::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crate::asset::asset_loading::{BlockTextureIndex, MainAtlas};
+use crate::asset::asset_loading::{BlockTextureIndex, MainAtlas, NO_ANIMATION};
+use crate::asset::resource_packs::ResourcePacksChangedEvent;
 use crate::{Assets, Commands, Entity, Handle, Query, Res, ResMut};
 
-use super::{BlockMeshRegistry, CosmosMeshBuilder, MeshBuilder, MeshInformation};
+use super::chunk_culling::ChunkConnectivity;
+use super::light_field::{LightLevel, StructureLightField};
+use super::{BlockMeshInformation, BlockMeshRegistry, MeshBuilder, MeshInformation};
 
 #[derive(Debug)]
 struct MeshMaterial {
     mesh: Mesh,
-    material: Handle<StandardMaterial>,
+    material: Handle<BlockMaterial>,
 }
 
 #[derive(Debug)]
 struct ChunkMesh {
     mesh_materials: Vec<MeshMaterial>,
-    lights: HashMap<(usize, usize, usize), BlockLightProperties>,
 }
 
 fn monitor_block_updates_system(
@@ -174,58 +182,130 @@ fn monitor_block_updates_system(
     }
 }
 
-#[derive(Component)]
-struct ChunkNeedsRendered;
-
-#[derive(Debug, Reflect, FromReflect, Clone, Copy)]
-struct LightEntry {
-    entity: Entity,
-    light: BlockLightProperties,
-    position: StructureBlock,
-    valid: bool,
-}
+/// Block textures may have changed, so every chunk has to be rebuilt with the new ones
+fn rerender_chunks_on_resource_pack_change(
+    mut event_reader: EventReader<ResourcePacksChangedEvent>,
+    chunks: Query<Entity, With<ChunkEntity>>,
+    mut commands: Commands,
+) {
+    if event_reader.iter().last().is_none() {
+        return;
+    }
 
-#[derive(Component, Debug, Reflect, FromReflect, Default)]
-struct LightsHolder {
-    lights: Vec<LightEntry>,
+    for chunk_entity in chunks.iter() {
+        commands.entity(chunk_entity).insert(ChunkNeedsRendered);
+    }
 }
 
+#[derive(Component)]
+/// Chunks with this will have their meshes rebuilt
+pub(super) struct ChunkNeedsRendered;
+
 #[derive(Component, Debug, Reflect, FromReflect, Default)]
 struct ChunkMeshes(Vec<Entity>);
 
-/// Performance hot spot
-fn monitor_needs_rendered_system(
+#[derive(Resource, Debug, Clone)]
+/// Limits how much chunk rendering work is done each frame, so loading lots of chunks at once doesn't freeze the game
+pub struct ChunkRenderingBudget {
+    /// The most chunks that can be rendering in the background at once
+    pub max_rendering_chunks: usize,
+    /// How long each frame can spend starting chunk renders & creating the meshes of finished ones.
+    ///
+    /// At least one chunk is always started & finished each frame, even if this is exceeded.
+    pub frame_time: Duration,
+}
+
+impl Default for ChunkRenderingBudget {
+    fn default() -> Self {
+        Self {
+            max_rendering_chunks: 32,
+            frame_time: Duration::from_millis(4),
+        }
+    }
+}
+
+/// Everything needed to render any chunk, shared between every chunk rendering in the background.
+///
+/// This is rebuilt whenever any of these resources change.
+struct RenderingContext {
+    atlas: MainAtlas,
+    materials: ManyToOneRegistry<Block, CosmosMaterial>,
+    blocks: Registry<Block>,
+    meshes: BlockMeshRegistry,
+    block_textures: Registry<BlockTextureIndex>,
+}
+
+#[derive(Component)]
+/// A chunk whose meshes are being built in the background.
+///
+/// Removing this (or despawning the chunk) cancels the render.
+struct RenderingChunk(Task<(ChunkMesh, ChunkFaceConnectivity)>);
+
+/// Starts rendering the chunks closest to the player in the background, within the [`ChunkRenderingBudget`]
+///
+/// Chunks that need rendered again before their last render finished have that render cancelled, since it's out of date.
+pub(super) fn monitor_needs_rendered_system(
     mut commands: Commands,
     structure_query: Query<&Structure>,
     atlas: Res<MainAtlas>,
-    mesh_query: Query<Option<&Handle<Mesh>>>,
-    mut meshes: ResMut<Assets<Mesh>>,
     blocks: Res<Registry<Block>>,
     materials: Res<ManyToOneRegistry<Block, CosmosMaterial>>,
     meshes_registry: Res<BlockMeshRegistry>,
-    lighting: Res<Registry<BlockLighting>>,
-    lights_query: Query<&LightsHolder>,
-    chunk_meshes_query: Query<&ChunkMeshes>,
+    light_fields: Query<&StructureLightField>,
     block_textures: Res<Registry<BlockTextureIndex>>,
+    budget: Res<ChunkRenderingBudget>,
+    mut context: Local<Option<Arc<RenderingContext>>>,
 
     local_player: Query<&GlobalTransform, With<LocalPlayer>>,
 
-    chunks_need_rendered: Query<(Entity, &ChunkEntity, &GlobalTransform), With<ChunkNeedsRendered>>,
+    chunks_need_rendered: Query<
+        (
+            Entity,
+            &ChunkEntity,
+            &GlobalTransform,
+            Option<&RenderingChunk>,
+        ),
+        With<ChunkNeedsRendered>,
+    >,
+    rendering_chunks: Query<(), With<RenderingChunk>>,
 ) {
     let Ok(local_transform) = local_player.get_single() else {
         return;
     };
 
-    let timer: UtilsTimer = UtilsTimer::start();
+    let start = Instant::now();
+
+    if context.is_none()
+        || atlas.is_changed()
+        || blocks.is_changed()
+        || materials.is_changed()
+        || meshes_registry.is_changed()
+        || block_textures.is_changed()
+    {
+        *context = Some(Arc::new(RenderingContext {
+            atlas: atlas.clone(),
+            materials: materials.clone(),
+            blocks: blocks.clone(),
+            meshes: meshes_registry.clone(),
+            block_textures: block_textures.clone(),
+        }));
+    }
+
+    let context = context.as_ref().expect("This was just set");
+
+    let mut n_rendering = rendering_chunks.iter().count();
 
-    // by making the Vec an Option<Vec> I can take ownership of it later, which I cannot do with
-    // just a plain Mutex<Vec>.
-    // https://stackoverflow.com/questions/30573188/cannot-move-data-out-of-a-mutex
-    let to_process = Mutex::new(Some(Vec::new()));
+    // Any render that started before the chunk changed again is out of date
+    for (entity, _, _, rendering) in chunks_need_rendered.iter() {
+        if rendering.is_some() {
+            commands.entity(entity).remove::<RenderingChunk>();
+            n_rendering -= 1;
+        }
+    }
 
     let mut todo = chunks_need_rendered
         .iter()
-        .map(|(x, y, transform)| {
+        .map(|(x, y, transform, _)| {
             (
                 x,
                 y,
@@ -238,11 +318,12 @@ fn monitor_needs_rendered_system(
         .filter(|(_, _, distance_sqrd)| *distance_sqrd < SECTOR_DIMENSIONS * SECTOR_DIMENSIONS)
         .collect::<Vec<(Entity, &ChunkEntity, f32)>>();
 
-    let chunks_per_frame = 10;
-
-    // Only sort first `chunks_per_frame`, so no built-in sort algorithm
-    let n: usize = chunks_per_frame.min(todo.len());
+    let n = budget
+        .max_rendering_chunks
+        .saturating_sub(n_rendering)
+        .min(todo.len());
 
+    // Only sort first `n`, so no built-in sort algorithm
     for i in 0..n {
         let mut min = todo[i].2;
         let mut best_i = i;
@@ -257,67 +338,105 @@ fn monitor_needs_rendered_system(
         todo.swap(i, best_i);
     }
 
-    // Render chunks in parallel
-    todo.par_iter()
-        .take(chunks_per_frame)
-        .copied()
-        .for_each(|(entity, ce, _)| {
-            let Ok(structure) = structure_query.get(ce.structure_entity) else {
-                return;
-            };
+    let thread_pool = AsyncComputeTaskPool::get();
 
-            let mut renderer = ChunkRenderer::new();
+    for (i, (entity, ce, _)) in todo.into_iter().take(n).enumerate() {
+        if i != 0 && start.elapsed() > budget.frame_time {
+            break;
+        }
+
+        let Ok(structure) = structure_query.get(ce.structure_entity) else {
+            continue;
+        };
+
+        // Wait for the structure's lighting to be calculated before rendering it
+        let Ok(light_field) = light_fields.get(ce.structure_entity) else {
+            continue;
+        };
+
+        let chunk_location = ce.chunk_location;
+        let (cx, cy, cz) = chunk_location;
+
+        let Some(chunk) = structure.chunk_from_chunk_coordinates(cx, cy, cz).cloned() else {
+            continue;
+        };
 
-            let (cx, cy, cz) = ce.chunk_location;
+        let (xi, yi, zi) = (cx as i32, cy as i32, cz as i32);
 
-            let Some(chunk) = structure.chunk_from_chunk_coordinates(cx, cy, cz) else {
-                return;
-            };
+        let neighbor =
+            |x: i32, y: i32, z: i32| structure.chunk_from_chunk_coordinates_oob(x, y, z).cloned();
 
-            let (xi, yi, zi) = (cx as i32, cy as i32, cz as i32);
+        let left = neighbor(xi - 1, yi, zi);
+        let right = neighbor(xi + 1, yi, zi);
+        let bottom = neighbor(xi, yi - 1, zi);
+        let top = neighbor(xi, yi + 1, zi);
+        let back = neighbor(xi, yi, zi - 1);
+        let front = neighbor(xi, yi, zi + 1);
 
-            let left = structure.chunk_from_chunk_coordinates_oob(xi - 1, yi, zi);
-            let right = structure.chunk_from_chunk_coordinates_oob(xi + 1, yi, zi);
-            let bottom = structure.chunk_from_chunk_coordinates_oob(xi, yi - 1, zi);
-            let top = structure.chunk_from_chunk_coordinates_oob(xi, yi + 1, zi);
-            let back = structure.chunk_from_chunk_coordinates_oob(xi, yi, zi - 1);
-            let front = structure.chunk_from_chunk_coordinates_oob(xi, yi, zi + 1);
+        let light_field = light_field.around_chunk(chunk_location);
+        let context = context.clone();
+
+        let task = thread_pool.spawn(async move {
+            let mut renderer = ChunkRenderer::new();
 
             renderer.render(
-                &atlas,
-                &materials,
-                &lighting,
-                chunk,
-                left,
-                right,
-                bottom,
-                top,
-                back,
-                front,
-                &blocks,
-                &meshes_registry,
-                &block_textures,
+                &context.atlas,
+                &context.materials,
+                &light_field,
+                chunk_location,
+                &chunk,
+                left.as_ref(),
+                right.as_ref(),
+                bottom.as_ref(),
+                top.as_ref(),
+                back.as_ref(),
+                front.as_ref(),
+                &context.blocks,
+                &context.meshes,
+                &context.block_textures,
             );
 
-            let mut mutex = to_process.lock().expect("Error locking to_process vec!");
+            let connectivity = ChunkFaceConnectivity::compute(|x, y, z| {
+                chunk.has_see_through_block_at(x, y, z, &context.blocks)
+            });
 
-            mutex
-                .as_mut()
-                .unwrap()
-                .push((entity, renderer.create_mesh()));
+            (renderer.create_mesh(), connectivity)
         });
 
-    let to_process_chunks = to_process.lock().unwrap().take().unwrap();
-
-    if !to_process_chunks.is_empty() {
-        timer.log_duration(&format!(
-            "Rendering {} chunks took",
-            to_process_chunks.len()
-        ));
+        commands
+            .entity(entity)
+            .remove::<ChunkNeedsRendered>()
+            .insert(RenderingChunk(task));
     }
+}
 
-    for (entity, mut chunk_mesh) in to_process_chunks {
-        commands.entity(entity).remove::<ChunkNeedsRendered>();
+/// Creates the meshes of chunks that finished rendering in the background, within the [`ChunkRenderingBudget`]
+fn poll_rendering_chunks(
+    mut commands: Commands,
+    mut rendering_chunks: Query<(Entity, &mut RenderingChunk), Without<ChunkNeedsRendered>>,
+    mesh_query: Query<Option<&Handle<Mesh>>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    chunk_meshes_query: Query<&ChunkMeshes>,
+    budget: Res<ChunkRenderingBudget>,
+) {
+    let start = Instant::now();
+
+    let mut finished = 0;
+
+    for (entity, mut rendering_chunk) in rendering_chunks.iter_mut() {
+        if finished != 0 && start.elapsed() > budget.frame_time {
+            break;
+        }
+
+        let Some((mut chunk_mesh, connectivity)) =
+            future::block_on(future::poll_once(&mut rendering_chunk.0))
+        else {
+            continue;
+        };
+
+        finished += 1;
+
+        commands.entity(entity).remove::<RenderingChunk>();
 
         let mut old_mesh_entities = Vec::new();
 
@@ -335,81 +454,14 @@ fn monitor_needs_rendered_system(
             }
         }
 
-        let mut new_lights = LightsHolder::default();
-
-        if let Ok(lights) = lights_query.get(entity) {
-            for light in lights.lights.iter() {
-                let mut light = *light;
-                light.valid = false;
-                new_lights.lights.push(light);
-            }
-        }
-
         let mut entities_to_add = Vec::new();
 
-        if !chunk_mesh.lights.is_empty() {
-            for light in chunk_mesh.lights {
-                let (x, y, z) = light.0;
-                let properties = light.1;
-
-                let mut found = false;
-                for light in new_lights.lights.iter_mut() {
-                    if light.position.x == x && light.position.y == y && light.position.z == z {
-                        if light.light == properties {
-                            light.valid = true;
-                            found = true;
-                        }
-                        break;
-                    }
-                }
-
-                if !found {
-                    let light_entity = commands
-                        .spawn(PointLightBundle {
-                            point_light: PointLight {
-                                color: properties.color,
-                                intensity: properties.intensity,
-                                range: properties.range,
-                                radius: 1.0,
-                                // Shadows kill all performance
-                                shadows_enabled: false, // !properties.shadows_disabled,
-                                ..Default::default()
-                            },
-                            transform: Transform::from_xyz(
-                                x as f32 - (CHUNK_DIMENSIONS as f32 / 2.0 - 0.5),
-                                y as f32 - (CHUNK_DIMENSIONS as f32 / 2.0 - 0.5),
-                                z as f32 - (CHUNK_DIMENSIONS as f32 / 2.0 - 0.5),
-                            ),
-                            ..Default::default()
-                        })
-                        .id();
-
-                    new_lights.lights.push(LightEntry {
-                        entity: light_entity,
-                        light: properties,
-                        position: StructureBlock::new(x, y, z),
-                        valid: true,
-                    });
-                    entities_to_add.push(light_entity);
-                }
-            }
-        }
-
-        for light in new_lights.lights.iter().filter(|x| !x.valid) {
-            commands.entity(light.entity).despawn_recursive();
-        }
-
-        new_lights.lights.retain(|x| x.valid);
-
-        // end lighting
-        // meshes
-
         // If the chunk previously only had one chunk mesh, then it would be on
         // the chunk entity instead of child entities
         commands
             .entity(entity)
             .remove::<Handle<Mesh>>()
-            .remove::<Handle<StandardMaterial>>();
+            .remove::<Handle<BlockMaterial>>();
 
         let mut chunk_meshes_component = ChunkMeshes::default();
 
@@ -429,7 +481,7 @@ fn monitor_needs_rendered_system(
 
                     let ent = commands
                         .spawn((
-                            PbrBundle {
+                            MaterialMeshBundle::<BlockMaterial> {
                                 mesh,
                                 material: mesh_material.material,
                                 ..Default::default()
@@ -475,44 +527,439 @@ fn monitor_needs_rendered_system(
             entity_commands.add_child(ent);
         }
 
-        entity_commands
-            // .insert(meshes.add(chunk_mesh.mesh))
-            .insert(new_lights)
-            .insert(chunk_meshes_component);
+        entity_commands.insert((chunk_meshes_component, ChunkConnectivity(connectivity)));
     }
 }
 
 #[derive(Default, Debug, Reflect, FromReflect)]
-struct ChunkRendererInstance {
+/// Unlike the `CosmosMeshBuilder`, this keeps the uvs relative to each block's texture & stores the
+/// texture's position in the atlas separately so the `BlockMaterial` can repeat it across merged faces.
+struct MeshInfo {
+    last_index: u32,
     indices: Vec<u32>,
     uvs: Vec<[f32; 2]>,
+    atlas_rects: Vec<[f32; 4]>,
+    animations: Vec<[f32; 4]>,
+    lights: Vec<[f32; 4]>,
+    ambient_occlusion: Vec<f32>,
     positions: Vec<[f32; 3]>,
     normals: Vec<[f32; 3]>,
-    lights: HashMap<(usize, usize, usize), BlockLightProperties>,
 }
 
-#[derive(Default, Debug, Reflect, FromReflect)]
-struct MeshInfo {
-    renderer: ChunkRendererInstance,
-    mesh_builder: CosmosMeshBuilder,
+impl MeshInfo {
+    /// Adds the mesh information, lighting every vertex with the given light level
+    ///
+    /// * `animation` How the texture is animated, see [`MainAtlas::animation_for_index`]
+    /// * `occlusion` The ambient occlusion level of each vertex, see [`ChunkNeighborhood::vertex_occlusion`]
+    fn add_lit_mesh_information(
+        &mut self,
+        mesh_info: &MeshInformation,
+        position: Vec3,
+        uvs: Rect,
+        animation: [f32; 4],
+        light: LightLevel,
+        occlusion: &[u8],
+    ) {
+        debug_assert_eq!(occlusion.len(), mesh_info.positions.len());
+
+        let mut max_index = -1;
+
+        self.positions.extend(
+            mesh_info
+                .positions
+                .iter()
+                .map(|x| [x[0] + position.x, x[1] + position.y, x[2] + position.z]),
+        );
+        self.normals.extend(mesh_info.normals.iter());
+        self.uvs.extend(mesh_info.uvs.iter());
+        self.atlas_rects.extend(
+            std::iter::repeat([uvs.min.x, uvs.min.y, uvs.max.x, uvs.max.y])
+                .take(mesh_info.uvs.len()),
+        );
+        self.animations
+            .extend(std::iter::repeat(animation).take(mesh_info.uvs.len()));
+        self.lights
+            .extend(std::iter::repeat(light.vertex_color()).take(mesh_info.positions.len()));
+        self.ambient_occlusion.extend(
+            occlusion
+                .iter()
+                .map(|level| AMBIENT_OCCLUSION_CURVE[*level as usize]),
+        );
+
+        // Quads are split along the diagonal between their brightest corners, otherwise the darkness of one corner
+        // bleeds across the whole quad.
+        let flip_diagonal = mesh_info.indices == QUAD_INDICES
+            && occlusion[0] + occlusion[2] < occlusion[1] + occlusion[3];
+
+        let indices: &[u32] = if flip_diagonal {
+            &FLIPPED_QUAD_INDICES
+        } else {
+            mesh_info.indices.as_slice()
+        };
+
+        for index in indices.iter() {
+            self.indices.push(*index + self.last_index);
+            max_index = max_index.max(*index as i32);
+        }
+
+        self.last_index += (max_index + 1) as u32;
+    }
 }
 
 impl MeshBuilder for MeshInfo {
     #[inline]
     fn add_mesh_information(&mut self, mesh_info: &MeshInformation, position: Vec3, uvs: Rect) {
-        self.mesh_builder
-            .add_mesh_information(mesh_info, position, uvs);
+        self.add_lit_mesh_information(
+            mesh_info,
+            position,
+            uvs,
+            NO_ANIMATION,
+            LightLevel::OPEN_SPACE,
+            &vec![MAX_OCCLUSION_LEVEL; mesh_info.positions.len()],
+        );
     }
 
     fn build_mesh(self) -> Mesh {
-        self.mesh_builder.build_mesh()
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+
+        mesh.set_indices(Some(Indices::U32(self.indices)));
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs);
+        mesh.insert_attribute(ATTRIBUTE_ATLAS_RECT, self.atlas_rects);
+        mesh.insert_attribute(ATTRIBUTE_ANIMATION, self.animations);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, self.lights);
+        mesh.insert_attribute(ATTRIBUTE_AMBIENT_OCCLUSION, self.ambient_occlusion);
+
+        mesh
+    }
+}
+
+/// The mesh information for one face of a block with a given rotation, ready to be put into a chunk's mesh.
+#[derive(Debug, Clone)]
+struct FaceInfo {
+    material: Handle<BlockMaterial>,
+    /// Already rotated to match the block's rotation
+    mesh_info: MeshInformation,
+    uvs: Rect,
+    /// See [`MainAtlas::animation_for_index`]
+    animation: [f32; 4],
+    /// If this face is a single quad covering this entire side of the block, it can be merged with its neighbors
+    full: bool,
+}
+
+/// How far off a face's corner can be from the block's corner & still be considered a full face
+const FULL_FACE_EPSILON: f32 = 0.001;
+
+/// Every face of a block, in the order they are checked for visibility
+const ALL_BLOCK_FACES: [BlockFace; 6] = [
+    BlockFace::Right,
+    BlockFace::Left,
+    BlockFace::Top,
+    BlockFace::Bottom,
+    BlockFace::Back,
+    BlockFace::Front,
+];
+
+/// Returns the axis (0 = x, 1 = y, 2 = z) & sign of the direction this face of a block model points in
+fn face_axis(face: BlockFace) -> (usize, f32) {
+    // These match the faces of the `cosmos:base_block` model, which is what the neighbor checks are based on
+    match face {
+        BlockFace::Right => (0, 1.0),
+        BlockFace::Left => (0, -1.0),
+        BlockFace::Top => (1, 1.0),
+        BlockFace::Bottom => (1, -1.0),
+        BlockFace::Back => (2, 1.0),
+        BlockFace::Front => (2, -1.0),
+    }
+}
+
+/// Returns the face on the other side of the block
+fn opposite_face(face: BlockFace) -> BlockFace {
+    match face {
+        BlockFace::Right => BlockFace::Left,
+        BlockFace::Left => BlockFace::Right,
+        BlockFace::Top => BlockFace::Bottom,
+        BlockFace::Bottom => BlockFace::Top,
+        BlockFace::Back => BlockFace::Front,
+        BlockFace::Front => BlockFace::Back,
+    }
+}
+
+/// Returns the two axes that lie on a face perpendicular to this axis
+fn plane_axes(axis: usize) -> (usize, usize) {
+    match axis {
+        0 => (1, 2),
+        1 => (0, 2),
+        _ => (0, 1),
+    }
+}
+
+/// Returns true if this mesh is a single quad covering the entire side of the block
+fn is_full_face(mesh_info: &MeshInformation, axis: usize, sign: f32) -> bool {
+    if mesh_info.positions.len() != 4 || mesh_info.indices.len() != 6 {
+        return false;
+    }
+
+    let (u, v) = plane_axes(axis);
+    let mut corners = [false; 4];
+
+    for pos in mesh_info.positions.iter() {
+        if (pos[axis] - sign * 0.5).abs() > FULL_FACE_EPSILON
+            || (pos[u].abs() - 0.5).abs() > FULL_FACE_EPSILON
+            || (pos[v].abs() - 0.5).abs() > FULL_FACE_EPSILON
+        {
+            return false;
+        }
+
+        corners[(pos[u] > 0.0) as usize * 2 + (pos[v] > 0.0) as usize] = true;
+    }
+
+    corners.iter().all(|x| *x)
+}
+
+/// Stretches a full face to cover `width` x `height` blocks.
+///
+/// The uvs are extended past [0.0, 1.0] at the same rate they change across a single block,
+/// so the texture is repeated once per block instead of being stretched.
+fn stretch_face(mesh_info: &mut MeshInformation, axis: usize, width: f32, height: f32) {
+    let (u, v) = plane_axes(axis);
+    let flat = |pos: [f32; 3]| Vec2::new(pos[u], pos[v]);
+
+    let (p, t) = (&mesh_info.positions, &mesh_info.uvs);
+
+    // Any 3 corners of a square form two edges that aren't parallel, so this is always invertible
+    let edges = Mat2::from_cols(flat(p[1]) - flat(p[0]), flat(p[3]) - flat(p[0]));
+    let uv_edges = Mat2::from_cols(
+        Vec2::from(t[1]) - Vec2::from(t[0]),
+        Vec2::from(t[3]) - Vec2::from(t[0]),
+    );
+    let position_to_uv = uv_edges * edges.inverse();
+
+    for (pos, uv) in mesh_info.positions.iter_mut().zip(mesh_info.uvs.iter_mut()) {
+        let before = flat(*pos);
+
+        pos[u] *= width;
+        pos[v] *= height;
+
+        *uv = (Vec2::from(*uv) + position_to_uv * (flat(*pos) - before)).into();
+    }
+}
+
+/// Computes the mesh information for the given face of a block
+///
+/// * `face` The side of the block that is visible, before the block's rotation is applied
+/// * `inner` If true, this is the geometry inside the block using this face's texture instead of the face itself
+fn block_face_info(
+    block: &Block,
+    face: BlockFace,
+    rotation: BlockFace,
+    inner: bool,
+    atlas: &MainAtlas,
+    materials: &ManyToOneRegistry<Block, CosmosMaterial>,
+    meshes: &BlockMeshRegistry,
+    block_textures: &Registry<BlockTextureIndex>,
+) -> Option<FaceInfo> {
+    let material = materials.get_value(block)?;
+    let mesh: &BlockMeshInformation = meshes.get_value(block)?;
+
+    let model_face = BlockFace::rotate_face(face, rotation);
+
+    let mut mesh_info = if inner {
+        mesh.inner_info_for_face(model_face).clone()
+    } else {
+        mesh.info_for_face(model_face).clone()
+    };
+
+    if mesh_info.positions.is_empty() {
+        return None;
+    }
+
+    let index = block_textures
+        .from_id(block.unlocalized_name())
+        .unwrap_or_else(|| {
+            block_textures
+                .from_id("missing")
+                .expect("Missing texture should exist.")
+        });
+
+    let Some(image_index) = index.atlas_index_from_face(model_face) else {
+        warn!("Missing image index -- {index:?}");
+        return None;
+    };
+
+    let uvs = atlas.uvs_for_index(image_index);
+    let animation = atlas.animation_for_index(image_index);
+
+    let rotation = block_rotation(rotation);
+
+    for pos in mesh_info.positions.iter_mut() {
+        *pos = rotation.mul_vec3((*pos).into()).into();
+    }
+
+    for norm in mesh_info.normals.iter_mut() {
+        *norm = rotation.mul_vec3((*norm).into()).into();
+    }
+
+    let (axis, sign) = face_axis(face);
+    let full = !inner && is_full_face(&mesh_info, axis, sign);
+
+    Some(FaceInfo {
+        material: material.handle.clone(),
+        mesh_info,
+        uvs,
+        animation,
+        full,
+    })
+}
+
+/// A vertex with this ambient occlusion level has no blocks around it
+const MAX_OCCLUSION_LEVEL: u8 = 3;
+/// How bright a vertex is for each ambient occlusion level
+const AMBIENT_OCCLUSION_CURVE: [f32; 4] = [0.45, 0.65, 0.85, 1.0];
+
+/// The indices of a quad, which is split along the diagonal between vertices 0 & 2
+const QUAD_INDICES: [u32; 6] = [0, 1, 2, 2, 3, 0];
+/// The same quad as `QUAD_INDICES`, but split along the diagonal between vertices 1 & 3
+const FLIPPED_QUAD_INDICES: [u32; 6] = [1, 2, 3, 3, 0, 1];
+
+/// A chunk & the chunks that share a face with it, used to look at blocks just outside the chunk
+struct ChunkNeighborhood<'a> {
+    chunk: &'a Chunk,
+    left: Option<&'a Chunk>,
+    right: Option<&'a Chunk>,
+    bottom: Option<&'a Chunk>,
+    top: Option<&'a Chunk>,
+    back: Option<&'a Chunk>,
+    front: Option<&'a Chunk>,
+    blocks: &'a Registry<Block>,
+    meshes: &'a BlockMeshRegistry,
+}
+
+impl<'a> ChunkNeighborhood<'a> {
+    /// Returns the chunk these chunk coordinates are in & the coordinates within that chunk.
+    ///
+    /// Chunks that are diagonal to this one, or aren't loaded, will give `None`.
+    fn chunk_at(&self, x: i32, y: i32, z: i32) -> Option<(&'a Chunk, usize, usize, usize)> {
+        let cd = CHUNK_DIMENSIONS as i32;
+        let outside = |c: i32| !(0..cd).contains(&c);
+
+        let chunk = match [x, y, z].into_iter().filter(|c| outside(*c)).count() {
+            0 => Some(self.chunk),
+            1 if x < 0 => self.left,
+            1 if x >= cd => self.right,
+            1 if y < 0 => self.bottom,
+            1 if y >= cd => self.top,
+            1 if z < 0 => self.back,
+            1 => self.front,
+            _ => None,
+        };
+
+        chunk.map(|chunk| {
+            (
+                chunk,
+                x.rem_euclid(cd) as usize,
+                y.rem_euclid(cd) as usize,
+                z.rem_euclid(cd) as usize,
+            )
+        })
+    }
+
+    /// Returns true if there is a block at these chunk coordinates that can't be seen through.
+    ///
+    /// Blocks in chunks that are diagonal to this one are never counted.
+    fn is_occluder(&self, x: i32, y: i32, z: i32) -> bool {
+        self.chunk_at(x, y, z)
+            .map(|(chunk, x, y, z)| !chunk.has_see_through_block_at(x, y, z, self.blocks))
+            .unwrap_or(false)
+    }
+
+    /// Returns true if the block in front of this face completely covers it, so it can never be seen.
+    ///
+    /// Faces on the edge of an unloaded chunk are never hidden.
+    fn is_face_hidden(&self, (x, y, z): (usize, usize, usize), face: BlockFace) -> bool {
+        let (axis, sign) = face_axis(face);
+
+        let mut coords = [x as i32, y as i32, z as i32];
+        coords[axis] += sign as i32;
+
+        let Some((chunk, x, y, z)) = self.chunk_at(coords[0], coords[1], coords[2]) else {
+            return false;
+        };
+
+        let block = self.blocks.from_numeric_id(chunk.block_at(x, y, z));
+
+        if block.is_transparent() {
+            return false;
+        }
+
+        // The neighbor's face touching this one, in terms of the neighbor's model
+        let model_face = BlockFace::rotate_face(opposite_face(face), chunk.block_rotation(x, y, z));
+
+        self.meshes
+            .get_value(block)
+            .map(|mesh| mesh.covers_face(model_face))
+            .unwrap_or_else(|| block.is_full())
+    }
+
+    /// Calculates how occluded a vertex of a face is by the blocks in front of that face, from 0 (fully occluded)
+    /// to `MAX_OCCLUSION_LEVEL` (not occluded).
+    ///
+    /// * `pos` The position of this vertex relative to the block's center
+    fn vertex_occlusion(
+        &self,
+        (x, y, z): (usize, usize, usize),
+        face: BlockFace,
+        pos: [f32; 3],
+    ) -> u8 {
+        let (axis, sign) = face_axis(face);
+        let (u, v) = plane_axes(axis);
+
+        let mut in_front = [x as i32, y as i32, z as i32];
+        in_front[axis] += sign as i32;
+
+        // Vertices in the middle of a face aren't next to any of the surrounding blocks
+        let side_of = |c: f32| {
+            if c > 0.25 {
+                1
+            } else if c < -0.25 {
+                -1
+            } else {
+                0
+            }
+        };
+        let (du, dv) = (side_of(pos[u]), side_of(pos[v]));
+
+        let occluder_at = |du: i32, dv: i32| {
+            if du == 0 && dv == 0 {
+                return false;
+            }
+
+            let mut coords = in_front;
+            coords[u] += du;
+            coords[v] += dv;
+
+            self.is_occluder(coords[0], coords[1], coords[2])
+        };
+
+        let side_u = occluder_at(du, 0);
+        let side_v = occluder_at(0, dv);
+
+        if side_u && side_v {
+            // The corner is hidden completely, so it doesn't matter what's in the corner
+            0
+        } else {
+            MAX_OCCLUSION_LEVEL
+                - side_u as u8
+                - side_v as u8
+                - (du != 0 && dv != 0 && occluder_at(du, dv)) as u8
+        }
     }
 }
 
 #[derive(Default, Debug, Reflect)]
 struct ChunkRenderer {
-    meshes: HashMap<Handle<StandardMaterial>, MeshInfo>,
-    lights: HashMap<(usize, usize, usize), BlockLightProperties>,
+    meshes: HashMap<Handle<BlockMaterial>, MeshInfo>,
 }
 
 impl ChunkRenderer {
@@ -521,11 +968,15 @@ impl ChunkRenderer {
     }
 
     /// Renders a chunk into mesh information that can then be turned into a bevy mesh
+    ///
+    /// Full faces facing the same way in the same slice of the chunk are merged together if they are the same block
+    /// with the same rotation, which drastically cuts down on the number of vertices for large flat surfaces.
     fn render(
         &mut self,
         atlas: &MainAtlas,
         materials: &ManyToOneRegistry<Block, CosmosMaterial>,
-        lighting: &Registry<BlockLighting>,
+        light_field: &StructureLightField,
+        (cx, cy, cz): (usize, usize, usize),
         chunk: &Chunk,
         left: Option<&Chunk>,
         right: Option<&Chunk>,
@@ -539,9 +990,45 @@ impl ChunkRenderer {
     ) {
         let cd2 = CHUNK_DIMENSIONSF / 2.0;
 
+        let neighborhood = ChunkNeighborhood {
+            chunk,
+            left,
+            right,
+            bottom,
+            top,
+            back,
+            front,
+            blocks,
+            meshes,
+        };
+
         let mut faces = Vec::with_capacity(6);
+        let mut occlusion = Vec::with_capacity(4);
+
+        // Most chunks only have a couple different types of blocks, so this saves recalculating the same faces over and over
+        let mut face_infos = HashMap::<(u16, BlockFace, BlockFace), Option<FaceInfo>>::default();
+        let mut inner_face_infos =
+            HashMap::<(u16, BlockFace, BlockFace), Option<FaceInfo>>::default();
+        // The full faces that can be merged, for every (face, slice along that face's axis)
+        // Faces are only merged if they have the same light level & ambient occlusion, since those are stored per vertex
+        let mut merge_masks =
+            HashMap::<(BlockFace, usize), Vec<Option<(u16, BlockFace, LightLevel, u8)>>>::default();
+
+        // Each face is lit by the light of the block in front of it
+        let light_in_front = |(x, y, z): (usize, usize, usize), face: BlockFace| {
+            let (axis, sign) = face_axis(face);
+
+            let mut coords = [
+                (cx * CHUNK_DIMENSIONS + x) as i32,
+                (cy * CHUNK_DIMENSIONS + y) as i32,
+                (cz * CHUNK_DIMENSIONS + z) as i32,
+            ];
+            coords[axis] += sign as i32;
+
+            light_field.light_at(coords[0], coords[1], coords[2])
+        };
 
-        for ((x, y, z), (block, block_info)) in chunk
+        for ((x, y, z), (block_id, block_info)) in chunk
             .blocks()
             .copied()
             .zip(chunk.block_info_iterator().copied())
@@ -554,130 +1041,155 @@ impl ChunkRenderer {
                 y as f32 - cd2 + 0.5,
                 z as f32 - cd2 + 0.5,
             );
-            // right
-            if (x != CHUNK_DIMENSIONS - 1 && chunk.has_see_through_block_at(x + 1, y, z, blocks))
-                || (x == CHUNK_DIMENSIONS - 1
-                    && (right
-                        .map(|c| c.has_see_through_block_at(0, y, z, blocks))
-                        .unwrap_or(true)))
-            {
-                faces.push(BlockFace::Right);
-            }
-            // left
-            if (x != 0 && chunk.has_see_through_block_at(x - 1, y, z, blocks))
-                || (x == 0
-                    && (left
-                        .map(|c| c.has_see_through_block_at(CHUNK_DIMENSIONS - 1, y, z, blocks))
-                        .unwrap_or(true)))
-            {
-                faces.push(BlockFace::Left);
-            }
 
-            // top
-            if (y != CHUNK_DIMENSIONS - 1 && chunk.has_see_through_block_at(x, y + 1, z, blocks))
-                || (y == CHUNK_DIMENSIONS - 1
-                    && (top
-                        .map(|c| c.has_see_through_block_at(x, 0, z, blocks))
-                        .unwrap_or(true)))
-            {
-                faces.push(BlockFace::Top);
-            }
-            // bottom
-            if (y != 0 && chunk.has_see_through_block_at(x, y - 1, z, blocks))
-                || (y == 0
-                    && (bottom
-                        .map(|c| c.has_see_through_block_at(x, CHUNK_DIMENSIONS - 1, z, blocks))
-                        .unwrap_or(true)))
-            {
-                faces.push(BlockFace::Bottom);
-            }
-
-            // back
-            if (z != CHUNK_DIMENSIONS - 1 && chunk.has_see_through_block_at(x, y, z + 1, blocks))
-                || (z == CHUNK_DIMENSIONS - 1
-                    && (front
-                        .map(|c| c.has_see_through_block_at(x, y, 0, blocks))
-                        .unwrap_or(true)))
-            {
-                faces.push(BlockFace::Back);
-            }
-            // front
-            if (z != 0 && chunk.has_see_through_block_at(x, y, z - 1, blocks))
-                || (z == 0
-                    && (back
-                        .map(|c| c.has_see_through_block_at(x, y, CHUNK_DIMENSIONS - 1, blocks))
-                        .unwrap_or(true)))
-            {
-                faces.push(BlockFace::Front);
+            for face in ALL_BLOCK_FACES {
+                if !neighborhood.is_face_hidden((x, y, z), face) {
+                    faces.push(face);
+                }
             }
 
             if !faces.is_empty() {
-                let block = blocks.from_numeric_id(block);
+                let block = blocks.from_numeric_id(block_id);
+                let rotation = block_info.get_rotation();
+                let coords = [x, y, z];
+
+                for &face in faces.iter() {
+                    let face_info =
+                        face_infos
+                            .entry((block_id, rotation, face))
+                            .or_insert_with(|| {
+                                block_face_info(
+                                    block,
+                                    face,
+                                    rotation,
+                                    false,
+                                    atlas,
+                                    materials,
+                                    meshes,
+                                    block_textures,
+                                )
+                            });
+
+                    let Some(face_info) = face_info else {
+                        continue;
+                    };
 
-                let Some(material) = materials.get_value(block) else {
-                    continue;
-                };
+                    let light = light_in_front((x, y, z), face);
 
-                let Some(mesh) = meshes.get_value(block) else {
-                    continue;
-                };
+                    occlusion.clear();
+                    occlusion.extend(
+                        face_info
+                            .mesh_info
+                            .positions
+                            .iter()
+                            .map(|pos| neighborhood.vertex_occlusion((x, y, z), face, *pos)),
+                    );
 
-                if !self.meshes.contains_key(&material.handle) {
-                    self.meshes
-                        .insert(material.handle.clone(), Default::default());
+                    // Faces that are darker in some corners than others would be stretched wrong if merged
+                    let uniform_occlusion = occlusion
+                        .first()
+                        .copied()
+                        .filter(|first| occlusion.iter().all(|level| level == first));
+
+                    if let Some(uniform_occlusion) = uniform_occlusion.filter(|_| face_info.full) {
+                        let (axis, _) = face_axis(face);
+                        let (u, v) = plane_axes(axis);
+
+                        let mask = merge_masks
+                            .entry((face, coords[axis]))
+                            .or_insert_with(|| vec![None; CHUNK_DIMENSIONS * CHUNK_DIMENSIONS]);
+
+                        mask[coords[v] * CHUNK_DIMENSIONS + coords[u]] =
+                            Some((block_id, rotation, light, uniform_occlusion));
+                    } else {
+                        self.meshes
+                            .entry(face_info.material.clone())
+                            .or_default()
+                            .add_lit_mesh_information(
+                                &face_info.mesh_info,
+                                Vec3::new(center_offset_x, center_offset_y, center_offset_z),
+                                face_info.uvs,
+                                face_info.animation,
+                                light,
+                                &occlusion,
+                            );
+                    }
                 }
 
-                let mesh_builder = self.meshes.get_mut(&material.handle).unwrap();
-
-                let rotation = block_info.get_rotation();
-
-                for face in faces.iter().map(|x| BlockFace::rotate_face(*x, rotation)) {
-                    let index = block_textures
-                        .from_id(block.unlocalized_name())
-                        .unwrap_or_else(|| {
-                            block_textures
-                                .from_id("missing")
-                                .expect("Missing texture should exist.")
+                // Anything inside of the block is never covered by its neighbors, so it's lit by the block itself
+                let light = light_field.light_at(
+                    (cx * CHUNK_DIMENSIONS + x) as i32,
+                    (cy * CHUNK_DIMENSIONS + y) as i32,
+                    (cz * CHUNK_DIMENSIONS + z) as i32,
+                );
+
+                for face in ALL_BLOCK_FACES {
+                    let inner_info = inner_face_infos
+                        .entry((block_id, rotation, face))
+                        .or_insert_with(|| {
+                            block_face_info(
+                                block,
+                                face,
+                                rotation,
+                                true,
+                                atlas,
+                                materials,
+                                meshes,
+                                block_textures,
+                            )
                         });
 
-                    let Some(image_index) = index.atlas_index_from_face(face) else {
-                        warn!("Missing image index -- {index:?}");
+                    let Some(inner_info) = inner_info else {
                         continue;
                     };
 
-                    let uvs = atlas.uvs_for_index(image_index);
+                    self.meshes
+                        .entry(inner_info.material.clone())
+                        .or_default()
+                        .add_lit_mesh_information(
+                            &inner_info.mesh_info,
+                            Vec3::new(center_offset_x, center_offset_y, center_offset_z),
+                            inner_info.uvs,
+                            inner_info.animation,
+                            light,
+                            &vec![MAX_OCCLUSION_LEVEL; inner_info.mesh_info.positions.len()],
+                        );
+                }
 
-                    let mut mesh_info = mesh.info_for_face(face).clone();
+                faces.clear();
+            }
+        }
 
-                    let rotation = match rotation {
-                        BlockFace::Top => Quat::IDENTITY,
-                        BlockFace::Front => Quat::from_axis_angle(Vec3::X, PI / 2.0),
-                        BlockFace::Back => Quat::from_axis_angle(Vec3::X, -PI / 2.0),
-                        BlockFace::Left => Quat::from_axis_angle(Vec3::Z, PI / 2.0),
-                        BlockFace::Right => Quat::from_axis_angle(Vec3::Z, -PI / 2.0),
-                        BlockFace::Bottom => Quat::from_axis_angle(Vec3::X, PI),
-                    };
+        for ((face, slice), mut cells) in merge_masks {
+            let (axis, _) = face_axis(face);
+            let (u, v) = plane_axes(axis);
 
-                    for pos in mesh_info.positions.iter_mut() {
-                        *pos = rotation.mul_vec3((*pos).into()).into();
-                    }
+            for rect in merge_rectangles(&mut cells, CHUNK_DIMENSIONS, CHUNK_DIMENSIONS) {
+                let (block_id, rotation, light, uniform_occlusion) = rect.value;
 
-                    for norm in mesh_info.normals.iter_mut() {
-                        *norm = rotation.mul_vec3((*norm).into()).into();
-                    }
+                let Some(Some(face_info)) = face_infos.get(&(block_id, rotation, face)) else {
+                    continue;
+                };
 
-                    mesh_builder.add_mesh_information(
-                        &mesh_info,
-                        Vec3::new(center_offset_x, center_offset_y, center_offset_z),
-                        uvs,
-                    );
-                }
+                let mut mesh_info = face_info.mesh_info.clone();
+                stretch_face(&mut mesh_info, axis, rect.width as f32, rect.height as f32);
 
-                faces.clear();
+                let mut center = Vec3::ZERO;
+                center[axis] = slice as f32 - cd2 + 0.5;
+                center[u] = rect.x as f32 + rect.width as f32 / 2.0 - cd2;
+                center[v] = rect.y as f32 + rect.height as f32 / 2.0 - cd2;
 
-                if let Some(lighting) = lighting.from_id(block.unlocalized_name()) {
-                    self.lights.insert((x, y, z), lighting.properties);
-                }
+                self.meshes
+                    .entry(face_info.material.clone())
+                    .or_default()
+                    .add_lit_mesh_information(
+                        &mesh_info,
+                        center,
+                        face_info.uvs,
+                        face_info.animation,
+                        light,
+                        &[uniform_occlusion; 4],
+                    );
             }
         }
     }
@@ -691,21 +1203,20 @@ impl ChunkRenderer {
             mesh_materials.push(MeshMaterial { material, mesh });
         }
 
-        let lights = self.lights;
-
-        ChunkMesh {
-            lights,
-            mesh_materials,
-        }
+        ChunkMesh { mesh_materials }
     }
 }
 
 pub(super) fn register(app: &mut App) {
     app.add_systems(
-        (monitor_needs_rendered_system, monitor_block_updates_system)
+        (
+            monitor_needs_rendered_system,
+            poll_rendering_chunks.after(monitor_needs_rendered_system),
+            monitor_block_updates_system,
+            rerender_chunks_on_resource_pack_change,
+        )
             .in_set(OnUpdate(GameState::Playing))
             .before(unload_chunks_far_from_players),
     )
-    // .add_system(add_renderer)
-    .register_type::<LightsHolder>();
+    .init_resource::<ChunkRenderingBudget>();
 }
@@ -1,7 +1,6 @@
 //! Load a cubemap texture onto a cube like a skybox and cycle through different compressed texture formats
 
 use bevy::{
-    asset::LoadState,
     pbr::{MaterialPipeline, MaterialPipelineKey, NotShadowCaster},
     prelude::*,
     reflect::TypeUuid,
@@ -11,14 +10,25 @@ use bevy::{
         render_resource::{
             AsBindGroup, AsBindGroupError, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
             BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType,
-            OwnedBindingResource, PreparedBindGroup, RenderPipelineDescriptor, SamplerBindingType,
-            ShaderRef, ShaderStages, SpecializedMeshPipelineError, TextureSampleType,
-            TextureViewDescriptor, TextureViewDimension,
+            BufferBindingType, BufferInitDescriptor, BufferUsages, OwnedBindingResource,
+            PreparedBindGroup, RenderPipelineDescriptor, SamplerBindingType, ShaderRef,
+            ShaderStages, SpecializedMeshPipelineError, TextureSampleType, TextureViewDescriptor,
+            TextureViewDimension,
         },
         renderer::RenderDevice,
-        texture::FallbackImage,
+        texture::{CompressedImageFormats, FallbackImage, ImageType},
     },
 };
+use cosmos_core::physics::location::Location;
+
+use crate::{
+    asset::resource_packs::{ResourcePacks, ResourcePacksChangedEvent},
+    netty::flags::LocalPlayer,
+    state::game_state::GameState,
+    universe::{star::Daylight, star_catalog::StarCatalog},
+};
+
+mod starfield;
 
 /// Order from top to bottom:
 /// Right, Left, Top, Bottom, Front, Back
@@ -30,8 +40,33 @@ struct Cubemap {
     image_handle: Handle<Image>,
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let skybox_handle = asset_server.load(CUBEMAP);
+/// The skybox is read through the resource packs so they can replace it, then the stars around the viewer are drawn onto it
+fn load_cubemap_image(
+    packs: &ResourcePacks,
+    stars: Option<(&StarCatalog, &Location)>,
+    images: &mut Assets<Image>,
+) -> Handle<Image> {
+    let data = packs
+        .read(CUBEMAP)
+        .unwrap_or_else(|| panic!("Missing skybox texture {CUBEMAP}"));
+
+    let mut image = Image::from_buffer(
+        &data,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+    )
+    .unwrap_or_else(|_| panic!("Invalid skybox texture {CUBEMAP}"));
+
+    if let Some((catalog, viewer)) = stars {
+        starfield::paint_stars(&mut image, catalog, viewer);
+    }
+
+    images.add(image)
+}
+
+fn setup(mut commands: Commands, packs: Res<ResourcePacks>, mut images: ResMut<Assets<Image>>) {
+    let skybox_handle = load_cubemap_image(&packs, None, &mut images);
 
     commands.insert_resource(Cubemap {
         is_loaded: false,
@@ -39,19 +74,43 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     });
 }
 
+/// Redraws the skybox when the resource packs change, or when the server sends the stars around a new system
+fn reload_cubemap(
+    mut event_reader: EventReader<ResourcePacksChangedEvent>,
+    star_catalog: Option<Res<StarCatalog>>,
+    local_player: Query<&Location, With<LocalPlayer>>,
+    packs: Res<ResourcePacks>,
+    mut images: ResMut<Assets<Image>>,
+    mut cubemap: ResMut<Cubemap>,
+) {
+    let packs_changed = event_reader.iter().last().is_some();
+    let stars_changed = star_catalog
+        .as_ref()
+        .map(|catalog| catalog.is_changed())
+        .unwrap_or(false);
+
+    if !packs_changed && !stars_changed {
+        return;
+    }
+
+    let stars = star_catalog.as_deref().zip(local_player.get_single().ok());
+
+    cubemap.image_handle = load_cubemap_image(&packs, stars, &mut images);
+    cubemap.is_loaded = false;
+}
+
 fn asset_loaded(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
     mut images: ResMut<Assets<Image>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut cubemap_materials: ResMut<Assets<CubemapMaterial>>,
     mut cubemap: ResMut<Cubemap>,
     cubes: Query<&Handle<CubemapMaterial>>,
 ) {
-    if !cubemap.is_loaded
-        && asset_server.get_load_state(cubemap.image_handle.clone_weak()) == LoadState::Loaded
-    {
-        let image = images.get_mut(&cubemap.image_handle).unwrap();
+    if !cubemap.is_loaded {
+        let Some(image) = images.get_mut(&cubemap.image_handle) else {
+            return;
+        };
         // NOTE: PNGs do not have any metadata that could indicate they contain a cubemap texture,
         // so they appear as one texture. The following code reconfigures the texture as necessary.
         if image.texture_descriptor.array_layer_count() == 1 {
@@ -82,6 +141,7 @@ fn asset_loaded(
                     })),
                     material: cubemap_materials.add(CubemapMaterial {
                         base_color_texture: Some(cubemap.image_handle.clone()),
+                        sky_color: Color::NONE,
                     }),
                     ..default()
                 },
@@ -97,6 +157,8 @@ fn asset_loaded(
 #[uuid = "9509a0f8-3c05-48ee-a13e-a93226c7f488"]
 struct CubemapMaterial {
     base_color_texture: Option<Handle<Image>>,
+    /// Drawn over the stars - the alpha is how much of them it hides
+    sky_color: Color,
 }
 
 impl Material for CubemapMaterial {
@@ -132,6 +194,20 @@ impl AsBindGroup for CubemapMaterial {
         let image = images
             .get(base_color_texture)
             .ok_or(AsBindGroupError::RetryNextUpdate)?;
+
+        let sky_color = self
+            .sky_color
+            .as_linear_rgba_f32()
+            .iter()
+            .flat_map(|c| c.to_le_bytes())
+            .collect::<Vec<u8>>();
+
+        let sky_color_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("cubemap_sky_color_buffer"),
+            contents: &sky_color,
+            usage: BufferUsages::UNIFORM,
+        });
+
         let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
             entries: &[
                 BindGroupEntry {
@@ -142,6 +218,10 @@ impl AsBindGroup for CubemapMaterial {
                     binding: 1,
                     resource: BindingResource::Sampler(&image.sampler),
                 },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::Buffer(sky_color_buffer.as_entire_buffer_binding()),
+                },
             ],
             label: Some("cubemap_texture_material_bind_group"),
             layout,
@@ -152,6 +232,7 @@ impl AsBindGroup for CubemapMaterial {
             bindings: vec![
                 OwnedBindingResource::TextureView(image.texture_view.clone()),
                 OwnedBindingResource::Sampler(image.sampler.clone()),
+                OwnedBindingResource::Buffer(sky_color_buffer),
             ],
             data: (),
         })
@@ -178,14 +259,44 @@ impl AsBindGroup for CubemapMaterial {
                     ty: BindingType::Sampler(SamplerBindingType::Filtering),
                     count: None,
                 },
+                // Sky Color
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
             ],
             label: None,
         })
     }
 }
 
+/// Brightens the sky during the day on a planet, and lets the stars show through at night & in space
+fn tint_sky(
+    daylight: Res<Daylight>,
+    cubes: Query<&Handle<CubemapMaterial>>,
+    mut cubemap_materials: ResMut<Assets<CubemapMaterial>>,
+) {
+    if !daylight.is_changed() {
+        return;
+    }
+
+    for handle in cubes.iter() {
+        if let Some(material) = cubemap_materials.get_mut(handle) {
+            material.sky_color = daylight.sky_color();
+        }
+    }
+}
+
 pub(super) fn register(app: &mut App) {
     app.add_plugin(MaterialPlugin::<CubemapMaterial>::default())
         .add_startup_system(setup)
-        .add_system(asset_loaded);
+        .add_system(asset_loaded)
+        .add_system(reload_cubemap.in_set(OnUpdate(GameState::Playing)))
+        .add_system(tint_sky);
 }
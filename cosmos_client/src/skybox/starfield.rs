@@ -0,0 +1,119 @@
+//! Draws the stars the server sends onto the skybox, so the sky matches where the player actually is
+
+use bevy::{
+    prelude::{Image, Vec3},
+    render::render_resource::TextureFormat,
+};
+use cosmos_core::physics::location::{Location, SYSTEM_DIMENSIONS};
+
+use crate::universe::star_catalog::StarCatalog;
+
+/// The radius (in pixels) of the brightest stars
+const MAX_STAR_RADIUS: f32 = 3.0;
+
+/// Stars closer than this are drawn at full brightness, and further ones fade with the square of their distance
+const FULL_BRIGHTNESS_DISTANCE: f32 = SYSTEM_DIMENSIONS * 2.0;
+
+/// Stars are never drawn dimmer than this, or the furthest ones wouldn't be visible at all
+const MIN_BRIGHTNESS: f32 = 0.15;
+
+/// Returns which layer of the cubemap this direction points to, and the pixel coordinates on that layer.
+///
+/// The skybox shader flips the z axis before sampling, so that is done here too.
+fn cubemap_pixel(direction: Vec3, face_size: u32) -> (usize, f32, f32) {
+    let (x, y, z) = (direction.x, direction.y, -direction.z);
+    let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+
+    // Layers are in the order Right, Left, Top, Bottom, Front, Back
+    let (layer, sc, tc, ma) = if ax >= ay && ax >= az {
+        if x > 0.0 {
+            (0, -z, -y, ax)
+        } else {
+            (1, z, -y, ax)
+        }
+    } else if ay >= az {
+        if y > 0.0 {
+            (2, x, z, ay)
+        } else {
+            (3, x, -z, ay)
+        }
+    } else if z > 0.0 {
+        (4, x, -y, az)
+    } else {
+        (5, -x, -y, az)
+    };
+
+    let size = face_size as f32;
+
+    (
+        layer,
+        (sc / ma + 1.0) / 2.0 * size,
+        (tc / ma + 1.0) / 2.0 * size,
+    )
+}
+
+/// Adds every star in the catalog to the stacked cubemap image, as seen from the viewer's location
+///
+/// The star in the catalog's center system is skipped, since that one is drawn as an actual object.
+pub(super) fn paint_stars(image: &mut Image, catalog: &StarCatalog, viewer: &Location) {
+    if image.texture_descriptor.format != TextureFormat::Rgba8UnormSrgb {
+        println!("Skybox texture is not an rgba image -- not drawing stars onto it.");
+        return;
+    }
+
+    let face_size = image.texture_descriptor.size.width;
+    let layers = image.texture_descriptor.size.height / face_size.max(1);
+
+    if face_size == 0 || layers != 6 {
+        println!("Skybox texture is not 6 square faces -- not drawing stars onto it.");
+        return;
+    }
+
+    for catalog_star in catalog.stars() {
+        if catalog_star.system() == catalog.center() {
+            continue;
+        }
+
+        let offset = viewer.relative_coords_to(&catalog_star.location());
+        let distance = offset.length();
+
+        if distance == 0.0 {
+            continue;
+        }
+
+        let brightness = (FULL_BRIGHTNESS_DISTANCE / distance)
+            .powi(2)
+            .clamp(MIN_BRIGHTNESS, 1.0);
+        let radius = 1.0 + (MAX_STAR_RADIUS - 1.0) * brightness;
+
+        let color = catalog_star.star().color().as_rgba_f32();
+        let (layer, center_x, center_y) = cubemap_pixel(offset / distance, face_size);
+
+        let min_x = (center_x - radius).floor().max(0.0) as u32;
+        let max_x = ((center_x + radius).ceil() as u32).min(face_size - 1);
+        let min_y = (center_y - radius).floor().max(0.0) as u32;
+        let max_y = ((center_y + radius).ceil() as u32).min(face_size - 1);
+
+        for py in min_y..=max_y {
+            for px in min_x..=max_x {
+                let dx = px as f32 + 0.5 - center_x;
+                let dy = py as f32 + 0.5 - center_y;
+
+                let falloff = (1.0 - (dx * dx + dy * dy).sqrt() / radius).max(0.0);
+                if falloff == 0.0 {
+                    continue;
+                }
+
+                let intensity = brightness * falloff * falloff;
+                let index =
+                    ((layer as u32 * face_size * face_size + py * face_size + px) * 4) as usize;
+
+                for (channel, value) in color.iter().take(3).enumerate() {
+                    let pixel = &mut image.data[index + channel];
+
+                    *pixel = (*pixel as f32 + value * intensity * 255.0).min(255.0) as u8;
+                }
+            }
+        }
+    }
+}
@@ -7,7 +7,7 @@ pub mod chunk_retreiver;
 pub mod client_structure_builder;
 pub mod planet;
 pub mod ship;
-mod systems;
+pub mod systems;
 
 pub(super) fn register(app: &mut App) {
     systems::register(app);
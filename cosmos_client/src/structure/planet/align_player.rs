@@ -27,7 +27,7 @@ fn align_player(
         ),
         (With<LocalPlayer>, Without<Parent>),
     >,
-    planets: Query<(&Location, &GravityEmitter), With<Planet>>,
+    planets: Query<(&Location, &GravityEmitter, &Transform), (With<Planet>, Without<LocalPlayer>)>,
     mut commands: Commands,
 ) {
     if let Ok((entity, location, mut transform, alignment, prev_orientation)) =
@@ -36,16 +36,17 @@ fn align_player(
         let mut best_planet = None;
         let mut best_dist = f32::INFINITY;
 
-        for (loc, ge) in planets.iter() {
+        for (loc, ge, planet_transform) in planets.iter() {
             let dist = loc.distance_sqrd(location);
             if dist < best_dist {
                 best_dist = dist;
-                best_planet = Some((loc, ge));
+                best_planet = Some((loc, ge, planet_transform.rotation));
             }
         }
 
-        if let Some((loc, ge)) = best_planet {
-            let relative_position = loc.relative_coords_to(location);
+        if let Some((loc, ge, planet_rotation)) = best_planet {
+            // Planets spin, so which face the player is on depends on how the planet is rotated
+            let relative_position = planet_rotation.inverse() * loc.relative_coords_to(location);
 
             let dist = relative_position.abs().max_element();
 
@@ -64,43 +65,44 @@ fn align_player(
                     }
                 }
 
-                transform.rotation = transform.rotation.lerp(
-                    match face {
-                        BlockFace::Top => {
-                            commands.entity(entity).insert(PlayerAlignment(Axis::Y));
-                            Quat::IDENTITY
-                        }
-                        BlockFace::Bottom => {
-                            commands.entity(entity).insert(PlayerAlignment(Axis::Y));
-
-                            match prev_orientation {
-                                // Fixes the player rotating in a weird direction when coming from
-                                // the left/right faces of a planet.
-                                Some(PreviousOrientation(Axis::X)) => {
-                                    Quat::from_axis_angle(Vec3::Z, PI)
-                                }
-                                _ => Quat::from_axis_angle(Vec3::X, PI),
+                let face_rotation = match face {
+                    BlockFace::Top => {
+                        commands.entity(entity).insert(PlayerAlignment(Axis::Y));
+                        Quat::IDENTITY
+                    }
+                    BlockFace::Bottom => {
+                        commands.entity(entity).insert(PlayerAlignment(Axis::Y));
+
+                        match prev_orientation {
+                            // Fixes the player rotating in a weird direction when coming from
+                            // the left/right faces of a planet.
+                            Some(PreviousOrientation(Axis::X)) => {
+                                Quat::from_axis_angle(Vec3::Z, PI)
                             }
+                            _ => Quat::from_axis_angle(Vec3::X, PI),
                         }
-                        BlockFace::Back => {
-                            commands.entity(entity).insert(PlayerAlignment(Axis::Z));
-                            Quat::from_axis_angle(Vec3::X, -PI / 2.0)
-                        }
-                        BlockFace::Front => {
-                            commands.entity(entity).insert(PlayerAlignment(Axis::Z));
-                            Quat::from_axis_angle(Vec3::X, PI / 2.0)
-                        }
-                        BlockFace::Right => {
-                            commands.entity(entity).insert(PlayerAlignment(Axis::X));
-                            Quat::from_axis_angle(Vec3::Z, -PI / 2.0)
-                        }
-                        BlockFace::Left => {
-                            commands.entity(entity).insert(PlayerAlignment(Axis::X));
-                            Quat::from_axis_angle(Vec3::Z, PI / 2.0)
-                        }
-                    },
-                    0.1,
-                );
+                    }
+                    BlockFace::Back => {
+                        commands.entity(entity).insert(PlayerAlignment(Axis::Z));
+                        Quat::from_axis_angle(Vec3::X, -PI / 2.0)
+                    }
+                    BlockFace::Front => {
+                        commands.entity(entity).insert(PlayerAlignment(Axis::Z));
+                        Quat::from_axis_angle(Vec3::X, PI / 2.0)
+                    }
+                    BlockFace::Right => {
+                        commands.entity(entity).insert(PlayerAlignment(Axis::X));
+                        Quat::from_axis_angle(Vec3::Z, -PI / 2.0)
+                    }
+                    BlockFace::Left => {
+                        commands.entity(entity).insert(PlayerAlignment(Axis::X));
+                        Quat::from_axis_angle(Vec3::Z, PI / 2.0)
+                    }
+                };
+
+                transform.rotation = transform
+                    .rotation
+                    .lerp(planet_rotation * face_rotation, 0.1);
             }
         }
     }
@@ -43,6 +43,22 @@ impl Identifiable for BiosphereColor {
 
 fn register_biospheres(mut reigstry: ResMut<Registry<BiosphereColor>>) {
     reigstry.register(BiosphereColor::new("cosmos:biosphere_grass", Color::GREEN));
+    reigstry.register(BiosphereColor::new(
+        "cosmos:biosphere_desert",
+        Color::rgb(0.86, 0.8, 0.58),
+    ));
+    reigstry.register(BiosphereColor::new(
+        "cosmos:biosphere_ice",
+        Color::rgb(0.85, 0.92, 1.0),
+    ));
+    reigstry.register(BiosphereColor::new(
+        "cosmos:biosphere_molten",
+        Color::rgb(0.6, 0.2, 0.05),
+    ));
+    reigstry.register(BiosphereColor::new(
+        "cosmos:biosphere_ocean",
+        Color::rgb(0.15, 0.35, 0.8),
+    ));
     reigstry.register(BiosphereColor::new(
         "cosmos:biosphere_test_stone",
         Color::GRAY,
@@ -2,27 +2,15 @@
 
 use bevy::{
     ecs::system::EntityCommands,
-    pbr::NotShadowCaster,
-    prelude::{
-        shape::UVSphere, Added, App, Assets, Color, Commands, ComputedVisibility, Entity, Mesh,
-        Query, Res, ResMut, StandardMaterial, Visibility,
-    },
+    prelude::{Added, App, Commands, ComputedVisibility, Entity, Query, Visibility, With},
 };
-use cosmos_core::{
-    registry::Registry,
-    structure::{
-        planet::{
-            biosphere::BiosphereMarker, planet_builder::PlanetBuilder,
-            planet_builder::TPlanetBuilder, Planet,
-        },
-        Structure,
-    },
+use cosmos_core::structure::{
+    planet::{planet_builder::PlanetBuilder, planet_builder::TPlanetBuilder, Planet},
+    Structure,
 };
 
 use crate::structure::client_structure_builder::ClientStructureBuilder;
 
-use super::biosphere::BiosphereColor;
-
 /// Responsible for building planets for the client.
 pub struct ClientPlanetBuilder {
     planet_builder: PlanetBuilder<ClientStructureBuilder>,
@@ -47,36 +35,12 @@ impl TPlanetBuilder for ClientPlanetBuilder {
     }
 }
 
-fn added_planet(
-    query: Query<(Entity, &Structure, &BiosphereMarker), Added<Planet>>,
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    color_registry: Res<Registry<BiosphereColor>>,
-) {
-    for (ent, structure, marker) in query.iter() {
-        let blocks_radius = structure.blocks_width() as f32 / 2.0;
-
-        commands.entity(ent).insert((
-            meshes.add(
-                UVSphere {
-                    radius: (blocks_radius * blocks_radius * 2.0).sqrt() * 1.1,
-                    sectors: 128,
-                    stacks: 128,
-                }
-                .into(),
-            ),
-            materials.add(StandardMaterial {
-                base_color: color_registry
-                    .from_id(marker.biosphere_name())
-                    .map(|x| x.color())
-                    .unwrap_or(Color::WHITE),
-                ..Default::default()
-            }),
-            Visibility::default(),
-            ComputedVisibility::default(),
-            NotShadowCaster,
-        ));
+/// Planets are drawn from far away using the coarse terrain the server sends, which is added as children of the planet
+fn added_planet(query: Query<Entity, (Added<Planet>, With<Structure>)>, mut commands: Commands) {
+    for ent in query.iter() {
+        commands
+            .entity(ent)
+            .insert((Visibility::default(), ComputedVisibility::default()));
     }
 }
 
@@ -0,0 +1,391 @@
+//! Draws the coarse terrain the server sends for each planet, so planets can be seen from far away.
+//!
+//! The coarse terrain is split into cells the size of a chunk. Any cell whose chunks are loaded is cut out
+//! of the mesh, so the real terrain replaces it as chunks are loaded around the player.
+
+use std::time::Duration;
+
+use bevy::{
+    pbr::NotShadowCaster,
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    time::common_conditions::on_timer,
+};
+use bevy_renet::renet::RenetClient;
+use cosmos_core::{
+    block::BlockFace,
+    netty::{cosmos_encoder, NettyChannel},
+    registry::Registry,
+    structure::{
+        chunk::{CHUNK_DIMENSIONS, CHUNK_DIMENSIONSF},
+        planet::{
+            biosphere::BiosphereMarker,
+            lod::{lod_surface_point, LOD_RESOLUTION},
+            planet_netty::PlanetServerMessages,
+            Planet,
+        },
+        ChunkState, Structure,
+    },
+};
+
+use crate::netty::mapping::NetworkMapping;
+
+use super::biosphere::BiosphereColor;
+
+/// How long LOD faces are kept around waiting for their planet to be created before they're given up on
+const MAX_LOD_WAIT_SEC: f32 = 10.0;
+/// The shortest time between warnings about invalid LOD faces, so a misbehaving server can't flood the log
+const INVALID_LOD_WARNING_INTERVAL_SEC: f32 = 5.0;
+
+#[derive(Component, Debug)]
+/// The mesh of one face of a planet's coarse terrain
+struct PlanetLodFace {
+    face: BlockFace,
+    heights: Vec<u16>,
+    /// The cells that are cut out because the real terrain is loaded there
+    hidden: Vec<bool>,
+}
+
+/// Samples the coarse terrain's height at these block coordinates along a face
+fn height_at(heights: &[u16], (u, v): (f32, f32), s_dimensions: f32) -> f32 {
+    let last = (LOD_RESOLUTION - 1) as f32;
+    let sample_spacing = s_dimensions / last;
+
+    let (u, v) = (
+        (u / sample_spacing).clamp(0.0, last),
+        (v / sample_spacing).clamp(0.0, last),
+    );
+
+    let (u0, v0) = (
+        (u as usize).min(LOD_RESOLUTION - 2),
+        (v as usize).min(LOD_RESOLUTION - 2),
+    );
+    let (tu, tv) = (u - u0 as f32, v - v0 as f32);
+
+    let sample = |u: usize, v: usize| heights[v * LOD_RESOLUTION + u] as f32;
+
+    let near = sample(u0, v0) * (1.0 - tu) + sample(u0 + 1, v0) * tu;
+    let far = sample(u0, v0 + 1) * (1.0 - tu) + sample(u0 + 1, v0 + 1) * tu;
+
+    near * (1.0 - tv) + far * tv
+}
+
+/// The position (relative to the planet's corner) of the coarse terrain at these block coordinates along a face
+///
+/// * `depth` How far below the surface the point is
+fn surface_point(
+    face: BlockFace,
+    heights: &[u16],
+    (u, v): (f32, f32),
+    depth: f32,
+    s_dimensions: f32,
+) -> Vec3 {
+    let s = s_dimensions;
+    let height = height_at(heights, (u, v), s) - depth;
+
+    // The faces of a planet meet at 45 degree angles, so each face only covers the part of the planet
+    // where it is higher up than the faces next to it
+    let (low, high) = ((s - height).min(height), (s - height).max(height));
+
+    lod_surface_point(face, (u.clamp(low, high), v.clamp(low, high)), height, s)
+}
+
+/// Finds which cells of a face have their real terrain loaded, so the coarse terrain isn't needed there
+///
+/// A cell counts as loaded once the chunk at its surface & the chunks directly above & below it are,
+/// since the coarse terrain skips over small hills & valleys.
+fn loaded_cells(face: BlockFace, heights: &[u16], structure: &Structure) -> Vec<bool> {
+    let s = structure.blocks_height() as f32;
+    let cells = structure.chunks_height();
+
+    let mut hidden = Vec::with_capacity(cells * cells);
+
+    for v in 0..cells {
+        for u in 0..cells {
+            let center = (
+                (u as f32 + 0.5) * CHUNK_DIMENSIONSF,
+                (v as f32 + 0.5) * CHUNK_DIMENSIONSF,
+            );
+
+            let loaded = [-CHUNK_DIMENSIONSF, 0.0, CHUNK_DIMENSIONSF]
+                .iter()
+                .all(|&depth| {
+                    let point = surface_point(face, heights, center, depth, s) / CHUNK_DIMENSIONSF;
+
+                    let [cx, cy, cz] = point
+                        .floor()
+                        .clamp(Vec3::ZERO, Vec3::splat((cells - 1) as f32))
+                        .to_array()
+                        .map(|c| c as usize);
+
+                    structure.get_chunk_state(cx, cy, cz) == ChunkState::Loaded
+                });
+
+            hidden.push(loaded);
+        }
+    }
+
+    hidden
+}
+
+/// Creates the mesh for one face of a planet's coarse terrain, relative to the planet's center
+///
+/// * `hidden` The cells to leave out of the mesh, see [`loaded_cells`]
+fn create_lod_face_mesh(
+    face: BlockFace,
+    heights: &[u16],
+    hidden: &[bool],
+    s_dimensions: usize,
+) -> Mesh {
+    let s = s_dimensions as f32;
+    let cells = s_dimensions / CHUNK_DIMENSIONS;
+    let center = Vec3::splat(s / 2.0);
+
+    let coordinate = |i: usize| (i as f32 * CHUNK_DIMENSIONSF).min(s);
+    let point = |u: usize, v: usize, depth: f32| {
+        surface_point(face, heights, (coordinate(u), coordinate(v)), depth, s) - center
+    };
+
+    let mut positions = Vec::with_capacity((cells + 1) * (cells + 1));
+    let mut normals = Vec::with_capacity((cells + 1) * (cells + 1));
+    let mut indices = Vec::new();
+
+    let grid_index = |u: usize, v: usize| (v * (cells + 1) + u) as u32;
+    let is_hidden = |u: i32, v: i32| {
+        u < 0
+            || v < 0
+            || u as usize >= cells
+            || v as usize >= cells
+            || hidden[v as usize * cells + u as usize]
+    };
+
+    for v in 0..=cells {
+        for u in 0..=cells {
+            positions.push(point(u, v, 0.0));
+
+            let du = point((u + 1).min(cells), v, 0.0) - point(u.saturating_sub(1), v, 0.0);
+            let dv = point(u, (v + 1).min(cells), 0.0) - point(u, v.saturating_sub(1), 0.0);
+
+            let mut normal = du.cross(dv).normalize_or_zero();
+            if normal.dot(face.direction_vec3()) < 0.0 {
+                normal = -normal;
+            }
+
+            normals.push(normal);
+        }
+    }
+
+    for v in 0..cells {
+        for u in 0..cells {
+            if is_hidden(u as i32, v as i32) {
+                continue;
+            }
+
+            let (a, b, c, d) = (
+                grid_index(u, v),
+                grid_index(u + 1, v),
+                grid_index(u + 1, v + 1),
+                grid_index(u, v + 1),
+            );
+
+            indices.extend([a, b, c, c, d, a]);
+
+            // The real terrain & the edges of neighboring faces don't line up perfectly with the coarse terrain,
+            // so a skirt hanging down from each edge that isn't next to more coarse terrain hides the gaps
+            let edges = [
+                ((u, v), (u + 1, v), (0, -1)),
+                ((u + 1, v), (u + 1, v + 1), (1, 0)),
+                ((u + 1, v + 1), (u, v + 1), (0, 1)),
+                ((u, v + 1), (u, v), (-1, 0)),
+            ];
+
+            for (start, end, (du, dv)) in edges {
+                if !is_hidden(u as i32 + du, v as i32 + dv) {
+                    continue;
+                }
+
+                let skirt_start = positions.len() as u32;
+
+                for (u, v) in [end, start] {
+                    positions.push(point(u, v, CHUNK_DIMENSIONSF));
+                    normals.push(normals[grid_index(u, v) as usize]);
+                }
+
+                let (a, b) = (grid_index(start.0, start.1), grid_index(end.0, end.1));
+                let (c, d) = (skirt_start, skirt_start + 1);
+
+                indices.extend([a, b, c, c, d, a]);
+            }
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        positions
+            .into_iter()
+            .map(|x| x.to_array())
+            .collect::<Vec<[f32; 3]>>(),
+    );
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_NORMAL,
+        normals
+            .into_iter()
+            .map(|x| x.to_array())
+            .collect::<Vec<[f32; 3]>>(),
+    );
+
+    mesh
+}
+
+fn receive_planet_lods(
+    mut client: ResMut<RenetClient>,
+    mut waiting: Local<Vec<(Entity, BlockFace, Vec<u16>, f32)>>,
+    mut last_invalid_warning: Local<Option<f32>>,
+    time: Res<Time>,
+    network_mapping: Res<NetworkMapping>,
+    planets: Query<(&Structure, &BiosphereMarker, Option<&Children>), With<Planet>>,
+    lod_faces: Query<&PlanetLodFace>,
+    color_registry: Res<Registry<BiosphereColor>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    let mut received = std::mem::take(&mut *waiting);
+
+    while let Some(message) = client.receive_message(NettyChannel::Planets.id()) {
+        let msg: PlanetServerMessages = cosmos_encoder::deserialize(&message).unwrap();
+
+        match msg {
+            PlanetServerMessages::LodFace {
+                entity,
+                face,
+                heights,
+            } => {
+                received.push((entity, face, heights, 0.0));
+            }
+        }
+    }
+
+    for (server_entity, face, heights, waited) in received {
+        if heights.len() != LOD_RESOLUTION * LOD_RESOLUTION {
+            let now = time.elapsed_seconds();
+
+            if last_invalid_warning
+                .map(|last| now - last >= INVALID_LOD_WARNING_INTERVAL_SEC)
+                .unwrap_or(true)
+            {
+                println!(
+                    "Got invalid planet LOD heights for {server_entity:?} ({} heights) -- ignoring them.",
+                    heights.len()
+                );
+                *last_invalid_warning = Some(now);
+            }
+
+            continue;
+        }
+
+        // The planet itself is sent on a different channel, so it may not have been created yet
+        let Some((planet_entity, (structure, biosphere_marker, children))) = network_mapping
+            .client_from_server(&server_entity)
+            .and_then(|entity| planets.get(entity).ok().map(|planet| (entity, planet)))
+        else {
+            // If the planet takes too long, it was probably despawned or left this client's interest before being created
+            let waited = waited + time.delta_seconds();
+            if waited < MAX_LOD_WAIT_SEC {
+                waiting.push((server_entity, face, heights, waited));
+            }
+
+            continue;
+        };
+
+        // Only one mesh is kept per face, in case the server sends it again
+        if let Some(children) = children {
+            for &child in children.iter() {
+                if lod_faces
+                    .get(child)
+                    .map(|x| x.face == face)
+                    .unwrap_or(false)
+                {
+                    commands.entity(child).despawn_recursive();
+                }
+            }
+        }
+
+        let color = color_registry
+            .from_id(biosphere_marker.biosphere_name())
+            .map(|x| x.color())
+            .unwrap_or(Color::WHITE);
+
+        let hidden = loaded_cells(face, &heights, structure);
+
+        let lod_entity = commands
+            .spawn((
+                PbrBundle {
+                    mesh: meshes.add(create_lod_face_mesh(
+                        face,
+                        &heights,
+                        &hidden,
+                        structure.blocks_height(),
+                    )),
+                    material: materials.add(StandardMaterial {
+                        base_color: color,
+                        perceptual_roughness: 1.0,
+                        reflectance: 0.1,
+                        // The skirts are seen from both sides
+                        cull_mode: None,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                NotShadowCaster,
+                PlanetLodFace {
+                    face,
+                    heights,
+                    hidden,
+                },
+            ))
+            .id();
+
+        commands.entity(planet_entity).add_child(lod_entity);
+    }
+}
+
+/// Cuts the coarse terrain out wherever chunks have been loaded, & puts it back wherever they've been unloaded
+fn hide_loaded_lod_cells(
+    mut lod_faces: Query<(&mut PlanetLodFace, &Handle<Mesh>, &Parent)>,
+    planets: Query<&Structure, With<Planet>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for (mut lod_face, mesh_handle, parent) in lod_faces.iter_mut() {
+        let Ok(structure) = planets.get(parent.get()) else {
+            continue;
+        };
+
+        let hidden = loaded_cells(lod_face.face, &lod_face.heights, structure);
+
+        if hidden == lod_face.hidden {
+            continue;
+        }
+
+        if let Some(mesh) = meshes.get_mut(mesh_handle) {
+            *mesh = create_lod_face_mesh(
+                lod_face.face,
+                &lod_face.heights,
+                &hidden,
+                structure.blocks_height(),
+            );
+        }
+
+        lod_face.hidden = hidden;
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems((
+        receive_planet_lods.run_if(resource_exists::<RenetClient>()),
+        hide_loaded_lod_cells.run_if(on_timer(Duration::from_millis(250))),
+    ));
+}
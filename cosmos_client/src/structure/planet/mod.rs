@@ -1,18 +1,16 @@
 //! Handles client-related planet things
 
 use bevy::prelude::{
-    in_state, App, Commands, CoreSet, Entity, IntoSystemConfig, OnUpdate, Query, Res, ResMut, Vec3,
-    With,
+    in_state, App, Commands, CoreSet, Entity, IntoSystemConfig, OnUpdate, Query, Res, ResMut,
+    Transform, With,
 };
 use bevy_renet::renet::RenetClient;
 use cosmos_core::{
     netty::{client_reliable_messages::ClientReliableMessages, cosmos_encoder, NettyChannel},
     physics::location::Location,
     structure::{
-        chunk::{Chunk, CHUNK_DIMENSIONSF},
-        planet::Planet,
-        structure_iterator::ChunkIteratorResult,
-        ChunkState, Structure,
+        chunk::Chunk, planet::Planet, structure_iterator::ChunkIteratorResult, ChunkState,
+        Structure,
     },
 };
 
@@ -24,6 +22,7 @@ use crate::{
 pub mod align_player;
 pub mod biosphere;
 pub mod client_planet_builder;
+mod lod;
 
 #[cfg(debug_assertions)]
 const RENDER_DISTANCE: i32 = 2;
@@ -32,24 +31,17 @@ const RENDER_DISTANCE: i32 = 3;
 
 fn load_planet_chunks(
     query: Query<&Location, With<LocalPlayer>>,
-    mut planet: Query<(Entity, &Location, &mut Structure), With<Planet>>,
+    mut planet: Query<(Entity, &Location, &Transform, &mut Structure), With<Planet>>,
     mapper: Res<NetworkMapping>,
     mut client: ResMut<RenetClient>,
 ) {
     if let Ok(player) = query.get_single() {
-        for (entity, location, mut best_planet) in planet.iter_mut() {
+        for (entity, location, transform, mut best_planet) in planet.iter_mut() {
             if let Some(server_entity) = mapper.server_from_client(&entity) {
-                let player_relative_position: Vec3 = (*player - *location).into();
-                let (px, py, pz) = best_planet.relative_coords_to_local_coords(
-                    player_relative_position.x,
-                    player_relative_position.y,
-                    player_relative_position.z,
-                );
-
-                let (px, py, pz) = (
-                    (px as f32 / CHUNK_DIMENSIONSF).floor() as i32,
-                    (py as f32 / CHUNK_DIMENSIONSF).floor() as i32,
-                    (pz as f32 / CHUNK_DIMENSIONSF).floor() as i32,
+                let (px, py, pz) = Planet::chunk_coords_at(
+                    &best_planet,
+                    transform.rotation,
+                    (*player - *location).into(),
                 );
 
                 let mut chunks = vec![];
@@ -92,23 +84,13 @@ fn load_planet_chunks(
 /// Put systems that mess with chunks before this.
 pub fn unload_chunks_far_from_players(
     player: Query<&Location, With<LocalPlayer>>,
-    mut planets: Query<(&Location, &mut Structure), With<Planet>>,
+    mut planets: Query<(&Location, &Transform, &mut Structure), With<Planet>>,
     mut commands: Commands,
 ) {
     if let Ok(player) = player.get_single() {
-        for (location, mut planet) in planets.iter_mut() {
-            let player_relative_position: Vec3 = (*player - *location).into();
-            let (px, py, pz) = planet.relative_coords_to_local_coords(
-                player_relative_position.x,
-                player_relative_position.y,
-                player_relative_position.z,
-            );
-
-            let (px, py, pz) = (
-                (px as f32 / CHUNK_DIMENSIONSF).floor() as i32,
-                (py as f32 / CHUNK_DIMENSIONSF).floor() as i32,
-                (pz as f32 / CHUNK_DIMENSIONSF).floor() as i32,
-            );
+        for (location, transform, mut planet) in planets.iter_mut() {
+            let (px, py, pz) =
+                Planet::chunk_coords_at(&planet, transform.rotation, (*player - *location).into());
 
             let rd = RENDER_DISTANCE + 1;
 
@@ -143,6 +125,7 @@ pub(super) fn register(app: &mut App) {
     align_player::register(app);
     client_planet_builder::register(app);
     biosphere::register(app);
+    lod::register(app);
 
     app.add_system(load_planet_chunks.in_set(OnUpdate(GameState::Playing)))
         .add_system(
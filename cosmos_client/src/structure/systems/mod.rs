@@ -1,4 +1,7 @@
+//! Handles the client-side parts of a structure's systems, such as using them while piloting
+
 mod player_interactions;
+pub mod warp_drive;
 
 use bevy::prelude::*;
 use bevy_renet::renet::RenetClient;
@@ -54,4 +57,5 @@ pub(super) fn register(app: &mut App) {
     );
 
     player_interactions::register(app);
+    warp_drive::register(app);
 }
@@ -0,0 +1,79 @@
+//! Keeps track of the warp drive of the ship the player is piloting, which the server regularly sends
+
+use bevy::prelude::{
+    resource_exists, App, Commands, IntoSystemConfig, Query, Res, ResMut, Resource, With,
+};
+use bevy_renet::renet::RenetClient;
+use cosmos_core::{
+    netty::{cosmos_encoder, warp_drive_messages::ServerWarpDriveMessages, NettyChannel},
+    physics::location::Location,
+    structure::ship::pilot::Pilot,
+};
+
+use crate::netty::{flags::LocalPlayer, mapping::NetworkMapping};
+
+#[derive(Resource, Debug, Clone, Copy)]
+/// The state of the warp drive of the ship the player is piloting
+///
+/// This is only present while the player is piloting a ship with a warp drive.
+pub struct WarpDriveStatus {
+    /// How much charge the warp drive has
+    pub charge: f32,
+    /// The most charge the warp drive can hold
+    pub charge_capacity: f32,
+    /// How much charge it would take to warp to the current destination
+    pub charge_needed: f32,
+    /// Where the ship will warp to
+    pub destination: Location,
+    /// How many seconds are left until the ship warps, or None if it isn't spooling up
+    pub spool_remaining: Option<f32>,
+}
+
+fn receive_warp_drive_status(
+    mut client: ResMut<RenetClient>,
+    network_mapping: Res<NetworkMapping>,
+    mut commands: Commands,
+) {
+    while let Some(message) = client.receive_message(NettyChannel::WarpDrive.id()) {
+        let msg: ServerWarpDriveMessages = cosmos_encoder::deserialize(&message).unwrap();
+
+        match msg {
+            ServerWarpDriveMessages::WarpDriveStatus {
+                structure_entity,
+                charge,
+                charge_capacity,
+                charge_needed,
+                destination,
+                spool_remaining,
+            } => {
+                if network_mapping
+                    .client_from_server(&structure_entity)
+                    .is_some()
+                {
+                    commands.insert_resource(WarpDriveStatus {
+                        charge,
+                        charge_capacity,
+                        charge_needed,
+                        destination,
+                        spool_remaining,
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn remove_status_when_not_piloting(
+    query: Query<(), (With<Pilot>, With<LocalPlayer>)>,
+    status: Option<Res<WarpDriveStatus>>,
+    mut commands: Commands,
+) {
+    if status.is_some() && query.is_empty() {
+        commands.remove_resource::<WarpDriveStatus>();
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_system(receive_warp_drive_status.run_if(resource_exists::<RenetClient>()))
+        .add_system(remove_status_when_not_piloting);
+}
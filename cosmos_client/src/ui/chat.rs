@@ -0,0 +1,220 @@
+//! Displays the chat & lets the player type into it
+//!
+//! While the chat is open, all other keyboard & mouse inputs are consumed by it.
+
+use bevy::{input::InputSystem, prelude::*};
+use bevy_renet::renet::RenetClient;
+use cosmos_core::netty::{
+    chat_messages::{ClientChatMessages, ServerChatMessages, MAX_CHAT_MESSAGE_LENGTH},
+    cosmos_encoder, NettyChannel,
+};
+
+use crate::{
+    input::inputs::{CosmosInputHandler, CosmosInputs},
+    state::game_state::GameState,
+};
+
+/// How long a message stays visible when the chat isn't open
+const MESSAGE_VISIBLE_SEC: f32 = 10.0;
+/// The most messages that will be displayed at once
+const MAX_DISPLAYED_MESSAGES: usize = 10;
+/// The most messages that will be remembered
+const MAX_STORED_MESSAGES: usize = 100;
+
+struct ChatLine {
+    text: String,
+    age: f32,
+}
+
+#[derive(Resource, Default)]
+/// Every message the player has received
+struct ChatHistory {
+    lines: Vec<ChatLine>,
+}
+
+#[derive(Resource, Default, Debug)]
+/// The state of the chat box the player types into
+pub struct ChatInput {
+    open: bool,
+    text: String,
+}
+
+impl ChatInput {
+    /// Returns true if the player is currently typing into the chat
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+#[derive(Component)]
+struct ChatHistoryText;
+
+#[derive(Component)]
+struct ChatInputText;
+
+fn add_chat(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_style = TextStyle {
+        color: Color::WHITE,
+        font_size: 24.0,
+        font: asset_server.load("fonts/PixeloidSans.ttf"),
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(150.0),
+                    left: Val::Px(5.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section("", text_style.clone()),
+                    ..default()
+                },
+                ChatHistoryText,
+            ));
+
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section("", text_style),
+                    ..default()
+                },
+                ChatInputText,
+            ));
+        });
+}
+
+fn receive_messages(mut client: ResMut<RenetClient>, mut history: ResMut<ChatHistory>) {
+    while let Some(message) = client.receive_message(NettyChannel::Chat.id()) {
+        let msg: ServerChatMessages = cosmos_encoder::deserialize(&message).unwrap();
+
+        match msg {
+            ServerChatMessages::ChatMessage { sender, message } => {
+                let text = match sender {
+                    Some(sender) => format!("<{sender}> {message}"),
+                    None => message,
+                };
+
+                for line in text.lines() {
+                    println!("[Chat] {line}");
+
+                    history.lines.push(ChatLine {
+                        text: line.to_owned(),
+                        age: 0.0,
+                    });
+                }
+
+                if history.lines.len() > MAX_STORED_MESSAGES {
+                    let overflow = history.lines.len() - MAX_STORED_MESSAGES;
+                    history.lines.drain(0..overflow);
+                }
+            }
+        }
+    }
+}
+
+pub(super) fn type_in_chat(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut mouse: ResMut<Input<MouseButton>>,
+    input_handler: Res<CosmosInputHandler>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut chat_input: ResMut<ChatInput>,
+    mut client: ResMut<RenetClient>,
+) {
+    if !chat_input.open {
+        if input_handler.check_just_pressed(CosmosInputs::OpenChat, &keys, &mouse) {
+            chat_input.open = true;
+        } else if input_handler.check_just_pressed(CosmosInputs::OpenCommand, &keys, &mouse) {
+            chat_input.open = true;
+            chat_input.text.push('/');
+        } else {
+            return;
+        }
+
+        // The key used to open the chat shouldn't be typed into it
+        characters.clear();
+        keys.reset_all();
+        mouse.reset_all();
+
+        return;
+    }
+
+    for ev in characters.iter() {
+        if ev.char == '\u{8}' {
+            chat_input.text.pop();
+        } else if !ev.char.is_control() && chat_input.text.len() < MAX_CHAT_MESSAGE_LENGTH {
+            chat_input.text.push(ev.char);
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Return) {
+        let message = std::mem::take(&mut chat_input.text);
+
+        if !message.trim().is_empty() {
+            client.send_message(
+                NettyChannel::Chat.id(),
+                cosmos_encoder::serialize(&ClientChatMessages::SendMessage { message }),
+            );
+        }
+
+        chat_input.open = false;
+    } else if keys.just_pressed(KeyCode::Escape) {
+        chat_input.text.clear();
+        chat_input.open = false;
+    }
+
+    keys.reset_all();
+    mouse.reset_all();
+}
+
+fn update_chat_display(
+    mut history: ResMut<ChatHistory>,
+    chat_input: Res<ChatInput>,
+    mut history_text: Query<&mut Text, (With<ChatHistoryText>, Without<ChatInputText>)>,
+    mut input_text: Query<&mut Text, (With<ChatInputText>, Without<ChatHistoryText>)>,
+    time: Res<Time>,
+) {
+    for line in history.lines.iter_mut() {
+        line.age += time.delta_seconds();
+    }
+
+    if let Ok(mut text) = history_text.get_single_mut() {
+        let start = history.lines.len().saturating_sub(MAX_DISPLAYED_MESSAGES);
+
+        text.sections[0].value = history.lines[start..]
+            .iter()
+            .filter(|line| chat_input.open || line.age < MESSAGE_VISIBLE_SEC)
+            .map(|line| line.text.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n");
+    }
+
+    if let Ok(mut text) = input_text.get_single_mut() {
+        text.sections[0].value = if chat_input.open {
+            format!("> {}_", chat_input.text)
+        } else {
+            String::new()
+        };
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.insert_resource(ChatHistory::default())
+        .insert_resource(ChatInput::default())
+        .add_system(add_chat.in_schedule(OnEnter(GameState::Playing)))
+        .add_system(
+            type_in_chat
+                .in_base_set(CoreSet::PreUpdate)
+                .after(InputSystem)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems((receive_messages, update_chat_display).in_set(OnUpdate(GameState::Playing)));
+}
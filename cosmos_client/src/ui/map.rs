@@ -0,0 +1,526 @@
+//! A map of the player's system & the stars around it, which the player can set waypoints from
+//!
+//! While the map is open, all other keyboard & mouse inputs are consumed by it.
+
+use bevy::{input::InputSystem, prelude::*};
+use cosmos_core::{
+    physics::location::{
+        Location, Sector, SectorUnit, SystemUnit, UniverseSystem, SYSTEM_DIMENSIONS, SYSTEM_SECTORS,
+    },
+    universe::{
+        star_catalog::{CatalogStar, STAR_CATALOG_RADIUS},
+        system_map::SystemBodyKind,
+    },
+};
+
+use crate::{
+    input::inputs::{CosmosInputHandler, CosmosInputs},
+    netty::flags::LocalPlayer,
+    state::game_state::GameState,
+    universe::{
+        star_catalog::StarCatalog,
+        system_map::SystemMap,
+        waypoints::{Waypoint, Waypoints},
+    },
+};
+
+use super::chat::{self, ChatInput};
+
+/// How many pixels wide & tall the map is
+const MAP_SIZE: f32 = 600.0;
+/// How many pixels wide & tall the dot showing the player is
+const PLAYER_DOT_SIZE: f32 = 8.0;
+/// The most entries that will be listed next to the map at once
+const MAX_LISTED_ENTRIES: usize = 20;
+/// How many sectors away from a star its waypoint is, so nobody warps into it
+const STAR_WAYPOINT_OFFSET: SectorUnit = 5;
+/// How far above the surface of a body its waypoint is
+const BODY_WAYPOINT_CLEARANCE: f32 = 200.0;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum MapView {
+    #[default]
+    /// Everything in the player's system
+    System,
+    /// The stars around the player's system
+    Galaxy,
+}
+
+#[derive(Resource, Default, Debug)]
+/// The state of the map the player can open
+pub struct MapState {
+    open: bool,
+    view: MapView,
+    selected: usize,
+}
+
+impl MapState {
+    /// Returns true if the player currently has the map open
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+/// Something that can be selected on the map
+struct MapEntry {
+    name: String,
+    location: Location,
+    color: Color,
+    size: f32,
+    /// Where a waypoint to this should be put
+    waypoint: Location,
+}
+
+#[derive(Component)]
+struct MapRoot;
+
+#[derive(Component)]
+struct MapCanvas;
+
+#[derive(Component)]
+struct MapDot;
+
+#[derive(Component)]
+struct MapPlayerDot;
+
+#[derive(Component)]
+struct MapListText;
+
+/// The location at the center of this system, which is where its star would be
+fn system_center(system: UniverseSystem) -> Location {
+    let half = SYSTEM_SECTORS as SystemUnit / 2;
+
+    Location::new(
+        Vec3::ZERO,
+        Sector::new(
+            system.x() * SYSTEM_SECTORS as SystemUnit + half,
+            system.y() * SYSTEM_SECTORS as SystemUnit + half,
+            system.z() * SYSTEM_SECTORS as SystemUnit + half,
+        ),
+    )
+}
+
+/// The location at the center of the map & how wide of an area the map covers
+fn view_area(
+    view: MapView,
+    system_map: Option<&SystemMap>,
+    catalog: Option<&StarCatalog>,
+) -> Option<(Location, f64)> {
+    match view {
+        MapView::System => {
+            system_map.map(|map| (system_center(map.system()), SYSTEM_DIMENSIONS as f64))
+        }
+        MapView::Galaxy => catalog.map(|catalog| {
+            (
+                system_center(catalog.center()),
+                SYSTEM_DIMENSIONS as f64 * (STAR_CATALOG_RADIUS * 2 + 1) as f64,
+            )
+        }),
+    }
+}
+
+/// Where on the map this location is, looking down from above, or None if it's off of the map
+fn project(center: &Location, width: f64, location: &Location) -> Option<Vec2> {
+    let delta = location.absolute_coords_f64() - center.absolute_coords_f64();
+
+    let x = delta.x / width + 0.5;
+    let y = delta.z / width + 0.5;
+
+    ((0.0..=1.0).contains(&x) && (0.0..=1.0).contains(&y))
+        .then(|| Vec2::new(x as f32, y as f32) * MAP_SIZE)
+}
+
+fn distance(a: &Location, b: &Location) -> f64 {
+    (a.absolute_coords_f64() - b.absolute_coords_f64()).norm()
+}
+
+/// Everything that can be selected in this view, closest to the center first
+fn map_entries(
+    view: MapView,
+    system_map: Option<&SystemMap>,
+    catalog: Option<&StarCatalog>,
+) -> Vec<MapEntry> {
+    let Some((center, _)) = view_area(view, system_map, catalog) else {
+        return vec![];
+    };
+
+    let star_entry = |star: &CatalogStar, size: f32| {
+        let system = star.system();
+        let location = star.location();
+
+        MapEntry {
+            name: format!("Star [{}, {}, {}]", system.x(), system.y(), system.z()),
+            color: star.star().color(),
+            size,
+            waypoint: Location::new(
+                Vec3::ZERO,
+                location.sector() + Sector::new(0, 0, STAR_WAYPOINT_OFFSET),
+            ),
+            location,
+        }
+    };
+
+    let mut entries = match view {
+        MapView::System => {
+            let Some(system_map) = system_map else {
+                return vec![];
+            };
+
+            let mut entries = catalog
+                .and_then(|catalog| {
+                    catalog
+                        .stars()
+                        .iter()
+                        .find(|star| star.system() == system_map.system())
+                })
+                .map(|star| vec![star_entry(star, 14.0)])
+                .unwrap_or_default();
+
+            entries.extend(system_map.bodies().iter().map(|body| {
+                let (color, size) = match body.kind() {
+                    SystemBodyKind::Planet => (Color::rgb(0.3, 0.7, 0.4), 10.0),
+                    SystemBodyKind::Asteroid => (Color::rgb(0.6, 0.55, 0.5), 6.0),
+                    SystemBodyKind::Ship => (Color::rgb(0.5, 0.7, 1.0), 4.0),
+                    SystemBodyKind::Player => (Color::WHITE, 4.0),
+                };
+
+                MapEntry {
+                    name: format!("{} [{}]", body.name(), body.location().sector()),
+                    location: *body.location(),
+                    color,
+                    size,
+                    waypoint: *body.location()
+                        + Vec3::Y * (body.radius() + BODY_WAYPOINT_CLEARANCE),
+                }
+            }));
+
+            entries
+        }
+        MapView::Galaxy => catalog
+            .map(|catalog| {
+                catalog
+                    .stars()
+                    .iter()
+                    .map(|star| star_entry(star, 8.0))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+
+    // Sorted by distance to the center rather than the player so the selection doesn't jump around as they move
+    entries
+        .sort_by(|a, b| distance(&a.location, &center).total_cmp(&distance(&b.location, &center)));
+
+    entries
+}
+
+fn add_map(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_style = TextStyle {
+        color: Color::WHITE,
+        font_size: 20.0,
+        font: asset_server.load("fonts/PixeloidSans.ttf"),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    display: Display::None,
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.05, 0.9).into(),
+                ..default()
+            },
+            MapRoot,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(MAP_SIZE), Val::Px(MAP_SIZE)),
+                            margin: UiRect::right(Val::Px(20.0)),
+                            ..default()
+                        },
+                        background_color: Color::rgb(0.05, 0.05, 0.12).into(),
+                        ..default()
+                    },
+                    MapCanvas,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                size: Size::new(Val::Px(PLAYER_DOT_SIZE), Val::Px(PLAYER_DOT_SIZE)),
+                                ..default()
+                            },
+                            background_color: Color::RED.into(),
+                            z_index: ZIndex::Local(1),
+                            ..default()
+                        },
+                        MapPlayerDot,
+                    ));
+                });
+
+            parent.spawn((
+                TextBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(500.0), Val::Px(MAP_SIZE)),
+                        ..default()
+                    },
+                    text: Text::from_section("", text_style),
+                    ..default()
+                },
+                MapListText,
+            ));
+        });
+}
+
+fn use_map(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut mouse: ResMut<Input<MouseButton>>,
+    input_handler: Res<CosmosInputHandler>,
+    chat_input: Res<ChatInput>,
+    mut map_state: ResMut<MapState>,
+    mut waypoints: ResMut<Waypoints>,
+    system_map: Option<Res<SystemMap>>,
+    catalog: Option<Res<StarCatalog>>,
+) {
+    if chat_input.is_open() {
+        return;
+    }
+
+    if !map_state.open {
+        if input_handler.check_just_pressed(CosmosInputs::ToggleMap, &keys, &mouse) {
+            map_state.open = true;
+
+            keys.reset_all();
+            mouse.reset_all();
+        }
+
+        return;
+    }
+
+    if input_handler.check_just_pressed(CosmosInputs::ToggleMap, &keys, &mouse)
+        || keys.just_pressed(KeyCode::Escape)
+    {
+        map_state.open = false;
+    } else if keys.just_pressed(KeyCode::Tab) {
+        map_state.view = match map_state.view {
+            MapView::System => MapView::Galaxy,
+            MapView::Galaxy => MapView::System,
+        };
+        map_state.selected = 0;
+    } else if keys.just_pressed(KeyCode::Back) {
+        waypoints.clear();
+    } else {
+        let entries = map_entries(map_state.view, system_map.as_deref(), catalog.as_deref());
+
+        if keys.just_pressed(KeyCode::Up) {
+            map_state.selected = map_state.selected.saturating_sub(1);
+        } else if keys.just_pressed(KeyCode::Down) {
+            map_state.selected = (map_state.selected + 1).min(entries.len().saturating_sub(1));
+        } else if keys.just_pressed(KeyCode::Return) {
+            if let Some(entry) = entries.get(map_state.selected) {
+                waypoints.toggle(Waypoint {
+                    name: entry.name.clone(),
+                    location: entry.waypoint,
+                });
+            }
+        }
+    }
+
+    keys.reset_all();
+    mouse.reset_all();
+}
+
+fn show_map(map_state: Res<MapState>, mut root: Query<&mut Style, With<MapRoot>>) {
+    if !map_state.is_changed() {
+        return;
+    }
+
+    if let Ok(mut style) = root.get_single_mut() {
+        style.display = if map_state.open {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+fn draw_map(
+    map_state: Res<MapState>,
+    system_map: Option<Res<SystemMap>>,
+    catalog: Option<Res<StarCatalog>>,
+    waypoints: Res<Waypoints>,
+    player: Query<&Location, With<LocalPlayer>>,
+    canvas: Query<Entity, With<MapCanvas>>,
+    dots: Query<Entity, With<MapDot>>,
+    mut list_text: Query<&mut Text, With<MapListText>>,
+    mut commands: Commands,
+) {
+    if !map_state.open {
+        return;
+    }
+
+    let changed = map_state.is_changed()
+        || waypoints.is_changed()
+        || system_map.as_ref().map_or(false, |x| x.is_changed())
+        || catalog.as_ref().map_or(false, |x| x.is_changed());
+
+    if !changed {
+        return;
+    }
+
+    for dot in dots.iter() {
+        commands.entity(dot).despawn_recursive();
+    }
+
+    let system_map = system_map.as_deref();
+    let catalog = catalog.as_deref();
+
+    let entries = map_entries(map_state.view, system_map, catalog);
+
+    if let (Ok(canvas), Some((center, width))) = (
+        canvas.get_single(),
+        view_area(map_state.view, system_map, catalog),
+    ) {
+        commands.entity(canvas).with_children(|parent| {
+            for (i, entry) in entries.iter().enumerate() {
+                let Some(at) = project(&center, width, &entry.location) else {
+                    continue;
+                };
+
+                let size = if i == map_state.selected {
+                    entry.size * 1.5
+                } else {
+                    entry.size
+                };
+
+                let color = if waypoints.contains(&entry.name) {
+                    Color::YELLOW
+                } else {
+                    entry.color
+                };
+
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            position: UiRect {
+                                left: Val::Px(at.x - size / 2.0),
+                                top: Val::Px(at.y - size / 2.0),
+                                ..default()
+                            },
+                            size: Size::new(Val::Px(size), Val::Px(size)),
+                            ..default()
+                        },
+                        background_color: color.into(),
+                        ..default()
+                    },
+                    MapDot,
+                ));
+            }
+        });
+    }
+
+    let Ok(mut text) = list_text.get_single_mut() else {
+        return;
+    };
+
+    let title = match map_state.view {
+        MapView::System => "System map - [Tab] galaxy map",
+        MapView::Galaxy => "Galaxy map - [Tab] system map",
+    };
+
+    let mut lines = vec![title.to_owned(), String::new()];
+
+    if entries.is_empty() {
+        lines.push("Waiting for the server...".into());
+    }
+
+    let player = player.get_single().ok();
+
+    let start = map_state
+        .selected
+        .saturating_sub(MAX_LISTED_ENTRIES / 2)
+        .min(entries.len().saturating_sub(MAX_LISTED_ENTRIES));
+
+    for (i, entry) in entries
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(MAX_LISTED_ENTRIES)
+    {
+        let selected = if i == map_state.selected { '>' } else { ' ' };
+        let waypoint = if waypoints.contains(&entry.name) {
+            '*'
+        } else {
+            ' '
+        };
+
+        let away = player
+            .map(|player| format!(" ({:.1} km)", distance(player, &entry.location) / 1000.0))
+            .unwrap_or_default();
+
+        lines.push(format!("{selected}{waypoint} {}{away}", entry.name));
+    }
+
+    lines.push(String::new());
+    lines.push("[Up/Down] select - [Enter] toggle waypoint".into());
+    lines.push("[Backspace] clear waypoints - [M] close".into());
+
+    text.sections[0].value = lines.join("\n");
+}
+
+fn move_player_dot(
+    map_state: Res<MapState>,
+    system_map: Option<Res<SystemMap>>,
+    catalog: Option<Res<StarCatalog>>,
+    player: Query<&Location, With<LocalPlayer>>,
+    mut dot: Query<(&mut Style, &mut Visibility), With<MapPlayerDot>>,
+) {
+    if !map_state.open {
+        return;
+    }
+
+    let Ok((mut style, mut visibility)) = dot.get_single_mut() else {
+        return;
+    };
+
+    let at = view_area(map_state.view, system_map.as_deref(), catalog.as_deref()).and_then(
+        |(center, width)| {
+            player
+                .get_single()
+                .ok()
+                .and_then(|location| project(&center, width, location))
+        },
+    );
+
+    if let Some(at) = at {
+        style.position.left = Val::Px(at.x - PLAYER_DOT_SIZE / 2.0);
+        style.position.top = Val::Px(at.y - PLAYER_DOT_SIZE / 2.0);
+        *visibility = Visibility::Inherited;
+    } else {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.insert_resource(MapState::default())
+        .add_system(add_map.in_schedule(OnEnter(GameState::Playing)))
+        .add_system(
+            // Before the chat so the chat can't be opened while the map is open
+            use_map
+                .in_base_set(CoreSet::PreUpdate)
+                .after(InputSystem)
+                .before(chat::type_in_chat)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems((show_map, draw_map, move_player_dot).in_set(OnUpdate(GameState::Playing)));
+}
@@ -2,12 +2,20 @@
 
 use bevy::prelude::App;
 
+pub mod chat;
 pub mod crosshair;
 pub mod debug_info_display;
 pub mod hotbar;
+pub mod map;
+pub mod warp_drive_display;
+pub mod waypoint_markers;
 
 pub(super) fn register(app: &mut App) {
+    chat::register(app);
     crosshair::register(app);
     hotbar::register(app);
     debug_info_display::register(app);
+    warp_drive_display::register(app);
+    map::register(app);
+    waypoint_markers::register(app);
 }
@@ -0,0 +1,83 @@
+//! Displays how charged the warp drive of the ship being piloted is, and where it will warp to
+
+use bevy::prelude::*;
+use cosmos_core::physics::location::Location;
+
+use crate::{
+    netty::flags::LocalPlayer, state::game_state::GameState,
+    structure::systems::warp_drive::WarpDriveStatus,
+};
+
+#[derive(Component)]
+struct WarpDriveText;
+
+fn add_text(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_style = TextStyle {
+        color: Color::rgb(0.6, 0.8, 1.0),
+        font_size: 24.0,
+        font: asset_server.load("fonts/PixeloidSans.ttf"),
+    };
+
+    commands.spawn((
+        TextBundle {
+            style: Style {
+                position: UiRect {
+                    top: Val::Px(5.0),
+                    right: Val::Px(5.0),
+                    ..default()
+                },
+                position_type: PositionType::Absolute,
+
+                ..default()
+            },
+            text: Text::from_section("", text_style),
+            ..default()
+        },
+        WarpDriveText,
+    ));
+}
+
+fn update_text(
+    status: Option<Res<WarpDriveStatus>>,
+    player: Query<&Location, With<LocalPlayer>>,
+    mut text: Query<&mut Text, With<WarpDriveText>>,
+) {
+    let Ok(mut text) = text.get_single_mut() else {
+        return;
+    };
+
+    let Some(status) = status else {
+        text.sections[0].value.clear();
+        return;
+    };
+
+    let percent = status.charge / status.charge_capacity * 100.0;
+
+    let state = if let Some(remaining) = status.spool_remaining {
+        format!("Warping in {remaining:.1}s")
+    } else if status.charge >= status.charge_needed {
+        "Ready to warp".into()
+    } else if status.charge_needed > status.charge_capacity {
+        "Destination out of range".into()
+    } else {
+        "Charging".into()
+    };
+
+    let distance = player
+        .get_single()
+        .map(|location| {
+            let delta = status.destination.absolute_coords_f64() - location.absolute_coords_f64();
+            format!(" ({:.0} km away)", delta.norm() / 1000.0)
+        })
+        .unwrap_or_default();
+
+    text.sections[0].value = format!(
+        "Warp drive: {percent:.0}% - {state}\nDestination: ({}){distance}",
+        status.destination.sector()
+    );
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_system(add_text.in_schedule(OnEnter(GameState::Playing)))
+        .add_system(update_text.in_set(OnUpdate(GameState::Playing)));
+}
@@ -0,0 +1,133 @@
+//! Shows where the player's waypoints are & how far away they are while flying
+
+use bevy::prelude::*;
+use cosmos_core::physics::location::Location;
+
+use crate::{
+    netty::flags::LocalPlayer, rendering::MainCamera, state::game_state::GameState,
+    universe::waypoints::Waypoints,
+};
+
+use super::map::MapState;
+
+/// How far in front of the camera markers are projected from, which keeps far away waypoints from losing precision
+const MARKER_PROJECTION_DISTANCE: f32 = 1000.0;
+
+#[derive(Component)]
+struct WaypointMarker {
+    name: String,
+}
+
+#[derive(Resource)]
+struct MarkerStyle(TextStyle);
+
+fn load_marker_style(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(MarkerStyle(TextStyle {
+        color: Color::WHITE,
+        font_size: 18.0,
+        font: asset_server.load("fonts/PixeloidSans.ttf"),
+    }));
+}
+
+fn create_markers(
+    waypoints: Res<Waypoints>,
+    markers: Query<Entity, With<WaypointMarker>>,
+    marker_style: Res<MarkerStyle>,
+    mut commands: Commands,
+) {
+    if !waypoints.is_changed() {
+        return;
+    }
+
+    for entity in markers.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let active = waypoints.active().map(|waypoint| waypoint.name.as_str());
+
+    for waypoint in waypoints.iter() {
+        let mut style = marker_style.0.clone();
+
+        if Some(waypoint.name.as_str()) == active {
+            style.color = Color::YELLOW;
+        }
+
+        commands.spawn((
+            TextBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                text: Text::from_section("", style),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            WaypointMarker {
+                name: waypoint.name.clone(),
+            },
+        ));
+    }
+}
+
+fn position_markers(
+    waypoints: Res<Waypoints>,
+    map_state: Res<MapState>,
+    player: Query<&Location, With<LocalPlayer>>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut markers: Query<(&WaypointMarker, &mut Style, &mut Text, &mut Visibility)>,
+) {
+    let (Ok(player_location), Ok((camera, camera_transform))) =
+        (player.get_single(), camera.get_single())
+    else {
+        return;
+    };
+
+    let Some(viewport_size) = camera.logical_viewport_size() else {
+        return;
+    };
+
+    for (marker, mut style, mut text, mut visibility) in markers.iter_mut() {
+        let Some(waypoint) = waypoints
+            .iter()
+            .find(|waypoint| waypoint.name == marker.name)
+        else {
+            continue;
+        };
+
+        let direction = player_location
+            .relative_coords_to(&waypoint.location)
+            .normalize_or_zero();
+
+        // Markers behind the camera aren't in the viewport, so they aren't shown
+        let on_screen = camera.world_to_viewport(
+            camera_transform,
+            camera_transform.translation() + direction * MARKER_PROJECTION_DISTANCE,
+        );
+
+        let (Some(on_screen), false) = (on_screen, map_state.is_open()) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        let distance = (waypoint.location.absolute_coords_f64()
+            - player_location.absolute_coords_f64())
+        .norm();
+
+        // The viewport's origin is its bottom left, but the ui's is its top left
+        style.position.left = Val::Px(on_screen.x - 6.0);
+        style.position.top = Val::Px(viewport_size.y - on_screen.y - 12.0);
+
+        text.sections[0].value = format!("+ {} ({:.1} km)", waypoint.name, distance / 1000.0);
+
+        *visibility = Visibility::Inherited;
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_system(load_marker_style.in_schedule(OnEnter(GameState::Playing)))
+        .add_systems(
+            (create_markers, position_markers)
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
@@ -3,7 +3,12 @@
 use bevy::prelude::App;
 
 pub mod star;
+pub mod star_catalog;
+pub mod system_map;
+pub mod waypoints;
 
 pub(super) fn register(app: &mut App) {
     star::register(app);
+    star_catalog::register(app);
+    waypoints::register(app);
 }
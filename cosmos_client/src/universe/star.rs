@@ -3,32 +3,147 @@
 use bevy::{
     pbr::NotShadowCaster,
     prelude::{
-        shape, Added, App, Assets, Commands, DirectionalLight, Entity, Mesh, PbrBundle, Query,
-        ResMut, StandardMaterial, Transform, Vec3, With, Without,
+        shape, Added, AmbientLight, App, Assets, Color, Commands, DetectChanges, DetectChangesMut,
+        DirectionalLight, Entity, IntoSystemConfig, Mesh, PbrBundle, Query, Res, ResMut, Resource,
+        StandardMaterial, Transform, Vec3, With, Without,
     },
 };
-use cosmos_core::{physics::location::SECTOR_DIMENSIONS, universe::star::Star};
+use cosmos_core::{
+    physics::{gravity_system::GravityEmitter, location::Location},
+    structure::planet::{planet_movement::CARRY_RADIUS_MULTIPLIER, Planet},
+    universe::star::{Star, STAR_RADIUS},
+};
+
+use crate::netty::flags::LocalPlayer;
 
 /// Determines how bright light is based off your distance from a star.
 ///
 /// This is a random number I made up, but looks nice enough
 const LIGHT_INTENSITY_CONSTANT: f32 = 3_000_000_000_000_000.0;
 
+/// Where the star is relative to the horizon (as the dot product of the ground's normal & the
+/// direction to the star) when it stops being night
+const DAWN_START: f32 = -0.1;
+/// Where the star is relative to the horizon when it becomes fully day
+const DAWN_END: f32 = 0.2;
+
+/// Bevy's default ambient brightness, which is used in space
+const SPACE_AMBIENT_BRIGHTNESS: f32 = 0.05;
+const DAY_AMBIENT_BRIGHTNESS: f32 = 0.3;
+const NIGHT_AMBIENT_BRIGHTNESS: f32 = 0.02;
+const NIGHT_AMBIENT_COLOR: Color = Color::rgb(0.5, 0.6, 1.0);
+
+/// The sky's color at noon. The alpha is how much of the stars behind it are hidden.
+const DAY_SKY_COLOR: Color = Color::rgba(0.45, 0.65, 0.95, 0.85);
+
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+/// How much of the star's light reaches the ground where the local player is standing.
+///
+/// This is `None` when they aren't on a planet, since there's no ground to block the light.
+pub struct Daylight(Option<f32>);
+
+impl Daylight {
+    /// From 0.0 (night) to 1.0 (day), or `None` if the local player isn't on a planet
+    pub fn amount(&self) -> Option<f32> {
+        self.0
+    }
+
+    /// The color the sky should be tinted. The alpha is how much of the stars behind it are hidden.
+    pub fn sky_color(&self) -> Color {
+        let amount = self.0.unwrap_or(0.0);
+
+        DAY_SKY_COLOR.with_a(DAY_SKY_COLOR.a() * amount)
+    }
+}
+
+fn lerp_color(from: Color, to: Color, amount: f32) -> Color {
+    Color::rgb(
+        from.r() + (to.r() - from.r()) * amount,
+        from.g() + (to.g() - from.g()) * amount,
+        from.b() + (to.b() - from.b()) * amount,
+    )
+}
+
+/// Faces of a planet that point away from the star are night, and faces that point towards it are day
+fn compute_daylight(
+    player: Query<&Location, With<LocalPlayer>>,
+    planets: Query<(&Location, &Transform, &GravityEmitter), With<Planet>>,
+    sun: Query<&Transform, With<Star>>,
+    mut daylight: ResMut<Daylight>,
+) {
+    let Ok(player_location) = player.get_single() else {
+        daylight.set_if_neq(Daylight(None));
+        return;
+    };
+
+    let on_planet = planets
+        .iter()
+        .find_map(|(location, transform, gravity_emitter)| {
+            let relative_position =
+                transform.rotation.inverse() * location.relative_coords_to(player_location);
+
+            let distance = relative_position.abs().max_element();
+
+            (distance <= gravity_emitter.radius * CARRY_RADIUS_MULTIPLIER)
+                .then_some((relative_position, transform))
+        });
+
+    let Some((relative_position, planet_transform)) = on_planet else {
+        daylight.set_if_neq(Daylight(None));
+        return;
+    };
+
+    let Ok(sun) = sun.get_single() else {
+        // Planets without a star are always dark
+        daylight.set_if_neq(Daylight(Some(0.0)));
+        return;
+    };
+
+    let ground_normal = planet_transform.rotation
+        * Planet::planet_face_relative(relative_position).direction_vec3();
+    // Everything is positioned around the local player, and the star is far enough away that exactly where they are doesn't matter
+    let to_sun = sun.translation.normalize_or_zero();
+
+    let t = ((ground_normal.dot(to_sun) - DAWN_START) / (DAWN_END - DAWN_START)).clamp(0.0, 1.0);
+
+    daylight.set_if_neq(Daylight(Some(t * t * (3.0 - 2.0 * t))));
+}
+
 fn point_light_from_sun(
     sun: Query<&Transform, With<Star>>,
     mut light: Query<(&mut Transform, &mut DirectionalLight), Without<Star>>,
+    daylight: Res<Daylight>,
 ) {
     if let Ok((mut transform, mut light)) = light.get_single_mut() {
         if let Ok(sun) = sun.get_single() {
             transform.look_at(-sun.translation, Vec3::Y);
             let sun_dist_sqrd = sun.translation.dot(sun.translation);
-            light.illuminance = LIGHT_INTENSITY_CONSTANT / sun_dist_sqrd;
+            // Otherwise the light would shine through the planet onto its night side
+            light.illuminance =
+                LIGHT_INTENSITY_CONSTANT / sun_dist_sqrd * daylight.amount().unwrap_or(1.0);
         } else {
             light.illuminance = 0.0;
         }
     }
 }
 
+fn tint_ambient_light(daylight: Res<Daylight>, mut ambient_light: ResMut<AmbientLight>) {
+    if !daylight.is_changed() {
+        return;
+    }
+
+    let (color, brightness) = match daylight.amount() {
+        Some(amount) => (
+            lerp_color(NIGHT_AMBIENT_COLOR, Color::WHITE, amount),
+            NIGHT_AMBIENT_BRIGHTNESS + (DAY_AMBIENT_BRIGHTNESS - NIGHT_AMBIENT_BRIGHTNESS) * amount,
+        ),
+        None => (Color::WHITE, SPACE_AMBIENT_BRIGHTNESS),
+    };
+
+    ambient_light.color = color;
+    ambient_light.brightness = brightness;
+}
+
 fn create_added_star(
     added: Query<(Entity, &Star), Added<Star>>,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -43,7 +158,7 @@ fn create_added_star(
                     shape::UVSphere {
                         sectors: 256,
                         stacks: 256,
-                        radius: SECTOR_DIMENSIONS * 2.0,
+                        radius: STAR_RADIUS,
                     }
                     .into(),
                 ),
@@ -60,6 +175,9 @@ fn create_added_star(
 }
 
 pub(super) fn register(app: &mut App) {
-    app.add_system(create_added_star)
-        .add_system(point_light_from_sun);
+    app.init_resource::<Daylight>()
+        .add_system(create_added_star)
+        .add_system(compute_daylight)
+        .add_system(point_light_from_sun.after(compute_daylight))
+        .add_system(tint_ambient_light.after(compute_daylight));
 }
@@ -0,0 +1,53 @@
+//! Keeps track of the stars around the player, which the server sends whenever the player enters a new system
+//!
+//! This also receives the rest of the universe messages, such as the [`SystemMap`].
+
+use bevy::prelude::{resource_exists, App, Commands, IntoSystemConfig, ResMut, Resource};
+use bevy_renet::renet::RenetClient;
+use cosmos_core::{
+    netty::{cosmos_encoder, NettyChannel},
+    physics::location::UniverseSystem,
+    universe::{star_catalog::CatalogStar, universe_netty::UniverseServerMessages},
+};
+
+use super::system_map::SystemMap;
+
+#[derive(Resource, Debug)]
+/// The stars around the player, see [`cosmos_core::universe::star_catalog`]
+///
+/// This is only present once the server has sent it.
+pub struct StarCatalog {
+    center: UniverseSystem,
+    stars: Vec<CatalogStar>,
+}
+
+impl StarCatalog {
+    /// The system the player was in when this catalog was sent
+    pub fn center(&self) -> UniverseSystem {
+        self.center
+    }
+
+    /// Every star around the center system
+    pub fn stars(&self) -> &[CatalogStar] {
+        &self.stars
+    }
+}
+
+fn receive_universe_messages(mut client: ResMut<RenetClient>, mut commands: Commands) {
+    while let Some(message) = client.receive_message(NettyChannel::Universe.id()) {
+        let msg: UniverseServerMessages = cosmos_encoder::deserialize(&message).unwrap();
+
+        match msg {
+            UniverseServerMessages::StarCatalog { center, stars } => {
+                commands.insert_resource(StarCatalog { center, stars });
+            }
+            UniverseServerMessages::SystemMap { system, bodies } => {
+                commands.insert_resource(SystemMap::new(system, bodies));
+            }
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_system(receive_universe_messages.run_if(resource_exists::<RenetClient>()));
+}
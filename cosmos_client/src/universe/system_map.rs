@@ -0,0 +1,29 @@
+//! Keeps track of everything in the player's system, which the server sends every few seconds
+
+use bevy::prelude::Resource;
+use cosmos_core::{physics::location::UniverseSystem, universe::system_map::SystemBody};
+
+#[derive(Resource, Debug)]
+/// The planets, asteroids, ships & players in the player's system, see [`cosmos_core::universe::system_map`]
+///
+/// This is only present once the server has sent it.
+pub struct SystemMap {
+    system: UniverseSystem,
+    bodies: Vec<SystemBody>,
+}
+
+impl SystemMap {
+    pub(super) fn new(system: UniverseSystem, bodies: Vec<SystemBody>) -> Self {
+        Self { system, bodies }
+    }
+
+    /// The system these bodies are in
+    pub fn system(&self) -> UniverseSystem {
+        self.system
+    }
+
+    /// Everything the server knows of in this system
+    pub fn bodies(&self) -> &[SystemBody] {
+        &self.bodies
+    }
+}
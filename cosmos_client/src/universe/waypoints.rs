@@ -0,0 +1,151 @@
+//! Places the player has marked on their map, which are shown on their screen while flying
+//!
+//! The most recently added waypoint is the active one, and is where the warp drive of any ship they pilot will take them.
+//!
+//! Waypoints are saved in `waypoints/`, with one file for each server since a location only makes sense on the server it's from.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::{
+    resource_exists, Added, App, IntoSystemAppConfig, IntoSystemConfig, OnEnter, OnUpdate, Query,
+    Res, ResMut, Resource, With,
+};
+use bevy_renet::renet::RenetClient;
+use cosmos_core::{
+    netty::{cosmos_encoder, warp_drive_messages::ClientWarpDriveMessages, NettyChannel},
+    physics::location::Location,
+    structure::ship::pilot::Pilot,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    netty::{connect::ConnectionConfig, flags::LocalPlayer},
+    state::game_state::GameState,
+};
+
+/// The folder waypoints are saved in
+const WAYPOINTS_FOLDER: &str = "waypoints";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A place the player has marked
+pub struct Waypoint {
+    /// What to call this waypoint
+    pub name: String,
+    /// Where this waypoint is
+    pub location: Location,
+}
+
+#[derive(Resource, Debug, Default)]
+/// Every waypoint the player has set
+pub struct Waypoints {
+    waypoints: Vec<Waypoint>,
+}
+
+impl Waypoints {
+    /// Every waypoint, in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = &Waypoint> {
+        self.waypoints.iter()
+    }
+
+    /// The most recently added waypoint
+    pub fn active(&self) -> Option<&Waypoint> {
+        self.waypoints.last()
+    }
+
+    /// Returns true if there's a waypoint with this name
+    pub fn contains(&self, name: &str) -> bool {
+        self.waypoints.iter().any(|waypoint| waypoint.name == name)
+    }
+
+    /// Adds this waypoint & makes it the active one, or removes it if there's already one with its name
+    pub fn toggle(&mut self, waypoint: Waypoint) {
+        if self.contains(&waypoint.name) {
+            self.waypoints.retain(|x| x.name != waypoint.name);
+        } else {
+            self.waypoints.push(waypoint);
+        }
+    }
+
+    /// Removes every waypoint
+    pub fn clear(&mut self) {
+        self.waypoints.clear();
+    }
+}
+
+/// The file the waypoints for this server are saved in
+fn waypoints_path(host_name: &str) -> PathBuf {
+    let file_name = host_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    Path::new(WAYPOINTS_FOLDER).join(format!("{file_name}.json"))
+}
+
+fn load_waypoints(connection_config: Res<ConnectionConfig>, mut waypoints: ResMut<Waypoints>) {
+    let path = waypoints_path(&connection_config.host_name);
+
+    let Ok(data) = fs::read(&path) else {
+        return;
+    };
+
+    match serde_json::from_slice::<Vec<Waypoint>>(&data) {
+        Ok(loaded) => waypoints.waypoints = loaded,
+        Err(e) => println!("Error reading waypoints in {} - {e}", path.display()),
+    }
+}
+
+fn save_waypoints(connection_config: Res<ConnectionConfig>, waypoints: Res<Waypoints>) {
+    if !waypoints.is_changed() {
+        return;
+    }
+
+    let path = waypoints_path(&connection_config.host_name);
+
+    let result = fs::create_dir_all(WAYPOINTS_FOLDER).and_then(|_| {
+        fs::write(
+            &path,
+            serde_json::to_vec_pretty(&waypoints.waypoints)
+                .expect("Waypoints are always valid json"),
+        )
+    });
+
+    if let Err(e) = result {
+        println!("Error saving waypoints to {} - {e}", path.display());
+    }
+}
+
+/// Warp drives take the player to their active waypoint, so the server has to know where it is
+fn send_warp_target(
+    waypoints: Res<Waypoints>,
+    piloting: Query<(), (With<Pilot>, With<LocalPlayer>)>,
+    started_piloting: Query<(), (Added<Pilot>, With<LocalPlayer>)>,
+    mut client: ResMut<RenetClient>,
+) {
+    if piloting.is_empty() || (!waypoints.is_changed() && started_piloting.is_empty()) {
+        return;
+    }
+
+    client.send_message(
+        NettyChannel::WarpDrive.id(),
+        cosmos_encoder::serialize(&ClientWarpDriveMessages::SetWarpTarget {
+            target: waypoints.active().map(|waypoint| waypoint.location),
+        }),
+    );
+}
+
+pub(super) fn register(app: &mut App) {
+    app.init_resource::<Waypoints>()
+        .add_system(load_waypoints.in_schedule(OnEnter(GameState::Connecting)))
+        .add_system(save_waypoints.in_set(OnUpdate(GameState::Playing)))
+        .add_system(send_warp_target.run_if(resource_exists::<RenetClient>()));
+}
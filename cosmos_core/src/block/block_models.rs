@@ -0,0 +1,331 @@
+//! The shapes of blocks that aren't just full cubes.
+//!
+//! Both a block's mesh on the client & its collider are built from its model, so the two always line up.
+//!
+//! A block uses a model by setting `"model"` in its `blocks/{block}.json` file, such as
+//! `"model": "cosmos:slab"`, which is then loaded from `blocks/models/slab.json`.
+//! The client reads both files through its resource packs, so packs can change or add models, while the server
+//! reads them from its own `assets` folder.
+//!
+//! A model file has an entry for each face of the block:
+//!
+//! ```json
+//! {
+//!     "bottom": {
+//!         "covers": true,
+//!         "quads": [{ "positions": [[...], [...], [...], [...]], "uvs": [[...], [...], [...], [...]] }]
+//!     },
+//!     "top": {
+//!         "inner": [{ "positions": [[...], [...], [...], [...]], "uvs": [[...], [...], [...], [...]] }]
+//!     }
+//! }
+//! ```
+//!
+//! - `quads` are hidden whenever the block on that side covers them. Triangles (3 corners) are also allowed.
+//! - `inner` is geometry inside of the block that is never hidden, such as the top of a slab.
+//! - `covers` is true if this face fills the entire side of the block, which hides the face of the block touching it.
+//!
+//! Both use that face's texture, and positions range from -0.5 to 0.5 like a full block.
+//! Corners should be listed counter-clockwise when looking at the front of the quad, and together
+//! the quads of a model should fully enclose it so its collider can be worked out.
+
+use bevy::prelude::{App, Vec3};
+use serde::{de::Error, Deserialize};
+
+use crate::registry::{
+    identifiable::Identifiable,
+    many_to_one::{self, ManyToOneRegistry},
+    Registry,
+};
+
+use super::{Block, BlockFace};
+
+#[derive(Deserialize, Debug)]
+/// The parts of a block's json file the models care about
+struct BlockModelInfo {
+    model: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+/// A quad or triangle of a block model
+pub struct QuadModel {
+    positions: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+}
+
+impl QuadModel {
+    /// The corners of this quad, counter-clockwise when looking at its front
+    pub fn positions(&self) -> &[[f32; 3]] {
+        &self.positions
+    }
+
+    /// The texture coordinates of each corner
+    pub fn uvs(&self) -> &[[f32; 2]] {
+        &self.uvs
+    }
+
+    /// Splits this quad into triangles
+    fn triangles(&self) -> impl Iterator<Item = [Vec3; 3]> + '_ {
+        (1..self.positions.len().saturating_sub(1))
+            .map(|i| [0, i, i + 1].map(|corner| Vec3::from(self.positions[corner])))
+    }
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+/// Everything drawn with the texture of one face of a block
+pub struct FaceModel {
+    covers: bool,
+    quads: Vec<QuadModel>,
+    inner: Vec<QuadModel>,
+}
+
+impl FaceModel {
+    /// Returns true if this face fills the entire side of the block
+    pub fn covers(&self) -> bool {
+        self.covers
+    }
+
+    /// The quads that are hidden whenever the block on this side covers them
+    pub fn quads(&self) -> &[QuadModel] {
+        &self.quads
+    }
+
+    /// The quads inside of the block, which are never hidden
+    pub fn inner(&self) -> &[QuadModel] {
+        &self.inner
+    }
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+/// The shape of a block that isn't a full cube
+pub struct BlockModel {
+    #[serde(skip)]
+    id: u16,
+    #[serde(skip)]
+    unlocalized_name: String,
+
+    right: FaceModel,
+    left: FaceModel,
+    top: FaceModel,
+    bottom: FaceModel,
+    front: FaceModel,
+    back: FaceModel,
+}
+
+impl BlockModel {
+    /// Reads a model from its json
+    ///
+    /// * `unlocalized_name` The name of the model, such as `cosmos:slab`
+    pub fn from_json(unlocalized_name: &str, json: &str) -> serde_json::Result<Self> {
+        let mut model = serde_json::from_str::<Self>(json)?;
+
+        let valid = model.faces().into_iter().all(|face| {
+            face.quads.iter().chain(face.inner.iter()).all(|quad| {
+                (3..=4).contains(&quad.positions.len()) && quad.uvs.len() == quad.positions.len()
+            })
+        });
+
+        if !valid {
+            return Err(serde_json::Error::custom(
+                "every quad needs 3 or 4 positions with a uv for each position",
+            ));
+        }
+
+        model.unlocalized_name = unlocalized_name.to_owned();
+
+        Ok(model)
+    }
+
+    fn faces(&self) -> [&FaceModel; 6] {
+        [
+            &self.right,
+            &self.left,
+            &self.top,
+            &self.bottom,
+            &self.front,
+            &self.back,
+        ]
+    }
+
+    /// Everything drawn with the texture of this face
+    pub fn face(&self, face: BlockFace) -> &FaceModel {
+        match face {
+            BlockFace::Right => &self.right,
+            BlockFace::Left => &self.left,
+            BlockFace::Top => &self.top,
+            BlockFace::Bottom => &self.bottom,
+            BlockFace::Front => &self.front,
+            BlockFace::Back => &self.back,
+        }
+    }
+
+    /// Every triangle of this model, counter-clockwise when looking at its front
+    pub fn triangles(&self) -> Vec<[Vec3; 3]> {
+        self.faces()
+            .into_iter()
+            .flat_map(|face| face.quads.iter().chain(face.inner.iter()))
+            .flat_map(|quad| quad.triangles())
+            .collect()
+    }
+}
+
+impl Identifiable for BlockModel {
+    fn id(&self) -> u16 {
+        self.id
+    }
+
+    fn set_numeric_id(&mut self, id: u16) {
+        self.id = id;
+    }
+
+    fn unlocalized_name(&self) -> &str {
+        &self.unlocalized_name
+    }
+}
+
+/// This is a `ManyToOneRegistry` mapping Blocks to the [`BlockModel`] they use.
+///
+/// Blocks without a model aren't in here, and are full cubes.
+pub type BlockModelRegistry = ManyToOneRegistry<Block, BlockModel>;
+
+/// Returns the name of the model a block uses, or None if it's a full cube
+///
+/// * `read` Reads a file relative to the assets folder, such as `blocks/grass.json`
+fn block_model_name(block: &Block, read: &impl Fn(&str) -> Option<Vec<u8>>) -> Option<String> {
+    let unlocalized_name = block.unlocalized_name();
+    let block_name = unlocalized_name
+        .split(':')
+        .nth(1)
+        .unwrap_or(unlocalized_name);
+
+    let json_path = format!("blocks/{block_name}.json");
+
+    let block_info = read(&json_path)?;
+
+    match serde_json::from_slice::<BlockModelInfo>(&block_info) {
+        Ok(info) => info.model,
+        Err(e) => {
+            println!("[Block Models] Error reading json data in {json_path} - {e}");
+            None
+        }
+    }
+}
+
+/// Reads a model from its json file, such as `blocks/models/slab.json` for `cosmos:slab`
+fn load_block_model(
+    model_name: &str,
+    read: &impl Fn(&str) -> Option<Vec<u8>>,
+) -> Option<BlockModel> {
+    let file_name = model_name.split(':').nth(1).unwrap_or(model_name);
+    let json_path = format!("blocks/models/{file_name}.json");
+
+    let Some(json) = read(&json_path) else {
+        println!("[Block Models] Missing model {model_name} ({json_path})");
+        return None;
+    };
+
+    let model = std::str::from_utf8(&json)
+        .map_err(serde_json::Error::custom)
+        .and_then(|json| BlockModel::from_json(model_name, json));
+
+    match model {
+        Ok(model) => Some(model),
+        Err(e) => {
+            println!("[Block Models] Error reading json data in {json_path} - {e}");
+            None
+        }
+    }
+}
+
+/// Loads the model of every block that has one.
+///
+/// Blocks whose model is missing or invalid are left out with a warning, which makes them full cubes.
+///
+/// * `read` Reads a file relative to the assets folder, such as `blocks/grass.json`
+pub fn load_block_models(
+    blocks: &Registry<Block>,
+    read: impl Fn(&str) -> Option<Vec<u8>>,
+) -> BlockModelRegistry {
+    let mut registry = BlockModelRegistry::new();
+
+    for block in blocks.iter() {
+        let Some(model_name) = block_model_name(block, &read) else {
+            continue;
+        };
+
+        if registry.add_link(block, &model_name).is_ok() {
+            continue;
+        }
+
+        if let Some(model) = load_block_model(&model_name, &read) {
+            registry.insert_value(model);
+
+            registry.add_link(block, &model_name).unwrap_or_else(|_| {
+                panic!("{model_name} model link wasn't inserted successfully!")
+            });
+        }
+    }
+
+    registry
+}
+
+pub(super) fn register(app: &mut App) {
+    many_to_one::create_many_to_one_registry::<Block, BlockModel>(app);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        block::{block_builder::BlockBuilder, Block},
+        registry::{identifiable::Identifiable, Registry},
+    };
+
+    use super::load_block_models;
+
+    const SLAB: &str = include_str!("../../../cosmos_client/assets/blocks/models/slab.json");
+
+    #[test]
+    fn test_missing_or_invalid_models_are_full_cubes() {
+        let mut blocks = Registry::<Block>::new();
+
+        for name in [
+            "cosmos:slab_block",
+            "cosmos:broken_block",
+            "cosmos:missing_block",
+            "cosmos:cube",
+        ] {
+            blocks.register(BlockBuilder::new(name.into(), 1.0).create());
+        }
+
+        let models = load_block_models(&blocks, |path| {
+            let file = match path {
+                "blocks/slab_block.json" => r#"{ "model": "cosmos:slab" }"#,
+                "blocks/broken_block.json" => r#"{ "model": "cosmos:broken" }"#,
+                "blocks/missing_block.json" => r#"{ "model": "cosmos:missing" }"#,
+                "blocks/models/slab.json" => SLAB,
+                "blocks/models/broken.json" => {
+                    r#"{ "top": { "quads": [{ "positions": [[0, 0, 0]], "uvs": [[0, 0]] }] } }"#
+                }
+                _ => return None,
+            };
+
+            Some(file.as_bytes().to_vec())
+        });
+
+        let model_name = |name: &str| {
+            models
+                .get_value(blocks.from_id(name).expect("Block registered above"))
+                .map(|model| model.unlocalized_name().to_owned())
+        };
+
+        assert_eq!(
+            model_name("cosmos:slab_block").as_deref(),
+            Some("cosmos:slab")
+        );
+        assert_eq!(model_name("cosmos:broken_block"), None);
+        assert_eq!(model_name("cosmos:missing_block"), None);
+        assert_eq!(model_name("cosmos:cube"), None);
+    }
+}
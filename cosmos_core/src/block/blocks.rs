@@ -43,6 +43,85 @@ fn add_cosmos_blocks(
             .create(),
     );
 
+    blocks.register(
+        BlockBuilder::new("cosmos:sand".into(), 3.0)
+            .add_property(BlockProperty::Opaque)
+            .add_property(BlockProperty::Full)
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:sandstone".into(), 8.0)
+            .add_property(BlockProperty::Opaque)
+            .add_property(BlockProperty::Full)
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:snow".into(), 2.0)
+            .add_property(BlockProperty::Opaque)
+            .add_property(BlockProperty::Full)
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:ice".into(), 5.0)
+            .add_property(BlockProperty::Opaque)
+            .add_property(BlockProperty::Full)
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:water".into(), 1.0)
+            .add_property(BlockProperty::Transparent)
+            .add_property(BlockProperty::Full)
+            .add_property(BlockProperty::Fluid)
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:basalt".into(), 12.0)
+            .add_property(BlockProperty::Opaque)
+            .add_property(BlockProperty::Full)
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:lava".into(), 1.0)
+            .add_property(BlockProperty::Transparent)
+            .add_property(BlockProperty::Full)
+            .add_property(BlockProperty::Fluid)
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:cactus".into(), 2.0)
+            .add_property(BlockProperty::Opaque)
+            .add_property(BlockProperty::Full)
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:iron_ore".into(), 12.0)
+            .add_property(BlockProperty::Opaque)
+            .add_property(BlockProperty::Full)
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:copper_ore".into(), 12.0)
+            .add_property(BlockProperty::Opaque)
+            .add_property(BlockProperty::Full)
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:crystal_ore".into(), 8.0)
+            .add_property(BlockProperty::Opaque)
+            .add_property(BlockProperty::Full)
+            .create(),
+    );
+
     blocks.register(
         BlockBuilder::new("cosmos:cherry_leaf".into(), 0.1)
             .add_property(BlockProperty::Transparent)
@@ -85,6 +164,13 @@ fn add_cosmos_blocks(
             .create(),
     );
 
+    blocks.register(
+        BlockBuilder::new("cosmos:warp_drive".to_owned(), 2.0)
+            .add_property(BlockProperty::Opaque)
+            .add_property(BlockProperty::Full)
+            .create(),
+    );
+
     blocks.register(
         BlockBuilder::new("cosmos:ship_hull".to_owned(), 6.0)
             .add_property(BlockProperty::Opaque)
@@ -92,6 +178,30 @@ fn add_cosmos_blocks(
             .create(),
     );
 
+    blocks.register(
+        BlockBuilder::new("cosmos:ship_hull_slab".to_owned(), 3.0)
+            .add_property(BlockProperty::Opaque)
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:ship_hull_stairs".to_owned(), 4.5)
+            .add_property(BlockProperty::Opaque)
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:ship_hull_wedge".to_owned(), 3.0)
+            .add_property(BlockProperty::Opaque)
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:ship_hull_panel".to_owned(), 0.75)
+            .add_property(BlockProperty::Opaque)
+            .create(),
+    );
+
     blocks.register(
         BlockBuilder::new("cosmos:thruster".to_owned(), 2.0)
             .add_property(BlockProperty::Opaque)
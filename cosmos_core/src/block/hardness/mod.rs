@@ -69,6 +69,17 @@ fn register_block_hardness(
     register_hardness(&mut registry, 10.0, &blocks, "cosmos:grass");
     register_hardness(&mut registry, 10.0, &blocks, "cosmos:dirt");
     register_hardness(&mut registry, 50.0, &blocks, "cosmos:stone");
+    register_hardness(&mut registry, 10.0, &blocks, "cosmos:sand");
+    register_hardness(&mut registry, 40.0, &blocks, "cosmos:sandstone");
+    register_hardness(&mut registry, 5.0, &blocks, "cosmos:snow");
+    register_hardness(&mut registry, 20.0, &blocks, "cosmos:ice");
+    register_hardness(&mut registry, 1.0, &blocks, "cosmos:water");
+    register_hardness(&mut registry, 60.0, &blocks, "cosmos:basalt");
+    register_hardness(&mut registry, 1.0, &blocks, "cosmos:lava");
+    register_hardness(&mut registry, 5.0, &blocks, "cosmos:cactus");
+    register_hardness(&mut registry, 70.0, &blocks, "cosmos:iron_ore");
+    register_hardness(&mut registry, 60.0, &blocks, "cosmos:copper_ore");
+    register_hardness(&mut registry, 90.0, &blocks, "cosmos:crystal_ore");
 
     register_hardness(&mut registry, 30.0, &blocks, "cosmos:cherry_log");
     register_hardness(&mut registry, 1.0, &blocks, "cosmos:cherry_leaf");
@@ -78,9 +89,14 @@ fn register_block_hardness(
     register_hardness(&mut registry, 20.0, &blocks, "cosmos:reactor");
     register_hardness(&mut registry, 20.0, &blocks, "cosmos:laser_cannon");
     register_hardness(&mut registry, 20.0, &blocks, "cosmos:thruster");
+    register_hardness(&mut registry, 20.0, &blocks, "cosmos:warp_drive");
     register_hardness(&mut registry, 20.0, &blocks, "cosmos:light");
 
     register_hardness(&mut registry, 100.0, &blocks, "cosmos:ship_hull");
+    register_hardness(&mut registry, 100.0, &blocks, "cosmos:ship_hull_slab");
+    register_hardness(&mut registry, 100.0, &blocks, "cosmos:ship_hull_stairs");
+    register_hardness(&mut registry, 100.0, &blocks, "cosmos:ship_hull_wedge");
+    register_hardness(&mut registry, 100.0, &blocks, "cosmos:ship_hull_panel");
     register_hardness(&mut registry, 100.0, &blocks, "cosmos:glass");
 }
 
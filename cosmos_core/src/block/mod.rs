@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use crate::registry::identifiable::Identifiable;
 
 pub mod block_builder;
+pub mod block_models;
 pub mod blocks;
 pub mod hardness;
 
@@ -27,6 +28,8 @@ pub enum BlockProperty {
     Empty,
     /// Does this block only belong on a ship
     ShipOnly,
+    /// Is this block a fluid that things can move through (such as water)
+    Fluid,
 }
 
 #[derive(
@@ -177,6 +180,7 @@ impl BlockProperty {
             Self::Full => 0b100,
             Self::Empty => 0b1000,
             Self::ShipOnly => 0b10000,
+            Self::Fluid => 0b100000,
         }
     }
 
@@ -261,6 +265,12 @@ impl Block {
         self.visibility & BlockProperty::Empty.id() != 0
     }
 
+    /// Returns true if this block is a fluid that things can move through
+    #[inline]
+    pub fn is_fluid(&self) -> bool {
+        self.visibility & BlockProperty::Fluid.id() != 0
+    }
+
     /// Returns the density of this block
     #[inline]
     pub fn density(&self) -> f32 {
@@ -281,6 +291,7 @@ pub(super) fn register<T: States + Clone + Copy>(
     post_loading_state: T,
 ) {
     blocks::register(app, pre_loading_state, loading_state);
+    block_models::register(app);
     hardness::register(app, loading_state, post_loading_state);
 
     app.register_type::<BlockFace>();
@@ -0,0 +1,36 @@
+//! Represents the chat messages sent between the client & server
+
+use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+/// The maximum number of characters a player can send in one chat message.
+///
+/// Anything longer will be cut off by the server.
+pub const MAX_CHAT_MESSAGE_LENGTH: usize = 256;
+
+#[derive(Debug, Serialize, Deserialize, Component)]
+/// All the chat messages a client can send
+pub enum ClientChatMessages {
+    /// The player typed something into the chat.
+    ///
+    /// If this starts with a `/`, the server will treat it as a command.
+    SendMessage {
+        /// What the player typed
+        message: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Component)]
+/// All the chat messages the server can send
+pub enum ServerChatMessages {
+    /// Something that should be displayed in the player's chat.
+    ///
+    /// This is how the server sends any text meant for players, such as the `say` command & command responses,
+    /// so there is no separate server message.
+    ChatMessage {
+        /// The name of the player that sent this, or None if this was sent by the server
+        sender: Option<String>,
+        /// The text of the message
+        message: String,
+    },
+}
@@ -0,0 +1,19 @@
+//! The information a client sends the server when it first connects
+
+use serde::{Deserialize, Serialize};
+
+/// How many bytes make up a player's key
+pub const PLAYER_KEY_LENGTH: usize = 32;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Sent as the user data of a client's connection, serialized with bincode.
+///
+/// The user data is a fixed 256 bytes, so this has to fit in that.
+pub struct ConnectionInfo {
+    /// The name the player wants to play as
+    pub name: String,
+    /// A random key the client generated once & keeps using.
+    ///
+    /// The server ties a name to the first key it was used with, so only that client can play as that name.
+    pub key: [u8; PLAYER_KEY_LENGTH],
+}
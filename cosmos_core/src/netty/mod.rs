@@ -1,12 +1,15 @@
 //! Contains all the information required for network requests
 
+pub mod chat_messages;
 pub mod client_reliable_messages;
 pub mod client_unreliable_messages;
+pub mod connection_info;
 pub mod cosmos_encoder;
 pub mod netty_rigidbody;
 pub mod server_laser_cannon_system_messages;
 pub mod server_reliable_messages;
 pub mod server_unreliable_messages;
+pub mod warp_drive_messages;
 pub mod world_tick;
 
 use bevy::{
@@ -38,12 +41,23 @@ pub enum NettyChannel {
 
     /// Used for asteroids
     Asteroids,
+
+    /// Used for `ClientChatMessages` and `ServerChatMessages`
+    Chat,
+
+    /// Used for `PlanetServerMessages`
+    Planets,
+
+    /// Used for `UniverseServerMessages`
+    Universe,
+    /// Used for `ClientWarpDriveMessages` and `ServerWarpDriveMessages`
+    WarpDrive,
 }
 
 /// In the future, this should be based off the game version.
 ///
 /// Must have the same protocol to connect to something
-pub const PROTOCOL_ID: u64 = 7;
+pub const PROTOCOL_ID: u64 = 12;
 
 impl NettyChannel {
     /// Gets the ID used in a netty channel
@@ -53,6 +67,10 @@ impl NettyChannel {
             Self::Unreliable => 1,
             Self::LaserCannonSystem => 2,
             Self::Asteroids => 3,
+            Self::Chat => 4,
+            Self::Planets => 5,
+            Self::Universe => 6,
+            Self::WarpDrive => 7,
         }
     }
 
@@ -94,6 +112,42 @@ impl NettyChannel {
                 ..Default::default()
             }
             .into(),
+            ReliableChannelConfig {
+                channel_id: Self::Chat.id(),
+                message_send_queue_size: 128,
+                message_receive_queue_size: 1024,
+                max_message_size: 6000,
+                packet_budget: 7000,
+                ..Default::default()
+            }
+            .into(),
+            ReliableChannelConfig {
+                channel_id: Self::Planets.id(),
+                message_send_queue_size: 1000,
+                message_receive_queue_size: 1024,
+                max_message_size: 6000,
+                packet_budget: 7000,
+                ..Default::default()
+            }
+            .into(),
+            ReliableChannelConfig {
+                channel_id: Self::Universe.id(),
+                message_send_queue_size: 128,
+                message_receive_queue_size: 128,
+                max_message_size: 12000,
+                packet_budget: 13000,
+                ..Default::default()
+            }
+            .into(),
+            ReliableChannelConfig {
+                channel_id: Self::WarpDrive.id(),
+                message_send_queue_size: 128,
+                message_receive_queue_size: 128,
+                max_message_size: 6000,
+                packet_budget: 7000,
+                ..Default::default()
+            }
+            .into(),
         ]
     }
 
@@ -135,6 +189,42 @@ impl NettyChannel {
                 ..Default::default()
             }
             .into(),
+            ReliableChannelConfig {
+                channel_id: Self::Chat.id(),
+                message_send_queue_size: 1024,
+                message_receive_queue_size: 128,
+                max_message_size: 6000,
+                packet_budget: 7000,
+                ..Default::default()
+            }
+            .into(),
+            ReliableChannelConfig {
+                channel_id: Self::Planets.id(),
+                message_send_queue_size: 1024 * 16,
+                message_receive_queue_size: 1000,
+                max_message_size: 6000,
+                packet_budget: 7000,
+                ..Default::default()
+            }
+            .into(),
+            ReliableChannelConfig {
+                channel_id: Self::Universe.id(),
+                message_send_queue_size: 1024,
+                message_receive_queue_size: 128,
+                max_message_size: 12000,
+                packet_budget: 13000,
+                ..Default::default()
+            }
+            .into(),
+            ReliableChannelConfig {
+                channel_id: Self::WarpDrive.id(),
+                message_send_queue_size: 128,
+                message_receive_queue_size: 128,
+                max_message_size: 6000,
+                packet_budget: 7000,
+                ..Default::default()
+            }
+            .into(),
         ]
     }
 }
@@ -40,11 +40,6 @@ pub enum ServerReliableMessages {
         /// The star
         star: Star,
     },
-    /// A player has been removed, and the client should remove them.
-    PlayerRemove {
-        /// The id of the player removed
-        id: u64,
-    },
     /// A structure has been removed, and the client should remove it.
     StructureRemove {
         /// The server's structure entity
@@ -134,6 +129,25 @@ pub enum ServerReliableMessages {
         /// The new pilot or None if the pilot is removed
         pilot_entity: Option<Entity>,
     },
+    /// The server has overridden the body of the player receiving this (e.g. they were teleported).
+    ///
+    /// The client should snap its player to this instead of trusting its own position.
+    SetPlayerBody {
+        /// The new rigidbody of the player
+        body: NettyRigidBody,
+    },
+    /// An entity is now relevant to this client. The information needed to create it will follow.
+    ///
+    /// This is sent on this channel so it is always received in order with the entity's other messages.
+    EntityEnter {
+        /// The server's entity
+        entity: Entity,
+    },
+    /// An entity is no longer relevant to this client (too far away or despawned), and the client should remove it.
+    EntityLeave {
+        /// The server's entity
+        entity: Entity,
+    },
     /// Sent when the laser cannon system fires - not used currently, will eventually generate a sound on the client.
     LaserCannonFire {},
 }
@@ -0,0 +1,38 @@
+//! Represents the communications needed to control a ship's warp drive
+
+use bevy::prelude::{Component, Entity};
+use serde::{Deserialize, Serialize};
+
+use crate::physics::location::Location;
+
+#[derive(Debug, Serialize, Deserialize, Component)]
+/// All the warp drive messages a client can send
+pub enum ClientWarpDriveMessages {
+    /// Sets where the ship the player is piloting will warp to.
+    ///
+    /// If this is None, the ship will warp one system ahead of wherever it is facing.
+    SetWarpTarget {
+        /// Where the ship should warp to
+        target: Option<Location>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Component)]
+/// All the warp drive messages the server can send
+pub enum ServerWarpDriveMessages {
+    /// The state of the warp drive of the ship the player is piloting
+    WarpDriveStatus {
+        /// The ship this warp drive is a part of
+        structure_entity: Entity,
+        /// How much charge the warp drive has
+        charge: f32,
+        /// The most charge the warp drive can hold
+        charge_capacity: f32,
+        /// How much charge it would take to warp to the current target
+        charge_needed: f32,
+        /// Where the ship will warp to if the warp drive is activated
+        destination: Location,
+        /// How many seconds are left until the ship warps, or None if it isn't spooling up
+        spool_remaining: Option<f32>,
+    },
+}
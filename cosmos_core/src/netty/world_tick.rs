@@ -10,10 +10,23 @@ use bevy::{
 /// The maximum amount of ticks per second the client/server will have.
 const MAX_TPS: u64 = 20;
 
-#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 /// Represents how many "game" ticks have occured
 pub struct WorldTick(u64);
 
+impl WorldTick {
+    #[inline]
+    /// Gets the number of ticks that have occured
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+
+    /// The maximum number of ticks that should happen every second
+    pub fn max_tps() -> u64 {
+        MAX_TPS
+    }
+}
+
 fn tick(mut world_ticks: ResMut<WorldTick>) {
     world_ticks.0 += 1;
 }
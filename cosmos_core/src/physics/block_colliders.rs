@@ -0,0 +1,356 @@
+//! The collider shapes of blocks that don't take up their entire 1x1x1 space.
+//!
+//! These are built from the same [`BlockModel`] the client draws the block with, so they always line up.
+//! Convex models get a single convex hull, and anything else is filled in with boxes.
+
+use std::f32::consts::PI;
+
+use bevy::prelude::{App, IntoSystemAppConfig, OnEnter, Quat, Res, ResMut, States, Vec3};
+use bevy_rapier3d::{
+    math::Vect,
+    prelude::{Collider, Rot},
+};
+
+use crate::{
+    block::{
+        block_models::{BlockModel, BlockModelRegistry},
+        Block, BlockFace,
+    },
+    registry::{self, identifiable::Identifiable, Registry},
+};
+
+/// How many cells each axis of a non-convex model is split into when filling it in with boxes
+const MODEL_RESOLUTION: usize = 16;
+
+/// How far a point can be in front of a triangle while still counting as behind it
+const CONVEX_TOLERANCE: f32 = 0.0001;
+
+/// One piece of a block's collider, relative to the block's center
+pub struct CustomCollider {
+    /// The position of this collider, where (0.0, 0.0, 0.0) is the center of the block
+    pub position: Vect,
+    /// The rotation of this collider, before the block's rotation is applied
+    pub rotation: Rot,
+    /// The shape of this collider
+    pub collider: Collider,
+}
+
+/// The shape a block's collider takes
+pub enum BlockColliderType {
+    /// Takes up the entire 1x1x1 space, and will be merged with the full blocks around it
+    Full,
+    /// Made up of any number of colliders that are rotated along with the block
+    Custom(Vec<CustomCollider>),
+    /// Has no collider at all
+    Empty,
+}
+
+/// The collider shape for a given block
+pub struct BlockCollider {
+    id: u16,
+    unlocalized_name: String,
+
+    /// The shape of this block's collider
+    pub collider: BlockColliderType,
+}
+
+impl BlockCollider {
+    /// Creates the collider for that block.
+    ///
+    /// This still needs to be registered!
+    pub fn new(block: &Block, collider: BlockColliderType) -> Self {
+        Self {
+            id: 0,
+            unlocalized_name: block.unlocalized_name().to_owned(),
+            collider,
+        }
+    }
+}
+
+impl Identifiable for BlockCollider {
+    fn id(&self) -> u16 {
+        self.id
+    }
+
+    fn set_numeric_id(&mut self, id: u16) {
+        self.id = id;
+    }
+
+    fn unlocalized_name(&self) -> &str {
+        &self.unlocalized_name
+    }
+}
+
+/// Returns the rotation that turns a block's model so its top faces the given direction
+pub fn block_rotation(top_face: BlockFace) -> Quat {
+    match top_face {
+        BlockFace::Top => Quat::IDENTITY,
+        BlockFace::Front => Quat::from_axis_angle(Vec3::X, PI / 2.0),
+        BlockFace::Back => Quat::from_axis_angle(Vec3::X, -PI / 2.0),
+        BlockFace::Left => Quat::from_axis_angle(Vec3::Z, PI / 2.0),
+        BlockFace::Right => Quat::from_axis_angle(Vec3::Z, -PI / 2.0),
+        BlockFace::Bottom => Quat::from_axis_angle(Vec3::X, PI),
+    }
+}
+
+/// A box going from `min` to `max`, where the block spans [-0.5, 0.5] on every axis
+fn cuboid(min: Vec3, max: Vec3) -> CustomCollider {
+    let half_extents = (max - min) / 2.0;
+
+    CustomCollider {
+        position: min + half_extents,
+        rotation: Rot::IDENTITY,
+        collider: Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
+    }
+}
+
+/// Returns true if every corner of the model is on or behind every one of its triangles
+fn is_convex(triangles: &[[Vec3; 3]]) -> bool {
+    triangles.iter().all(|[a, b, c]| {
+        let normal = (*b - *a).cross(*c - *a);
+
+        triangles
+            .iter()
+            .flatten()
+            .all(|point| normal.dot(*point - *a) <= CONVEX_TOLERANCE)
+    })
+}
+
+/// Returns the distance along the ray to where it hits this triangle, if it does (Möller–Trumbore)
+fn ray_hits_triangle(origin: Vec3, direction: Vec3, [a, b, c]: &[Vec3; 3]) -> Option<f32> {
+    let edge_1 = *b - *a;
+    let edge_2 = *c - *a;
+
+    let p = direction.cross(edge_2);
+    let determinant = edge_1.dot(p);
+
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let to_origin = origin - *a;
+
+    let u = to_origin.dot(p) / determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = to_origin.cross(edge_1);
+
+    let v = direction.dot(q) / determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let distance = edge_2.dot(q) / determinant;
+
+    (distance > 0.0).then_some(distance)
+}
+
+/// A point is inside of a closed model if a ray going out from it crosses the model's surface an odd number of times
+///
+/// A ray that clips an edge between two triangles gets counted twice, so the majority of a few rays going
+/// in different directions is used. These directions are skewed so they don't line up with the edges most models have.
+fn is_inside(point: Vec3, triangles: &[[Vec3; 3]]) -> bool {
+    const RAY_DIRECTIONS: [Vec3; 3] = [
+        Vec3::new(1.0, 0.414_213_56, 0.236_067_98),
+        Vec3::new(0.318_309_9, 1.0, 0.577_215_66),
+        Vec3::new(0.618_034, 0.267_949_2, 1.0),
+    ];
+
+    let votes = RAY_DIRECTIONS
+        .iter()
+        .filter(|direction| {
+            triangles
+                .iter()
+                .filter(|triangle| ray_hits_triangle(point, **direction, triangle).is_some())
+                .count()
+                % 2
+                == 1
+        })
+        .count();
+
+    votes * 2 > RAY_DIRECTIONS.len()
+}
+
+/// Splits the model into cells, then merges the cells inside of it into as few boxes as it can
+fn fill_with_boxes(triangles: &[[Vec3; 3]]) -> Vec<CustomCollider> {
+    const N: usize = MODEL_RESOLUTION;
+
+    let cell_size = 1.0 / N as f32;
+    let cell_min =
+        |x: usize, y: usize, z: usize| Vec3::new(x as f32, y as f32, z as f32) * cell_size - 0.5;
+
+    let mut filled = vec![false; N * N * N];
+    let index = |x: usize, y: usize, z: usize| (y * N + z) * N + x;
+
+    for y in 0..N {
+        for z in 0..N {
+            for x in 0..N {
+                filled[index(x, y, z)] = is_inside(cell_min(x, y, z) + cell_size / 2.0, triangles);
+            }
+        }
+    }
+
+    let mut colliders = Vec::new();
+
+    for y in 0..N {
+        for z in 0..N {
+            for x in 0..N {
+                if !filled[index(x, y, z)] {
+                    continue;
+                }
+
+                let mut max_x = x + 1;
+                while max_x < N && filled[index(max_x, y, z)] {
+                    max_x += 1;
+                }
+
+                let row_filled = |filled: &[bool], y: usize, z: usize| {
+                    (x..max_x).all(|x| filled[index(x, y, z)])
+                };
+
+                let mut max_z = z + 1;
+                while max_z < N && row_filled(&filled, y, max_z) {
+                    max_z += 1;
+                }
+
+                let mut max_y = y + 1;
+                while max_y < N && (z..max_z).all(|z| row_filled(&filled, max_y, z)) {
+                    max_y += 1;
+                }
+
+                for y in y..max_y {
+                    for z in z..max_z {
+                        for x in x..max_x {
+                            filled[index(x, y, z)] = false;
+                        }
+                    }
+                }
+
+                colliders.push(cuboid(cell_min(x, y, z), cell_min(max_x, max_y, max_z)));
+            }
+        }
+    }
+
+    colliders
+}
+
+/// Builds the collider pieces that match this model's shape
+fn model_colliders(model: &BlockModel) -> Vec<CustomCollider> {
+    let triangles = model.triangles();
+
+    if is_convex(&triangles) {
+        let points = triangles.iter().flatten().copied().collect::<Vec<Vect>>();
+
+        if let Some(collider) = Collider::convex_hull(&points) {
+            return vec![CustomCollider {
+                position: Vect::ZERO,
+                rotation: Rot::IDENTITY,
+                collider,
+            }];
+        }
+    }
+
+    fill_with_boxes(&triangles)
+}
+
+fn register_block_colliders(
+    blocks: Res<Registry<Block>>,
+    models: Res<BlockModelRegistry>,
+    mut registry: ResMut<Registry<BlockCollider>>,
+) {
+    for block in blocks.iter() {
+        // Fluids can be moved through, so they don't get a solid collider
+        let collider = if block.is_empty() || block.is_fluid() {
+            BlockColliderType::Empty
+        } else if let Some(model) = models.get_value(block) {
+            let colliders = model_colliders(model);
+
+            if colliders.is_empty() {
+                println!(
+                    "[Block Colliders] Model {} doesn't enclose any space",
+                    model.unlocalized_name()
+                );
+                BlockColliderType::Empty
+            } else {
+                BlockColliderType::Custom(colliders)
+            }
+        } else {
+            BlockColliderType::Full
+        };
+
+        registry.register(BlockCollider::new(block, collider));
+    }
+}
+
+pub(super) fn register<T: States + Clone + Copy>(app: &mut App, post_loading_state: T) {
+    registry::create_registry::<BlockCollider>(app);
+
+    // Block models are loaded by the client & server when leaving the loading state
+    app.add_system(register_block_colliders.in_schedule(OnEnter(post_loading_state)));
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Vec3;
+
+    use crate::block::block_models::BlockModel;
+
+    use super::model_colliders;
+
+    fn load_model(name: &str, json: &str) -> BlockModel {
+        BlockModel::from_json(name, json).expect("Valid model")
+    }
+
+    #[test]
+    fn test_convex_models_use_one_hull() {
+        let slab = load_model(
+            "slab",
+            include_str!("../../../cosmos_client/assets/blocks/models/slab.json"),
+        );
+        let wedge = load_model(
+            "wedge",
+            include_str!("../../../cosmos_client/assets/blocks/models/wedge.json"),
+        );
+
+        for model in [slab, wedge] {
+            let colliders = model_colliders(&model);
+
+            assert_eq!(colliders.len(), 1);
+            assert!(colliders[0].collider.as_convex_polyhedron().is_some());
+        }
+    }
+
+    #[test]
+    fn test_stairs_fill_with_boxes() {
+        let stairs = load_model(
+            "stairs",
+            include_str!("../../../cosmos_client/assets/blocks/models/stairs.json"),
+        );
+
+        let boxes = model_colliders(&stairs)
+            .into_iter()
+            .map(|piece| {
+                let half_extents = piece
+                    .collider
+                    .as_cuboid()
+                    .expect("Stairs are boxes")
+                    .half_extents();
+
+                (piece.position - half_extents, piece.position + half_extents)
+            })
+            .collect::<Vec<(Vec3, Vec3)>>();
+
+        assert_eq!(boxes.len(), 2);
+
+        let close = |a: Vec3, b: Vec3| a.abs_diff_eq(b, 0.0001);
+
+        assert!(
+            close(boxes[0].0, Vec3::splat(-0.5)) && close(boxes[0].1, Vec3::new(0.5, 0.0, 0.5))
+        );
+        assert!(
+            close(boxes[1].0, Vec3::new(-0.5, 0.0, 0.0)) && close(boxes[1].1, Vec3::splat(0.5))
+        );
+    }
+}
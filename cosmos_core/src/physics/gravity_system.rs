@@ -67,12 +67,14 @@ fn gravity_system(
 
             for (force_per_kilogram, radius, pos, rotation) in gravs.iter() {
                 let relative_position = pos.relative_coords_to(location);
-                let dist = relative_position.abs().max_element();
+                // Planets spin, so which face this is on depends on how the emitter is rotated
+                let local_position = rotation.inverse() * relative_position;
+                let dist = local_position.abs().max_element();
 
                 let ratio = ((radius * radius) / (dist * dist)).min(1.0);
 
                 if ratio >= 0.9 {
-                    let face = Planet::planet_face_relative(relative_position);
+                    let face = Planet::planet_face_relative(local_position);
 
                     let grav_dir = -rotation.mul_vec3(face.direction_vec3());
 
@@ -1,14 +1,16 @@
 //! Contains information need to have physics operate successfully
 
-use bevy::prelude::App;
+use bevy::prelude::{App, States};
 
+pub mod block_colliders;
 pub mod gravity_system;
 pub mod location;
 pub mod player_world;
 mod stop_near_unloaded_chunks;
 pub mod structure_physics;
 
-pub(super) fn register(app: &mut App) {
+pub(super) fn register<T: States + Clone + Copy>(app: &mut App, post_loading_state: T) {
+    block_colliders::register(app, post_loading_state);
     structure_physics::register(app);
     gravity_system::register(app);
     location::register(app);
@@ -4,17 +4,19 @@ use std::sync::Mutex;
 
 use crate::block::Block;
 use crate::events::block_events::BlockChangedEvent;
+use crate::physics::block_colliders::{block_rotation, BlockCollider, BlockColliderType};
 use crate::registry::Registry;
 use crate::structure::chunk::{Chunk, CHUNK_DIMENSIONS};
 use crate::structure::events::ChunkSetEvent;
 use crate::structure::Structure;
+use crate::utils::array_utils::expand;
+use crate::utils::greedy_meshing::merge_boxes;
 use bevy::prelude::{
     App, Commands, Component, Entity, EventReader, EventWriter, IntoSystemConfigs, Query, Res,
 };
 use bevy::reflect::{FromReflect, Reflect};
 use bevy::utils::HashSet;
 use bevy_rapier3d::math::Vect;
-use bevy_rapier3d::na::Vector3;
 use bevy_rapier3d::prelude::{
     Collider, ColliderMassProperties, ReadMassProperties, RigidBody, Rot,
 };
@@ -28,158 +30,84 @@ struct StructureMass {
     mass: f32,
 }
 
-/// This works by first checking if the cube that is within its bounds contains either all solid or empty blocks
+/// Merges all the full blocks in the chunk into as few box colliders as possible.
 ///
-/// If it does, this exits either creating a single cube collider for that or no collider
-///
-/// Otherwise, this recursively calls itself 8 times breaking this large cube into 8 equally sized smaller
-/// cubes and repeating this process.
-///
-/// This prevents the creation of tons of small colliders while being relatively easy to implement.
-fn generate_colliders(
+/// This prevents the creation of tons of small colliders - a flat wall becomes a single collider.
+/// Blocks with custom collider shapes are added on their own, rotated to match the block.
+fn generate_chunk_collider(
     chunk: &Chunk,
     blocks: &Registry<Block>,
-    colliders: &mut Vec<(Vect, Rot, Collider)>,
-    location: Vect,
-    offset: Vector3<usize>,
-    size: usize,
-    mass: &mut f32,
-) {
-    let mut last_seen_empty = None;
-
-    let mut temp_mass = 0.0;
-
-    for z in offset.z..(offset.z + size) {
-        for y in offset.y..(offset.y + size) {
-            for x in offset.x..(offset.x + size) {
-                let b = blocks.from_numeric_id(chunk.block_at(x, y, z));
-
-                let block_mass = b.density(); // mass = volume * density = 1*1*1*density = density
-
-                temp_mass += block_mass;
-
-                if last_seen_empty.is_none() {
-                    last_seen_empty = Some(b.is_empty());
-                } else if last_seen_empty.unwrap() != b.is_empty() {
-                    let s2 = size / 2;
-                    let s4 = s2 as f32 / 2.0;
-
-                    // left bottom back
-                    generate_colliders(
-                        chunk,
-                        blocks,
-                        colliders,
-                        Vect::new(location.x - s4, location.y - s4, location.z - s4),
-                        Vector3::new(offset.x, offset.y, offset.z),
-                        s2,
-                        mass,
-                    );
-
-                    // right bottom back
-                    generate_colliders(
-                        chunk,
-                        blocks,
-                        colliders,
-                        Vect::new(location.x + s4, location.y - s4, location.z - s4),
-                        Vector3::new(offset.x + s2, offset.y, offset.z),
-                        s2,
-                        mass,
-                    );
-
-                    // left top back
-                    generate_colliders(
-                        chunk,
-                        blocks,
-                        colliders,
-                        Vect::new(location.x - s4, location.y + s4, location.z - s4),
-                        Vector3::new(offset.x, offset.y + s2, offset.z),
-                        s2,
-                        mass,
-                    );
-
-                    // left bottom front
-                    generate_colliders(
-                        chunk,
-                        blocks,
-                        colliders,
-                        Vect::new(location.x - s4, location.y - s4, location.z + s4),
-                        Vector3::new(offset.x, offset.y, offset.z + s2),
-                        s2,
-                        mass,
-                    );
-
-                    // right bottom front
-                    generate_colliders(
-                        chunk,
-                        blocks,
-                        colliders,
-                        Vect::new(location.x + s4, location.y - s4, location.z + s4),
-                        Vector3::new(offset.x + s2, offset.y, offset.z + s2),
-                        s2,
-                        mass,
-                    );
-
-                    // left top front
-                    generate_colliders(
-                        chunk,
-                        blocks,
-                        colliders,
-                        Vect::new(location.x - s4, location.y + s4, location.z + s4),
-                        Vector3::new(offset.x, offset.y + s2, offset.z + s2),
-                        s2,
-                        mass,
-                    );
-
-                    // right top front
-                    generate_colliders(
-                        chunk,
-                        blocks,
-                        colliders,
-                        Vect::new(location.x + s4, location.y + s4, location.z + s4),
-                        Vector3::new(offset.x + s2, offset.y + s2, offset.z + s2),
-                        s2,
-                        mass,
-                    );
-
-                    // right top back
-                    generate_colliders(
-                        chunk,
-                        blocks,
-                        colliders,
-                        Vect::new(location.x + s4, location.y + s4, location.z - s4),
-                        Vector3::new(offset.x + s2, offset.y + s2, offset.z),
-                        s2,
-                        mass,
-                    );
-                    return;
-                }
-            }
-        }
-    }
-
-    // If this is true, then this cube was fully empty.
-    if !last_seen_empty.unwrap() {
-        let s2 = size as f32 / 2.0;
-
-        *mass += temp_mass;
-
-        colliders.push((location, Rot::IDENTITY, Collider::cuboid(s2, s2, s2)));
-    }
-}
-
-fn generate_chunk_collider(chunk: &Chunk, blocks: &Registry<Block>) -> Option<GenerateCollider> {
-    let mut colliders: Vec<(Vect, Rot, Collider)> = Vec::new();
-
+    block_colliders: &Registry<BlockCollider>,
+) -> Option<GenerateCollider> {
     let mut mass: f32 = 0.0;
 
-    generate_colliders(
-        chunk,
-        blocks,
-        &mut colliders,
-        Vect::new(0.0, 0.0, 0.0),
-        Vector3::new(0, 0, 0),
-        CHUNK_DIMENSIONS,
-        &mut mass,
+    let half_chunk = CHUNK_DIMENSIONS as f32 / 2.0;
+
+    let mut colliders = Vec::<(Vect, Rot, Collider)>::new();
+
+    let filled = chunk
+        .blocks()
+        .zip(chunk.block_info_iterator())
+        .enumerate()
+        .map(|(i, (&id, block_info))| {
+            let b = blocks.from_numeric_id(id);
+
+            // mass = volume * density = 1*1*1*density = density
+            mass += b.density();
+
+            match block_colliders
+                .from_id(b.unlocalized_name())
+                .map(|c| &c.collider)
+            {
+                Some(BlockColliderType::Full) => true,
+                Some(BlockColliderType::Custom(pieces)) => {
+                    let (x, y, z) = expand(i, CHUNK_DIMENSIONS, CHUNK_DIMENSIONS);
+                    let center =
+                        Vect::new(x as f32, y as f32, z as f32) + Vect::splat(0.5 - half_chunk);
+                    let rotation = block_rotation(block_info.get_rotation());
+
+                    colliders.extend(pieces.iter().map(|piece| {
+                        (
+                            center + rotation * piece.position,
+                            rotation * piece.rotation,
+                            piece.collider.clone(),
+                        )
+                    }));
+
+                    false
+                }
+                Some(BlockColliderType::Empty) => false,
+                // Colliders haven't been registered for this block, so fall back to treating it like a cube
+                None => !b.is_empty(),
+            }
+        })
+        .collect::<Vec<bool>>();
+
+    colliders.extend(
+        merge_boxes(
+            &filled,
+            CHUNK_DIMENSIONS,
+            CHUNK_DIMENSIONS,
+            CHUNK_DIMENSIONS,
+        )
+        .into_iter()
+        .map(|b| {
+            let (hw, hh, hl) = (
+                b.width as f32 / 2.0,
+                b.height as f32 / 2.0,
+                b.length as f32 / 2.0,
+            );
+
+            (
+                Vect::new(
+                    b.x as f32 + hw - half_chunk,
+                    b.y as f32 + hh - half_chunk,
+                    b.z as f32 + hl - half_chunk,
+                ),
+                Rot::IDENTITY,
+                Collider::cuboid(hw, hh, hl),
+            )
+        }),
     );
 
     if colliders.is_empty() {
@@ -202,6 +130,7 @@ fn listen_for_new_physics_event(
     query: Query<(&Structure, &RigidBody)>,
     mut event_reader: EventReader<ChunkNeedsPhysicsEvent>,
     blocks: Res<Registry<Block>>,
+    block_colliders: Res<Registry<BlockCollider>>,
 ) {
     let commands_mutex = Mutex::new(commands);
 
@@ -225,7 +154,7 @@ fn listen_for_new_physics_event(
             return;
         };
 
-        let chunk_collider = generate_chunk_collider(chunk, &blocks);
+        let chunk_collider = generate_chunk_collider(chunk, &blocks, &block_colliders);
 
         if let Some(mut structure_entity_commands) = commands_mutex
             .lock()
@@ -281,8 +210,14 @@ pub(super) fn register(app: &mut App) {
 
 #[cfg(test)]
 mod test {
+    use bevy_rapier3d::{
+        math::Vect,
+        prelude::{Collider, Rot},
+    };
+
     use crate::{
-        block::{block_builder::BlockBuilder, Block, BlockFace},
+        block::{block_builder::BlockBuilder, Block, BlockFace, BlockProperty},
+        physics::block_colliders::{BlockCollider, BlockColliderType, CustomCollider},
         registry::Registry,
         structure::chunk::{Chunk, CHUNK_DIMENSIONS},
     };
@@ -301,7 +236,7 @@ mod test {
 
         chunk.set_block_at(1, 2, 3, test_block, BlockFace::Top);
 
-        let (_, mass) = generate_chunk_collider(&chunk, &blocks).unwrap();
+        let (_, mass) = generate_chunk_collider(&chunk, &blocks, &Registry::new()).unwrap();
 
         assert_eq!(mass, 4.0);
     }
@@ -326,7 +261,7 @@ mod test {
             BlockFace::Top,
         );
 
-        let (_, mass) = generate_chunk_collider(&chunk, &blocks).unwrap();
+        let (_, mass) = generate_chunk_collider(&chunk, &blocks, &Registry::new()).unwrap();
 
         assert_eq!(mass, 8.0);
     }
@@ -353,8 +288,61 @@ mod test {
             BlockFace::Top,
         );
 
-        let (_, mass) = generate_chunk_collider(&chunk, &blocks).unwrap();
+        let (_, mass) = generate_chunk_collider(&chunk, &blocks, &Registry::new()).unwrap();
 
         assert_eq!(mass, 5.0);
     }
+
+    #[test]
+    fn test_gen_colliders_custom_shape() {
+        let mut chunk = Chunk::new(10, 10, 10);
+        let mut blocks = Registry::<Block>::new();
+        let mut block_colliders = Registry::<BlockCollider>::new();
+
+        blocks.register(
+            BlockBuilder::new("air".into(), 0.0)
+                .add_property(BlockProperty::Empty)
+                .create(),
+        );
+        blocks.register(BlockBuilder::new("slab".into(), 2.0).create());
+
+        let slab = blocks.from_id("slab").unwrap();
+
+        block_colliders.register(BlockCollider::new(
+            slab,
+            BlockColliderType::Custom(vec![CustomCollider {
+                position: Vect::new(0.0, -0.25, 0.0),
+                rotation: Rot::IDENTITY,
+                collider: Collider::cuboid(0.5, 0.25, 0.5),
+            }]),
+        ));
+
+        chunk.set_block_at(4, 4, 4, slab, BlockFace::Front);
+
+        let (_, mass) = generate_chunk_collider(&chunk, &blocks, &block_colliders).unwrap();
+
+        assert_eq!(mass, 2.0);
+    }
+
+    #[test]
+    fn test_gen_colliders_empty_shape() {
+        let mut chunk = Chunk::new(10, 10, 10);
+        let mut blocks = Registry::<Block>::new();
+        let mut block_colliders = Registry::<BlockCollider>::new();
+
+        blocks.register(
+            BlockBuilder::new("air".into(), 0.0)
+                .add_property(BlockProperty::Empty)
+                .create(),
+        );
+        blocks.register(BlockBuilder::new("ghost".into(), 1.0).create());
+
+        let ghost = blocks.from_id("ghost").unwrap();
+
+        block_colliders.register(BlockCollider::new(ghost, BlockColliderType::Empty));
+
+        chunk.set_block_at(4, 4, 4, ghost, BlockFace::Top);
+
+        assert!(generate_chunk_collider(&chunk, &blocks, &block_colliders).is_none());
+    }
 }
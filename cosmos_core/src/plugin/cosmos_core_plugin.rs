@@ -90,7 +90,7 @@ impl<T: States + Clone + Copy> Plugin for CosmosCorePlugin<T> {
         );
         item::register(app);
         blockitems::register(app, self.post_loading_state);
-        physics::register(app);
+        physics::register(app, self.post_loading_state);
         events::register(app, self.playing_game_state);
         structure::register(app, self.post_loading_state, self.playing_game_state);
         inventory::register(app);
@@ -13,7 +13,7 @@ use super::identifiable::Identifiable;
 use super::AddLinkError;
 
 /// Represents a many to one link
-#[derive(Resource, Default)]
+#[derive(Resource, Default, Clone)]
 pub struct ManyToOneRegistry<K: Identifiable + Sync + Send, V: Identifiable + Sync + Send> {
     values: HashMap<u16, V>,
 
@@ -35,7 +35,7 @@ impl fmt::Display for AddLinkError {
 impl std::error::Error for AddLinkError {}
 
 /// Represents a bunch of values that are identifiable by their unlocalized name + numeric ids.
-#[derive(Default, Resource)]
+#[derive(Default, Resource, Clone)]
 pub struct Registry<T: Identifiable + Sync + Send> {
     contents: Vec<T>,
     unlocalized_name_to_id: HashMap<String, u16>,
@@ -13,7 +13,7 @@ use crate::{block::hardness::BlockHardness, utils::array_utils::flatten};
 
 use super::chunk::CHUNK_DIMENSIONS;
 
-#[derive(Debug, Default, Serialize, Deserialize, Reflect, FromReflect)]
+#[derive(Debug, Default, Serialize, Deserialize, Reflect, FromReflect, Clone)]
 /// Each block's health is represented here
 pub struct BlockHealth {
     /// Block index -> block health
@@ -27,7 +27,7 @@ pub const CHUNK_DIMENSIONSF: f32 = CHUNK_DIMENSIONS as f32;
 /// The number of blocks a chunk contains (`CHUNK_DIMENSIONS^3`)
 const N_BLOCKS: usize = CHUNK_DIMENSIONS * CHUNK_DIMENSIONS * CHUNK_DIMENSIONS;
 
-#[derive(Debug, Reflect, FromReflect, Serialize, Deserialize)]
+#[derive(Debug, Reflect, FromReflect, Serialize, Deserialize, Clone)]
 /// Stores a bunch of blocks, information about those blocks, and where they are in the structure.
 pub struct Chunk {
     x: usize,
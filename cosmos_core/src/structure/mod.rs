@@ -12,6 +12,7 @@ pub mod block_health;
 pub mod chunk;
 pub mod events;
 pub mod loading;
+pub mod occlusion;
 pub mod planet;
 pub mod ship;
 pub mod structure_block;
@@ -0,0 +1,335 @@
+//! Figures out which chunks of a structure could possibly be seen from a given chunk.
+//!
+//! Each chunk stores which of its faces are connected to each other through see-through blocks.
+//! Starting at the chunk the camera is in, chunks are flood-filled outwards - only ever moving away from
+//! the camera, and only leaving a chunk through a face that is connected to the face it was entered from.
+//! Any chunk that isn't reached can't be seen, since there are solid blocks in every line of sight to it.
+
+use std::collections::VecDeque;
+
+use bevy::utils::HashSet;
+
+use crate::{
+    block::BlockFace,
+    utils::array_utils::{expand, flatten},
+};
+
+use super::chunk::CHUNK_DIMENSIONS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Which faces of a chunk can be seen from which other faces of that chunk
+///
+/// Faces are connected if there is a path of see-through blocks between them.
+pub struct ChunkFaceConnectivity(u64);
+
+impl Default for ChunkFaceConnectivity {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/// The face on the other side of a chunk, see [`BlockFace::index`]
+fn opposite(face_index: usize) -> usize {
+    face_index ^ 1
+}
+
+impl ChunkFaceConnectivity {
+    /// Every face can see every other face, like for a chunk with no blocks in it
+    pub fn all() -> Self {
+        Self(u64::MAX)
+    }
+
+    /// No face can see any other face, like for a chunk filled with solid blocks
+    pub fn none() -> Self {
+        Self(0)
+    }
+
+    /// Returns true if something entering the chunk through face `a` could leave through face `b`
+    pub fn connected(&self, a: BlockFace, b: BlockFace) -> bool {
+        self.0 & (1 << (a.index() * 6 + b.index())) != 0
+    }
+
+    /// Marks these two faces as being able to see each other
+    pub fn connect(&mut self, a: BlockFace, b: BlockFace) {
+        self.0 |= 1 << (a.index() * 6 + b.index());
+        self.0 |= 1 << (b.index() * 6 + a.index());
+    }
+
+    /// Flood-fills every group of see-through blocks in a chunk & connects all the faces each group touches
+    ///
+    /// * `is_see_through` Returns true if the block at these chunk coordinates can be seen through
+    pub fn compute(is_see_through: impl Fn(usize, usize, usize) -> bool) -> Self {
+        const N: usize = CHUNK_DIMENSIONS;
+
+        let mut connectivity = Self::none();
+
+        let mut visited = vec![false; N * N * N];
+        let mut stack = Vec::new();
+
+        for start in 0..N * N * N {
+            if visited[start] {
+                continue;
+            }
+
+            visited[start] = true;
+
+            let (x, y, z) = expand(start, N, N);
+            if !is_see_through(x, y, z) {
+                continue;
+            }
+
+            // Bitmask of every face this group of blocks touches
+            let mut touched = 0u8;
+
+            stack.push((x, y, z));
+
+            while let Some((x, y, z)) = stack.pop() {
+                let mut visit = |x: usize, y: usize, z: usize, stack: &mut Vec<_>| {
+                    let index = flatten(x, y, z, N, N);
+
+                    if !visited[index] {
+                        visited[index] = true;
+
+                        if is_see_through(x, y, z) {
+                            stack.push((x, y, z));
+                        }
+                    }
+                };
+
+                if x == N - 1 {
+                    touched |= 1 << BlockFace::Right.index();
+                } else {
+                    visit(x + 1, y, z, &mut stack);
+                }
+                if x == 0 {
+                    touched |= 1 << BlockFace::Left.index();
+                } else {
+                    visit(x - 1, y, z, &mut stack);
+                }
+                if y == N - 1 {
+                    touched |= 1 << BlockFace::Top.index();
+                } else {
+                    visit(x, y + 1, z, &mut stack);
+                }
+                if y == 0 {
+                    touched |= 1 << BlockFace::Bottom.index();
+                } else {
+                    visit(x, y - 1, z, &mut stack);
+                }
+                if z == N - 1 {
+                    touched |= 1 << BlockFace::Front.index();
+                } else {
+                    visit(x, y, z + 1, &mut stack);
+                }
+                if z == 0 {
+                    touched |= 1 << BlockFace::Back.index();
+                } else {
+                    visit(x, y, z - 1, &mut stack);
+                }
+            }
+
+            for a in 0..6 {
+                for b in 0..6 {
+                    if touched & (1 << a) != 0 && touched & (1 << b) != 0 {
+                        connectivity.connect(BlockFace::from_index(a), BlockFace::from_index(b));
+                    }
+                }
+            }
+        }
+
+        connectivity
+    }
+}
+
+/// A chunk waiting to be visited by [`visible_chunks`]
+struct VisitStep {
+    chunk: (usize, usize, usize),
+    /// The face this chunk was entered through, or `None` for the chunk the camera is in
+    entered_through: Option<usize>,
+    /// Bitmask of every direction moved in to get here
+    directions: u8,
+}
+
+/// Finds every chunk of a structure that could be seen from the camera
+///
+/// * `camera_chunk` The chunk coordinates the camera is in, which may be outside the structure
+/// * `bounds` The smallest & largest (inclusive) chunk coordinates to look through
+/// * `connectivity` The connectivity of the chunk at these coordinates
+/// * `in_view` Returns false for chunks that shouldn't be looked through, such as ones that aren't loaded
+pub fn visible_chunks(
+    camera_chunk: (i64, i64, i64),
+    bounds: ((usize, usize, usize), (usize, usize, usize)),
+    connectivity: impl Fn((usize, usize, usize)) -> ChunkFaceConnectivity,
+    in_view: impl Fn((usize, usize, usize)) -> bool,
+) -> HashSet<(usize, usize, usize)> {
+    let (min, max) = bounds;
+    let (min, max) = ([min.0, min.1, min.2], [max.0, max.1, max.2]);
+    let camera = [camera_chunk.0, camera_chunk.1, camera_chunk.2];
+
+    let mut visible = HashSet::default();
+    let mut queue = VecDeque::new();
+
+    let inside = |coords: [i64; 3]| {
+        (0..3).all(|axis| coords[axis] >= min[axis] as i64 && coords[axis] <= max[axis] as i64)
+    };
+
+    if inside(camera) {
+        queue.push_back(VisitStep {
+            chunk: (camera[0] as usize, camera[1] as usize, camera[2] as usize),
+            entered_through: None,
+            directions: 0,
+        });
+    } else {
+        // From outside the bounds, the camera can only look into the chunks on the sides facing it
+        let mut facing_faces = Vec::new();
+        let mut directions = 0;
+
+        for face in 0..6 {
+            let direction = BlockFace::from_index(face).direction();
+            let direction = [direction.0, direction.1, direction.2];
+
+            let axis = (0..3)
+                .find(|&axis| direction[axis] != 0)
+                .expect("Every face points along an axis");
+
+            let facing = if direction[axis] > 0 {
+                camera[axis] > max[axis] as i64
+            } else {
+                camera[axis] < min[axis] as i64
+            };
+
+            if facing {
+                facing_faces.push((face, axis, direction[axis] > 0));
+                directions |= 1 << opposite(face);
+            }
+        }
+
+        for (face, axis, positive) in facing_faces {
+            let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+
+            for i in min[a]..=max[a] {
+                for j in min[b]..=max[b] {
+                    let mut coords = [0; 3];
+                    coords[axis] = if positive { max[axis] } else { min[axis] };
+                    coords[a] = i;
+                    coords[b] = j;
+
+                    let chunk = (coords[0], coords[1], coords[2]);
+
+                    if in_view(chunk) {
+                        queue.push_back(VisitStep {
+                            chunk,
+                            entered_through: Some(face),
+                            directions,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    while let Some(step) = queue.pop_front() {
+        if !visible.insert(step.chunk) {
+            continue;
+        }
+
+        let chunk_connectivity = connectivity(step.chunk);
+
+        for face in 0..6 {
+            // Never go back towards the camera
+            if step.directions & (1 << opposite(face)) != 0 {
+                continue;
+            }
+
+            if let Some(entered_through) = step.entered_through {
+                if !chunk_connectivity.connected(
+                    BlockFace::from_index(entered_through),
+                    BlockFace::from_index(face),
+                ) {
+                    continue;
+                }
+            }
+
+            let (dx, dy, dz) = BlockFace::from_index(face).direction();
+            let next = [
+                step.chunk.0 as i64 + dx as i64,
+                step.chunk.1 as i64 + dy as i64,
+                step.chunk.2 as i64 + dz as i64,
+            ];
+
+            if !inside(next) {
+                continue;
+            }
+
+            let next = (next[0] as usize, next[1] as usize, next[2] as usize);
+
+            if visible.contains(&next) || !in_view(next) {
+                continue;
+            }
+
+            queue.push_back(VisitStep {
+                chunk: next,
+                entered_through: Some(opposite(face)),
+                directions: step.directions | (1 << face),
+            });
+        }
+    }
+
+    visible
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solid_wall_blocks_faces() {
+        // A solid wall across the middle of the chunk, splitting the bottom from the top
+        let connectivity = ChunkFaceConnectivity::compute(|_, y, _| y != CHUNK_DIMENSIONS / 2);
+
+        assert!(!connectivity.connected(BlockFace::Top, BlockFace::Bottom));
+        assert!(connectivity.connected(BlockFace::Top, BlockFace::Left));
+        assert!(connectivity.connected(BlockFace::Bottom, BlockFace::Front));
+        assert!(connectivity.connected(BlockFace::Left, BlockFace::Right));
+    }
+
+    #[test]
+    fn solid_structure_hides_inner_chunks() {
+        let visible = visible_chunks(
+            (1, 1, 1),
+            ((0, 0, 0), (4, 4, 4)),
+            |_| ChunkFaceConnectivity::none(),
+            |_| true,
+        );
+
+        // Only the camera's chunk & the ones right next to it are visible
+        assert_eq!(visible.len(), 7);
+        assert!(!visible.contains(&(3, 1, 1)));
+    }
+
+    #[test]
+    fn open_structure_is_all_visible() {
+        let visible = visible_chunks(
+            (2, 2, 2),
+            ((0, 0, 0), (4, 4, 4)),
+            |_| ChunkFaceConnectivity::all(),
+            |_| true,
+        );
+
+        assert_eq!(visible.len(), 125);
+    }
+
+    #[test]
+    fn outside_camera_sees_facing_side() {
+        let visible = visible_chunks(
+            (-3, 1, 1),
+            ((0, 0, 0), (3, 3, 3)),
+            |_| ChunkFaceConnectivity::none(),
+            |_| true,
+        );
+
+        // The whole left side can be seen, but solid chunks hide everything behind it
+        assert!((0..4).all(|y| (0..4).all(|z| visible.contains(&(0, y, z)))));
+        assert!(!visible.contains(&(2, 1, 1)));
+    }
+}
@@ -0,0 +1,85 @@
+//! A coarse version of a planet's terrain, used to draw planets that are too far away to have any chunks loaded.
+//!
+//! Each face of the planet has a square grid of heights sampled from its biosphere's terrain,
+//! which is generated by the server & sent to the client once per planet.
+
+use bevy::prelude::Vec3;
+
+use crate::block::BlockFace;
+
+/// How many heights are sampled along each side of a planet face.
+///
+/// This is kept small so each face fits into a single message.
+pub const LOD_RESOLUTION: usize = 33;
+
+/// Returns the coordinate (in blocks) of the `index`th sample along a face of a planet with the given size
+pub fn lod_sample_coordinate(index: usize, s_dimensions: usize) -> usize {
+    (index * s_dimensions / (LOD_RESOLUTION - 1)).min(s_dimensions - 1)
+}
+
+/// Returns the position of a point on a planet's face, relative to the corner of the planet at (0, 0, 0)
+///
+/// * `face` The face of the planet this point is on
+/// * `(u, v)` The coordinates of the point along the face, see [`lod_face_coordinates`]
+/// * `height` How far the point is from the opposite side of the planet, along the face's direction
+/// * `s_dimensions` The size of the planet in blocks
+pub fn lod_surface_point(
+    face: BlockFace,
+    (u, v): (f32, f32),
+    height: f32,
+    s_dimensions: f32,
+) -> Vec3 {
+    match face {
+        BlockFace::Top => Vec3::new(u, height, v),
+        BlockFace::Bottom => Vec3::new(u, s_dimensions - height, v),
+        BlockFace::Front => Vec3::new(u, v, height),
+        BlockFace::Back => Vec3::new(u, v, s_dimensions - height),
+        BlockFace::Right => Vec3::new(height, u, v),
+        BlockFace::Left => Vec3::new(s_dimensions - height, u, v),
+    }
+}
+
+/// Returns the block coordinates that the `(u, v)` coordinates of a planet face correspond to,
+/// where `height_coordinate` is used for the axis the face points along.
+///
+/// This matches how the biospheres pick the coordinates used to generate the height of each face.
+pub fn lod_face_coordinates(
+    face: BlockFace,
+    (u, v): (usize, usize),
+    height_coordinate: usize,
+) -> (usize, usize, usize) {
+    match face {
+        BlockFace::Top | BlockFace::Bottom => (u, height_coordinate, v),
+        BlockFace::Front | BlockFace::Back => (u, v, height_coordinate),
+        BlockFace::Right | BlockFace::Left => (height_coordinate, u, v),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_cover_face() {
+        assert_eq!(lod_sample_coordinate(0, 1600), 0);
+        assert_eq!(lod_sample_coordinate(LOD_RESOLUTION - 1, 1600), 1599);
+        assert_eq!(lod_sample_coordinate(16, 1600), 800);
+    }
+
+    #[test]
+    fn surface_points_face_outwards() {
+        let s = 100.0;
+
+        for index in 0..6 {
+            let face = BlockFace::from_index(index);
+
+            let center = Vec3::splat(s / 2.0);
+            let high = lod_surface_point(face, (s / 2.0, s / 2.0), 90.0, s) - center;
+            let low = lod_surface_point(face, (s / 2.0, s / 2.0), 60.0, s) - center;
+
+            // A bigger height is further out in the direction of that face
+            assert!(high.dot(face.direction_vec3()) > low.dot(face.direction_vec3()));
+            assert!(high.dot(face.direction_vec3()) > 0.0);
+        }
+    }
+}
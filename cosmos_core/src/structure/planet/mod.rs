@@ -3,18 +3,23 @@
 //! These are not made by the player but generated
 
 use bevy::{
-    prelude::{App, Component, Vec3},
+    prelude::{App, Component, Quat, Vec3},
     reflect::{FromReflect, Reflect},
 };
 use bigdecimal::Signed;
 use serde::{Deserialize, Serialize};
 
-use crate::{block::BlockFace, physics::location::SYSTEM_SECTORS};
+use crate::{
+    block::BlockFace, physics::location::SYSTEM_SECTORS, structure::chunk::CHUNK_DIMENSIONSF,
+};
 
 use super::Structure;
 
 pub mod biosphere;
+pub mod lod;
 pub mod planet_builder;
+pub mod planet_movement;
+pub mod planet_netty;
 
 #[derive(Component, Debug, Reflect, FromReflect, Serialize, Deserialize, Clone, Copy)]
 /// If a structure has this, it is a planet.
@@ -68,6 +73,32 @@ impl Planet {
         ))
     }
 
+    /// Gets the coordinates of the chunk at this position, which may be outside of the planet.
+    ///
+    /// Planets spin, so the position is turned into the planet's unrotated coordinates first.
+    ///
+    /// * `planet_rotation` The planet's current rotation
+    /// * `relative_position` The position relative to the planet's center, such as `player_location - planet_location`
+    pub fn chunk_coords_at(
+        structure: &Structure,
+        planet_rotation: Quat,
+        relative_position: Vec3,
+    ) -> (i32, i32, i32) {
+        let relative_position = planet_rotation.inverse() * relative_position;
+
+        let (bx, by, bz) = structure.relative_coords_to_local_coords(
+            relative_position.x,
+            relative_position.y,
+            relative_position.z,
+        );
+
+        (
+            (bx as f32 / CHUNK_DIMENSIONSF).floor() as i32,
+            (by as f32 / CHUNK_DIMENSIONSF).floor() as i32,
+            (bz as f32 / CHUNK_DIMENSIONSF).floor() as i32,
+        )
+    }
+
     /// Gets the face of a planet this location is closest to
     pub fn planet_face_relative(relative_position: Vec3) -> BlockFace {
         let normalized = relative_position.normalize_or_zero();
@@ -104,3 +135,31 @@ pub(super) fn register(app: &mut App) {
 
     app.register_type::<Planet>();
 }
+
+#[cfg(test)]
+mod test {
+    use std::f32::consts::FRAC_PI_2;
+
+    use bevy::prelude::{Quat, Vec3};
+
+    use crate::structure::{chunk::CHUNK_DIMENSIONSF, Structure};
+
+    use super::Planet;
+
+    #[test]
+    fn test_chunk_coords_at_undoes_rotation() {
+        let structure = Structure::new(4, 4, 4);
+        let position = Vec3::new(CHUNK_DIMENSIONSF * 1.5, 0.0, 0.0);
+
+        assert_eq!(
+            Planet::chunk_coords_at(&structure, Quat::IDENTITY, position),
+            (3, 2, 2)
+        );
+
+        // Turning the planet 90 degrees around y points its +z side where +x was
+        assert_eq!(
+            Planet::chunk_coords_at(&structure, Quat::from_rotation_y(FRAC_PI_2), position),
+            (2, 2, 3)
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Moves everything standing on or flying just above a planet along with it as the planet moves & spins
+//!
+//! Nothing here decides how planets move - this just makes sure things near a planet don't get
+//! left behind when it does.
+
+use bevy::{
+    ecs::query::ReadOnlyWorldQuery,
+    prelude::{Commands, Component, Entity, Parent, Quat, Query, Transform, Vec3, With, Without},
+};
+
+use crate::physics::{gravity_system::GravityEmitter, location::Location};
+
+use super::Planet;
+
+/// Anything within this many times a planet's gravity radius is moved along with it.
+///
+/// This is a bit past where the planet's gravity starts to weaken, so anything in its full gravity is carried.
+pub const CARRY_RADIUS_MULTIPLIER: f32 = 1.1;
+
+#[derive(Component, Debug, Clone, Copy)]
+/// Where this planet was & how it was rotated the last time things near it were moved along with it
+pub struct LastPlanetPosition {
+    location: Location,
+    rotation: Quat,
+}
+
+/// Moves every entity matching `F` that is near a planet by however much that planet moved & rotated since this last ran.
+///
+/// Entities with a parent are moved with their parent instead, so they are skipped.
+pub fn move_with_planets<F: ReadOnlyWorldQuery>(
+    mut planets: Query<
+        (
+            Entity,
+            &Location,
+            &Transform,
+            &GravityEmitter,
+            Option<&mut LastPlanetPosition>,
+        ),
+        With<Planet>,
+    >,
+    mut carried: Query<
+        (&mut Location, Option<&mut Transform>),
+        (Without<Planet>, Without<Parent>, F),
+    >,
+    mut commands: Commands,
+) {
+    for (entity, location, transform, gravity_emitter, last_position) in planets.iter_mut() {
+        let Some(mut last_position) = last_position else {
+            commands.entity(entity).insert(LastPlanetPosition {
+                location: *location,
+                rotation: transform.rotation,
+            });
+            continue;
+        };
+
+        let previous = *last_position;
+
+        let delta = previous.location.relative_coords_to(location);
+        let rotation_delta = transform.rotation * previous.rotation.inverse();
+
+        last_position.location = *location;
+        last_position.rotation = transform.rotation;
+
+        let rotated = !rotation_delta.abs_diff_eq(Quat::IDENTITY, f32::EPSILON);
+
+        if delta == Vec3::ZERO && !rotated {
+            continue;
+        }
+
+        let carry_distance = gravity_emitter.radius * CARRY_RADIUS_MULTIPLIER;
+
+        for (mut carried_location, carried_transform) in carried.iter_mut() {
+            let offset = previous.location.relative_coords_to(&carried_location);
+
+            // Same distance measurement gravity uses, since planets are cubes
+            let distance = (previous.rotation.inverse() * offset).abs().max_element();
+
+            if distance > carry_distance {
+                continue;
+            }
+
+            // Changing the local coordinates directly keeps `last_transform_loc` intact
+            carried_location.local += delta + (rotation_delta * offset - offset);
+            carried_location.fix_bounds();
+
+            if rotated {
+                if let Some(mut carried_transform) = carried_transform {
+                    carried_transform.rotation = rotation_delta * carried_transform.rotation;
+                }
+            }
+        }
+    }
+}
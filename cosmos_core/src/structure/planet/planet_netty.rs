@@ -0,0 +1,22 @@
+//! Represents the communications a planet needs
+
+use bevy::prelude::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::block::BlockFace;
+
+#[derive(Debug, Serialize, Deserialize)]
+/// All the planet server messages
+pub enum PlanetServerMessages {
+    /// The coarse terrain of one face of a planet, used to draw it from far away.
+    ///
+    /// Sent once for each face when a client requests information for a planet
+    LodFace {
+        /// The planet's server entity
+        entity: Entity,
+        /// The face of the planet these heights are for
+        face: BlockFace,
+        /// `LOD_RESOLUTION * LOD_RESOLUTION` heights, see [`super::lod`]
+        heights: Vec<u16>,
+    },
+}
@@ -15,6 +15,7 @@ pub mod energy_generation_system;
 pub mod energy_storage_system;
 pub mod laser_cannon_system;
 pub mod thruster_system;
+pub mod warp_drive_system;
 
 #[derive(Component)]
 #[component(storage = "SparseSet")]
@@ -155,4 +156,5 @@ pub(super) fn register<T: States + Clone + Copy>(
     energy_generation_system::register(app, post_loading_state, playing_state);
     thruster_system::register(app, post_loading_state, playing_state);
     laser_cannon_system::register(app, post_loading_state, playing_state);
+    warp_drive_system::register(app, post_loading_state, playing_state);
 }
@@ -0,0 +1,186 @@
+//! Represents all the warp drives on a structure
+//!
+//! Warp drives slowly charge up from the structure's energy storage, and spend that charge
+//! to jump the structure to a far away location. How far they can jump depends on how much charge they hold.
+
+use bevy::{
+    prelude::{
+        App, Commands, Component, EventReader, IntoSystemAppConfig, IntoSystemConfig, OnEnter,
+        OnUpdate, Query, Res, ResMut, Resource, States,
+    },
+    reflect::{FromReflect, Reflect},
+    utils::HashMap,
+};
+
+use crate::{
+    block::Block,
+    events::block_events::BlockChangedEvent,
+    physics::location::{Location, SECTOR_DIMENSIONS},
+    registry::{identifiable::Identifiable, Registry},
+    structure::{events::StructureLoadedEvent, Structure},
+};
+
+use super::Systems;
+
+/// How much charge it takes to warp the distance of one sector
+pub const CHARGE_PER_SECTOR: f32 = 100.0;
+
+#[derive(Default, FromReflect, Reflect, Clone, Copy)]
+/// Every block that is part of a warp drive should have this property
+pub struct WarpDriveProperty {
+    /// How much charge this block can hold
+    pub charge_capacity: f32,
+    /// How much energy per second this block can take from the energy storage to charge itself
+    pub charge_rate: f32,
+}
+
+#[derive(Default, Resource)]
+struct WarpDriveBlocks {
+    blocks: HashMap<u16, WarpDriveProperty>,
+}
+
+impl WarpDriveBlocks {
+    pub fn insert(&mut self, block: &Block, warp_drive_property: WarpDriveProperty) {
+        self.blocks.insert(block.id(), warp_drive_property);
+    }
+
+    pub fn get(&self, block: &Block) -> Option<&WarpDriveProperty> {
+        self.blocks.get(&block.id())
+    }
+}
+
+#[derive(Component, Default, Reflect, FromReflect)]
+/// Represents the warp drives of a structure
+pub struct WarpDriveSystem {
+    charge: f32,
+    charge_capacity: f32,
+    charge_rate: f32,
+}
+
+impl WarpDriveSystem {
+    fn block_added(&mut self, prop: &WarpDriveProperty) {
+        self.charge_capacity += prop.charge_capacity;
+        self.charge_rate += prop.charge_rate;
+    }
+
+    fn block_removed(&mut self, prop: &WarpDriveProperty) {
+        self.charge_capacity -= prop.charge_capacity;
+        self.charge_rate -= prop.charge_rate;
+        self.charge = self.charge.min(self.charge_capacity);
+    }
+
+    /// Adds charge to this system, up to its capacity.
+    ///
+    /// Returns how much charge was actually added.
+    pub fn increase_charge(&mut self, delta: f32) -> f32 {
+        let before = self.charge;
+
+        self.charge = self.charge_capacity.min(self.charge + delta);
+
+        self.charge - before
+    }
+
+    /// Gets the current charge of the system
+    pub fn get_charge(&self) -> f32 {
+        self.charge
+    }
+
+    /// Gets the most charge this system can hold
+    pub fn get_charge_capacity(&self) -> f32 {
+        self.charge_capacity
+    }
+
+    /// Gets how much energy per second this system can use to charge itself
+    pub fn get_charge_rate(&self) -> f32 {
+        self.charge_rate
+    }
+
+    /// How much charge it would take to warp between these two locations
+    pub fn charge_needed(from: &Location, to: &Location) -> f32 {
+        let delta = to.absolute_coords_f64() - from.absolute_coords_f64();
+
+        (delta.norm() / SECTOR_DIMENSIONS as f64) as f32 * CHARGE_PER_SECTOR
+    }
+
+    /// Returns true if this system has enough charge to warp between these two locations
+    pub fn can_warp(&self, from: &Location, to: &Location) -> bool {
+        self.charge_capacity > 0.0 && self.charge >= Self::charge_needed(from, to)
+    }
+
+    /// Uses up the charge needed to warp between these two locations.
+    ///
+    /// Make sure to check using `can_warp` if there is enough charge first.
+    pub fn discharge_for_warp(&mut self, from: &Location, to: &Location) {
+        self.charge = (self.charge - Self::charge_needed(from, to)).max(0.0);
+    }
+}
+
+fn register_warp_drive_blocks(blocks: Res<Registry<Block>>, mut storage: ResMut<WarpDriveBlocks>) {
+    if let Some(block) = blocks.from_id("cosmos:warp_drive") {
+        storage.insert(
+            block,
+            WarpDriveProperty {
+                charge_capacity: 20000.0,
+                charge_rate: 500.0,
+            },
+        );
+    }
+}
+
+fn block_update_system(
+    mut event: EventReader<BlockChangedEvent>,
+    warp_drive_blocks: Res<WarpDriveBlocks>,
+    blocks: Res<Registry<Block>>,
+    mut system_query: Query<&mut WarpDriveSystem>,
+    systems_query: Query<&Systems>,
+) {
+    for ev in event.iter() {
+        if let Ok(systems) = systems_query.get(ev.structure_entity) {
+            if let Ok(mut system) = systems.query_mut(&mut system_query) {
+                if let Some(prop) = warp_drive_blocks.get(blocks.from_numeric_id(ev.old_block)) {
+                    system.block_removed(prop);
+                }
+
+                if let Some(prop) = warp_drive_blocks.get(blocks.from_numeric_id(ev.new_block)) {
+                    system.block_added(prop);
+                }
+            }
+        }
+    }
+}
+
+fn structure_loaded_event(
+    mut event_reader: EventReader<StructureLoadedEvent>,
+    mut structure_query: Query<(&Structure, &mut Systems)>,
+    blocks: Res<Registry<Block>>,
+    mut commands: Commands,
+    warp_drive_blocks: Res<WarpDriveBlocks>,
+) {
+    for ev in event_reader.iter() {
+        if let Ok((structure, mut systems)) = structure_query.get_mut(ev.structure_entity) {
+            let mut system = WarpDriveSystem::default();
+
+            for block in structure.all_blocks_iter(false) {
+                if let Some(prop) = warp_drive_blocks.get(block.block(structure, &blocks)) {
+                    system.block_added(prop);
+                }
+            }
+
+            systems.add_system(&mut commands, system);
+        }
+    }
+}
+
+pub(super) fn register<T: States + Clone + Copy>(
+    app: &mut App,
+    post_loading_state: T,
+    playing_state: T,
+) {
+    app.insert_resource(WarpDriveBlocks::default())
+        .add_systems((
+            register_warp_drive_blocks.in_schedule(OnEnter(post_loading_state)),
+            structure_loaded_event.in_set(OnUpdate(playing_state)),
+            block_update_system.in_set(OnUpdate(playing_state)),
+        ))
+        .register_type::<WarpDriveSystem>();
+}
@@ -3,6 +3,9 @@
 use bevy::prelude::App;
 
 pub mod star;
+pub mod star_catalog;
+pub mod system_map;
+pub mod universe_netty;
 
 pub(super) fn register(app: &mut App) {
     star::register(app);
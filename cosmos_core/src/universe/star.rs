@@ -6,6 +6,8 @@ use bevy::{
 };
 use serde::{Deserialize, Serialize};
 
+use crate::physics::location::SECTOR_DIMENSIONS;
+
 /// Taken from http://www.vendian.org/mncharity/dir3/blackbody/UnstableURLs/bbr_color.html
 // Clippy thinks some of these are random constants
 #[allow(clippy::approx_constant)]
@@ -415,6 +417,8 @@ pub struct Star {
 pub const MIN_TEMPERATURE: f32 = 1_000.0;
 /// The maximum temperature a star can be
 pub const MAX_TEMPERATURE: f32 = 40_000.0;
+/// How far the surface of a star is from its center
+pub const STAR_RADIUS: f32 = SECTOR_DIMENSIONS * 2.0;
 
 impl Star {
     /// Creates a new star with the given temperature.
@@ -0,0 +1,51 @@
+//! A list of the stars around a player, which the client uses to draw the stars in the sky.
+//!
+//! Stars are far too far away to be loaded as entities, so the server sends this instead
+//! whenever a player moves into a new system.
+
+use serde::{Deserialize, Serialize};
+
+use crate::physics::location::{Location, Sector, SystemUnit, UniverseSystem, SYSTEM_SECTORS};
+
+use super::star::Star;
+
+/// How many systems away from the player's system (along each axis) stars are put into the catalog
+pub const STAR_CATALOG_RADIUS: SystemUnit = 8;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+/// A star and the system it's in
+pub struct CatalogStar {
+    system: UniverseSystem,
+    star: Star,
+}
+
+impl CatalogStar {
+    /// Creates a catalog entry for the star at the center of this system
+    pub fn new(system: UniverseSystem, star: Star) -> Self {
+        Self { system, star }
+    }
+
+    /// The system this star is in
+    pub fn system(&self) -> UniverseSystem {
+        self.system
+    }
+
+    /// The star
+    pub fn star(&self) -> &Star {
+        &self.star
+    }
+
+    /// The location of this star, which is always at the center of its system
+    pub fn location(&self) -> Location {
+        let half = SYSTEM_SECTORS as SystemUnit / 2;
+
+        Location::new(
+            Default::default(),
+            Sector::new(
+                self.system.x() * SYSTEM_SECTORS as SystemUnit + half,
+                self.system.y() * SYSTEM_SECTORS as SystemUnit + half,
+                self.system.z() * SYSTEM_SECTORS as SystemUnit + half,
+            ),
+        )
+    }
+}
@@ -0,0 +1,81 @@
+//! A summary of everything in a player's system, which the client uses to draw its map.
+//!
+//! Most of a system is too far away to be sent to the player as entities, so the server
+//! regularly sends this instead.
+
+use serde::{Deserialize, Serialize};
+
+use crate::physics::location::Location;
+
+/// The most bodies that will be sent in one system map
+pub const MAX_SYSTEM_MAP_BODIES: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+/// What kind of thing a body in the system map is
+pub enum SystemBodyKind {
+    /// A planet
+    Planet,
+    /// An asteroid
+    Asteroid,
+    /// A ship
+    Ship,
+    /// A player
+    Player,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Something in the system map
+pub struct SystemBody {
+    kind: SystemBodyKind,
+    location: Location,
+    radius: f32,
+    name: Option<String>,
+}
+
+impl SystemBody {
+    /// Creates a body for the system map
+    ///
+    /// * `radius` Roughly how far from its center this body's surface is
+    /// * `name` What to call this body, or None if it should just be called by its kind
+    pub fn new(
+        kind: SystemBodyKind,
+        location: Location,
+        radius: f32,
+        name: Option<String>,
+    ) -> Self {
+        Self {
+            kind,
+            location,
+            radius,
+            name,
+        }
+    }
+
+    /// What kind of thing this body is
+    pub fn kind(&self) -> SystemBodyKind {
+        self.kind
+    }
+
+    /// Where this body was when the map was sent
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    /// Roughly how far from its center this body's surface is
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// What to call this body
+    pub fn name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => match self.kind {
+                SystemBodyKind::Planet => "Planet".into(),
+                SystemBodyKind::Asteroid => "Asteroid".into(),
+                SystemBodyKind::Ship => "Ship".into(),
+                SystemBodyKind::Player => "Player".into(),
+            },
+        }
+    }
+}
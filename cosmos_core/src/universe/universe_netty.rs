@@ -0,0 +1,30 @@
+//! Represents the communications needed for the parts of the universe too far away to be loaded
+
+use serde::{Deserialize, Serialize};
+
+use crate::physics::location::UniverseSystem;
+
+use super::{star_catalog::CatalogStar, system_map::SystemBody};
+
+#[derive(Debug, Serialize, Deserialize)]
+/// All the universe server messages
+pub enum UniverseServerMessages {
+    /// Every star around the player, see [`super::star_catalog`]
+    ///
+    /// Sent whenever the player enters a new system
+    StarCatalog {
+        /// The system the player is in, which the catalog is centered around
+        center: UniverseSystem,
+        /// Every star within [`super::star_catalog::STAR_CATALOG_RADIUS`] systems of the center
+        stars: Vec<CatalogStar>,
+    },
+    /// Everything the server knows of in the player's system, see [`super::system_map`]
+    ///
+    /// Sent every few seconds
+    SystemMap {
+        /// The system the player is in
+        system: UniverseSystem,
+        /// The planets & asteroids in this system, along with the ships & players this player can see
+        bodies: Vec<SystemBody>,
+    },
+}
@@ -0,0 +1,269 @@
+//! Greedy meshing merges many small cells into as few large rectangles/boxes as possible.
+//!
+//! This is used to reduce the number of faces in chunk meshes and the number of colliders in chunk colliders.
+
+use super::array_utils::flatten;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A rectangle of cells that all had the same value
+pub struct MergedRectangle<T> {
+    /// The smallest x coordinate this covers
+    pub x: usize,
+    /// The smallest y coordinate this covers
+    pub y: usize,
+    /// How many cells this covers on the x axis
+    pub width: usize,
+    /// How many cells this covers on the y axis
+    pub height: usize,
+    /// The value every cell in this rectangle had
+    pub value: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A box of cells that were all filled
+pub struct MergedBox {
+    /// The smallest x coordinate this covers
+    pub x: usize,
+    /// The smallest y coordinate this covers
+    pub y: usize,
+    /// The smallest z coordinate this covers
+    pub z: usize,
+    /// How many cells this covers on the x axis
+    pub width: usize,
+    /// How many cells this covers on the y axis
+    pub height: usize,
+    /// How many cells this covers on the z axis
+    pub length: usize,
+}
+
+/// Merges every cell with a value into rectangles of cells with equal values.
+///
+/// The cells are stored row by row (index = `y * width + x`), and will all be `None` once this is done.
+pub fn merge_rectangles<T: Copy + PartialEq>(
+    cells: &mut [Option<T>],
+    width: usize,
+    height: usize,
+) -> Vec<MergedRectangle<T>> {
+    debug_assert_eq!(cells.len(), width * height);
+
+    let mut rectangles = Vec::new();
+
+    for y in 0..height {
+        let mut x = 0;
+
+        while x < width {
+            let Some(value) = cells[y * width + x] else {
+                x += 1;
+                continue;
+            };
+
+            let mut rect_width = 1;
+            while x + rect_width < width && cells[y * width + x + rect_width] == Some(value) {
+                rect_width += 1;
+            }
+
+            let mut rect_height = 1;
+            while y + rect_height < height
+                && (x..x + rect_width)
+                    .all(|dx| cells[(y + rect_height) * width + dx] == Some(value))
+            {
+                rect_height += 1;
+            }
+
+            for dy in y..y + rect_height {
+                for cell in cells[dy * width + x..dy * width + x + rect_width].iter_mut() {
+                    *cell = None;
+                }
+            }
+
+            rectangles.push(MergedRectangle {
+                x,
+                y,
+                width: rect_width,
+                height: rect_height,
+                value,
+            });
+
+            x += rect_width;
+        }
+    }
+
+    rectangles
+}
+
+/// Merges every filled cell into boxes that only contain filled cells.
+///
+/// The cells are indexed the same way [`flatten`] does.
+pub fn merge_boxes(filled: &[bool], width: usize, height: usize, length: usize) -> Vec<MergedBox> {
+    debug_assert_eq!(filled.len(), width * height * length);
+
+    let mut used = vec![false; filled.len()];
+    let available = |used: &[bool], x: usize, y: usize, z: usize| {
+        let i = flatten(x, y, z, width, height);
+        filled[i] && !used[i]
+    };
+
+    let mut boxes = Vec::new();
+
+    for z in 0..length {
+        for y in 0..height {
+            for x in 0..width {
+                if !available(&used, x, y, z) {
+                    continue;
+                }
+
+                let mut box_width = 1;
+                while x + box_width < width && available(&used, x + box_width, y, z) {
+                    box_width += 1;
+                }
+
+                let mut box_height = 1;
+                while y + box_height < height
+                    && (x..x + box_width).all(|dx| available(&used, dx, y + box_height, z))
+                {
+                    box_height += 1;
+                }
+
+                let mut box_length = 1;
+                while z + box_length < length
+                    && (y..y + box_height).all(|dy| {
+                        (x..x + box_width).all(|dx| available(&used, dx, dy, z + box_length))
+                    })
+                {
+                    box_length += 1;
+                }
+
+                for dz in z..z + box_length {
+                    for dy in y..y + box_height {
+                        for dx in x..x + box_width {
+                            used[flatten(dx, dy, dz, width, height)] = true;
+                        }
+                    }
+                }
+
+                boxes.push(MergedBox {
+                    x,
+                    y,
+                    z,
+                    width: box_width,
+                    height: box_height,
+                    length: box_length,
+                });
+            }
+        }
+    }
+
+    boxes
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::utils::array_utils::flatten;
+
+    use super::{merge_boxes, merge_rectangles};
+
+    const SIZE: usize = 16;
+
+    /// Makes sure every filled cell is covered exactly once and no empty cell is covered
+    fn assert_boxes_cover(filled: &[bool]) {
+        let mut covered = vec![0; filled.len()];
+
+        for b in merge_boxes(filled, SIZE, SIZE, SIZE) {
+            for z in b.z..b.z + b.length {
+                for y in b.y..b.y + b.height {
+                    for x in b.x..b.x + b.width {
+                        covered[flatten(x, y, z, SIZE, SIZE)] += 1;
+                    }
+                }
+            }
+        }
+
+        for (filled, covered) in filled.iter().zip(covered) {
+            assert_eq!(covered, *filled as i32);
+        }
+    }
+
+    #[test]
+    fn test_boxes_full() {
+        let boxes = merge_boxes(&[true; SIZE * SIZE * SIZE], SIZE, SIZE, SIZE);
+
+        assert_eq!(boxes.len(), 1);
+        assert_eq!(
+            (boxes[0].width, boxes[0].height, boxes[0].length),
+            (SIZE, SIZE, SIZE)
+        );
+    }
+
+    #[test]
+    fn test_boxes_wall() {
+        let mut filled = vec![false; SIZE * SIZE * SIZE];
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                filled[flatten(x, y, 3, SIZE, SIZE)] = true;
+            }
+        }
+
+        assert_eq!(merge_boxes(&filled, SIZE, SIZE, SIZE).len(), 1);
+        assert_boxes_cover(&filled);
+    }
+
+    #[test]
+    fn test_boxes_random_coverage() {
+        let mut rng = StdRng::seed_from_u64(12345);
+
+        for fill_chance in [0.1, 0.5, 0.9] {
+            let filled = (0..SIZE * SIZE * SIZE)
+                .map(|_| rng.gen_bool(fill_chance))
+                .collect::<Vec<bool>>();
+
+            assert_boxes_cover(&filled);
+        }
+    }
+
+    #[test]
+    fn test_rectangles_random_coverage() {
+        let mut rng = StdRng::seed_from_u64(54321);
+
+        let cells = (0..SIZE * SIZE)
+            .map(|_| match rng.gen_range(0..4) {
+                0 => None,
+                n => Some(n),
+            })
+            .collect::<Vec<Option<i32>>>();
+
+        let mut merged = cells.clone();
+        let rectangles = merge_rectangles(&mut merged, SIZE, SIZE);
+
+        assert!(merged.iter().all(|x| x.is_none()));
+
+        let mut covered = vec![None; SIZE * SIZE];
+        for rect in rectangles {
+            for y in rect.y..rect.y + rect.height {
+                for x in rect.x..rect.x + rect.width {
+                    assert!(covered[y * SIZE + x].is_none(), "Rectangles overlap");
+                    covered[y * SIZE + x] = Some(rect.value);
+                }
+            }
+        }
+
+        assert_eq!(covered, cells);
+    }
+
+    #[test]
+    fn test_rectangles_only_merge_equal() {
+        let mut cells = vec![Some(1); SIZE * SIZE];
+        cells[SIZE * SIZE / 2] = Some(2);
+
+        let rectangles = merge_rectangles(&mut cells, SIZE, SIZE);
+
+        assert!(rectangles
+            .iter()
+            .any(|r| r.value == 2 && r.width * r.height == 1));
+        assert_eq!(
+            rectangles.iter().map(|r| r.width * r.height).sum::<usize>(),
+            SIZE * SIZE
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //! Just a collection of helpful utilities that could be separated into separate packages, but that's far too much effort.
 
 pub mod array_utils;
+pub mod greedy_meshing;
 pub mod quat_math;
 pub mod resource_wrapper;
 pub mod smooth_clamp;
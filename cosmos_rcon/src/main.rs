@@ -0,0 +1,111 @@
+//! A tiny command line client for the Cosmos server's remote administration listener.
+//!
+//! Usage: `cosmos_rcon [address] [command?]`
+//!
+//! The password is read from the `COSMOS_RCON_PASSWORD` environment variable, or asked for if that isn't set.
+//! It isn't taken as an argument, since those can be seen by other users through the process list & shell history.
+//!
+//! If a command is given, it is run & its output printed. Otherwise, commands are read from stdin
+//! one line at a time until it is closed.
+
+#![warn(missing_docs)]
+
+use std::{
+    env,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    process::exit,
+};
+
+/// Reads the password from `COSMOS_RCON_PASSWORD`, or asks for it on stdin if that isn't set
+fn read_password() -> String {
+    if let Ok(password) = env::var("COSMOS_RCON_PASSWORD") {
+        return password;
+    }
+
+    eprint!("Password: ");
+    io::stderr().flush().expect("Unable to write to stderr");
+
+    let mut password = String::new();
+    io::stdin()
+        .read_line(&mut password)
+        .expect("Unable to read password from stdin");
+
+    password.trim_end_matches(['\r', '\n']).to_owned()
+}
+
+/// Sends the command & prints every line of output until the end of output marker is received
+fn run_command(
+    command: &str,
+    stream: &mut TcpStream,
+    reader: &mut BufReader<TcpStream>,
+) -> io::Result<()> {
+    stream.write_all(format!("{command}\n").as_bytes())?;
+
+    let mut line = String::new();
+    loop {
+        line.clear();
+
+        if reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "The server closed the connection",
+            ));
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            return Ok(());
+        }
+
+        println!("{line}");
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 2 {
+        eprintln!("Usage: {} [address] [command?]", args[0]);
+        exit(1);
+    }
+
+    let password = read_password();
+
+    let mut stream = TcpStream::connect(&args[1]).unwrap_or_else(|e| {
+        eprintln!("Unable to connect to {} - {e}", args[1]);
+        exit(1);
+    });
+
+    let mut reader = BufReader::new(stream.try_clone().expect("Unable to clone tcp stream"));
+
+    stream
+        .write_all(format!("{password}\n").as_bytes())
+        .expect("Unable to send password");
+
+    let mut response = String::new();
+    if reader.read_line(&mut response).is_err() || response.trim() != "OK" {
+        eprintln!("Invalid password");
+        exit(1);
+    }
+
+    if args.len() > 2 {
+        if let Err(e) = run_command(&args[2..].join(" "), &mut stream, &mut reader) {
+            eprintln!("{e}");
+            exit(1);
+        }
+    } else {
+        for command in io::stdin().lock().lines() {
+            let command = command.expect("Unable to read from stdin");
+
+            if command.trim().is_empty() {
+                continue;
+            }
+
+            if let Err(e) = run_command(&command, &mut stream, &mut reader) {
+                eprintln!("{e}");
+                exit(1);
+            }
+        }
+    }
+}
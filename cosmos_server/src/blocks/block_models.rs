@@ -0,0 +1,29 @@
+//! Loads the models of blocks that aren't full cubes, which their colliders are built from.
+//!
+//! These are read from `assets/blocks`, the same layout the client uses.
+
+use std::fs;
+
+use bevy::prelude::{App, IntoSystemAppConfig, OnExit, Res, ResMut};
+use cosmos_core::{
+    block::{
+        block_models::{self, BlockModelRegistry},
+        Block,
+    },
+    registry::Registry,
+};
+
+use crate::state::GameState;
+
+/// The directory block & block model files are read relative to
+const ASSETS_DIRECTORY: &str = "assets";
+
+fn load_block_models(blocks: Res<Registry<Block>>, mut registry: ResMut<BlockModelRegistry>) {
+    *registry = block_models::load_block_models(&blocks, |path| {
+        fs::read(format!("{ASSETS_DIRECTORY}/{path}")).ok()
+    });
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_system(load_block_models.in_schedule(OnExit(GameState::Loading)));
+}
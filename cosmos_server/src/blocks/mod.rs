@@ -2,8 +2,10 @@
 
 use bevy::prelude::App;
 
+mod block_models;
 pub mod interactable;
 
 pub(super) fn register(app: &mut App) {
+    block_models::register(app);
     interactable::register(app);
 }
@@ -0,0 +1,146 @@
+//! Handles the in-game chat
+//!
+//! Messages starting with a `/` are treated as commands, and can only be used by operators.
+
+use bevy::{
+    prelude::{
+        Added, App, Commands, Component, Entity, EventWriter, IntoSystemConfigs, OnUpdate, Query,
+        Res, ResMut,
+    },
+    time::Time,
+};
+use bevy_renet::renet::RenetServer;
+use cosmos_core::{
+    entities::player::Player,
+    netty::{
+        chat_messages::{ClientChatMessages, ServerChatMessages, MAX_CHAT_MESSAGE_LENGTH},
+        cosmos_encoder, NettyChannel,
+    },
+};
+
+use crate::{
+    commands::{CommandResponse, CommandSender, CosmosCommandSent},
+    moderation::OperatorList,
+    netty::network_helpers::ServerLobby,
+    state::GameState,
+};
+
+/// The most messages a player can send in a row before being rate limited
+const MAX_MESSAGE_BURST: f32 = 5.0;
+/// How many messages a player regains the ability to send every second
+const MESSAGES_PER_SECOND: f32 = 1.0;
+
+#[derive(Component, Debug)]
+/// Prevents a player from spamming the chat
+struct ChatRateLimit {
+    /// How many messages the player can currently send
+    allowance: f32,
+}
+
+impl Default for ChatRateLimit {
+    fn default() -> Self {
+        Self {
+            allowance: MAX_MESSAGE_BURST,
+        }
+    }
+}
+
+fn refill_rate_limits(mut query: Query<&mut ChatRateLimit>, time: Res<Time>) {
+    for mut rate_limit in query.iter_mut() {
+        rate_limit.allowance = (rate_limit.allowance + time.delta_seconds() * MESSAGES_PER_SECOND)
+            .min(MAX_MESSAGE_BURST);
+    }
+}
+
+fn add_rate_limits(mut commands: Commands, query: Query<Entity, Added<Player>>) {
+    for entity in query.iter() {
+        commands.entity(entity).insert(ChatRateLimit::default());
+    }
+}
+
+fn receive_chat_messages(
+    mut server: ResMut<RenetServer>,
+    lobby: Res<ServerLobby>,
+    mut players: Query<(&Player, &mut ChatRateLimit)>,
+    operators: Res<OperatorList>,
+    mut command_writer: EventWriter<CosmosCommandSent>,
+    mut responses: EventWriter<CommandResponse>,
+) {
+    for client_id in server.clients_id().into_iter() {
+        while let Some(message) = server.receive_message(client_id, NettyChannel::Chat.id()) {
+            let Ok(msg) = cosmos_encoder::deserialize::<ClientChatMessages>(&message) else {
+                println!("Unable to deserialize chat message from client {client_id}");
+                continue;
+            };
+
+            let Some(player_entity) = lobby.player_from_id(client_id) else {
+                continue;
+            };
+
+            let Ok((player, mut rate_limit)) = players.get_mut(player_entity) else {
+                continue;
+            };
+
+            let sender = CommandSender::Player {
+                client_id,
+                entity: player_entity,
+            };
+
+            match msg {
+                ClientChatMessages::SendMessage { message } => {
+                    let message = message
+                        .trim()
+                        .chars()
+                        .take(MAX_CHAT_MESSAGE_LENGTH)
+                        .collect::<String>();
+
+                    if message.is_empty() {
+                        continue;
+                    }
+
+                    if rate_limit.allowance < 1.0 {
+                        responses.send(CommandResponse {
+                            to: sender,
+                            message: "You are sending messages too quickly!".into(),
+                        });
+                        continue;
+                    }
+
+                    rate_limit.allowance -= 1.0;
+
+                    if let Some(command) = message.strip_prefix('/') {
+                        // A player's name is checked against their key when they connect, so it can be trusted here
+                        if operators.is_operator(player.name()) {
+                            println!("{} issued command: /{command}", player.name());
+
+                            command_writer.send(CosmosCommandSent::new(command.to_owned(), sender));
+                        } else {
+                            responses.send(CommandResponse {
+                                to: sender,
+                                message: "You do not have permission to use commands.".into(),
+                            });
+                        }
+                    } else {
+                        println!("<{}> {message}", player.name());
+
+                        server.broadcast_message(
+                            NettyChannel::Chat.id(),
+                            cosmos_encoder::serialize(&ServerChatMessages::ChatMessage {
+                                sender: Some(player.name().clone()),
+                                message,
+                            }),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems(
+        (add_rate_limits, refill_rate_limits, receive_chat_messages)
+            .chain()
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
@@ -1,17 +1,38 @@
 //! Handles all the server console commands
 
-use bevy::prelude::{App, Commands, Entity, EventReader, EventWriter, Query, Res, ResMut, With};
+use std::time::Duration;
+
+use bevy::{
+    prelude::{
+        App, Commands, Entity, EventReader, EventWriter, IntoSystemConfig, Query, Res, ResMut,
+        Resource, Transform, Vec3, With,
+    },
+    time::{common_conditions::on_timer, Time},
+};
+use bevy_rapier3d::prelude::Velocity;
+use bevy_renet::renet::RenetServer;
 use cosmos_core::{
     ecs::NeedsDespawned,
-    physics::location::Location,
-    structure::{planet::Planet, ship::Ship, Structure},
+    entities::player::Player,
+    inventory::Inventory,
+    item::Item,
+    netty::{
+        chat_messages::ServerChatMessages, cosmos_encoder, world_tick::WorldTick, NettyChannel,
+    },
+    physics::location::{Location, Sector, SectorUnit},
+    registry::Registry,
+    structure::{asteroid::Asteroid, planet::Planet, ship::Ship, Structure},
 };
 
-use crate::structure::saving::{
-    load_structure, SaveStructure, SendDelayedStructureLoadEvent, StructureType,
+use crate::{
+    moderation::{BanList, OperatorList, PlayerIdentities, Whitelist},
+    netty::validation::send_body_correction,
+    structure::saving::{
+        load_structure, SaveStructure, SendDelayedStructureLoadEvent, StructureType,
+    },
 };
 
-use super::{CosmosCommandInfo, CosmosCommandSent, CosmosCommands};
+use super::{CommandResponse, CosmosCommandInfo, CosmosCommandSent, CosmosCommands};
 
 fn register_commands(mut commands: ResMut<CosmosCommands>) {
     commands.add_command_info(CosmosCommandInfo {
@@ -51,22 +72,141 @@ fn register_commands(mut commands: ResMut<CosmosCommands>) {
         usage: "despawn [entity_id]".into(),
         description: "Despawns the given entity.".into(),
     });
+
+    commands.add_command_info(CosmosCommandInfo {
+        name: "tp".into(),
+        usage: "tp [player] [target] OR tp [player] [x] [y] [z] ([sector_x] [sector_y] [sector_z])".into(),
+        description: "Teleports the player to the target player/entity index, or to the given coordinates. If no sector is given, the player's current sector is used.".into(),
+    });
+
+    commands.add_command_info(CosmosCommandInfo {
+        name: "give".into(),
+        usage: "give [player] [item_id] [quantity?]".into(),
+        description: "Gives the player that many of the item (ex: cosmos:stone). Defaults to 1."
+            .into(),
+    });
+
+    commands.add_command_info(CosmosCommandInfo {
+        name: "players".into(),
+        usage: "players".into(),
+        description: "Lists every player on the server with their id and entity index.".into(),
+    });
+
+    commands.add_command_info(CosmosCommandInfo {
+        name: "tps".into(),
+        usage: "tps".into(),
+        description:
+            "Displays the current ticks per second, frame time & entity counts of the server."
+                .into(),
+    });
+
+    commands.add_command_info(CosmosCommandInfo {
+        name: "status".into(),
+        usage: "status".into(),
+        description: "Same as the 'tps' command.".into(),
+    });
+
+    commands.add_command_info(CosmosCommandInfo {
+        name: "say".into(),
+        usage: "say [message]".into(),
+        description: "Sends the message to every player.".into(),
+    });
+
+    commands.add_command_info(CosmosCommandInfo {
+        name: "kick".into(),
+        usage: "kick [player]".into(),
+        description: "Disconnects the player from the server.".into(),
+    });
+
+    commands.add_command_info(CosmosCommandInfo {
+        name: "ban".into(),
+        usage: "ban [player_name]".into(),
+        description: "Bans that player name from the server & kicks them if they are online."
+            .into(),
+    });
+
+    commands.add_command_info(CosmosCommandInfo {
+        name: "unban".into(),
+        usage: "unban [player_name]".into(),
+        description: "Removes that player name from the ban list.".into(),
+    });
+
+    commands.add_command_info(CosmosCommandInfo {
+        name: "banlist".into(),
+        usage: "banlist".into(),
+        description: "Lists every banned player name.".into(),
+    });
+
+    commands.add_command_info(CosmosCommandInfo {
+        name: "op".into(),
+        usage: "op [player_name]".into(),
+        description: "Makes that player an operator, allowing them to use commands from the chat. They must have joined this server before."
+            .into(),
+    });
+
+    commands.add_command_info(CosmosCommandInfo {
+        name: "deop".into(),
+        usage: "deop [player_name]".into(),
+        description: "Removes that player from the operator list.".into(),
+    });
+
+    commands.add_command_info(CosmosCommandInfo {
+        name: "whitelist".into(),
+        usage: "whitelist [on/off/add/remove/list] [player_name?]".into(),
+        description: "Turns the whitelist on or off, or adds/removes/lists whitelisted player names. When on, only whitelisted players can join.".into(),
+    });
+}
+
+#[derive(Resource, Debug, Default)]
+/// Tracks how many world ticks happened over the last second
+struct TickRate {
+    last_tick: u64,
+    tps: u64,
+}
+
+fn measure_tick_rate(world_tick: Res<WorldTick>, mut tick_rate: ResMut<TickRate>) {
+    tick_rate.tps = world_tick.get() - tick_rate.last_tick;
+    tick_rate.last_tick = world_tick.get();
 }
 
-fn display_help(command_name: Option<&str>, commands: &CosmosCommands) {
+/// Finds a player either by their name (case insensitive) or by their id
+fn find_player<'a>(
+    identifier: &str,
+    players: impl Iterator<Item = (Entity, &'a Player)>,
+) -> Option<(Entity, &'a Player)> {
+    let id = identifier.parse::<u64>().ok();
+
+    players.find(|(_, player)| {
+        player.name().eq_ignore_ascii_case(identifier) || Some(player.id()) == id
+    })
+}
+
+fn display_help(
+    command_name: Option<&str>,
+    commands: &CosmosCommands,
+    ev: &CosmosCommandSent,
+    responses: &mut EventWriter<CommandResponse>,
+) {
     if let Some(command_name) = command_name {
         if let Some(info) = commands.command_info(command_name) {
-            println!("=== {} ===", info.name);
-            println!("\t{}\n\t{}", info.usage, info.description);
+            responses.send(ev.respond(format!(
+                "=== {} ===\n\t{}\n\t{}",
+                info.name, info.usage, info.description
+            )));
 
             return;
         }
     }
 
-    println!("=== All Commands ===");
+    let mut help = "=== All Commands ===".to_owned();
     for (_, info) in commands.commands() {
-        println!("{}\n\t{}\n\t{}", info.name, info.usage, info.description);
+        help.push_str(&format!(
+            "\n{}\n\t{}\n\t{}",
+            info.name, info.usage, info.description
+        ));
     }
+
+    responses.send(ev.respond(help));
 }
 
 fn cosmos_command_listener(
@@ -76,53 +216,66 @@ fn cosmos_command_listener(
 
     mut structure_loaded_delayed: EventWriter<SendDelayedStructureLoadEvent>,
 
-    structure_query: Query<(Option<&Planet>, Option<&Ship>), With<Structure>>,
+    structure_query: Query<(Option<&Planet>, Option<&Ship>, Option<&Asteroid>), With<Structure>>,
 
     all_saveable_entities: Query<Entity, With<Structure>>,
+    all_entities: Query<Entity>,
+
+    mut server: ResMut<RenetServer>,
+    players: Query<(Entity, &Player)>,
+    mut location_query: Query<&mut Location>,
+    mut player_body_query: Query<(&mut Velocity, &Transform), With<Player>>,
+    mut inventory_query: Query<&mut Inventory, With<Player>>,
+    items: Res<Registry<Item>>,
+    tick_rate: Res<TickRate>,
+    time: Res<Time>,
+    mut responses: EventWriter<CommandResponse>,
 ) {
     for ev in command_events.iter() {
         match ev.name.as_str() {
             "help" => {
                 if ev.args.len() != 1 {
-                    display_help(None, &cosmos_commands);
+                    display_help(None, &cosmos_commands, ev, &mut responses);
                 } else {
-                    display_help(Some(&ev.args[0]), &cosmos_commands);
+                    display_help(Some(&ev.args[0]), &cosmos_commands, ev, &mut responses);
                 }
             }
             "ping" => {
-                println!("Pong");
+                responses.send(ev.respond("Pong"));
             }
             "list" => {
-                println!("All saveable entities: ");
+                let mut list = "All saveable entities: ".to_owned();
                 for entity in all_saveable_entities.iter() {
-                    print!("{} ", entity.index());
+                    list.push_str(&format!("{} ", entity.index()));
                 }
-                println!();
+                responses.send(ev.respond(list));
             }
             "despawn" => {
                 if ev.args.len() != 1 {
-                    display_help(Some("despawn"), &cosmos_commands);
+                    display_help(Some("despawn"), &cosmos_commands, ev, &mut responses);
                 } else if let Ok(index) = ev.args[0].parse::<u64>() {
                     let entity = Entity::from_bits(index);
 
                     if let Some(mut entity_commands) = commands.get_entity(entity) {
                         entity_commands.insert(NeedsDespawned);
-                        println!("Despawned entity {index}");
+                        responses.send(ev.respond(format!("Despawned entity {index}")));
                     } else {
-                        println!("Entity not found");
+                        responses.send(ev.respond("Entity not found"));
                     }
                 } else {
-                    println!("This must be the entity's ID (positive whole number)");
+                    responses
+                        .send(ev.respond("This must be the entity's ID (positive whole number)"));
                 }
             }
             "load" => {
                 if ev.args.len() < 2 {
-                    display_help(Some("load"), &cosmos_commands);
+                    display_help(Some("load"), &cosmos_commands, ev, &mut responses);
                 } else if let Some(structure_type) = match ev.args[1].to_lowercase().as_str() {
                     "ship" => Some(StructureType::Ship),
                     "planet" => Some(StructureType::Planet),
                     _ => {
-                        println!("Invalid structure type! Should be ship or planet");
+                        responses
+                            .send(ev.respond("Invalid structure type! Should be ship or planet"));
                         None
                     }
                 } {
@@ -150,14 +303,14 @@ fn cosmos_command_listener(
             }
             "save" => {
                 if ev.args.len() != 2 {
-                    display_help(Some("save"), &cosmos_commands);
+                    display_help(Some("save"), &cosmos_commands, ev, &mut responses);
                 } else if let Ok(index) = ev.args[0].parse::<u32>() {
                     if let Some(entity) = all_saveable_entities
                         .iter()
                         .find(|ent| ent.index() == index)
                     {
                         let mut entity_cmds = commands.get_entity(entity).unwrap();
-                        if let Ok((planet, ship)) = structure_query.get(entity) {
+                        if let Ok((planet, ship, _)) = structure_query.get(entity) {
                             if planet.is_some() {
                                 entity_cmds.insert(SaveStructure {
                                     structure_type: StructureType::Planet,
@@ -169,26 +322,348 @@ fn cosmos_command_listener(
                                     name: ev.args[1].clone(),
                                 });
                             } else {
-                                println!("Error: No valid structure type (planet/ship) for this structure");
+                                responses.send(ev.respond("Error: No valid structure type (planet/ship) for this structure"));
                             }
                         } else {
-                            println!("You can only save structures!");
+                            responses.send(ev.respond("You can only save structures!"));
+                        }
+                    } else {
+                        responses.send(ev.respond(format!("Invalid entity index {index}")));
+                    }
+                } else {
+                    responses.send(ev.respond(
+                        "The first argument must be the entity's index (positive number)",
+                    ));
+                }
+            }
+            "tp" => {
+                if ev.args.len() != 2 && ev.args.len() != 4 && ev.args.len() != 7 {
+                    display_help(Some("tp"), &cosmos_commands, ev, &mut responses);
+                } else if let Some((player_entity, player)) =
+                    find_player(&ev.args[0], players.iter())
+                {
+                    let destination = if ev.args.len() == 2 {
+                        if let Some((target, _)) = find_player(&ev.args[1], players.iter()) {
+                            location_query.get(target).ok().copied()
+                        } else if let Ok(index) = ev.args[1].parse::<u32>() {
+                            all_entities
+                                .iter()
+                                .find(|ent| ent.index() == index)
+                                .and_then(|ent| location_query.get(ent).ok().copied())
+                        } else {
+                            None
                         }
                     } else {
-                        println!("Invalid entity index {index}");
+                        let coords = ev.args[1..4]
+                            .iter()
+                            .map(|x| x.parse::<f32>())
+                            .collect::<Result<Vec<f32>, _>>();
+
+                        let sector = if ev.args.len() == 7 {
+                            ev.args[4..7]
+                                .iter()
+                                .map(|x| x.parse::<SectorUnit>())
+                                .collect::<Result<Vec<SectorUnit>, _>>()
+                                .ok()
+                                .map(|s| Sector::new(s[0], s[1], s[2]))
+                        } else {
+                            location_query.get(player_entity).ok().map(|l| l.sector())
+                        };
+
+                        match (coords, sector) {
+                            (Ok(coords), Some(sector)) => {
+                                let mut location = Location::new(
+                                    Vec3::new(coords[0], coords[1], coords[2]),
+                                    sector,
+                                );
+                                location.fix_bounds();
+
+                                Some(location)
+                            }
+                            _ => None,
+                        }
+                    };
+
+                    if let Some(destination) = destination {
+                        if let Ok(mut location) = location_query.get_mut(player_entity) {
+                            location.set_from(&destination);
+                        }
+
+                        if let Ok((mut velocity, transform)) =
+                            player_body_query.get_mut(player_entity)
+                        {
+                            *velocity = Velocity::zero();
+
+                            // Any movement the client sends before it receives this would undo the teleport
+                            send_body_correction(
+                                &mut server,
+                                &mut commands,
+                                player.id(),
+                                player_entity,
+                                destination,
+                                transform.rotation,
+                                time.elapsed_seconds(),
+                            );
+                        }
+
+                        responses.send(
+                            ev.respond(format!("Teleported {} to {destination}", player.name())),
+                        );
+                    } else {
+                        responses.send(ev.respond(format!(
+                            "Invalid destination - must be a player, entity index or coordinates"
+                        )));
                     }
                 } else {
-                    println!("The first argument must be the entity's index (positive number)");
+                    responses.send(ev.respond(format!("No player found for '{}'", ev.args[0])));
+                }
+            }
+            "give" => {
+                if ev.args.len() != 2 && ev.args.len() != 3 {
+                    display_help(Some("give"), &cosmos_commands, ev, &mut responses);
+                } else if let Some((player_entity, player)) =
+                    find_player(&ev.args[0], players.iter())
+                {
+                    let quantity = if ev.args.len() == 3 {
+                        ev.args[2].parse::<u16>().ok()
+                    } else {
+                        Some(1)
+                    };
+
+                    if let Some(item) = items.from_id(&ev.args[1]) {
+                        if let Some(quantity) = quantity {
+                            if let Ok(mut inventory) = inventory_query.get_mut(player_entity) {
+                                let overflow = inventory.insert(item, quantity);
+
+                                responses.send(ev.respond(format!(
+                                    "Gave {} {} to {}",
+                                    quantity - overflow,
+                                    ev.args[1],
+                                    player.name()
+                                )));
+
+                                if overflow != 0 {
+                                    responses.send(ev.respond(format!(
+                                        "{overflow} items did not fit in their inventory"
+                                    )));
+                                }
+                            }
+                        } else {
+                            responses
+                                .send(ev.respond("The quantity must be a positive whole number"));
+                        }
+                    } else {
+                        responses
+                            .send(ev.respond(format!("No item found with id '{}'", ev.args[1])));
+                    }
+                } else {
+                    responses.send(ev.respond(format!("No player found for '{}'", ev.args[0])));
+                }
+            }
+            "players" => {
+                responses.send(ev.respond(format!("Players online ({}):", players.iter().count())));
+                for (entity, player) in players.iter() {
+                    if let Ok(location) = location_query.get(entity) {
+                        responses.send(ev.respond(format!(
+                            "{} (id: {}, entity: {}) at {location}",
+                            player.name(),
+                            player.id(),
+                            entity.index()
+                        )));
+                    } else {
+                        responses.send(ev.respond(format!(
+                            "{} (id: {}, entity: {})",
+                            player.name(),
+                            player.id(),
+                            entity.index()
+                        )));
+                    }
+                }
+            }
+            "tps" | "status" => {
+                let (mut planets, mut ships, mut asteroids) = (0, 0, 0);
+
+                for (planet, ship, asteroid) in structure_query.iter() {
+                    if planet.is_some() {
+                        planets += 1;
+                    } else if ship.is_some() {
+                        ships += 1;
+                    } else if asteroid.is_some() {
+                        asteroids += 1;
+                    }
+                }
+
+                responses.send(ev.respond(format!(
+                    "TPS: {}/{}",
+                    tick_rate.tps,
+                    WorldTick::max_tps()
+                )));
+                responses.send(ev.respond(format!(
+                    "Frame time: {:.2}ms",
+                    time.delta_seconds() * 1000.0
+                )));
+                responses.send(ev.respond(format!(
+                    "Entities: {} total, {} players, {} planets, {} ships, {} asteroids",
+                    all_entities.iter().count(),
+                    players.iter().count(),
+                    planets,
+                    ships,
+                    asteroids
+                )));
+            }
+            "say" => {
+                if ev.args.is_empty() {
+                    display_help(Some("say"), &cosmos_commands, ev, &mut responses);
+                } else {
+                    let message = ev.args.join(" ");
+
+                    server.broadcast_message(
+                        NettyChannel::Chat.id(),
+                        cosmos_encoder::serialize(&ServerChatMessages::ChatMessage {
+                            sender: None,
+                            message: message.clone(),
+                        }),
+                    );
+
+                    println!("[Server] {message}");
                 }
             }
             _ => {
-                display_help(Some(&ev.text), &cosmos_commands);
+                if !cosmos_commands.command_exists(&ev.name) {
+                    display_help(Some(&ev.text), &cosmos_commands, ev, &mut responses);
+                }
             }
         }
     }
 }
 
+fn moderation_command_listener(
+    mut command_events: EventReader<CosmosCommandSent>,
+    cosmos_commands: Res<CosmosCommands>,
+    mut server: ResMut<RenetServer>,
+    players: Query<(Entity, &Player)>,
+    mut ban_list: ResMut<BanList>,
+    mut whitelist: ResMut<Whitelist>,
+    mut operators: ResMut<OperatorList>,
+    identities: Res<PlayerIdentities>,
+    mut responses: EventWriter<CommandResponse>,
+) {
+    for ev in command_events.iter() {
+        match ev.name.as_str() {
+            "kick" => {
+                if ev.args.len() != 1 {
+                    display_help(Some("kick"), &cosmos_commands, ev, &mut responses);
+                } else if let Some((_, player)) = find_player(&ev.args[0], players.iter()) {
+                    server.disconnect(player.id());
+                    responses.send(ev.respond(format!("Kicked {}", player.name())));
+                } else {
+                    responses.send(ev.respond(format!("No player found for '{}'", ev.args[0])));
+                }
+            }
+            "ban" => {
+                if ev.args.len() != 1 {
+                    display_help(Some("ban"), &cosmos_commands, ev, &mut responses);
+                } else {
+                    if ban_list.ban(&ev.args[0]) {
+                        responses.send(ev.respond(format!("Banned {}", ev.args[0])));
+                    } else {
+                        responses.send(ev.respond(format!("{} is already banned", ev.args[0])));
+                    }
+
+                    if let Some((_, player)) = players
+                        .iter()
+                        .find(|(_, player)| player.name().eq_ignore_ascii_case(&ev.args[0]))
+                    {
+                        server.disconnect(player.id());
+                    }
+                }
+            }
+            "unban" => {
+                if ev.args.len() != 1 {
+                    display_help(Some("unban"), &cosmos_commands, ev, &mut responses);
+                } else if ban_list.unban(&ev.args[0]) {
+                    responses.send(ev.respond(format!("Unbanned {}", ev.args[0])));
+                } else {
+                    responses.send(ev.respond(format!("{} is not banned", ev.args[0])));
+                }
+            }
+            "op" => {
+                if ev.args.len() != 1 {
+                    display_help(Some("op"), &cosmos_commands, ev, &mut responses);
+                } else if !identities.is_registered(&ev.args[0]) {
+                    // Otherwise, whoever joins with this name first would get to be an operator
+                    responses.send(ev.respond(format!(
+                        "{} has never joined this server, so they can't be made an operator yet",
+                        ev.args[0]
+                    )));
+                } else if operators.add(&ev.args[0]) {
+                    responses.send(ev.respond(format!("{} is now an operator", ev.args[0])));
+                } else {
+                    responses.send(ev.respond(format!("{} is already an operator", ev.args[0])));
+                }
+            }
+            "deop" => {
+                if ev.args.len() != 1 {
+                    display_help(Some("deop"), &cosmos_commands, ev, &mut responses);
+                } else if operators.remove(&ev.args[0]) {
+                    responses.send(ev.respond(format!("{} is no longer an operator", ev.args[0])));
+                } else {
+                    responses.send(ev.respond(format!("{} is not an operator", ev.args[0])));
+                }
+            }
+            "banlist" => {
+                let mut list = "Banned players: ".to_owned();
+                for name in ban_list.iter() {
+                    list.push_str(&format!("{name} "));
+                }
+                responses.send(ev.respond(list));
+            }
+            "whitelist" => match (ev.args.first().map(|x| x.as_str()), ev.args.get(1)) {
+                (Some("on"), None) => {
+                    whitelist.set_enabled(true);
+                    responses.send(ev.respond("Whitelist enabled"));
+                }
+                (Some("off"), None) => {
+                    whitelist.set_enabled(false);
+                    responses.send(ev.respond("Whitelist disabled"));
+                }
+                (Some("add"), Some(name)) => {
+                    if whitelist.add(name) {
+                        responses.send(ev.respond(format!("Added {name} to the whitelist")));
+                    } else {
+                        responses.send(ev.respond(format!("{name} is already whitelisted")));
+                    }
+                }
+                (Some("remove"), Some(name)) => {
+                    if whitelist.remove(name) {
+                        responses.send(ev.respond(format!("Removed {name} from the whitelist")));
+                    } else {
+                        responses.send(ev.respond(format!("{name} is not whitelisted")));
+                    }
+                }
+                (Some("list"), None) => {
+                    let mut list = format!(
+                        "Whitelist ({}): ",
+                        if whitelist.enabled() { "on" } else { "off" }
+                    );
+                    for name in whitelist.iter() {
+                        list.push_str(&format!("{name} "));
+                    }
+                    responses.send(ev.respond(list));
+                }
+                _ => display_help(Some("whitelist"), &cosmos_commands, ev, &mut responses),
+            },
+            _ => {}
+        }
+    }
+}
+
 pub(super) fn register(app: &mut App) {
-    app.add_startup_system(register_commands)
-        .add_system(cosmos_command_listener);
+    app.insert_resource(TickRate::default())
+        .add_startup_system(register_commands)
+        .add_systems((
+            cosmos_command_listener,
+            moderation_command_listener,
+            measure_tick_rate.run_if(on_timer(Duration::from_secs(1))),
+        ));
 }
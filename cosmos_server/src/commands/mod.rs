@@ -3,15 +3,40 @@
 use std::time::Duration;
 
 use bevy::{
-    prelude::{App, EventWriter, ResMut, Resource},
+    prelude::{App, Entity, EventReader, EventWriter, ResMut, Resource},
     reflect::{FromReflect, Reflect},
     utils::HashMap,
 };
+use bevy_renet::renet::RenetServer;
+use cosmos_core::netty::{chat_messages::ServerChatMessages, cosmos_encoder, NettyChannel};
 use crossterm::event::{poll, read, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 pub mod cosmos_command_handler;
+pub mod rcon;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Represents whoever sent a command, so the response can be sent back to them
+pub enum CommandSender {
+    /// The server admin typed this into the server's console
+    Console,
+    /// A player typed this into their chat
+    Player {
+        /// The player's client id
+        client_id: u64,
+        /// The player's entity
+        entity: Entity,
+    },
+    /// This was sent over the remote administration listener
+    Rcon {
+        /// The id of the connection that sent this
+        connection_id: u64,
+        /// The id of this command on that connection, so its output can be told apart from other commands
+        request_id: u64,
+    },
+}
 
 #[derive(Debug)]
-/// This event is sent when the server admin types a console command
+/// This event is sent when the server admin types a console command, an operator sends a command via the chat,
+/// or a command is received over the remote administration listener
 pub struct CosmosCommandSent {
     /// The raw string the user typed
     pub text: String,
@@ -19,13 +44,25 @@ pub struct CosmosCommandSent {
     pub name: String,
     /// The args split around spaces
     pub args: Vec<String>,
+    /// Who sent this command
+    pub sender: CommandSender,
+}
+
+#[derive(Debug)]
+/// Send this event to display the output of a command to whoever sent it
+pub struct CommandResponse {
+    /// Who should receive this
+    pub to: CommandSender,
+    /// The text to display
+    pub message: String,
 }
 
 impl CosmosCommandSent {
     /// Creates a new command event.
     ///
     /// * `text` The entire string of text the user typed
-    pub fn new(text: String) -> Self {
+    /// * `sender` Who sent this command
+    pub fn new(text: String, sender: CommandSender) -> Self {
         let split: Vec<&str> = text.split(' ').collect();
         let (name_arr, args_arr) = split.split_at(1);
 
@@ -36,7 +73,20 @@ impl CosmosCommandSent {
             .map(|x| (*x).to_owned())
             .collect::<Vec<String>>();
 
-        Self { text, name, args }
+        Self {
+            text,
+            name,
+            args,
+            sender,
+        }
+    }
+
+    /// Creates a response that will be sent back to whoever sent this command
+    pub fn respond(&self, message: impl Into<String>) -> CommandResponse {
+        CommandResponse {
+            to: self.sender,
+            message: message.into(),
+        }
     }
 }
 
@@ -131,17 +181,40 @@ fn monitor_inputs(
     if !text.0.trim().is_empty() && text.0.ends_with('\n') {
         event_writer.send(CosmosCommandSent::new(
             text.0[0..text.0.len() - 1].to_owned(),
+            CommandSender::Console,
         ));
 
         text.0.clear();
     }
 }
 
+fn send_responses(mut responses: EventReader<CommandResponse>, mut server: ResMut<RenetServer>) {
+    for response in responses.iter() {
+        match response.to {
+            CommandSender::Console => println!("{}", response.message),
+            CommandSender::Player { client_id, .. } => {
+                server.send_message(
+                    client_id,
+                    NettyChannel::Chat.id(),
+                    cosmos_encoder::serialize(&ServerChatMessages::ChatMessage {
+                        sender: None,
+                        message: response.message.clone(),
+                    }),
+                );
+            }
+            // Handled by the rcon module
+            CommandSender::Rcon { .. } => {}
+        }
+    }
+}
+
 pub(super) fn register(app: &mut App) {
     app.insert_resource(CosmosCommands::default())
         .insert_resource(CurrentlyWriting::default())
-        .add_system(monitor_inputs)
-        .add_event::<CosmosCommandSent>();
+        .add_systems((monitor_inputs, send_responses))
+        .add_event::<CosmosCommandSent>()
+        .add_event::<CommandResponse>();
 
     cosmos_command_handler::register(app);
+    rcon::register(app);
 }
@@ -0,0 +1,330 @@
+//! A remote administration listener that lets server commands be sent over TCP.
+//!
+//! This is disabled unless the `COSMOS_RCON_PASSWORD` environment variable is set. By default it
+//! only listens on localhost, but this can be changed via the `COSMOS_RCON_ADDRESS` environment variable.
+//!
+//! The protocol is line based:
+//! - The first line a connection sends must be the password. The server replies with `OK` or closes the connection.
+//!   Connections that don't send it within a few seconds are closed.
+//! - Every line after that is treated as a command, exactly as if it were typed into the server's console.
+//! - The output of each command is sent back line by line, followed by an empty line to mark the end of that command's output.
+//!   Commands are answered in the order they were sent.
+//!
+//! An address that uses the wrong password too many times is locked out for a while.
+
+use std::{
+    env,
+    io::{ErrorKind, Read, Write},
+    net::{IpAddr, TcpListener, TcpStream},
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    prelude::{App, CoreSet, EventReader, EventWriter, IntoSystemConfig, ResMut, Resource},
+    utils::HashMap,
+};
+
+use super::{CommandResponse, CommandSender, CosmosCommandSent};
+
+/// The address the listener will use if none is specified
+const DEFAULT_RCON_ADDRESS: &str = "127.0.0.1:1338";
+/// Lines longer than this will cause the connection to be closed
+const MAX_LINE_LENGTH: usize = 4096;
+/// If more output than this is waiting to be sent, the other end isn't reading it & the connection will be closed
+const MAX_PENDING_OUTPUT: usize = 1024 * 1024;
+/// How many times an address can use the wrong password before it is locked out
+const MAX_FAILED_ATTEMPTS: u32 = 5;
+/// How long an address is locked out for once it has used the wrong password too many times
+const LOCKOUT_DURATION: Duration = Duration::from_secs(60);
+/// How long a connection has to send the password before it is closed
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A command sent over a connection that hasn't had its output sent back yet
+struct RconRequest {
+    id: u64,
+    output: Vec<String>,
+}
+
+struct RconConnection {
+    id: u64,
+    address: IpAddr,
+    stream: TcpStream,
+    authenticated: bool,
+    buffer: Vec<u8>,
+    /// Output that couldn't be sent yet because the stream would have blocked
+    outgoing: Vec<u8>,
+    /// Commands waiting for their output, in the order they were sent
+    requests: Vec<RconRequest>,
+    next_request_id: u64,
+    connected_at: Instant,
+    closed: bool,
+}
+
+impl RconConnection {
+    /// Queues this line to be sent, see [`RconConnection::flush`]
+    fn write_line(&mut self, line: &str) {
+        self.outgoing.extend_from_slice(line.as_bytes());
+        self.outgoing.push(b'\n');
+
+        if self.outgoing.len() > MAX_PENDING_OUTPUT {
+            self.closed = true;
+        }
+    }
+
+    /// Sends as much of the queued output as the stream will take without blocking.
+    ///
+    /// Whatever is left over will be sent the next time this is called.
+    fn flush(&mut self) {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => {
+                    self.closed = true;
+                    return;
+                }
+                Ok(n) => {
+                    self.outgoing.drain(0..n);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => {
+                    self.closed = true;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+struct FailedAttempts {
+    count: u32,
+    last: Instant,
+}
+
+/// Compares the two passwords in a time that doesn't depend on how much of them matches,
+/// so the password can't be guessed one character at a time.
+fn passwords_match(given: &str, password: &str) -> bool {
+    let given = given.as_bytes();
+    let password = password.as_bytes();
+
+    let mut difference = given.len() ^ password.len();
+
+    for (i, byte) in password.iter().enumerate() {
+        difference |= (given.get(i).copied().unwrap_or(0) ^ byte) as usize;
+    }
+
+    difference == 0
+}
+
+#[derive(Resource)]
+/// Contains the remote administration listener & every connection to it
+struct RconServer {
+    listener: TcpListener,
+    password: String,
+    connections: Vec<RconConnection>,
+    next_id: u64,
+    failed_attempts: HashMap<IpAddr, FailedAttempts>,
+}
+
+fn accept_connections(mut rcon: ResMut<RconServer>) {
+    while let Ok((stream, address)) = rcon.listener.accept() {
+        if let Some(failed) = rcon.failed_attempts.get(&address.ip()) {
+            if failed.last.elapsed() >= LOCKOUT_DURATION {
+                rcon.failed_attempts.remove(&address.ip());
+            } else if failed.count >= MAX_FAILED_ATTEMPTS {
+                println!("Refused remote administration connection from {address} - too many failed attempts");
+                continue;
+            }
+        }
+
+        if stream.set_nonblocking(true).is_err() {
+            continue;
+        }
+
+        println!("Remote administration connection from {address}");
+
+        let id = rcon.next_id;
+        rcon.next_id += 1;
+
+        rcon.connections.push(RconConnection {
+            id,
+            address: address.ip(),
+            stream,
+            authenticated: false,
+            buffer: Vec::new(),
+            outgoing: Vec::new(),
+            requests: Vec::new(),
+            next_request_id: 0,
+            connected_at: Instant::now(),
+            closed: false,
+        });
+    }
+}
+
+fn read_commands(mut rcon: ResMut<RconServer>, mut command_writer: EventWriter<CosmosCommandSent>) {
+    let rcon = &mut *rcon;
+
+    for connection in rcon.connections.iter_mut() {
+        if !connection.authenticated && connection.connected_at.elapsed() >= LOGIN_TIMEOUT {
+            println!(
+                "Remote administration connection from {} didn't log in in time",
+                connection.address
+            );
+
+            connection.closed = true;
+            continue;
+        }
+
+        let mut buf = [0; 1024];
+
+        loop {
+            match connection.stream.read(&mut buf) {
+                Ok(0) => {
+                    connection.closed = true;
+                    break;
+                }
+                Ok(n) => connection.buffer.extend_from_slice(&buf[0..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => {
+                    connection.closed = true;
+                    break;
+                }
+            }
+        }
+
+        while let Some(newline) = connection.buffer.iter().position(|x| *x == b'\n') {
+            let line = connection.buffer.drain(0..=newline).collect::<Vec<u8>>();
+            let line = String::from_utf8_lossy(&line).trim().to_owned();
+
+            if !connection.authenticated {
+                if passwords_match(&line, &rcon.password) {
+                    rcon.failed_attempts.remove(&connection.address);
+
+                    connection.authenticated = true;
+                    connection.write_line("OK");
+                } else {
+                    println!("Remote administration connection used an invalid password");
+
+                    let failed =
+                        rcon.failed_attempts
+                            .entry(connection.address)
+                            .or_insert(FailedAttempts {
+                                count: 0,
+                                last: Instant::now(),
+                            });
+                    failed.count += 1;
+                    failed.last = Instant::now();
+
+                    connection.closed = true;
+                    break;
+                }
+            } else if !line.is_empty() {
+                println!("Remote administration command: {line}");
+
+                let request_id = connection.next_request_id;
+                connection.next_request_id += 1;
+
+                connection.requests.push(RconRequest {
+                    id: request_id,
+                    output: Vec::new(),
+                });
+
+                command_writer.send(CosmosCommandSent::new(
+                    line,
+                    CommandSender::Rcon {
+                        connection_id: connection.id,
+                        request_id,
+                    },
+                ));
+            }
+        }
+
+        if connection.buffer.len() > MAX_LINE_LENGTH {
+            connection.closed = true;
+        }
+    }
+
+    rcon.connections.retain(|x| !x.closed);
+}
+
+fn send_responses(mut rcon: ResMut<RconServer>, mut responses: EventReader<CommandResponse>) {
+    for response in responses.iter() {
+        let CommandSender::Rcon {
+            connection_id,
+            request_id,
+        } = response.to
+        else {
+            continue;
+        };
+
+        let request = rcon
+            .connections
+            .iter_mut()
+            .find(|x| x.id == connection_id)
+            .and_then(|connection| connection.requests.iter_mut().find(|x| x.id == request_id));
+
+        if let Some(request) = request {
+            request.output.extend(
+                response
+                    .message
+                    .lines()
+                    .filter(|x| !x.trim().is_empty())
+                    .map(|x| x.to_owned()),
+            );
+        }
+    }
+
+    for connection in rcon.connections.iter_mut() {
+        // Commands are read before the command listeners run, so every command waiting here has been fully handled
+        for request in std::mem::take(&mut connection.requests) {
+            for line in request.output.iter() {
+                connection.write_line(line);
+            }
+
+            connection.write_line("");
+        }
+
+        // Large outputs won't fit in the socket's buffer, so they are sent over multiple frames
+        connection.flush();
+    }
+
+    rcon.connections.retain(|x| !x.closed);
+}
+
+pub(super) fn register(app: &mut App) {
+    let Ok(password) = env::var("COSMOS_RCON_PASSWORD") else {
+        return;
+    };
+
+    if password.is_empty() {
+        println!("COSMOS_RCON_PASSWORD is empty - remote administration will not be enabled.");
+        return;
+    }
+
+    let address = env::var("COSMOS_RCON_ADDRESS").unwrap_or(DEFAULT_RCON_ADDRESS.into());
+
+    let listener = TcpListener::bind(&address).unwrap_or_else(|e| {
+        panic!("Unable to bind remote administration listener to {address} - {e}")
+    });
+    listener
+        .set_nonblocking(true)
+        .expect("Cannot set non-blocking mode!");
+
+    println!("Remote administration listening on {address}");
+
+    app.insert_resource(RconServer {
+        listener,
+        password,
+        connections: Vec::new(),
+        next_id: 0,
+        failed_attempts: HashMap::default(),
+    })
+    // Commands are sent before the command listeners run & responses are sent after,
+    // so every response to a command is sent before that command's end of output marker.
+    .add_system(accept_connections.in_base_set(CoreSet::PreUpdate))
+    .add_system(
+        read_commands
+            .in_base_set(CoreSet::PreUpdate)
+            .after(accept_connections),
+    )
+    .add_system(send_responses.in_base_set(CoreSet::PostUpdate));
+}
@@ -1,6 +1,19 @@
 //! Server-related components for the player
 
-use bevy::prelude::{Component, Quat};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::Velocity;
+use bevy_renet::renet::RenetServer;
+use cosmos_core::{
+    entities::player::{render_distance::RenderDistance, Player},
+    inventory::Inventory,
+    netty::{
+        cosmos_encoder, netty_rigidbody::NettyRigidBody,
+        server_reliable_messages::ServerReliableMessages, NettyChannel,
+    },
+    physics::location::Location,
+};
+
+use crate::netty::sync::entities::RequestedEntityEvent;
 
 #[derive(Component)]
 /// The server doesn't have a camera, so this is used to track where the player is looking
@@ -8,3 +21,42 @@ pub struct PlayerLooking {
     /// What the player's camera rotation would be
     pub rotation: Quat,
 }
+
+fn on_request_player(
+    mut event_reader: EventReader<RequestedEntityEvent>,
+    query: Query<(
+        &Player,
+        Option<&Transform>,
+        &Location,
+        &Velocity,
+        &Inventory,
+        Option<&RenderDistance>,
+    )>,
+    mut server: ResMut<RenetServer>,
+) {
+    for ev in event_reader.iter() {
+        if let Ok((player, transform, location, velocity, inventory, render_distance)) =
+            query.get(ev.entity)
+        {
+            // A player that just joined may not have their transform yet
+            let rotation = transform.map(|x| x.rotation).unwrap_or(Quat::IDENTITY);
+
+            server.send_message(
+                ev.client_id,
+                NettyChannel::Reliable.id(),
+                cosmos_encoder::serialize(&ServerReliableMessages::PlayerCreate {
+                    entity: ev.entity,
+                    id: player.id(),
+                    name: player.name().clone(),
+                    body: NettyRigidBody::new(velocity, rotation, *location),
+                    inventory_serialized: cosmos_encoder::serialize(inventory),
+                    render_distance: render_distance.copied(),
+                }),
+            );
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_system(on_request_player);
+}
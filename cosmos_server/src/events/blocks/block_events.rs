@@ -14,7 +14,7 @@ use cosmos_core::{
     structure::{structure_block::StructureBlock, Structure},
 };
 
-use crate::GameState;
+use crate::{netty::sync::interest::ClientInterest, GameState};
 
 /// This is sent whenever a player breaks a block
 pub struct BlockBreakEvent {
@@ -149,9 +149,12 @@ fn handle_block_place_events(
 fn handle_block_changed_event(
     mut event_reader: EventReader<BlockChangedEvent>,
     mut server: ResMut<RenetServer>,
+    interest: Res<ClientInterest>,
 ) {
     for ev in event_reader.iter() {
-        server.broadcast_message(
+        interest.send_to_interested(
+            &mut server,
+            ev.structure_entity,
             NettyChannel::Reliable.id(),
             cosmos_encoder::serialize(&ServerReliableMessages::BlockChange {
                 structure_entity: ev.structure_entity,
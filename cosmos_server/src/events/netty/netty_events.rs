@@ -4,22 +4,20 @@ use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 use bevy_renet::renet::{RenetServer, ServerEvent};
 use cosmos_core::ecs::NeedsDespawned;
-use cosmos_core::entities::player::render_distance::RenderDistance;
 use cosmos_core::inventory::Inventory;
 use cosmos_core::item::Item;
+use cosmos_core::netty::connection_info::ConnectionInfo;
 use cosmos_core::netty::cosmos_encoder;
 use cosmos_core::netty::server_reliable_messages::ServerReliableMessages;
 use cosmos_core::physics::location::{Location, Sector};
 use cosmos_core::physics::player_world::WorldWithin;
 use cosmos_core::registry::Registry;
 use cosmos_core::structure::chunk::CHUNK_DIMENSIONSF;
-use cosmos_core::{
-    entities::player::Player,
-    netty::{netty_rigidbody::NettyRigidBody, NettyChannel},
-};
+use cosmos_core::{entities::player::Player, netty::NettyChannel};
 use renet_visualizer::RenetServerVisualizer;
 
 use crate::entities::player::PlayerLooking;
+use crate::moderation::{BanList, IdentityCheck, OperatorList, PlayerIdentities, Whitelist};
 use crate::netty::network_helpers::{ClientTicks, ServerLobby};
 
 fn generate_player_inventory(items: &Registry<Item>) -> Inventory {
@@ -101,58 +99,66 @@ fn handle_events_system(
     mut server_events: EventReader<ServerEvent>,
     mut lobby: ResMut<ServerLobby>,
     mut client_ticks: ResMut<ClientTicks>,
-    players: Query<(
-        Entity,
-        &Player,
-        &Transform,
-        &Location,
-        &Velocity,
-        &Inventory,
-        &RenderDistance,
-    )>,
     player_worlds: Query<(&Location, &WorldWithin, &PhysicsWorld), (With<Player>, Without<Parent>)>,
     items: Res<Registry<Item>>,
     mut visualizer: ResMut<RenetServerVisualizer<200>>,
     mut rapier_context: ResMut<RapierContext>,
+    ban_list: Res<BanList>,
+    whitelist: Res<Whitelist>,
+    mut identities: ResMut<PlayerIdentities>,
+    mut operators: ResMut<OperatorList>,
 ) {
     for event in server_events.iter() {
         match event {
             ServerEvent::ClientConnected(id, user_data) => {
-                println!("Client {id} connected");
-                visualizer.add_client(*id);
+                let Ok(ConnectionInfo { name, key }) =
+                    bincode::deserialize::<ConnectionInfo>(user_data.as_slice())
+                else {
+                    println!("Unable to deserialize connection info!");
+                    server.disconnect(*id);
+                    continue;
+                };
 
-                for (entity, player, transform, location, velocity, inventory, render_distance) in
-                    players.iter()
-                {
-                    let body = NettyRigidBody::new(velocity, transform.rotation, *location);
-
-                    let msg = cosmos_encoder::serialize(&ServerReliableMessages::PlayerCreate {
-                        entity,
-                        id: player.id(),
-                        body,
-                        name: player.name().clone(),
-                        inventory_serialized: cosmos_encoder::serialize(inventory),
-                        render_distance: Some(*render_distance),
-                    });
-
-                    server.send_message(*id, NettyChannel::Reliable.id(), msg);
+                let identity = identities.check(&name, &key);
+
+                if identity == IdentityCheck::WrongKey {
+                    println!("Client {id} tried to connect as {name}, but doesn't own that name");
+                    server.disconnect(*id);
+                    continue;
                 }
 
-                let Ok(name) = bincode::deserialize::<String>(user_data.as_slice()) else {
-                    println!("Unable to deserialize name!");
+                // The name can't belong to anyone else now, so bans & the whitelist can be checked against it
+                if ban_list.is_banned(&name) {
+                    println!("Client {id} ({name}) tried to connect, but is banned");
+                    server.disconnect(*id);
                     continue;
-                };
+                }
+
+                if !whitelist.is_allowed(&name) {
+                    println!("Client {id} ({name}) tried to connect, but is not whitelisted");
+                    server.disconnect(*id);
+                    continue;
+                }
+
+                if identity == IdentityCheck::Unregistered {
+                    identities.register(&name, key);
+                    println!("{name} joined for the first time, and now owns that name");
 
-                let player = Player::new(name.clone(), *id);
+                    // Operators added before their name had an owner can't be trusted, since anyone could have claimed it
+                    if operators.remove(&name) {
+                        println!("{name} was made an operator before joining, so they were removed as one. Use `op {name}` if this is the right player.");
+                    }
+                }
+
+                println!("Client {id} connected");
+                visualizer.add_client(*id);
+
+                let player = Player::new(name, *id);
                 let starting_pos = Vec3::new(0.0, CHUNK_DIMENSIONSF * 50.0 / 2.0, 0.0);
                 let location = Location::new(starting_pos, Sector::new(0, 0, 0));
                 let velocity = Velocity::default();
                 let inventory = generate_player_inventory(&items);
 
-                let netty_body = NettyRigidBody::new(&velocity, Quat::IDENTITY, location);
-
-                let inventory_serialized = cosmos_encoder::serialize(&inventory);
-
                 let player_commands = commands.spawn((
                     location,
                     LockedAxes::ROTATION_LOCKED,
@@ -171,6 +177,8 @@ fn handle_events_system(
 
                 lobby.add_player(*id, entity);
 
+                // This player is sent to each client (including this one) once it becomes relevant to them
+
                 assign_player_world(
                     &player_worlds,
                     entity,
@@ -179,15 +187,6 @@ fn handle_events_system(
                     &mut rapier_context,
                 );
 
-                let msg = cosmos_encoder::serialize(&ServerReliableMessages::PlayerCreate {
-                    entity,
-                    id: *id,
-                    name,
-                    body: netty_body,
-                    inventory_serialized,
-                    render_distance: None,
-                });
-
                 server.send_message(
                     *id,
                     NettyChannel::Reliable.id(),
@@ -195,8 +194,6 @@ fn handle_events_system(
                         motd: "Welcome to the server!".into(),
                     }),
                 );
-
-                server.broadcast_message(NettyChannel::Reliable.id(), msg);
             }
             ServerEvent::ClientDisconnected(id) => {
                 println!("Client {id} disconnected");
@@ -206,11 +203,6 @@ fn handle_events_system(
                 if let Some(player_entity) = lobby.remove_player(*id) {
                     commands.entity(player_entity).insert(NeedsDespawned);
                 }
-
-                let message =
-                    cosmos_encoder::serialize(&ServerReliableMessages::PlayerRemove { id: *id });
-
-                server.broadcast_message(NettyChannel::Reliable.id(), message);
             }
         }
     }
@@ -12,7 +12,7 @@ use cosmos_core::{
     },
 };
 
-use crate::state::GameState;
+use crate::{netty::sync::interest::ClientInterest, state::GameState};
 
 fn on_melting_down(
     mut commands: Commands,
@@ -23,6 +23,7 @@ fn on_melting_down(
     pilot_query: Query<&Pilot>,
     mut change_pilot_event: EventWriter<ChangePilotEvent>,
     mut server: ResMut<RenetServer>,
+    interest: Res<ClientInterest>,
 ) {
     for (entity, mut structure, mut melting_down) in query.iter_mut() {
         if pilot_query.contains(entity) {
@@ -46,7 +47,9 @@ fn on_melting_down(
             } else {
                 commands.entity(entity).insert(NeedsDespawned);
 
-                server.broadcast_message(
+                interest.send_to_interested(
+                    &mut server,
+                    entity,
                     NettyChannel::Reliable.id(),
                     cosmos_encoder::serialize(&ServerReliableMessages::StructureRemove { entity }),
                 );
@@ -1,6 +1,6 @@
 //! Events for the ship
 
-use bevy::prelude::{App, Entity, EventReader, IntoSystemConfig, OnUpdate, Query, ResMut};
+use bevy::prelude::{App, Entity, EventReader, IntoSystemConfig, OnUpdate, Query, Res, ResMut};
 use bevy_renet::renet::RenetServer;
 use cosmos_core::{
     events::structure::change_pilot_event::ChangePilotEvent,
@@ -11,7 +11,7 @@ use cosmos_core::{
     structure::ship::ship_movement::ShipMovement,
 };
 
-use crate::state::GameState;
+use crate::{netty::sync::interest::ClientInterest, state::GameState};
 
 mod core;
 
@@ -28,12 +28,15 @@ fn monitor_set_movement_events(
     mut query: Query<&mut ShipMovement>,
     mut event_reader: EventReader<ShipSetMovementEvent>,
     mut server: ResMut<RenetServer>,
+    interest: Res<ClientInterest>,
 ) {
     for ev in event_reader.iter() {
         if let Ok(mut current_movement) = query.get_mut(ev.ship) {
             current_movement.set(&ev.movement);
 
-            server.broadcast_message(
+            interest.send_to_interested(
+                &mut server,
+                ev.ship,
                 NettyChannel::Unreliable.id(),
                 cosmos_encoder::serialize(&ServerUnreliableMessages::SetMovement {
                     movement: ev.movement.clone(),
@@ -47,9 +50,12 @@ fn monitor_set_movement_events(
 fn monitor_pilot_changes(
     mut event_reader: EventReader<ChangePilotEvent>,
     mut server: ResMut<RenetServer>,
+    interest: Res<ClientInterest>,
 ) {
     for ev in event_reader.iter() {
-        server.broadcast_message(
+        interest.send_to_interested(
+            &mut server,
+            ev.structure_entity,
             NettyChannel::Reliable.id(),
             cosmos_encoder::serialize(&ServerReliableMessages::PilotChange {
                 structure_entity: ev.structure_entity,
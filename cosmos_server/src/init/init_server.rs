@@ -9,7 +9,9 @@ use std::{
 
 use bevy::prelude::*;
 use bevy_renet::renet::{RenetServer, ServerAuthentication, ServerConfig};
-use cosmos_core::netty::{get_local_ipaddress, server_connection_config, PROTOCOL_ID};
+use cosmos_core::netty::{
+    get_local_ipaddress, server_connection_config, world_tick::WorldTick, PROTOCOL_ID,
+};
 
 use crate::netty::network_helpers::{ClientTicks, NetworkTick, ServerLobby};
 
@@ -35,6 +37,7 @@ pub fn init(app: &mut App, address: Option<String>) {
 
     app.insert_resource(ServerLobby::default())
         .insert_resource(NetworkTick(0))
+        .insert_resource(WorldTick::default())
         .insert_resource(ClientTicks::default())
         .insert_resource(server);
 
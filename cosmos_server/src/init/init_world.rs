@@ -11,6 +11,11 @@ use serde::{Deserialize, Serialize};
 pub struct ServerSeed(u64);
 
 impl ServerSeed {
+    /// Creates a server seed from this number
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
     /// Gets the u64 representation of this seed
     pub fn as_u64(&self) -> u64 {
         self.0
@@ -27,7 +32,7 @@ pub(super) fn register(app: &mut App) {
         cosmos_encoder::deserialize::<ServerSeed>(&seed)
             .expect("Unable to understand './world/seed.dat' seed file. Is it corrupted?")
     } else {
-        let seed = ServerSeed(rand::random());
+        let seed = ServerSeed::new(rand::random());
 
         fs::create_dir("./world/").expect("Error creating world directory!");
         fs::write("./world/seed.dat", cosmos_encoder::serialize(&seed))
@@ -1,23 +1,32 @@
 //! Syncs player inventories
 
-use bevy::prelude::{App, Changed, Entity, IntoSystemConfig, OnUpdate, Query, ResMut};
+use bevy::prelude::{App, Changed, Entity, IntoSystemConfig, OnUpdate, Query, Res, ResMut};
 use bevy_renet::renet::RenetServer;
 use cosmos_core::{
+    entities::player::Player,
     inventory::Inventory,
     netty::{cosmos_encoder, server_reliable_messages::ServerReliableMessages, NettyChannel},
 };
 
-use crate::state::GameState;
+use crate::{netty::sync::interest::ClientInterest, state::GameState};
 
-fn sync(query: Query<(Entity, &Inventory), Changed<Inventory>>, mut server: ResMut<RenetServer>) {
-    for (entity, inventory) in query.iter() {
-        server.broadcast_message(
-            NettyChannel::Reliable.id(),
-            cosmos_encoder::serialize(&ServerReliableMessages::EntityInventory {
-                serialized_inventory: cosmos_encoder::serialize(&inventory),
-                owner: entity,
-            }),
-        );
+fn sync(
+    query: Query<(Entity, &Inventory, Option<&Player>), Changed<Inventory>>,
+    mut server: ResMut<RenetServer>,
+    interest: Res<ClientInterest>,
+) {
+    for (entity, inventory, player) in query.iter() {
+        let message = cosmos_encoder::serialize(&ServerReliableMessages::EntityInventory {
+            serialized_inventory: cosmos_encoder::serialize(&inventory),
+            owner: entity,
+        });
+
+        // Only the player who owns an inventory needs to know what's in it
+        if let Some(player) = player {
+            server.send_message(player.id(), NettyChannel::Reliable.id(), message);
+        } else {
+            interest.send_to_interested(&mut server, entity, NettyChannel::Reliable.id(), message);
+        }
     }
 }
 
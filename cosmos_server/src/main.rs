@@ -14,11 +14,13 @@ use plugin::server_plugin::ServerPlugin;
 use state::GameState;
 
 pub mod blocks;
+pub mod chat;
 pub mod commands;
 pub mod entities;
 pub mod events;
 pub mod init;
 pub mod inventory;
+pub mod moderation;
 pub mod netty;
 pub mod persistence;
 pub mod physics;
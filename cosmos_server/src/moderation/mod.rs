@@ -0,0 +1,267 @@
+//! Keeps track of who is & isn't allowed to join the server, and who is allowed to use commands
+//!
+//! All these lists are saved in the world folder whenever they are changed.
+//!
+//! # Player identities
+//!
+//! There are no accounts, so the only thing that identifies a player is the key their client
+//! generated & sends when connecting (see [`ConnectionInfo`]). The first key a name is used with is
+//! saved in [`PlayerIdentities`], and anyone connecting with that name afterwards must send the same key.
+//! The [`BanList`], [`Whitelist`] & [`OperatorList`] are only ever checked against names verified this way.
+//!
+//! This stops other players from joining as someone else, but it has limits:
+//! - Connections aren't encrypted, so anyone who can see a player's traffic can steal their key.
+//! - Whoever joins with a name first owns it, which is why only players who have joined before can be made operators.
+//! - The keys are saved as-is in the world folder, so it must be kept private.
+//!
+//! [`ConnectionInfo`]: cosmos_core::netty::connection_info::ConnectionInfo
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+};
+
+use bevy::prelude::{App, Resource};
+use cosmos_core::netty::{connection_info::PLAYER_KEY_LENGTH, cosmos_encoder};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+const BAN_LIST_PATH: &str = "./world/bans.dat";
+const WHITELIST_PATH: &str = "./world/whitelist.dat";
+const OPERATORS_PATH: &str = "./world/operators.dat";
+const IDENTITIES_PATH: &str = "./world/identities.dat";
+
+/// Reads a list from the world folder, starting with an empty one if it doesn't exist or can't be read.
+///
+/// Unreadable files are moved to `{path}.corrupt` first, so they aren't lost when the new list is saved.
+fn read_list<T: Default + DeserializeOwned>(path: &str) -> T {
+    let Ok(data) = fs::read(path) else {
+        return T::default();
+    };
+
+    match cosmos_encoder::deserialize::<T>(&data) {
+        Ok(list) => list,
+        Err(e) => {
+            let backup_path = format!("{path}.corrupt");
+
+            println!("Unable to understand '{path}' ({e}). Is it corrupted? Starting with an empty list, and moving the old one to '{backup_path}'.");
+
+            if let Err(e) = fs::rename(path, &backup_path) {
+                println!("Error moving '{path}' to '{backup_path}' - {e}");
+            }
+
+            T::default()
+        }
+    }
+}
+
+fn write_list<T: Serialize>(path: &str, list: &T) {
+    fs::create_dir_all("./world/").expect("Error creating world directory!");
+    fs::write(path, cosmos_encoder::serialize(list))
+        .unwrap_or_else(|e| panic!("Error writing file '{path}' - {e}"));
+}
+
+/// The result of checking a player's key against their name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentityCheck {
+    /// This name belongs to this key
+    Verified,
+    /// Nobody has joined with this name yet, so it can be given to this key with [`PlayerIdentities::register`]
+    Unregistered,
+    /// This name belongs to a different key, so this isn't the player that owns it
+    WrongKey,
+}
+
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+/// Ties every name that has joined this server to the key of the client that first used it.
+///
+/// See the [module docs](self) for what this does & doesn't protect against.
+pub struct PlayerIdentities {
+    keys: HashMap<String, [u8; PLAYER_KEY_LENGTH]>,
+}
+
+impl PlayerIdentities {
+    /// Checks if this key owns this name
+    pub fn check(&self, name: &str, key: &[u8; PLAYER_KEY_LENGTH]) -> IdentityCheck {
+        match self.keys.get(&name.to_lowercase()) {
+            Some(owner) if owner == key => IdentityCheck::Verified,
+            Some(_) => IdentityCheck::WrongKey,
+            None => IdentityCheck::Unregistered,
+        }
+    }
+
+    /// Gives this name to this key & saves the identities, if nobody owns the name yet.
+    ///
+    /// Returns false if the name already belongs to a key.
+    pub fn register(&mut self, name: &str, key: [u8; PLAYER_KEY_LENGTH]) -> bool {
+        let name = name.to_lowercase();
+
+        if self.keys.contains_key(&name) {
+            return false;
+        }
+
+        self.keys.insert(name, key);
+        write_list(IDENTITIES_PATH, self);
+
+        true
+    }
+
+    /// Returns true if a player with this name has joined this server before, meaning the name has an owner
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.keys.contains_key(&name.to_lowercase())
+    }
+}
+
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+/// Contains the names of every player that is banned from this server
+pub struct BanList {
+    names: HashSet<String>,
+}
+
+impl BanList {
+    /// Returns true if a player with this name is banned
+    ///
+    /// This should only be given names verified with [`PlayerIdentities`].
+    pub fn is_banned(&self, name: &str) -> bool {
+        self.names.contains(&name.to_lowercase())
+    }
+
+    /// Bans this player's name & saves the ban list.
+    ///
+    /// Returns false if they were already banned.
+    pub fn ban(&mut self, name: &str) -> bool {
+        let changed = self.names.insert(name.to_lowercase());
+
+        if changed {
+            write_list(BAN_LIST_PATH, self);
+        }
+
+        changed
+    }
+
+    /// Unbans this player's name & saves the ban list.
+    ///
+    /// Returns false if they were not banned.
+    pub fn unban(&mut self, name: &str) -> bool {
+        let changed = self.names.remove(&name.to_lowercase());
+
+        if changed {
+            write_list(BAN_LIST_PATH, self);
+        }
+
+        changed
+    }
+
+    /// Iterates over the names of every banned player
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.names.iter()
+    }
+}
+
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+/// If enabled, only players on this list will be able to join the server
+pub struct Whitelist {
+    enabled: bool,
+    names: HashSet<String>,
+}
+
+impl Whitelist {
+    /// Returns true if the whitelist is being enforced
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables the whitelist & saves it.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+
+        write_list(WHITELIST_PATH, self);
+    }
+
+    /// Returns true if a player with this name can join the server.
+    ///
+    /// This will always be true if the whitelist isn't enabled.
+    ///
+    /// This should only be given names verified with [`PlayerIdentities`].
+    pub fn is_allowed(&self, name: &str) -> bool {
+        !self.enabled || self.names.contains(&name.to_lowercase())
+    }
+
+    /// Adds this player's name to the whitelist & saves it.
+    ///
+    /// Returns false if they were already on the whitelist.
+    pub fn add(&mut self, name: &str) -> bool {
+        let changed = self.names.insert(name.to_lowercase());
+
+        if changed {
+            write_list(WHITELIST_PATH, self);
+        }
+
+        changed
+    }
+
+    /// Removes this player's name from the whitelist & saves it.
+    ///
+    /// Returns false if they were not on the whitelist.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let changed = self.names.remove(&name.to_lowercase());
+
+        if changed {
+            write_list(WHITELIST_PATH, self);
+        }
+
+        changed
+    }
+
+    /// Iterates over the names of every whitelisted player
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.names.iter()
+    }
+}
+
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+/// Contains the names of every player that is allowed to use commands from the chat
+///
+/// Only names in [`PlayerIdentities`] should be added, otherwise anyone could claim an operator's name by joining with it first.
+pub struct OperatorList {
+    names: HashSet<String>,
+}
+
+impl OperatorList {
+    /// Returns true if a player with this name is an operator
+    pub fn is_operator(&self, name: &str) -> bool {
+        self.names.contains(&name.to_lowercase())
+    }
+
+    /// Makes this player's name an operator & saves the operator list.
+    ///
+    /// Returns false if they were already an operator.
+    pub fn add(&mut self, name: &str) -> bool {
+        let changed = self.names.insert(name.to_lowercase());
+
+        if changed {
+            write_list(OPERATORS_PATH, self);
+        }
+
+        changed
+    }
+
+    /// Removes this player's name from the operators & saves the operator list.
+    ///
+    /// Returns false if they were not an operator.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let changed = self.names.remove(&name.to_lowercase());
+
+        if changed {
+            write_list(OPERATORS_PATH, self);
+        }
+
+        changed
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.insert_resource(read_list::<BanList>(BAN_LIST_PATH))
+        .insert_resource(read_list::<Whitelist>(WHITELIST_PATH))
+        .insert_resource(read_list::<OperatorList>(OPERATORS_PATH))
+        .insert_resource(read_list::<PlayerIdentities>(IDENTITIES_PATH));
+}
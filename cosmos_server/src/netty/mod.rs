@@ -5,8 +5,10 @@ use bevy::prelude::App;
 pub mod network_helpers;
 pub mod server_listener;
 pub mod sync;
+pub mod validation;
 
 pub(super) fn register(app: &mut App) {
     sync::register(app);
     server_listener::register(app);
+    validation::register(app);
 }
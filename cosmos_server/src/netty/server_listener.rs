@@ -1,6 +1,8 @@
 //! Listens to almost all the messages received from the client
 //!
 //! Eventually this should be broken down into more specific functions
+//!
+//! Block actions & player movement are checked by the [`super::validation`] module before being used.
 
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::Velocity;
@@ -32,23 +34,60 @@ use crate::structure::planet::generation::planet_generator::RequestChunkEvent;
 
 use super::network_helpers::ServerLobby;
 use super::sync::entities::RequestedEntityEvent;
+use super::validation::{
+    send_body_correction, validate_player_body, within_reach, ActionRateLimit,
+    AwaitingBodyCorrection, MovementValidation, ViolationEvent,
+};
 
-/// Bevy system that listens to almost all the messages received from the client
-///
-/// Eventually this should be broken down into more specific functions
-pub fn server_listen_messages(
+/// Checks that the player can reach the block they are trying to break/place/interact with, and isn't doing so too quickly
+fn validate_block_action(
+    player_entity: Entity,
+    structure_entity: Entity,
+    block: &StructureBlock,
+    player_query: &mut Query<(&Location, &mut ActionRateLimit), With<Player>>,
+    structure_query: &Query<(&Structure, &Location, &GlobalTransform)>,
+) -> Result<(), String> {
+    let Ok((player_location, mut rate_limit)) = player_query.get_mut(player_entity) else {
+        return Err("Player is missing a body".into());
+    };
+
+    if !rate_limit.try_act() {
+        return Err("Interacting with blocks too quickly".into());
+    }
+
+    let Ok((structure, structure_location, structure_transform)) =
+        structure_query.get(structure_entity)
+    else {
+        return Err(format!(
+            "Interacted with invalid structure {structure_entity:?}"
+        ));
+    };
+
+    if !within_reach(
+        player_location,
+        structure,
+        structure_location,
+        structure_transform,
+        block,
+    ) {
+        return Err(format!(
+            "Interacted with unreachable block ({}, {}, {})",
+            block.x(),
+            block.y(),
+            block.z()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Bevy system that listens to all the unreliable messages received from the client
+pub fn server_listen_unreliable_messages(
     mut commands: Commands,
     mut server: ResMut<RenetServer>,
-    lobby: ResMut<ServerLobby>,
-    structure_query: Query<&Structure>,
+    lobby: Res<ServerLobby>,
     mut systems_query: Query<&mut Systems>,
-    mut break_block_event: EventWriter<BlockBreakEvent>,
-    mut block_interact_event: EventWriter<BlockInteractEvent>,
-    mut place_block_event: EventWriter<BlockPlaceEvent>,
-    mut create_ship_event_writer: EventWriter<CreateShipEvent>,
-
     mut ship_movement_event_writer: EventWriter<ShipSetMovementEvent>,
-    mut pilot_change_event_writer: EventWriter<ChangePilotEvent>,
     pilot_query: Query<&Pilot>,
     mut change_player_query: Query<
         (
@@ -56,13 +95,20 @@ pub fn server_listen_messages(
             &mut Location,
             &mut PlayerLooking,
             &mut Velocity,
+            &mut MovementValidation,
+            Option<&AwaitingBodyCorrection>,
         ),
         With<Player>,
     >,
-    mut requested_entities_writer: EventWriter<RequestedEntityEvent>,
-    mut request_chunk_event_writer: EventWriter<RequestChunkEvent>,
+    mut violation_writer: EventWriter<ViolationEvent>,
+    time: Res<Time>,
 ) {
+    let now = time.elapsed_seconds();
+
     for client_id in server.clients_id().into_iter() {
+        // Once a correction is sent, the rest of the bodies sent this frame are outdated
+        let mut corrected = false;
+
         while let Some(message) = server.receive_message(client_id, NettyChannel::Unreliable.id()) {
             if let Some(player_entity) = lobby.player_from_id(client_id) {
                 let command: ClientUnreliableMessages =
@@ -70,16 +116,74 @@ pub fn server_listen_messages(
 
                 match command {
                     ClientUnreliableMessages::PlayerBody { body, looking } => {
+                        if corrected {
+                            continue;
+                        }
+
                         if let Ok((
                             mut transform,
                             mut location,
                             mut currently_looking,
                             mut velocity,
+                            mut movement,
+                            awaiting_correction,
                         )) = change_player_query.get_mut(player_entity)
                         {
+                            currently_looking.rotation = looking;
+
+                            if let Some(correction) = awaiting_correction {
+                                if !correction.accepted_by(&body.location) {
+                                    if correction.should_resend(now) {
+                                        send_body_correction(
+                                            &mut server,
+                                            &mut commands,
+                                            client_id,
+                                            player_entity,
+                                            correction.location,
+                                            transform.rotation,
+                                            now,
+                                        );
+                                        corrected = true;
+                                    }
+
+                                    continue;
+                                }
+
+                                commands
+                                    .entity(player_entity)
+                                    .remove::<AwaitingBodyCorrection>();
+                            } else if !pilot_query.contains(player_entity) {
+                                // Pilots are moved along with their ship, so their body isn't theirs to control
+                                if let Err(reason) = validate_player_body(
+                                    &body,
+                                    &location,
+                                    now - movement.last_update,
+                                ) {
+                                    violation_writer.send(ViolationEvent {
+                                        player: player_entity,
+                                        reason,
+                                    });
+
+                                    send_body_correction(
+                                        &mut server,
+                                        &mut commands,
+                                        client_id,
+                                        player_entity,
+                                        *location,
+                                        transform.rotation,
+                                        now,
+                                    );
+                                    *velocity = Velocity::zero();
+                                    corrected = true;
+
+                                    continue;
+                                }
+                            }
+
+                            movement.last_update = now;
+
                             location.set_from(&body.location);
                             location.last_transform_loc = Some(transform.translation);
-                            currently_looking.rotation = looking;
                             velocity.linvel = body.body_vel.linvel.into();
                             transform.rotation = body.rotation;
                         }
@@ -111,14 +215,36 @@ pub fn server_listen_messages(
                 }
             }
         }
+    }
+}
 
+/// Bevy system that listens to all the reliable messages received from the client
+///
+/// Eventually this should be broken down into more specific functions
+pub fn server_listen_reliable_messages(
+    mut commands: Commands,
+    mut server: ResMut<RenetServer>,
+    lobby: Res<ServerLobby>,
+    structure_query: Query<(&Structure, &Location, &GlobalTransform)>,
+    mut player_query: Query<(&Location, &mut ActionRateLimit), With<Player>>,
+    mut break_block_event: EventWriter<BlockBreakEvent>,
+    mut block_interact_event: EventWriter<BlockInteractEvent>,
+    mut place_block_event: EventWriter<BlockPlaceEvent>,
+    mut create_ship_event_writer: EventWriter<CreateShipEvent>,
+    mut pilot_change_event_writer: EventWriter<ChangePilotEvent>,
+    pilot_query: Query<&Pilot>,
+    mut requested_entities_writer: EventWriter<RequestedEntityEvent>,
+    mut request_chunk_event_writer: EventWriter<RequestChunkEvent>,
+    mut violation_writer: EventWriter<ViolationEvent>,
+) {
+    for client_id in server.clients_id().into_iter() {
         while let Some(message) = server.receive_message(client_id, NettyChannel::Reliable.id()) {
             let command: ClientReliableMessages = cosmos_encoder::deserialize(&message).unwrap();
 
             match command {
                 ClientReliableMessages::PlayerDisconnect => {}
                 ClientReliableMessages::SendAllChunks { server_entity } => {
-                    if let Ok(structure) = structure_query.get(server_entity) {
+                    if let Ok((structure, _, _)) = structure_query.get(server_entity) {
                         for (_, chunk) in structure.chunks() {
                             server.send_message(
                                 client_id,
@@ -148,13 +274,26 @@ pub fn server_listen_messages(
                     z,
                 } => {
                     if let Some(player_entity) = lobby.player_from_id(client_id) {
-                        break_block_event.send(BlockBreakEvent {
+                        let structure_block =
+                            StructureBlock::new(x as usize, y as usize, z as usize);
+
+                        match validate_block_action(
+                            player_entity,
                             structure_entity,
-                            breaker: player_entity,
-                            structure_block: StructureBlock::new(
-                                x as usize, y as usize, z as usize,
-                            ),
-                        });
+                            &structure_block,
+                            &mut player_query,
+                            &structure_query,
+                        ) {
+                            Ok(()) => break_block_event.send(BlockBreakEvent {
+                                structure_entity,
+                                breaker: player_entity,
+                                structure_block,
+                            }),
+                            Err(reason) => violation_writer.send(ViolationEvent {
+                                player: player_entity,
+                                reason,
+                            }),
+                        }
                     }
                 }
                 ClientReliableMessages::PlaceBlock {
@@ -167,16 +306,29 @@ pub fn server_listen_messages(
                     inventory_slot,
                 } => {
                     if let Some(player_entity) = lobby.player_from_id(client_id) {
-                        place_block_event.send(BlockPlaceEvent {
+                        let structure_block =
+                            StructureBlock::new(x as usize, y as usize, z as usize);
+
+                        match validate_block_action(
+                            player_entity,
                             structure_entity,
-                            structure_block: StructureBlock::new(
-                                x as usize, y as usize, z as usize,
-                            ),
-                            block_id,
-                            block_up,
-                            inventory_slot: inventory_slot as usize,
-                            placer: player_entity,
-                        });
+                            &structure_block,
+                            &mut player_query,
+                            &structure_query,
+                        ) {
+                            Ok(()) => place_block_event.send(BlockPlaceEvent {
+                                structure_entity,
+                                structure_block,
+                                block_id,
+                                block_up,
+                                inventory_slot: inventory_slot as usize,
+                                placer: player_entity,
+                            }),
+                            Err(reason) => violation_writer.send(ViolationEvent {
+                                player: player_entity,
+                                reason,
+                            }),
+                        }
                     }
                 }
                 ClientReliableMessages::InteractWithBlock {
@@ -185,15 +337,42 @@ pub fn server_listen_messages(
                     y,
                     z,
                 } => {
-                    block_interact_event.send(BlockInteractEvent {
-                        structure_entity,
-                        structure_block: StructureBlock::new(x as usize, y as usize, z as usize),
-                        interactor: lobby.player_from_id(client_id).unwrap(),
-                    });
+                    if let Some(player_entity) = lobby.player_from_id(client_id) {
+                        let structure_block =
+                            StructureBlock::new(x as usize, y as usize, z as usize);
+
+                        match validate_block_action(
+                            player_entity,
+                            structure_entity,
+                            &structure_block,
+                            &mut player_query,
+                            &structure_query,
+                        ) {
+                            Ok(()) => block_interact_event.send(BlockInteractEvent {
+                                structure_entity,
+                                structure_block,
+                                interactor: player_entity,
+                            }),
+                            Err(reason) => violation_writer.send(ViolationEvent {
+                                player: player_entity,
+                                reason,
+                            }),
+                        }
+                    }
                 }
                 ClientReliableMessages::CreateShip { name: _name } => {
-                    if let Some(client) = lobby.player_from_id(client_id) {
-                        if let Ok((_, location, looking, _)) = change_player_query.get(client) {
+                    if let Some(player_entity) = lobby.player_from_id(client_id) {
+                        if let Ok((location, looking, mut rate_limit)) =
+                            player_query.get_mut(player_entity)
+                        {
+                            if !rate_limit.try_act() {
+                                violation_writer.send(ViolationEvent {
+                                    player: player_entity,
+                                    reason: "Creating ships too quickly".into(),
+                                });
+                                continue;
+                            }
+
                             let ship_location =
                                 *location + looking.rotation.mul_vec3(Vec3::new(0.0, 0.0, 4.0));
 
@@ -252,5 +431,8 @@ pub fn server_listen_messages(
 }
 
 pub(super) fn register(app: &mut App) {
-    app.add_system(server_listen_messages);
+    app.add_systems((
+        server_listen_unreliable_messages,
+        server_listen_reliable_messages,
+    ));
 }
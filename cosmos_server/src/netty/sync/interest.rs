@@ -0,0 +1,159 @@
+//! Keeps track of which entities each client should know about
+//!
+//! An entity is relevant to a client if it is within that entity's loading distance or that
+//! player's render distance. Players have no loading distance, so they are only relevant within
+//! render distance, and a player is always relevant to themselves. Clients are told whenever an
+//! entity becomes relevant or stops being relevant, and per-entity traffic should only be sent
+//! to the clients it is relevant to.
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use bevy_renet::renet::RenetServer;
+use cosmos_core::{
+    entities::player::{render_distance::RenderDistance, Player},
+    netty::{
+        cosmos_encoder, server_reliable_messages::ServerReliableMessages, NettyChannel,
+        NoSendEntity,
+    },
+    persistence::LoadingDistance,
+    physics::location::{Location, SECTOR_DIMENSIONS},
+};
+
+use super::entities::RequestedEntityEvent;
+
+#[derive(Resource, Debug, Default)]
+/// Stores the entities that are currently relevant to each client
+pub struct ClientInterest {
+    relevant: HashMap<u64, HashSet<Entity>>,
+}
+
+impl ClientInterest {
+    /// Returns true if this client currently knows about this entity
+    pub fn is_relevant(&self, client_id: u64, entity: Entity) -> bool {
+        self.relevant
+            .get(&client_id)
+            .map(|entities| entities.contains(&entity))
+            .unwrap_or(false)
+    }
+
+    /// Iterates over the ids of every client this entity is relevant to
+    pub fn interested_clients(&self, entity: Entity) -> impl Iterator<Item = u64> + '_ {
+        self.relevant
+            .iter()
+            .filter(move |(_, entities)| entities.contains(&entity))
+            .map(|(client_id, _)| *client_id)
+    }
+
+    /// Sends the message to every client this entity is relevant to
+    pub fn send_to_interested(
+        &self,
+        server: &mut RenetServer,
+        entity: Entity,
+        channel_id: u8,
+        message: Vec<u8>,
+    ) {
+        for client_id in self.interested_clients(entity) {
+            server.send_message(client_id, channel_id, message.clone());
+        }
+    }
+}
+
+/// Returns true if the two locations are within this many sectors of each other
+fn within_sectors(a: &Location, b: &Location, sectors: u32) -> bool {
+    a.relative_coords_to(b).abs().max_element() < sectors as f32 * SECTOR_DIMENSIONS
+}
+
+pub(super) fn update_interest(
+    mut interest: ResMut<ClientInterest>,
+    players: Query<(Entity, &Player, &Location, Option<&RenderDistance>)>,
+    entities: Query<
+        (Entity, &Location, Option<&LoadingDistance>),
+        (
+            Without<NoSendEntity>,
+            Or<(With<LoadingDistance>, With<Player>)>,
+        ),
+    >,
+    mut server: ResMut<RenetServer>,
+    mut requested_entities_writer: EventWriter<RequestedEntityEvent>,
+) {
+    interest.relevant.retain(|client_id, _| {
+        players
+            .iter()
+            .any(|(_, player, _, _)| player.id() == *client_id)
+    });
+
+    for (player_entity, player, player_location, render_distance) in players.iter() {
+        let client_id = player.id();
+        let render_distance = render_distance.copied().unwrap_or_default().sector_range as u32;
+
+        let relevant = interest.relevant.entry(client_id).or_default();
+
+        let mut left = vec![];
+
+        for &entity in relevant.iter() {
+            // Entities stop being relevant a bit further than they become relevant so they don't flicker in & out at the edge
+            let still_relevant = entities
+                .get(entity)
+                .map(|(_, location, loading_distance)| {
+                    entity == player_entity
+                        || within_sectors(
+                            player_location,
+                            location,
+                            loading_distance
+                                .map(|x| x.unload_distance())
+                                .unwrap_or(0)
+                                .max(render_distance + 2),
+                        )
+                })
+                .unwrap_or(false);
+
+            if !still_relevant {
+                left.push(entity);
+            }
+        }
+
+        for entity in left {
+            relevant.remove(&entity);
+
+            server.send_message(
+                client_id,
+                NettyChannel::Reliable.id(),
+                cosmos_encoder::serialize(&ServerReliableMessages::EntityLeave { entity }),
+            );
+        }
+
+        for (entity, location, loading_distance) in entities.iter() {
+            if relevant.contains(&entity)
+                || (entity != player_entity
+                    && !within_sectors(
+                        player_location,
+                        location,
+                        loading_distance
+                            .map(|x| x.load_distance())
+                            .unwrap_or(0)
+                            .max(render_distance),
+                    ))
+            {
+                continue;
+            }
+
+            relevant.insert(entity);
+
+            server.send_message(
+                client_id,
+                NettyChannel::Reliable.id(),
+                cosmos_encoder::serialize(&ServerReliableMessages::EntityEnter { entity }),
+            );
+
+            // Sends the client everything it needs to create this entity
+            requested_entities_writer.send(RequestedEntityEvent { client_id, entity });
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.insert_resource(ClientInterest::default())
+        .add_system(update_interest);
+}
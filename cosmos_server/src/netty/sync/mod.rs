@@ -3,9 +3,11 @@
 use bevy::prelude::App;
 
 pub mod entities;
+pub mod interest;
 pub mod sync_bodies;
 
 pub(super) fn register(app: &mut App) {
     sync_bodies::register(app);
     entities::register(app);
+    interest::register(app);
 }
@@ -4,7 +4,7 @@ use bevy::prelude::*;
 use bevy_rapier3d::prelude::Velocity;
 use bevy_renet::renet::RenetServer;
 use cosmos_core::{
-    entities::player::{render_distance::RenderDistance, Player},
+    entities::player::Player,
     netty::{
         cosmos_encoder, netty_rigidbody::NettyRigidBody,
         server_unreliable_messages::ServerUnreliableMessages, NettyChannel, NoSendEntity,
@@ -15,21 +15,21 @@ use cosmos_core::{
 
 use crate::netty::network_helpers::NetworkTick;
 
-/// Sends bodies to players only if it's within their render distance.
+use super::interest::{update_interest, ClientInterest};
+
+/// Sends bodies to players only if they are relevant to them.
 fn send_bodies(
-    players: &Query<(&Player, &RenderDistance, &Location)>,
-    bodies: &[(Entity, NettyRigidBody, LoadingDistance)],
+    players: &Query<&Player>,
+    bodies: &[(Entity, NettyRigidBody)],
+    interest: &ClientInterest,
     server: &mut RenetServer,
     tick: &NetworkTick,
 ) {
-    for (player, _, loc) in players.iter() {
+    for player in players.iter() {
         let players_bodies: Vec<(Entity, NettyRigidBody)> = bodies
             .iter()
-            .filter(|(_, rb, loading_distance)| {
-                rb.location.relative_coords_to(loc).abs().max_element()
-                    < loading_distance.load_block_distance()
-            })
-            .map(|(ent, net_rb, _)| (*ent, *net_rb))
+            .filter(|(ent, _)| interest.is_relevant(player.id(), *ent))
+            .copied()
             .collect();
 
         if !players_bodies.is_empty() {
@@ -50,34 +50,34 @@ fn server_sync_bodies(
     mut server: ResMut<RenetServer>,
     mut tick: ResMut<NetworkTick>,
     entities: Query<
-        (Entity, &Transform, &Location, &Velocity, &LoadingDistance),
-        Without<NoSendEntity>,
+        (Entity, &Transform, &Location, &Velocity),
+        (With<LoadingDistance>, Without<NoSendEntity>),
     >,
-    players: Query<(&Player, &RenderDistance, &Location)>,
+    players: Query<&Player>,
+    interest: Res<ClientInterest>,
 ) {
     tick.0 += 1;
 
     let mut bodies = Vec::new();
 
-    for (entity, transform, location, velocity, unload_distance) in entities.iter() {
+    for (entity, transform, location, velocity) in entities.iter() {
         bodies.push((
             entity,
             NettyRigidBody::new(velocity, transform.rotation, *location),
-            *unload_distance,
         ));
 
         // The packet size can only be so big, so limit syncing to 20 per packet
         if bodies.len() > 20 {
-            send_bodies(&players, &bodies, &mut server, &tick);
+            send_bodies(&players, &bodies, &interest, &mut server, &tick);
             bodies = Vec::new();
         }
     }
 
     if !bodies.is_empty() {
-        send_bodies(&players, &bodies, &mut server, &tick);
+        send_bodies(&players, &bodies, &interest, &mut server, &tick);
     }
 }
 
 pub(super) fn register(app: &mut App) {
-    app.add_system(server_sync_bodies);
+    app.add_system(server_sync_bodies.after(update_interest));
 }
@@ -0,0 +1,341 @@
+//! Makes sure the actions clients send are actually possible.
+//!
+//! Anything that fails validation is ignored & counted as a violation. If a player's body fails validation,
+//! they are sent back to where the server thinks they are. Players with too many violations are kicked.
+
+use bevy::prelude::*;
+use bevy_renet::renet::RenetServer;
+use cosmos_core::{
+    entities::player::Player,
+    netty::{
+        cosmos_encoder, netty_rigidbody::NettyRigidBody,
+        server_reliable_messages::ServerReliableMessages, NettyChannel,
+    },
+    physics::location::Location,
+    structure::{structure_block::StructureBlock, Structure},
+};
+
+use crate::state::GameState;
+
+/// The furthest away a player can be from a block they are breaking/placing/interacting with.
+///
+/// The client can only reach 10 blocks from their camera, but some leeway is given for latency.
+pub const MAX_REACH: f32 = 14.0;
+/// The fastest a player can move. This is a bit above the max speed of a ship, since players can be inside them.
+pub const MAX_PLAYER_SPEED: f32 = 200.0;
+/// How far a player's reported location can be from where they could have moved before it's considered a teleport
+const TELEPORT_TOLERANCE: f32 = 10.0;
+/// How long to wait for a client to accept a body correction before sending it again
+const CORRECTION_RESEND_SEC: f32 = 2.0;
+
+/// The most block actions a player can do in a row before being rate limited
+const MAX_ACTION_BURST: f32 = 20.0;
+/// How many block actions a player regains every second
+const ACTIONS_PER_SECOND: f32 = 10.0;
+
+/// Players with more violations than this are kicked
+const MAX_VIOLATIONS: f32 = 25.0;
+/// How many violations are forgiven every second
+const VIOLATIONS_FORGIVEN_PER_SECOND: f32 = 0.2;
+
+#[derive(Component, Debug)]
+/// Limits how quickly a player can break, place & interact with blocks
+pub struct ActionRateLimit {
+    allowance: f32,
+}
+
+impl Default for ActionRateLimit {
+    fn default() -> Self {
+        Self {
+            allowance: MAX_ACTION_BURST,
+        }
+    }
+}
+
+impl ActionRateLimit {
+    /// Returns true if the player is allowed to do another action right now.
+    ///
+    /// If they are, this counts as them doing that action.
+    pub fn try_act(&mut self) -> bool {
+        if self.allowance >= 1.0 {
+            self.allowance -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Component, Debug, Default)]
+/// Keeps track of how many times a player has failed validation recently
+pub struct Violations {
+    count: f32,
+}
+
+#[derive(Component, Debug, Default)]
+/// Keeps track of when this player's body was last updated
+pub struct MovementValidation {
+    /// The `Time::elapsed_seconds` the last body was accepted at
+    pub last_update: f32,
+}
+
+#[derive(Component, Debug)]
+/// Added to a player whenever the server overrides their body (teleports, corrections).
+///
+/// Until the client sends a body near this location, every body they send is ignored.
+pub struct AwaitingBodyCorrection {
+    /// Where the player was sent to
+    pub location: Location,
+    /// The `Time::elapsed_seconds` this correction was sent at
+    pub sent_at: f32,
+}
+
+impl AwaitingBodyCorrection {
+    /// Returns true if the client has accepted the correction & this location is based on it
+    pub fn accepted_by(&self, reported_location: &Location) -> bool {
+        self.location.distance_sqrd(reported_location) <= TELEPORT_TOLERANCE * TELEPORT_TOLERANCE
+    }
+
+    /// Returns true if the client has taken long enough to accept this that it was probably lost
+    ///
+    /// * `now` The current `Time::elapsed_seconds`
+    pub fn should_resend(&self, now: f32) -> bool {
+        now - self.sent_at > CORRECTION_RESEND_SEC
+    }
+}
+
+#[derive(Debug)]
+/// Sent whenever a player does something they shouldn't be able to
+pub struct ViolationEvent {
+    /// The player's entity
+    pub player: Entity,
+    /// What they did
+    pub reason: String,
+}
+
+/// Checks that the given block exists within the structure & is close enough to the player to be reached
+pub fn within_reach(
+    player_location: &Location,
+    structure: &Structure,
+    structure_location: &Location,
+    structure_transform: &GlobalTransform,
+    block: &StructureBlock,
+) -> bool {
+    if !structure.is_within_blocks(block.x(), block.y(), block.z()) {
+        return false;
+    }
+
+    let block_location = structure.block_world_location(
+        block.x(),
+        block.y(),
+        block.z(),
+        structure_transform,
+        structure_location,
+    );
+
+    player_location.distance_sqrd(&block_location) <= MAX_REACH * MAX_REACH
+}
+
+/// Checks that the player could have moved from their current location to the reported body's location
+///
+/// * `seconds_elapsed` How long it's been since the player's body was last updated
+pub fn validate_player_body(
+    reported: &NettyRigidBody,
+    current_location: &Location,
+    seconds_elapsed: f32,
+) -> Result<(), String> {
+    let speed = reported.create_velocity().linvel.length();
+    if !speed.is_finite() || speed > MAX_PLAYER_SPEED {
+        return Err(format!("Moving too fast ({speed:.1}m/s)"));
+    }
+
+    let max_distance = MAX_PLAYER_SPEED * seconds_elapsed + TELEPORT_TOLERANCE;
+    let distance_sqrd = current_location.distance_sqrd(&reported.location);
+
+    if !distance_sqrd.is_finite() || distance_sqrd > max_distance * max_distance {
+        return Err(format!(
+            "Moved too far ({:.1} blocks in {seconds_elapsed:.2}s)",
+            distance_sqrd.sqrt()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Tells the client their player is at this location & ignores their movement until they accept it.
+///
+/// * `now` The current `Time::elapsed_seconds`
+pub fn send_body_correction(
+    server: &mut RenetServer,
+    commands: &mut Commands,
+    client_id: u64,
+    player_entity: Entity,
+    location: Location,
+    rotation: Quat,
+    now: f32,
+) {
+    server.send_message(
+        client_id,
+        NettyChannel::Reliable.id(),
+        cosmos_encoder::serialize(&ServerReliableMessages::SetPlayerBody {
+            body: NettyRigidBody::new(&Default::default(), rotation, location),
+        }),
+    );
+
+    commands
+        .entity(player_entity)
+        .insert(AwaitingBodyCorrection {
+            location,
+            sent_at: now,
+        });
+}
+
+fn add_validation_components(
+    mut commands: Commands,
+    query: Query<Entity, Added<Player>>,
+    time: Res<Time>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert((
+            ActionRateLimit::default(),
+            Violations::default(),
+            MovementValidation {
+                last_update: time.elapsed_seconds(),
+            },
+        ));
+    }
+}
+
+fn refill_rate_limits(mut query: Query<(&mut ActionRateLimit, &mut Violations)>, time: Res<Time>) {
+    for (mut rate_limit, mut violations) in query.iter_mut() {
+        rate_limit.allowance = (rate_limit.allowance + ACTIONS_PER_SECOND * time.delta_seconds())
+            .min(MAX_ACTION_BURST);
+
+        violations.count =
+            (violations.count - VIOLATIONS_FORGIVEN_PER_SECOND * time.delta_seconds()).max(0.0);
+    }
+}
+
+fn handle_violations(
+    mut event_reader: EventReader<ViolationEvent>,
+    mut query: Query<(&Player, &mut Violations)>,
+    mut server: ResMut<RenetServer>,
+) {
+    for ev in event_reader.iter() {
+        let Ok((player, mut violations)) = query.get_mut(ev.player) else {
+            continue;
+        };
+
+        violations.count += 1.0;
+
+        println!(
+            "Violation by {} ({:.0} recent) - {}",
+            player.name(),
+            violations.count,
+            ev.reason
+        );
+
+        if violations.count > MAX_VIOLATIONS {
+            println!("Kicking {} for too many violations", player.name());
+
+            violations.count = 0.0;
+            server.disconnect(player.id());
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_event::<ViolationEvent>().add_systems(
+        (
+            add_validation_components,
+            refill_rate_limits,
+            handle_violations,
+        )
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use bevy_rapier3d::prelude::Velocity;
+    use cosmos_core::{physics::location::Sector, structure::chunk::CHUNK_DIMENSIONS};
+
+    use super::*;
+
+    /// Returns true if a player at the origin is allowed to report this body
+    fn body_valid(linvel: Vec3, position: Vec3, seconds_elapsed: f32) -> bool {
+        let origin = Location::new(Vec3::ZERO, Sector::new(0, 0, 0));
+        let body = NettyRigidBody::new(
+            &Velocity {
+                linvel,
+                angvel: Vec3::ZERO,
+            },
+            Quat::IDENTITY,
+            Location::new(position, Sector::new(0, 0, 0)),
+        );
+
+        validate_player_body(&body, &origin, seconds_elapsed).is_ok()
+    }
+
+    #[test]
+    fn test_within_reach_at_max_reach() {
+        let structure = Structure::new(1, 1, 1);
+        let structure_location = Location::new(Vec3::ZERO, Sector::new(0, 0, 0));
+        let block_position = structure.block_relative_position(0, 0, 0);
+
+        let reach = |offset: f32, block: StructureBlock| {
+            let player_location = Location::new(
+                block_position + Vec3::new(offset, 0.0, 0.0),
+                Sector::new(0, 0, 0),
+            );
+
+            within_reach(
+                &player_location,
+                &structure,
+                &structure_location,
+                &GlobalTransform::IDENTITY,
+                &block,
+            )
+        };
+
+        assert!(reach(MAX_REACH, StructureBlock::new(0, 0, 0)));
+        assert!(!reach(MAX_REACH + 0.1, StructureBlock::new(0, 0, 0)));
+
+        // Blocks outside the structure can never be reached, no matter how close they are
+        assert!(!reach(0.0, StructureBlock::new(CHUNK_DIMENSIONS, 0, 0)));
+    }
+
+    #[test]
+    fn test_player_body_rejects_invalid_velocity() {
+        assert!(body_valid(Vec3::X * MAX_PLAYER_SPEED, Vec3::ZERO, 1.0));
+        assert!(!body_valid(
+            Vec3::X * (MAX_PLAYER_SPEED + 1.0),
+            Vec3::ZERO,
+            1.0
+        ));
+        assert!(!body_valid(Vec3::splat(f32::NAN), Vec3::ZERO, 1.0));
+        assert!(!body_valid(
+            Vec3::new(f32::INFINITY, 0.0, 0.0),
+            Vec3::ZERO,
+            1.0
+        ));
+    }
+
+    #[test]
+    fn test_player_body_teleport_tolerance() {
+        // With no time passed, only the tolerance is allowed
+        assert!(body_valid(Vec3::ZERO, Vec3::X * TELEPORT_TOLERANCE, 0.0));
+        assert!(!body_valid(
+            Vec3::ZERO,
+            Vec3::X * (TELEPORT_TOLERANCE + 0.1),
+            0.0
+        ));
+
+        // Each second allows moving at max speed on top of that
+        let max_distance = MAX_PLAYER_SPEED + TELEPORT_TOLERANCE;
+        assert!(body_valid(Vec3::ZERO, Vec3::X * max_distance, 1.0));
+        assert!(!body_valid(Vec3::ZERO, Vec3::X * (max_distance + 1.0), 1.0));
+
+        assert!(!body_valid(Vec3::ZERO, Vec3::splat(f32::NAN), 1.0));
+    }
+}
@@ -65,6 +65,20 @@ impl SectorsCache {
         }
     }
 
+    /// Returns true if this cache knows of a save file for this entity in this sector. This does NOT
+    /// perform any IO operations, so sectors that haven't been searched yet will always return false.
+    pub fn contains(
+        &self,
+        sector: Sector,
+        entity_id: &EntityId,
+        load_distance: Option<u32>,
+    ) -> bool {
+        self.get(&sector)
+            .as_ref()
+            .map(|set| set.contains(&(entity_id.clone(), load_distance)))
+            .unwrap_or(false)
+    }
+
     /// Inserts an entity into this sector in this cache. This does not perform any IO operations.
     pub fn insert(&mut self, sector: Sector, entity_id: EntityId, load_distance: Option<u32>) {
         if let Some(x) = self.0.get_mut(&sector) {
@@ -292,10 +306,39 @@ impl SerializedData {
 }
 
 /// Returns true if a sector has at some point been generated at this location
+///
+/// Entities that can be generated again from the seed (such as untouched asteroids) are not saved,
+/// so this can be false for sectors that have been visited.
 pub fn is_sector_loaded(sector: Sector) -> bool {
     fs::try_exists(SaveFileIdentifier::get_sector_path(sector)).unwrap_or(false)
 }
 
+/// Marks this sector as generated, so [`is_sector_loaded`] returns true for it even if nothing is ever saved in it.
+///
+/// Use this for things that are generated in a sector but may have moved out of it by the time they're saved.
+pub fn mark_sector_loaded(sector: Sector) {
+    if let Err(e) = fs::create_dir_all(SaveFileIdentifier::get_sector_path(sector)) {
+        println!("Error marking sector {sector} as loaded: {e}");
+    }
+}
+
+/// Returns true if this entity has been saved in this sector.
+///
+/// The sectors cache is checked first, and the disk is only checked if the cache doesn't know about it.
+pub fn is_entity_saved(
+    sectors_cache: &SectorsCache,
+    sector: Sector,
+    entity_id: &EntityId,
+    load_distance: Option<u32>,
+) -> bool {
+    sectors_cache.contains(sector, entity_id, load_distance)
+        || fs::try_exists(
+            SaveFileIdentifier::new(Some(sector), entity_id.clone(), load_distance)
+                .get_save_file_path(),
+        )
+        .unwrap_or(false)
+}
+
 pub(super) fn register(app: &mut App) {
     saving::register(app);
     loading::register(app);
@@ -28,8 +28,15 @@ use super::{
     EntityId, SaveFileIdentifier, SectorsCache,
 };
 
+#[derive(Component, Debug, Clone, Copy)]
+/// Loads everything around this location as if a player was there, even though none are yet.
+///
+/// This is used to load where a ship is warping to before it arrives.
+pub struct PreloadAround(pub Location);
+
 fn unload_far(
     query: Query<&Location, With<Player>>,
+    preloading: Query<&PreloadAround>,
     others: Query<(&Location, Entity, &LoadingDistance), (Without<Player>, Without<NeedsUnloaded>)>,
     mut commands: Commands,
 ) {
@@ -38,6 +45,7 @@ fn unload_far(
 
         if let Some(min_dist) = query
             .iter()
+            .chain(preloading.iter().map(|x| &x.0))
             .map(|l| l.relative_coords_to(loc).abs().max_element())
             .reduce(f32::min)
         {
@@ -90,6 +98,7 @@ fn monitor_loading_task(
 /// Performance hot spot
 fn load_near(
     query: Query<&Location, With<Player>>,
+    preloading: Query<&PreloadAround>,
     loaded_entities: Query<&EntityId>,
     sectors_cache: Res<SectorsCache>,
     mut commands: Commands,
@@ -103,7 +112,11 @@ fn load_near(
 
     let thread_pool = AsyncComputeTaskPool::get();
 
-    let sectors = query.iter().map(|l| l.sector()).collect::<Vec<Sector>>();
+    let sectors = query
+        .iter()
+        .chain(preloading.iter().map(|x| &x.0))
+        .map(|l| l.sector())
+        .collect::<Vec<Sector>>();
 
     // If this ever gets laggy, either of these two clones could be the cause
     let mut sectors_cache = sectors_cache.clone();
@@ -18,6 +18,8 @@ const WORLD_SWITCH_DISTANCE: f32 = SECTOR_DIMENSIONS / 2.0;
 const WORLD_SWITCH_DISTANCE_SQRD: f32 = WORLD_SWITCH_DISTANCE * WORLD_SWITCH_DISTANCE;
 
 /// This is used to assign a player to a specific rapier world.
+///
+/// Returns the world the player was put in, so anything that should be in the same world as them can be put there too.
 pub fn assign_player_world(
     player_worlds: &Query<
         (&Location, &WorldWithin, &PhysicsWorld),
@@ -27,7 +29,7 @@ pub fn assign_player_world(
     location: &Location,
     commands: &mut Commands,
     rapier_context: &mut RapierContext,
-) {
+) -> (WorldWithin, PhysicsWorld) {
     let mut best_distance = None;
     let mut best_world = None;
     let mut best_world_id = None;
@@ -45,12 +47,14 @@ pub fn assign_player_world(
     }
 
     if let Some(world) = best_world {
+        let world_id = best_world_id.expect("This should never be None if world is some.");
+
         commands
             .entity(player_entity)
             .insert(world)
-            .insert(PhysicsWorld {
-                world_id: best_world_id.expect("This should never be None if world is some."),
-            });
+            .insert(PhysicsWorld { world_id });
+
+        (world, PhysicsWorld { world_id })
     } else {
         let world_id = rapier_context.add_world(RapierWorld::default());
 
@@ -68,6 +72,8 @@ pub fn assign_player_world(
             .entity(player_entity)
             .insert(WorldWithin(world_entity))
             .insert(PhysicsWorld { world_id });
+
+        (WorldWithin(world_entity), PhysicsWorld { world_id })
     }
 }
 
@@ -319,7 +325,18 @@ fn sync_transforms_and_locations(
     }
 
     for (world_entity, mut world, mut world_location) in world_query.iter_mut() {
-        if let Ok(mut player_entity) = entity_query.get(world.player) {
+        // A player that was moved into another world (such as by warping away) no longer decides where this one is
+        let player_in_world = players_query
+            .get(world.player)
+            .map(|(world_within, _)| world_within.0 == world_entity)
+            .unwrap_or(false);
+
+        let player_entity = entity_query
+            .get(world.player)
+            .ok()
+            .filter(|_| player_in_world);
+
+        if let Some(mut player_entity) = player_entity {
             while let Ok(parent) = parent_query.get(player_entity) {
                 let parent_entity = parent.get();
                 if trans_query_no_parent.contains(parent_entity) {
@@ -351,7 +368,7 @@ fn sync_transforms_and_locations(
                 }
             }
         } else {
-            // The player has disconnected
+            // The player has disconnected or left this world
             // Either: Find a new player to have the world
             // Or: Move everything over to the closest world by removing the WorldWithin component
 
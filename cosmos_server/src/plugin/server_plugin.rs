@@ -3,9 +3,9 @@
 use bevy::prelude::Plugin;
 
 use crate::{
-    blocks, commands, events,
+    blocks, chat, commands, entities, events,
     init::{self, init_server},
-    inventory, netty, persistence, physics, projectiles, structure, universe,
+    inventory, moderation, netty, persistence, physics, projectiles, structure, universe,
 };
 
 /// The server's plugin
@@ -20,9 +20,12 @@ impl Plugin for ServerPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         init_server::init(app, self.ip.clone());
         commands::register(app);
+        chat::register(app);
+        moderation::register(app);
         init::register(app);
         netty::register(app);
         events::register(app);
+        entities::register(app);
         physics::register(app);
         blocks::register(app);
         structure::register(app);
@@ -34,3 +34,20 @@ pub fn get_seed_for_sector_u32(server_seed: &ServerSeed, sector: &Sector) -> u32
 pub fn get_rng_for_sector(server_seed: &ServerSeed, sector: &Sector) -> ChaCha8Rng {
     ChaCha8Rng::seed_from_u64(get_seed_for_sector_u64(server_seed, sector))
 }
+
+/// Generates a random number generator for a single chunk of a structure in this sector.
+///
+/// Every chunk gets its own random numbers, so things generated for one chunk don't depend on
+/// which other chunks have been generated.
+pub fn get_rng_for_chunk(
+    server_seed: &ServerSeed,
+    sector: &Sector,
+    (cx, cy, cz): (usize, usize, usize),
+) -> ChaCha8Rng {
+    let chunk_seed = (cx as u64)
+        .wrapping_mul(73_856_093)
+        .wrapping_add((cy as u64).wrapping_mul(19_349_663))
+        .wrapping_add((cz as u64).wrapping_mul(83_492_791));
+
+    ChaCha8Rng::seed_from_u64(get_seed_for_sector_u64(server_seed, sector) ^ chunk_seed)
+}
@@ -17,12 +17,21 @@ use cosmos_core::{
         structure_iterator::ChunkIteratorResult,
         ChunkInitEvent, Structure,
     },
+    universe::star::Star,
     utils::{resource_wrapper::ResourceWrapper, timer::UtilsTimer},
 };
 use futures_lite::future;
 use noise::NoiseFn;
 
-use crate::state::GameState;
+use crate::{
+    init::init_world::ServerSeed,
+    state::GameState,
+    structure::{
+        ores::{OreDistribution, OrePlacement, OreRule},
+        planet::biosphere::TemperatureRange,
+    },
+    universe::planet_spawner::temperature_from_star,
+};
 
 #[derive(Component)]
 struct AsyncStructureGeneration {
@@ -45,40 +54,87 @@ fn notify_when_done_generating(
                     structure.set_chunk(chunk);
                 }
 
-                let itr = structure.all_chunks_iter(false);
-
-                commands
-                    .entity(generating_chunk.structure_entity)
-                    .insert(ChunksNeedLoaded {
-                        amount_needed: itr.len(),
-                    });
-
-                for res in itr {
-                    // This will always be true because include_empty is false
-                    if let ChunkIteratorResult::FilledChunk {
-                        position: (x, y, z),
-                        chunk: _,
-                    } = res
-                    {
-                        chunk_init_event_writer.send(ChunkInitEvent {
-                            structure_entity: generating_chunk.structure_entity,
-                            x,
-                            y,
-                            z,
-                        });
-                    }
-                }
+                send_chunk_init_events(
+                    generating_chunk.structure_entity,
+                    &structure,
+                    &mut commands,
+                    &mut chunk_init_event_writer,
+                );
             }
         }
     }
 }
 
+/// Sends a [`ChunkInitEvent`] for every filled chunk of this asteroid, and waits for them to be loaded
+pub(super) fn send_chunk_init_events(
+    structure_entity: Entity,
+    structure: &Structure,
+    commands: &mut Commands,
+    chunk_init_event_writer: &mut EventWriter<ChunkInitEvent>,
+) {
+    let itr = structure.all_chunks_iter(false);
+
+    commands.entity(structure_entity).insert(ChunksNeedLoaded {
+        amount_needed: itr.len(),
+    });
+
+    for res in itr {
+        // This will always be true because include_empty is false
+        if let ChunkIteratorResult::FilledChunk {
+            position: (x, y, z),
+            chunk: _,
+        } = res
+        {
+            chunk_init_event_writer.send(ChunkInitEvent {
+                structure_entity,
+                x,
+                y,
+                z,
+            });
+        }
+    }
+}
+
+/// Asteroids are mostly metal, and the ones far from stars are full of crystals
+fn asteroid_ores() -> Vec<OreRule> {
+    vec![
+        OreRule::new(
+            "cosmos:iron_ore",
+            OrePlacement::Cluster {
+                scale: 0.15,
+                threshold: 0.3,
+            },
+            TemperatureRange::new(0.0, f32::MAX),
+        ),
+        OreRule::new(
+            "cosmos:copper_ore",
+            OrePlacement::Cluster {
+                scale: 0.15,
+                threshold: 0.4,
+            },
+            TemperatureRange::new(0.0, f32::MAX),
+        ),
+        OreRule::new(
+            "cosmos:crystal_ore",
+            OrePlacement::Vein {
+                scale: 0.08,
+                thickness: 0.05,
+            },
+            TemperatureRange::new(0.0, 200.0),
+        ),
+    ]
+}
+
 fn start_generating_asteroid(
     query: Query<(Entity, &Structure, &Location), With<AsteroidNeedsCreated>>,
+    stars: Query<(&Location, &Star)>,
     noise: Res<ResourceWrapper<noise::OpenSimplex>>,
     blocks: Res<Registry<Block>>,
+    server_seed: Res<ServerSeed>,
     mut commands: Commands,
 ) {
+    let ore_table = asteroid_ores();
+
     for (structure_entity, structure, loc) in query.iter() {
         commands
             .entity(structure_entity)
@@ -92,6 +148,22 @@ fn start_generating_asteroid(
 
         let stone = blocks.from_id("cosmos:stone").unwrap().clone();
 
+        let temperature = stars
+            .iter()
+            .map(|(star_loc, star)| temperature_from_star(star, loc.distance_sqrd(star_loc)))
+            .reduce(f32::max)
+            .unwrap_or(0.0);
+
+        let ores = OreDistribution::new(
+            &ore_table,
+            &blocks,
+            &server_seed,
+            &loc.sector(),
+            temperature,
+        );
+
+        let absolute_coords = loc.absolute_coords_f64();
+
         let thread_pool = AsyncComputeTaskPool::get();
 
         let noise = **noise;
@@ -144,11 +216,22 @@ fn start_generating_asteroid(
                                 chunks.insert((cx, cy, cz), Chunk::new(cx, cy, cz));
                             }
 
+                            let block = ores
+                                .ore_at(
+                                    &noise,
+                                    (
+                                        absolute_coords.x + x_pos as f64,
+                                        absolute_coords.y + y_pos as f64,
+                                        absolute_coords.z + z_pos as f64,
+                                    ),
+                                )
+                                .unwrap_or(stone);
+
                             chunks.get_mut(&(cx, cy, cz)).unwrap().set_block_at(
                                 x % CHUNK_DIMENSIONS,
                                 y % CHUNK_DIMENSIONS,
                                 z % CHUNK_DIMENSIONS,
-                                stone,
+                                block,
                                 BlockFace::Top,
                             )
                         }
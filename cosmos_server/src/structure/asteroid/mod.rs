@@ -3,10 +3,12 @@
 use bevy::prelude::App;
 
 mod generator;
+pub mod persistence;
 pub mod server_asteroid_builder;
 mod sync;
 
 pub(super) fn register(app: &mut App) {
     sync::register(app);
     generator::register(app);
+    persistence::register(app);
 }
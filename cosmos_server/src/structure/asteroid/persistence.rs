@@ -0,0 +1,138 @@
+//! Saves asteroids that players have changed.
+//!
+//! Asteroids nobody has touched are never written to disk, since they can just be generated again
+//! from the sector's seed. See [`asteroid_entity_id`] for how generated asteroids are matched up with their saves.
+
+use bevy::prelude::*;
+use cosmos_core::{
+    events::block_events::BlockChangedEvent,
+    physics::location::{Location, Sector},
+    projectiles::laser::LaserCollideEvent,
+    structure::{
+        asteroid::{asteroid_builder::TAsteroidBuilder, Asteroid},
+        ChunkInitEvent, Structure,
+    },
+};
+
+use crate::persistence::{
+    loading::{begin_loading, done_loading, NeedsLoaded},
+    saving::{begin_saving, done_saving, NeedsSaved},
+    EntityId, SerializedData,
+};
+
+use super::{generator::send_chunk_init_events, server_asteroid_builder::ServerAsteroidBuilder};
+
+#[derive(Component, Debug)]
+/// This asteroid has been changed since it was generated, so it needs to be saved
+struct AsteroidModified;
+
+#[derive(Component, Debug)]
+/// This asteroid was just loaded from disk & its chunks still need to be initialized
+struct AsteroidNeedsChunksInitialized;
+
+/// The id that the `index`th asteroid generated in this sector is saved under.
+///
+/// Since asteroids are always generated in the same order, this lets the spawner know which
+/// asteroids have a save file & shouldn't be generated again.
+pub fn asteroid_entity_id(sector: Sector, index: usize) -> EntityId {
+    // Underscores are used to split up save file names, so they can't be used here
+    EntityId::new(format!(
+        "asteroid.{}.{}.{}.{index}",
+        sector.x(),
+        sector.y(),
+        sector.z()
+    ))
+}
+
+fn mark_modified_asteroids(
+    mut block_changed_reader: EventReader<BlockChangedEvent>,
+    mut laser_reader: EventReader<LaserCollideEvent>,
+    parent_query: Query<&Parent>,
+    unmodified_asteroids: Query<(), (With<Asteroid>, Without<AsteroidModified>)>,
+    mut commands: Commands,
+) {
+    // Lasers can damage blocks without destroying them, which doesn't send a block changed event
+    let hit_structures = laser_reader
+        .iter()
+        .filter_map(|ev| parent_query.get(ev.entity_hit()).ok())
+        .map(|parent| parent.get());
+
+    for structure_entity in block_changed_reader
+        .iter()
+        .map(|ev| ev.structure_entity)
+        .chain(hit_structures)
+    {
+        if unmodified_asteroids.contains(structure_entity) {
+            commands.entity(structure_entity).insert(AsteroidModified);
+        }
+    }
+}
+
+fn on_save_structure(
+    mut query: Query<
+        (&mut SerializedData, &Structure, Option<&AsteroidModified>),
+        (With<NeedsSaved>, With<Asteroid>),
+    >,
+) {
+    for (mut s_data, structure, modified) in query.iter_mut() {
+        if modified.is_none() {
+            // This asteroid will be the exact same when it is generated again
+            s_data.set_should_save(false);
+            continue;
+        }
+
+        s_data.serialize_data("cosmos:structure", structure);
+        s_data.serialize_data("cosmos:is_asteroid", &true);
+    }
+}
+
+fn on_load_structure(
+    query: Query<(Entity, &SerializedData), With<NeedsLoaded>>,
+    mut commands: Commands,
+) {
+    for (entity, s_data) in query.iter() {
+        if s_data
+            .deserialize_data::<bool>("cosmos:is_asteroid")
+            .unwrap_or(false)
+        {
+            if let Some(mut structure) = s_data.deserialize_data::<Structure>("cosmos:structure") {
+                let loc: Location = s_data
+                    .deserialize_data("cosmos:location")
+                    .expect("Every asteroid should have a location when saved!");
+
+                let mut entity_cmd = commands.entity(entity);
+
+                let builder = ServerAsteroidBuilder::default();
+
+                builder.insert_asteroid(&mut entity_cmd, loc, &mut structure);
+
+                entity_cmd.insert((structure, AsteroidModified, AsteroidNeedsChunksInitialized));
+            }
+        }
+    }
+}
+
+fn initialize_loaded_chunks(
+    query: Query<(Entity, &Structure), With<AsteroidNeedsChunksInitialized>>,
+    mut chunk_init_event_writer: EventWriter<ChunkInitEvent>,
+    mut commands: Commands,
+) {
+    for (entity, structure) in query.iter() {
+        commands
+            .entity(entity)
+            .remove::<AsteroidNeedsChunksInitialized>();
+
+        send_chunk_init_events(
+            entity,
+            structure,
+            &mut commands,
+            &mut chunk_init_event_writer,
+        );
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems((mark_modified_asteroids, initialize_loaded_chunks))
+        .add_system(on_save_structure.after(begin_saving).before(done_saving))
+        .add_system(on_load_structure.after(begin_loading).before(done_loading));
+}
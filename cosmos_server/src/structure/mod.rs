@@ -4,6 +4,7 @@ use bevy::prelude::App;
 
 pub mod asteroid;
 pub mod block_health;
+pub mod ores;
 pub mod planet;
 pub mod saving;
 pub mod server_structure_builder;
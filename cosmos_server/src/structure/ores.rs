@@ -0,0 +1,197 @@
+//! Scatters ores through the stone of planets & asteroids
+//!
+//! Each biosphere (and asteroids) has its own table of [`OreRule`]s. When a structure is generated,
+//! its table is turned into an [`OreDistribution`] for its sector & temperature, which decides which stone blocks
+//! become ores. Everything is based off the [`ServerSeed`], so the same structure always gets the same ores.
+
+use cosmos_core::{
+    block::Block,
+    physics::location::Sector,
+    registry::{identifiable::Identifiable, Registry},
+    structure::chunk::{Chunk, CHUNK_DIMENSIONS},
+};
+use noise::NoiseFn;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{init::init_world::ServerSeed, rng::get_rng_for_sector};
+
+use super::planet::biosphere::TemperatureRange;
+
+/// How far apart each ore's noise is sampled, so different ores don't end up in the same spots
+const ORE_NOISE_SPACING: f64 = 10_000.0;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+/// How an ore is spread through the stone
+pub enum OrePlacement {
+    /// Round blobs of ore, placed where the noise is above the threshold
+    Cluster {
+        /// How stretched out the noise is - smaller values make bigger clusters
+        scale: f64,
+        /// Between -1.0 and 1.0 - higher values make fewer clusters
+        threshold: f64,
+    },
+    /// Thin winding sheets of ore, placed where the noise is close to 0
+    Vein {
+        /// How stretched out the noise is - smaller values make longer veins
+        scale: f64,
+        /// How close to 0 the noise has to be - higher values make thicker veins
+        thickness: f64,
+    },
+}
+
+impl OrePlacement {
+    /// Makes this ore more common (`abundance` > 1.0) or less common (`abundance` < 1.0)
+    fn scaled(self, abundance: f64) -> Self {
+        match self {
+            Self::Cluster { scale, threshold } => Self::Cluster {
+                scale,
+                threshold: 1.0 - (1.0 - threshold) * abundance,
+            },
+            Self::Vein { scale, thickness } => Self::Vein {
+                scale,
+                thickness: thickness * abundance,
+            },
+        }
+    }
+
+    fn scale(&self) -> f64 {
+        match *self {
+            Self::Cluster { scale, .. } | Self::Vein { scale, .. } => scale,
+        }
+    }
+
+    fn contains(&self, noise_value: f64) -> bool {
+        match *self {
+            Self::Cluster { threshold, .. } => noise_value > threshold,
+            Self::Vein { thickness, .. } => noise_value.abs() < thickness,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// An entry in an ore table
+pub struct OreRule {
+    #[serde(rename = "block")]
+    block_id: String,
+    placement: OrePlacement,
+    /// Defaults to every temperature
+    #[serde(default, rename = "temperature")]
+    temperature_range: TemperatureRange,
+}
+
+impl OreRule {
+    /// Creates a rule that places this ore in this way, but only on structures within this temperature range
+    pub fn new(
+        block_id: impl Into<String>,
+        placement: OrePlacement,
+        temperature_range: TemperatureRange,
+    ) -> Self {
+        Self {
+            block_id: block_id.into(),
+            placement,
+            temperature_range,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PlacedOre {
+    block: Block,
+    placement: OrePlacement,
+    noise_offset: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+/// The ores a single structure will have, and how common each of them are
+pub struct OreDistribution {
+    ores: Vec<PlacedOre>,
+}
+
+impl OreDistribution {
+    /// Picks the ores from this table that fit this temperature.
+    ///
+    /// Every sector makes each ore a bit more or less common than usual, so some places are worth
+    /// travelling to.
+    pub fn new(
+        table: &[OreRule],
+        blocks: &Registry<Block>,
+        server_seed: &ServerSeed,
+        sector: &Sector,
+        temperature: f32,
+    ) -> Self {
+        let mut rng = get_rng_for_sector(server_seed, sector);
+
+        let ores = table
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.temperature_range.contains(temperature))
+            .filter_map(|(i, rule)| {
+                let Some(block) = blocks.from_id(&rule.block_id) else {
+                    println!("[Ores] Missing ore block {}", rule.block_id);
+                    return None;
+                };
+
+                Some(PlacedOre {
+                    block: block.clone(),
+                    placement: rule.placement.scaled(rng.gen_range(0.75..1.25)),
+                    noise_offset: (i + 1) as f64 * ORE_NOISE_SPACING,
+                })
+            })
+            .collect();
+
+        Self { ores }
+    }
+
+    /// Returns the ore that should be at these absolute block coordinates, if any.
+    ///
+    /// Ores earlier in the table take priority over later ones.
+    pub fn ore_at(&self, noise: &noise::OpenSimplex, (x, y, z): (f64, f64, f64)) -> Option<&Block> {
+        self.ores
+            .iter()
+            .find(|ore| {
+                let scale = ore.placement.scale();
+
+                let noise_value = noise.get([
+                    x * scale + ore.noise_offset,
+                    y * scale + ore.noise_offset,
+                    z * scale + ore.noise_offset,
+                ]);
+
+                ore.placement.contains(noise_value)
+            })
+            .map(|ore| &ore.block)
+    }
+
+    /// Turns stone in this chunk into ores, where `(cx, cy, cz)` are the absolute coordinates of this chunk's (0, 0, 0) block
+    pub fn replace_stone(
+        &self,
+        chunk: &mut Chunk,
+        stone: &Block,
+        noise: &noise::OpenSimplex,
+        (cx, cy, cz): (f64, f64, f64),
+    ) {
+        if self.ores.is_empty() {
+            return;
+        }
+
+        for z in 0..CHUNK_DIMENSIONS {
+            for y in 0..CHUNK_DIMENSIONS {
+                for x in 0..CHUNK_DIMENSIONS {
+                    if chunk.block_at(x, y, z) != stone.id() {
+                        continue;
+                    }
+
+                    if let Some(ore) =
+                        self.ore_at(noise, (cx + x as f64, cy + y as f64, cz + z as f64))
+                    {
+                        let block_up = chunk.block_rotation(x, y, z);
+
+                        chunk.set_block_at(x, y, z, ore, block_up);
+                    }
+                }
+            }
+        }
+    }
+}
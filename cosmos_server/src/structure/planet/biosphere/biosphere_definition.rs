@@ -0,0 +1,278 @@
+//! Biospheres that are described entirely by a definition file, rather than by code.
+//!
+//! Every `.json` file in [`BIOSPHERE_DEFINITIONS_DIRECTORY`] is read as a [`BiosphereDefinition`] when the server starts,
+//! and is registered as its own biosphere. For example:
+//!
+//! ```json
+//! {
+//!     "id": "cosmos:biosphere_grass",
+//!     "temperature": { "low": 200.0, "high": 450.0 },
+//!     "terrain": { "amplitude": 7.0, "delta": 0.05 },
+//!     "layers": [
+//!         { "block": "cosmos:grass", "depth": 1 },
+//!         { "block": "cosmos:dirt", "depth": 4 }
+//!     ],
+//!     "ores": [
+//!         {
+//!             "block": "cosmos:iron_ore",
+//!             "placement": { "type": "cluster", "scale": 0.12, "threshold": 0.45 }
+//!         }
+//!     ]
+//! }
+//! ```
+
+use std::fs;
+
+use bevy::{
+    prelude::{App, Component, Entity, IntoSystemConfigs, OnUpdate, Resource},
+    utils::HashMap,
+};
+use serde::Deserialize;
+
+use crate::{state::GameState, structure::ores::OreRule};
+
+use super::{
+    biosphere_generation::{generate_lod, generate_planet, notify_when_done_generating},
+    caves::CaveCarving,
+    features::FeatureDefinition,
+    register_biosphere, TBiosphere, TGenerateChunkEvent, TemperatureRange,
+};
+
+/// The directory every biosphere definition file is loaded from
+pub const BIOSPHERE_DEFINITIONS_DIRECTORY: &str = "assets/biospheres";
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+/// The layers of noise that make up a biosphere's hills
+pub struct TerrainNoise {
+    /// How tall the hills are
+    #[serde(default = "default_amplitude")]
+    pub amplitude: f64,
+    /// How stretched out the hills are - smaller values make wider hills
+    #[serde(default = "default_delta")]
+    pub delta: f64,
+    /// How many layers of noise are added together to make the hills
+    #[serde(default = "default_iterations")]
+    pub iterations: usize,
+    /// Moves the whole terrain up (or down, if negative) by this many blocks
+    #[serde(default)]
+    pub height_offset: f64,
+}
+
+fn default_amplitude() -> f64 {
+    7.0
+}
+
+fn default_delta() -> f64 {
+    0.05
+}
+
+fn default_iterations() -> usize {
+    9
+}
+
+impl Default for TerrainNoise {
+    fn default() -> Self {
+        Self {
+            amplitude: default_amplitude(),
+            delta: default_delta(),
+            iterations: default_iterations(),
+            height_offset: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// A layer of blocks just below the surface of the terrain
+pub struct BlockLayer {
+    /// The block this layer is made of
+    pub block: String,
+    /// How many blocks thick this layer is
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// Fills everything below a certain height that would otherwise be air with a block, like water or lava
+pub struct SeaLevel {
+    /// The block the sea is made of
+    pub block: String,
+    /// How far above (or below, if negative) the average terrain height the sea goes up to
+    pub level: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// Pillars of a block placed on top of the terrain, such as cacti
+pub struct SurfaceDecoration {
+    /// The block the pillars are made of
+    pub block: String,
+    /// How stretched out the noise deciding where pillars go is
+    pub scale: f64,
+    /// Between -1.0 and 1.0 - higher values make fewer pillars
+    pub threshold: f64,
+    /// How many blocks tall each pillar is
+    pub height: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// Describes everything about how a biosphere's terrain is generated
+pub struct BiosphereDefinition {
+    /// The biosphere's unlocalized name, such as `cosmos:biosphere_grass`
+    pub id: String,
+    /// The temperatures planets with this biosphere can have
+    pub temperature: TemperatureRange,
+    /// The shape of the hills
+    #[serde(default)]
+    pub terrain: TerrainNoise,
+    /// The layers of blocks under the surface, starting with the very top block
+    pub layers: Vec<BlockLayer>,
+    /// The block that makes up everything below the layers. Ores are only placed in this block.
+    #[serde(default = "default_stone")]
+    pub stone: String,
+    /// The sea that fills the low parts of the terrain, if there is one
+    #[serde(default)]
+    pub sea: Option<SeaLevel>,
+    /// What is placed on top of the terrain. Earlier decorations take priority over later ones.
+    #[serde(default)]
+    pub decorations: Vec<SurfaceDecoration>,
+    /// The ores found in this biosphere's stone
+    #[serde(default)]
+    pub ores: Vec<OreRule>,
+    /// The caves carved out of the terrain, if there are any
+    #[serde(default)]
+    pub caves: Option<CaveCarving>,
+    /// Things like trees & boulders placed on top of the terrain
+    #[serde(default)]
+    pub features: Vec<FeatureDefinition>,
+}
+
+fn default_stone() -> String {
+    "cosmos:stone".into()
+}
+
+#[derive(Resource, Debug, Default)]
+/// Every biosphere definition that was loaded, by biosphere id
+pub struct BiosphereDefinitions {
+    definitions: HashMap<String, BiosphereDefinition>,
+}
+
+impl BiosphereDefinitions {
+    /// Gets the definition for this biosphere id, if it was created from a definition file
+    pub fn get(&self, biosphere_id: &str) -> Option<&BiosphereDefinition> {
+        self.definitions.get(biosphere_id)
+    }
+}
+
+#[derive(Component, Debug, Default)]
+/// Marks that this planet's biosphere comes from a [`BiosphereDefinition`]
+pub struct DefinedBiosphereMarker;
+
+/// Marks that a chunk of a planet with a [`BiosphereDefinition`] needs generated
+pub struct DefinedChunkNeedsGeneratedEvent {
+    x: usize,
+    y: usize,
+    z: usize,
+    structure_entity: Entity,
+}
+
+impl TGenerateChunkEvent for DefinedChunkNeedsGeneratedEvent {
+    fn new(x: usize, y: usize, z: usize, structure_entity: Entity) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            structure_entity,
+        }
+    }
+
+    fn get_structure_entity(&self) -> Entity {
+        self.structure_entity
+    }
+
+    fn get_chunk_coordinates(&self) -> (usize, usize, usize) {
+        (self.x, self.y, self.z)
+    }
+}
+
+#[derive(Default, Debug)]
+/// Creates planets from their [`BiosphereDefinition`]
+pub struct DefinedBiosphere;
+
+impl TBiosphere<DefinedBiosphereMarker, DefinedChunkNeedsGeneratedEvent> for DefinedBiosphere {
+    fn get_marker_component(&self) -> DefinedBiosphereMarker {
+        DefinedBiosphereMarker {}
+    }
+
+    fn get_generate_chunk_event(
+        &self,
+        x: usize,
+        y: usize,
+        z: usize,
+        structure_entity: Entity,
+    ) -> DefinedChunkNeedsGeneratedEvent {
+        DefinedChunkNeedsGeneratedEvent::new(x, y, z, structure_entity)
+    }
+}
+
+/// Reads every biosphere definition in this directory.
+///
+/// These are sorted by file name, so planets are always given the same biospheres.
+pub fn load_biosphere_definitions(directory: &str) -> Vec<BiosphereDefinition> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        println!("[Biosphere] Unable to read biosphere definitions from {directory}");
+        return vec![];
+    };
+
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().map(|ext| ext == "json").unwrap_or(false))
+        .collect::<Vec<_>>();
+
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let data = fs::read(&path).ok()?;
+
+            match serde_json::from_slice::<BiosphereDefinition>(&data) {
+                Ok(definition) => Some(definition),
+                Err(e) => {
+                    println!("[Biosphere] Invalid biosphere definition {path:?}: {e}");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+pub(super) fn register(app: &mut App) {
+    let mut definitions = BiosphereDefinitions::default();
+
+    for definition in load_biosphere_definitions(BIOSPHERE_DEFINITIONS_DIRECTORY) {
+        println!("[Biosphere] Loaded biosphere {}", definition.id);
+
+        register_biosphere::<DefinedBiosphereMarker, DefinedChunkNeedsGeneratedEvent>(
+            app,
+            definition.id.clone(),
+            definition.temperature,
+        );
+
+        definitions
+            .definitions
+            .insert(definition.id.clone(), definition);
+    }
+
+    if definitions.definitions.is_empty() {
+        // Nothing would set up the resources these systems need
+        app.insert_resource(definitions);
+        return;
+    }
+
+    app.insert_resource(definitions).add_systems(
+        (
+            generate_planet::<DefinedBiosphereMarker, DefinedChunkNeedsGeneratedEvent>,
+            notify_when_done_generating::<DefinedBiosphereMarker>,
+            generate_lod::<DefinedBiosphereMarker>,
+        )
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
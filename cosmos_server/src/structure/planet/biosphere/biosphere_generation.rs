@@ -0,0 +1,951 @@
+//! Generates the terrain of biospheres that are layers of blocks over rolling hills, like the grass biosphere.
+//!
+//! Each planet's blocks & terrain come from the [`BiosphereDefinition`] of its biosphere, and
+//! [`generate_planet`], [`notify_when_done_generating`] & [`generate_lod`] are registered for their marker component.
+
+use std::{
+    collections::{HashMap, HashSet},
+    mem::swap,
+};
+
+use bevy::{
+    prelude::{
+        Commands, Component, Entity, EventReader, EventWriter, Query, Res, ResMut, With, Without,
+    },
+    tasks::AsyncComputeTaskPool,
+};
+use cosmos_core::{
+    block::{Block, BlockFace},
+    events::block_events::BlockChangedEvent,
+    registry::Registry,
+    structure::{
+        chunk::{Chunk, CHUNK_DIMENSIONS},
+        planet::{biosphere::BiosphereMarker, lod::lod_face_coordinates, Planet},
+        ChunkInitEvent, Structure,
+    },
+    utils::{resource_wrapper::ResourceWrapper, timer::UtilsTimer},
+};
+use futures_lite::future;
+use noise::NoiseFn;
+
+use crate::{
+    init::init_world::ServerSeed,
+    rng::get_rng_for_chunk,
+    structure::{
+        ores::OreDistribution,
+        planet::{generation::generation_origin::GenerationOrigin, lod::PlanetLod},
+    },
+};
+
+use super::{
+    biosphere_definition::{
+        BiosphereDefinition, BiosphereDefinitions, SurfaceDecoration, TerrainNoise,
+    },
+    caves::Caves,
+    features::{Features, PendingFeatureBlocks},
+    GeneratingChunk, GeneratingChunks, TGenerateChunkEvent,
+};
+
+/// Some chunks might not be getting flattened, or maybe I'm just crazy.
+/// Within (flattening_fraction * planet size) of the 45 starts the flattening.
+const FLAT_FRACTION: f64 = 0.4;
+
+/// This fraction of the original depth always remains, even on the very edge of the world.
+const UNFLATTENED: f64 = 0.25;
+
+/// How far apart each decoration's noise is sampled, so different decorations don't end up in the same spots
+const DECORATION_NOISE_SPACING: f64 = 10_000.0;
+
+#[derive(Debug, Clone)]
+/// The blocks & terrain a [`BiosphereDefinition`] uses, with its blocks taken from the block registry
+struct BiosphereBlocks {
+    terrain: TerrainNoise,
+    layers: Vec<(Block, usize)>,
+    stone: Block,
+    sea: Option<(Block, f64)>,
+    decorations: Vec<(Block, SurfaceDecoration)>,
+    caves: Option<Caves>,
+    features: Features,
+}
+
+impl BiosphereBlocks {
+    fn new(definition: &BiosphereDefinition, blocks: &Registry<Block>) -> Option<Self> {
+        let get_block = |id: &str| {
+            let block = blocks.from_id(id).cloned();
+
+            if block.is_none() {
+                println!("[Biosphere] Missing block {id} for {}", definition.id);
+            }
+
+            block
+        };
+
+        let layers = definition
+            .layers
+            .iter()
+            .map(|layer| get_block(&layer.block).map(|block| (block, layer.depth)))
+            .collect::<Option<Vec<_>>>()?;
+
+        let sea = match &definition.sea {
+            Some(sea) => Some((get_block(&sea.block)?, sea.level)),
+            None => None,
+        };
+
+        let decorations = definition
+            .decorations
+            .iter()
+            .map(|decoration| get_block(&decoration.block).map(|block| (block, decoration.clone())))
+            .collect::<Option<Vec<_>>>()?;
+
+        let caves = match &definition.caves {
+            Some(caves) => Some(Caves::new(caves, blocks)?),
+            None => None,
+        };
+
+        Some(Self {
+            terrain: definition.terrain,
+            layers,
+            stone: get_block(&definition.stone)?,
+            sea,
+            decorations,
+            caves,
+            features: Features::new(&definition.features, blocks)?,
+        })
+    }
+
+    /// The block this many blocks below the surface, where the surface is 0
+    fn block_at_depth(&self, depth: usize) -> &Block {
+        let mut layer_bottom = 0;
+
+        for (block, layer_depth) in self.layers.iter() {
+            layer_bottom += layer_depth;
+
+            if depth < layer_bottom {
+                return block;
+            }
+        }
+
+        &self.stone
+    }
+
+    /// The block on the very top of the terrain
+    fn surface(&self) -> &Block {
+        self.layers
+            .first()
+            .map(|(block, _)| block)
+            .unwrap_or(&self.stone)
+    }
+
+    /// The height everything at or below is part of the sea, or 0 if there is no sea
+    fn sea_level(&self, middle_air_start: usize) -> usize {
+        self.sea
+            .as_ref()
+            .map(|(_, level)| (middle_air_start as f64 + level).round().max(0.0) as usize)
+            .unwrap_or(0)
+    }
+}
+
+fn get_block_height(
+    terrain: &TerrainNoise,
+    (mut x, mut y, mut z): (usize, usize, usize),
+    (structure_x, structure_y, structure_z): (f64, f64, f64),
+    s_dimensions: usize,
+    noise_generator: &noise::OpenSimplex,
+    middle_air_start: usize,
+) -> usize {
+    let mut depth: f64 = 0.0;
+    for iteration in 1..=terrain.iterations {
+        let iteration = iteration as f64;
+        depth += noise_generator.get([
+            (x as f64 + structure_x) * (terrain.delta / iteration),
+            (y as f64 + structure_y) * (terrain.delta / iteration),
+            (z as f64 + structure_z) * (terrain.delta / iteration),
+        ]) * terrain.amplitude
+            * iteration;
+    }
+
+    depth += terrain.height_offset;
+
+    // For the flattening (it's like the rumbling).
+    x = x.min(s_dimensions - x);
+    y = y.min(s_dimensions - y);
+    z = z.min(s_dimensions - z);
+
+    let initial_height = middle_air_start as f64 + depth;
+
+    // Min is height of the face you're on, second min is the closer to the 45 of the 2 remaining.
+    let dist_from_space = s_dimensions as f64 - initial_height;
+    let dist_from_45 = x.min(y).max(x.max(y).min(z)) as f64 - dist_from_space;
+    let flattening_limit = (s_dimensions as f64 - 2.0 * dist_from_space) * FLAT_FRACTION;
+    depth *=
+        dist_from_45.min(flattening_limit) / flattening_limit * (1.0 - UNFLATTENED) + UNFLATTENED;
+
+    (middle_air_start as f64 + depth).round() as usize
+}
+
+/// Samples the same heights the chunks are generated from, so the planet looks the same from far away
+pub fn generate_lod<T: Component>(
+    query: Query<
+        (Entity, &Structure, &GenerationOrigin, &BiosphereMarker),
+        (With<T>, Without<PlanetLod>),
+    >,
+    noise_generator: Res<ResourceWrapper<noise::OpenSimplex>>,
+    blocks: Res<Registry<Block>>,
+    definitions: Res<BiosphereDefinitions>,
+    mut commands: Commands,
+) {
+    for (entity, structure, origin, biosphere) in query.iter() {
+        let Some(biosphere_blocks) = definitions
+            .get(biosphere.biosphere_name())
+            .and_then(|definition| BiosphereBlocks::new(definition, &blocks))
+        else {
+            continue;
+        };
+
+        let s_dimensions = structure.blocks_height();
+        let middle_air_start = s_dimensions - CHUNK_DIMENSIONS * 5;
+
+        let sea_level = biosphere_blocks.sea_level(middle_air_start);
+
+        let actual_pos = origin.location().absolute_coords_f64();
+        let structure_coords = (actual_pos.x, actual_pos.y, actual_pos.z);
+
+        let lod = PlanetLod::generate(s_dimensions, |face, coords| {
+            // The same seed coordinates `do_face` uses
+            let seed_height = match face {
+                BlockFace::Top | BlockFace::Front | BlockFace::Right => middle_air_start,
+                BlockFace::Bottom | BlockFace::Back | BlockFace::Left => {
+                    s_dimensions - middle_air_start
+                }
+            };
+
+            get_block_height(
+                &biosphere_blocks.terrain,
+                lod_face_coordinates(face, coords, seed_height),
+                structure_coords,
+                s_dimensions,
+                &noise_generator,
+                middle_air_start,
+            )
+            .max(sea_level)
+        });
+
+        commands.entity(entity).insert(lod);
+    }
+}
+
+/// Puts the chunks generated for this biosphere into their structures once they're done
+///
+/// This is also where blocks of features that crossed chunk boundaries are placed.
+pub fn notify_when_done_generating<T: Component>(
+    mut generating: ResMut<GeneratingChunks<T>>,
+    mut event_writer: EventWriter<ChunkInitEvent>,
+    mut block_changed_writer: EventWriter<BlockChangedEvent>,
+    mut structure_query: Query<(&mut Structure, Option<&mut PendingFeatureBlocks>)>,
+    blocks: Res<Registry<Block>>,
+) {
+    let mut still_todo = Vec::with_capacity(generating.generating.len());
+
+    swap(&mut generating.generating, &mut still_todo);
+
+    for mut generating_chunk in still_todo {
+        if let Some(chunks) = future::block_on(future::poll_once(&mut generating_chunk.task)) {
+            let (mut chunk, structure_entity, spilled_blocks) = chunks;
+
+            if let Ok((mut structure, pending)) = structure_query.get_mut(structure_entity) {
+                let (x, y, z) = (
+                    chunk.structure_x(),
+                    chunk.structure_y(),
+                    chunk.structure_z(),
+                );
+
+                if let Some(mut pending) = pending {
+                    pending.apply_to(&mut chunk, &blocks);
+
+                    structure.set_chunk(chunk);
+
+                    pending.add_spilled_blocks(
+                        spilled_blocks,
+                        &mut structure,
+                        &blocks,
+                        &mut block_changed_writer,
+                    );
+                } else {
+                    structure.set_chunk(chunk);
+                }
+
+                event_writer.send(ChunkInitEvent {
+                    structure_entity,
+                    x,
+                    y,
+                    z,
+                });
+            }
+        } else {
+            generating.generating.push(generating_chunk);
+        }
+    }
+}
+
+#[inline]
+fn do_face(
+    (sx, sy, sz): (usize, usize, usize),
+    (structure_x, structure_y, structure_z): (f64, f64, f64),
+    s_dimensions: usize,
+    noise_generator: &noise::OpenSimplex,
+    middle_air_start: usize,
+    blocks: &BiosphereBlocks,
+    chunk: &mut Chunk,
+    up: BlockFace,
+) {
+    let sea_level = blocks.sea_level(middle_air_start);
+
+    for i in 0..CHUNK_DIMENSIONS {
+        for j in 0..CHUNK_DIMENSIONS {
+            let seed_coordinates = match up {
+                BlockFace::Top => (sx + i, middle_air_start, sz + j),
+                BlockFace::Bottom => (sx + i, s_dimensions - middle_air_start, sz + j),
+                BlockFace::Front => (sx + i, sy + j, middle_air_start),
+                BlockFace::Back => (sx + i, sy + j, s_dimensions - middle_air_start),
+                BlockFace::Right => (middle_air_start, sy + i, sz + j),
+                BlockFace::Left => (s_dimensions - middle_air_start, sy + i, sz + j),
+            };
+
+            let top_height = get_block_height(
+                &blocks.terrain,
+                seed_coordinates,
+                (structure_x, structure_y, structure_z),
+                s_dimensions,
+                noise_generator,
+                middle_air_start,
+            );
+
+            // Decorations never grow underwater
+            let decoration = blocks
+                .decorations
+                .iter()
+                .enumerate()
+                .find(|(i, (_, decoration))| {
+                    let (x, y, z) = seed_coordinates;
+                    let offset = *i as f64 * DECORATION_NOISE_SPACING;
+
+                    top_height > sea_level
+                        && noise_generator.get([
+                            (x as f64 + structure_x) * decoration.scale + offset,
+                            (y as f64 + structure_y) * decoration.scale + offset,
+                            (z as f64 + structure_z) * decoration.scale + offset,
+                        ]) > decoration.threshold
+                })
+                .map(|(_, decoration)| decoration);
+
+            for height in 0..CHUNK_DIMENSIONS {
+                let (x, y, z, actual_height) = match up {
+                    BlockFace::Top => (i, height, j, sy + height),
+                    BlockFace::Bottom => (i, height, j, s_dimensions - (sy + height)),
+                    BlockFace::Front => (i, j, height, sz + height),
+                    BlockFace::Back => (i, j, height, s_dimensions - (sz + height)),
+                    BlockFace::Right => (height, i, j, sx + height),
+                    BlockFace::Left => (height, i, j, s_dimensions - (sx + height)),
+                };
+
+                if actual_height <= top_height {
+                    let block = blocks.block_at_depth(top_height - actual_height);
+
+                    chunk.set_block_at(x, y, z, block, up);
+                } else if actual_height <= sea_level {
+                    if let Some((sea, _)) = &blocks.sea {
+                        chunk.set_block_at(x, y, z, sea, up);
+                    }
+                } else if let Some((block, decoration)) = decoration {
+                    if actual_height <= top_height + decoration.height {
+                        chunk.set_block_at(x, y, z, block, up);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn do_edge(
+    (sx, sy, sz): (usize, usize, usize),
+    (structure_x, structure_y, structure_z): (f64, f64, f64),
+    s_dimensions: usize,
+    noise_generator: &noise::OpenSimplex,
+    middle_air_start: usize,
+    blocks: &BiosphereBlocks,
+    chunk: &mut Chunk,
+    j_up: BlockFace,
+    k_up: BlockFace,
+) {
+    let sea_level = blocks.sea_level(middle_air_start);
+
+    let mut j_top = [[0; CHUNK_DIMENSIONS]; CHUNK_DIMENSIONS];
+    for (i, layer) in j_top.iter_mut().enumerate().take(CHUNK_DIMENSIONS) {
+        for (k, height) in layer.iter_mut().enumerate().take(CHUNK_DIMENSIONS) {
+            // Seed coordinates for the noise function. Which loop variable goes to which xyz must agree everywhere.
+            let (mut x, mut y, mut z) = (sx + i, sy + i, sz + i);
+            match j_up {
+                BlockFace::Front => z = middle_air_start,
+                BlockFace::Back => z = s_dimensions - middle_air_start,
+                BlockFace::Left => x = s_dimensions - middle_air_start,
+                BlockFace::Right => x = middle_air_start,
+                BlockFace::Top => y = middle_air_start,
+                BlockFace::Bottom => y = s_dimensions - middle_air_start,
+            };
+            match k_up {
+                BlockFace::Front | BlockFace::Back => z = sz + k,
+                BlockFace::Left | BlockFace::Right => x = sx + k,
+                BlockFace::Top | BlockFace::Bottom => y = sy + k,
+            };
+
+            // Unmodified top height.
+            *height = get_block_height(
+                &blocks.terrain,
+                (x, y, z),
+                (structure_x, structure_y, structure_z),
+                s_dimensions,
+                noise_generator,
+                middle_air_start,
+            );
+
+            // Don't let the top fall "below" the 45.
+            let dim_45 = match k_up {
+                BlockFace::Front => z,
+                BlockFace::Back => s_dimensions - z,
+                BlockFace::Left => s_dimensions - x,
+                BlockFace::Right => x,
+                BlockFace::Top => y,
+                BlockFace::Bottom => s_dimensions - y,
+            };
+            *height = (*height).max(dim_45);
+        }
+    }
+
+    for i in 0..CHUNK_DIMENSIONS {
+        // The minimum (j, j) on the 45 where the two top heights intersect.
+        let mut first_both_45 = s_dimensions;
+        for j in 0..CHUNK_DIMENSIONS {
+            // Seed coordinates for the noise function. Which loop variable goes to which xyz must agree everywhere.
+            let (mut x, mut y, mut z) = (sx + i, sy + i, sz + i);
+            match k_up {
+                BlockFace::Front => z = middle_air_start,
+                BlockFace::Back => z = s_dimensions - middle_air_start,
+                BlockFace::Left => x = s_dimensions - middle_air_start,
+                BlockFace::Right => x = middle_air_start,
+                BlockFace::Top => y = middle_air_start,
+                BlockFace::Bottom => y = s_dimensions - middle_air_start,
+            };
+            match j_up {
+                BlockFace::Front | BlockFace::Back => z = sz + j,
+                BlockFace::Left | BlockFace::Right => x = sx + j,
+                BlockFace::Top | BlockFace::Bottom => y = sy + j,
+            };
+
+            // Unmodified top height.
+            let mut k_top = get_block_height(
+                &blocks.terrain,
+                (x, y, z),
+                (structure_x, structure_y, structure_z),
+                s_dimensions,
+                noise_generator,
+                middle_air_start,
+            );
+
+            // First height, and also the height of the other 45 bc of math.
+            let j_height = match j_up {
+                BlockFace::Front => z,
+                BlockFace::Back => s_dimensions - z,
+                BlockFace::Left => s_dimensions - x,
+                BlockFace::Right => x,
+                BlockFace::Top => y,
+                BlockFace::Bottom => s_dimensions - y,
+            };
+
+            // Don't let the top fall "below" the 45, but also don't let it go "above" the first shared 45.
+            // This probably won't interfere with anything before the first shared 45 is discovered bc of the loop order.
+            k_top = k_top.clamp(j_height, first_both_45);
+
+            // Get smallest top height that's on the 45 for both y and z.
+            if j_top[i][j] == j && k_top == j && first_both_45 == s_dimensions {
+                first_both_45 = k_top;
+            };
+
+            for k in 0..CHUNK_DIMENSIONS {
+                // Don't let the top rise "above" the first shared 45.
+                let j_top = j_top[i][k].min(first_both_45);
+
+                // This is super smart I promise, definitely no better way to decide which loop variables are x, y, z.
+                let (mut x, mut y, mut z) = (i, i, i);
+                match j_up {
+                    BlockFace::Front | BlockFace::Back => z = j,
+                    BlockFace::Left | BlockFace::Right => x = j,
+                    BlockFace::Top | BlockFace::Bottom => y = j,
+                };
+                match k_up {
+                    BlockFace::Front | BlockFace::Back => z = k,
+                    BlockFace::Left | BlockFace::Right => x = k,
+                    BlockFace::Top | BlockFace::Bottom => y = k,
+                };
+
+                let block_up = Planet::get_planet_face_without_structure(
+                    sx + x,
+                    sy + y,
+                    sz + z,
+                    s_dimensions,
+                    s_dimensions,
+                    s_dimensions,
+                );
+
+                // Second height, and also the height of the other 45 (dim_45 in the upper loop must be recalculated here).
+                let k_height = match k_up {
+                    BlockFace::Front => sz + z,
+                    BlockFace::Back => s_dimensions - (sz + z),
+                    BlockFace::Left => s_dimensions - (sx + x),
+                    BlockFace::Right => sx + x,
+                    BlockFace::Top => sy + y,
+                    BlockFace::Bottom => s_dimensions - (sy + y),
+                };
+
+                // The surface is only where exactly one of the two heights is at its top
+                if j_height <= j_top
+                    && k_height <= k_top
+                    && !(j_height == j_top && k_height == k_top)
+                {
+                    let depth = (j_top - j_height).min(k_top - k_height);
+
+                    let block_up = if depth != 0 {
+                        block_up
+                    } else if j_height == j_top {
+                        j_up
+                    } else {
+                        k_up
+                    };
+
+                    chunk.set_block_at(x, y, z, blocks.block_at_depth(depth), block_up);
+                } else if j_height <= sea_level && k_height <= sea_level {
+                    if let Some((sea, _)) = &blocks.sea {
+                        chunk.set_block_at(x, y, z, sea, block_up);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn do_corner(
+    (sx, sy, sz): (usize, usize, usize),
+    (structure_x, structure_y, structure_z): (f64, f64, f64),
+    s_dimensions: usize,
+    noise_generator: &noise::OpenSimplex,
+    middle_air_start: usize,
+    blocks: &BiosphereBlocks,
+    chunk: &mut Chunk,
+    x_up: BlockFace,
+    y_up: BlockFace,
+    z_up: BlockFace,
+) {
+    let sea_level = blocks.sea_level(middle_air_start);
+
+    // x top height cache.
+    let mut x_top = [[0; CHUNK_DIMENSIONS]; CHUNK_DIMENSIONS];
+    for (j, layer) in x_top.iter_mut().enumerate().take(CHUNK_DIMENSIONS) {
+        for (k, height) in layer.iter_mut().enumerate().take(CHUNK_DIMENSIONS) {
+            // Seed coordinates for the noise function.
+            let (x, y, z) = match x_up {
+                BlockFace::Right => (middle_air_start, sy + j, sz + k),
+                _ => (s_dimensions - middle_air_start, sy + j, sz + k),
+            };
+
+            // Unmodified top height.
+            *height = get_block_height(
+                &blocks.terrain,
+                (x, y, z),
+                (structure_x, structure_y, structure_z),
+                s_dimensions,
+                noise_generator,
+                middle_air_start,
+            );
+
+            // Don't let the top fall "below" the 45s.
+            let y_45 = match y_up {
+                BlockFace::Top => y,
+                _ => s_dimensions - y,
+            };
+            let z_45 = match z_up {
+                BlockFace::Front => z,
+                _ => s_dimensions - z,
+            };
+            *height = (*height).max(y_45).max(z_45);
+        }
+    }
+
+    // y top height cache.
+    let mut y_top = [[0; CHUNK_DIMENSIONS]; CHUNK_DIMENSIONS];
+    for (i, layer) in y_top.iter_mut().enumerate().take(CHUNK_DIMENSIONS) {
+        for (k, height) in layer.iter_mut().enumerate().take(CHUNK_DIMENSIONS) {
+            // Seed coordinates for the noise function. Which loop variable goes to which xyz must agree everywhere.
+            let (x, y, z) = match y_up {
+                BlockFace::Top => (sx + i, middle_air_start, sz + k),
+                _ => (sx + i, s_dimensions - middle_air_start, sz + k),
+            };
+
+            // Unmodified top height.
+            *height = get_block_height(
+                &blocks.terrain,
+                (x, y, z),
+                (structure_x, structure_y, structure_z),
+                s_dimensions,
+                noise_generator,
+                middle_air_start,
+            );
+
+            // Don't let the top fall "below" the 45s.
+            let x_45 = match x_up {
+                BlockFace::Right => x,
+                _ => s_dimensions - x,
+            };
+            let z_45 = match z_up {
+                BlockFace::Front => z,
+                _ => s_dimensions - z,
+            };
+            *height = (*height).max(x_45).max(z_45);
+        }
+    }
+
+    for i in 0..CHUNK_DIMENSIONS {
+        // The minimum (j, j, j) on the 45 where the three top heights intersect.
+        let mut first_all_45 = s_dimensions;
+        for j in 0..CHUNK_DIMENSIONS {
+            // Seed coordinates for the noise function.
+            let (x, y, z) = match z_up {
+                BlockFace::Front => (sx + i, sy + j, middle_air_start),
+                _ => (sx + i, sy + j, s_dimensions - middle_air_start),
+            };
+
+            // Unmodified top height.
+            let mut z_top = get_block_height(
+                &blocks.terrain,
+                (x, y, z),
+                (structure_x, structure_y, structure_z),
+                s_dimensions,
+                noise_generator,
+                middle_air_start,
+            );
+
+            let x_height = match x_up {
+                BlockFace::Right => x,
+                _ => s_dimensions - x,
+            };
+
+            let y_height = match y_up {
+                BlockFace::Top => y,
+                _ => s_dimensions - y,
+            };
+
+            // Don't let the top fall "below" the 45, but also don't let it go "above" the first shared 45.
+            // This probably won't interfere with anything before the first shared 45 is discovered bc of the loop order.
+            z_top = z_top.max(x_height).max(y_height);
+            z_top = z_top.min(first_all_45);
+
+            // Get smallest top height that's on the 45 for x, y, and z.
+            if x_top[i][j] == j && y_top[i][j] == j && z_top == j && first_all_45 == s_dimensions {
+                first_all_45 = z_top;
+            };
+
+            for k in 0..CHUNK_DIMENSIONS {
+                // Don't let the top rise "above" the first shared 45.
+                let x_top = x_top[j][k].min(first_all_45);
+                let y_top = y_top[i][k].min(first_all_45);
+
+                let z = sz + k;
+                let block_up = Planet::get_planet_face_without_structure(
+                    x,
+                    y,
+                    z,
+                    s_dimensions,
+                    s_dimensions,
+                    s_dimensions,
+                );
+
+                let z_height = match z_up {
+                    BlockFace::Front => z,
+                    _ => s_dimensions - z,
+                };
+
+                let at_top = [x_height == x_top, y_height == y_top, z_height == z_top];
+
+                // The surface is only where exactly one of the three heights is at its top
+                if x_height <= x_top
+                    && y_height <= y_top
+                    && z_height <= z_top
+                    && at_top.iter().filter(|&&x| x).count() <= 1
+                {
+                    let depth = (x_top - x_height)
+                        .min(y_top - y_height)
+                        .min(z_top - z_height);
+
+                    let block_up = if depth != 0 {
+                        block_up
+                    } else if at_top[0] {
+                        x_up
+                    } else if at_top[1] {
+                        y_up
+                    } else {
+                        z_up
+                    };
+
+                    chunk.set_block_at(i, j, k, blocks.block_at_depth(depth), block_up);
+                } else if x_height <= sea_level && y_height <= sea_level && z_height <= sea_level {
+                    if let Some((sea, _)) = &blocks.sea {
+                        chunk.set_block_at(i, j, k, sea, block_up);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Starts generating the chunks of planets with this biosphere in the background
+pub fn generate_planet<T: Component, E: TGenerateChunkEvent + Send + Sync + 'static>(
+    mut query: Query<(&mut Structure, &GenerationOrigin, &Planet, &BiosphereMarker)>,
+    mut generating: ResMut<GeneratingChunks<T>>,
+    mut events: EventReader<E>,
+    noise_generator: Res<ResourceWrapper<noise::OpenSimplex>>,
+    blocks: Res<Registry<Block>>,
+    definitions: Res<BiosphereDefinitions>,
+    server_seed: Res<ServerSeed>,
+) {
+    let chunks = events
+        .iter()
+        .filter_map(|ev| {
+            let (x, y, z) = ev.get_chunk_coordinates();
+            let structure_entity = ev.get_structure_entity();
+
+            if let Ok((mut structure, _, _, _)) = query.get_mut(structure_entity) {
+                Some((
+                    structure_entity,
+                    structure.take_or_create_chunk_for_loading(x, y, z),
+                ))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<(Entity, Chunk)>>();
+
+    if chunks.is_empty() {
+        return;
+    }
+
+    let thread_pool = AsyncComputeTaskPool::get();
+
+    // Most chunks being generated at once are from the same planet, so only look up each biosphere's blocks once
+    let mut biosphere_blocks_cache = HashMap::<&str, Option<BiosphereBlocks>>::new();
+
+    let chunks = chunks
+        .into_iter()
+        .flat_map(|(structure_entity, chunk)| {
+            let Ok((structure, origin, planet, biosphere)) = query.get(structure_entity) else {
+                return None;
+            };
+
+            let definition = definitions.get(biosphere.biosphere_name())?;
+
+            let biosphere_blocks = biosphere_blocks_cache
+                .entry(&definition.id)
+                .or_insert_with(|| BiosphereBlocks::new(definition, &blocks))
+                .clone()?;
+
+            let ores = OreDistribution::new(
+                &definition.ores,
+                &blocks,
+                &server_seed,
+                &origin.location().sector(),
+                planet.temperature(),
+            );
+
+            let rng = get_rng_for_chunk(
+                &server_seed,
+                &origin.location().sector(),
+                (
+                    chunk.structure_x(),
+                    chunk.structure_y(),
+                    chunk.structure_z(),
+                ),
+            );
+
+            let s_width = structure.blocks_width();
+            let s_height = structure.blocks_height();
+            let s_length = structure.blocks_length();
+            // Planets move, so their terrain is always generated from where they were spawned
+            let location = *origin.location();
+
+            Some((
+                chunk,
+                s_width,
+                s_height,
+                s_length,
+                location,
+                biosphere_blocks,
+                ores,
+                rng,
+                structure_entity,
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    println!("Doing {} chunks!", chunks.len());
+
+    for (
+        mut chunk,
+        s_width,
+        s_height,
+        s_length,
+        location,
+        biosphere_blocks,
+        ores,
+        mut rng,
+        structure_entity,
+    ) in chunks
+    {
+        // Not super expensive, only copies about 256 8 bit values.
+        // Still not ideal though.
+        let noise_generator = **noise_generator;
+
+        let task = thread_pool.spawn(async move {
+            let timer = UtilsTimer::start();
+            let blocks = &biosphere_blocks;
+
+            let middle_air_start = s_height - CHUNK_DIMENSIONS * 5;
+
+            let actual_pos = location.absolute_coords_f64();
+
+            let structure_z = actual_pos.z;
+            let structure_y = actual_pos.y;
+            let structure_x = actual_pos.x;
+
+            // To save multiplication operations later.
+            let sz = chunk.structure_z() * CHUNK_DIMENSIONS;
+            let sy = chunk.structure_y() * CHUNK_DIMENSIONS;
+            let sx = chunk.structure_x() * CHUNK_DIMENSIONS;
+
+            // Get all possible planet faces from the chunk corners. May or may not break near the center of the planet.
+            let mut planet_faces = HashSet::new();
+            for z in 0..=1 {
+                for y in 0..=1 {
+                    for x in 0..=1 {
+                        planet_faces.insert(Planet::get_planet_face_without_structure(
+                            sx + x * CHUNK_DIMENSIONS,
+                            sy + y * CHUNK_DIMENSIONS,
+                            sz + z * CHUNK_DIMENSIONS,
+                            s_width,
+                            s_height,
+                            s_length,
+                        ));
+                    }
+                }
+            }
+
+            // Support for the middle of the planet.
+            if planet_faces.contains(&BlockFace::Top) {
+                planet_faces.remove(&BlockFace::Bottom);
+            }
+            if planet_faces.contains(&BlockFace::Right) {
+                planet_faces.remove(&BlockFace::Left);
+            }
+            if planet_faces.contains(&BlockFace::Front) {
+                planet_faces.remove(&BlockFace::Back);
+            }
+
+            if planet_faces.len() == 1 {
+                // Chunks on only one face.
+                do_face(
+                    (sx, sy, sz),
+                    (structure_x, structure_y, structure_z),
+                    s_height,
+                    &noise_generator,
+                    middle_air_start,
+                    blocks,
+                    &mut chunk,
+                    *planet_faces.iter().next().unwrap(),
+                );
+            } else if planet_faces.len() == 2 {
+                // Chunks on an edge.
+                let mut face_iter = planet_faces.iter();
+                do_edge(
+                    (sx, sy, sz),
+                    (structure_x, structure_y, structure_z),
+                    s_height,
+                    &noise_generator,
+                    middle_air_start,
+                    blocks,
+                    &mut chunk,
+                    *face_iter.next().unwrap(),
+                    *face_iter.next().unwrap(),
+                );
+            } else {
+                let x_face = if planet_faces.contains(&BlockFace::Right) {
+                    BlockFace::Right
+                } else {
+                    BlockFace::Left
+                };
+                let y_face = if planet_faces.contains(&BlockFace::Top) {
+                    BlockFace::Top
+                } else {
+                    BlockFace::Bottom
+                };
+                let z_face = if planet_faces.contains(&BlockFace::Front) {
+                    BlockFace::Front
+                } else {
+                    BlockFace::Back
+                };
+                do_corner(
+                    (sx, sy, sz),
+                    (structure_x, structure_y, structure_z),
+                    s_height,
+                    &noise_generator,
+                    middle_air_start,
+                    blocks,
+                    &mut chunk,
+                    x_face,
+                    y_face,
+                    z_face,
+                );
+            }
+
+            let chunk_coords = (
+                structure_x + sx as f64,
+                structure_y + sy as f64,
+                structure_z + sz as f64,
+            );
+
+            ores.replace_stone(&mut chunk, &blocks.stone, &noise_generator, chunk_coords);
+
+            // Caves are carved after the ores are placed, so they expose the ores they go through
+            if let Some(caves) = &blocks.caves {
+                caves.carve(
+                    &mut chunk,
+                    &noise_generator,
+                    chunk_coords,
+                    (sx, sy, sz),
+                    s_height,
+                    middle_air_start,
+                    &blocks.stone,
+                    blocks.sea.as_ref().map(|(sea, _)| sea),
+                );
+            }
+
+            let spilled_blocks = blocks.features.place(
+                &mut chunk,
+                &mut rng,
+                (sx, sy, sz),
+                s_height,
+                blocks.surface(),
+            );
+
+            timer.log_duration("Chunk: ");
+            (chunk, structure_entity, spilled_blocks)
+        });
+
+        generating.generating.push(GeneratingChunk::new(task));
+    }
+}
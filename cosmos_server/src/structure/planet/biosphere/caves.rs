@@ -0,0 +1,278 @@
+//! Carves caves, arches & overhangs out of a planet's terrain
+//!
+//! The terrain itself is a heightmap, so this is done afterwards by removing every solid block where
+//! 3d noise says there should be a cave. The noise is sampled using the absolute coordinates of each block,
+//! so caves line up perfectly between chunks & between the faces of a planet.
+//!
+//! Caves are made of two shapes:
+//! - Tunnels, which are where two different noise values are both close to 0
+//! - Caverns, which are where a third noise value is above a threshold
+//!
+//! Since ores are placed before caves are carved, any ore the caves pass through is left exposed on their walls.
+
+use cosmos_core::{
+    block::{blocks::AIR_BLOCK_ID, Block, BlockFace},
+    registry::{identifiable::Identifiable, Registry},
+    structure::{
+        chunk::{Chunk, CHUNK_DIMENSIONS},
+        planet::Planet,
+    },
+};
+use noise::NoiseFn;
+use serde::Deserialize;
+
+/// Where the noise for each cave shape is sampled, so they don't line up with the terrain, ores, or each other
+const FIRST_TUNNEL_NOISE_OFFSET: f64 = -10_000.0;
+const SECOND_TUNNEL_NOISE_OFFSET: f64 = -20_000.0;
+const CAVERN_NOISE_OFFSET: f64 = -30_000.0;
+
+#[derive(Debug, Clone, Deserialize)]
+/// Describes the caves carved into a biosphere's terrain
+pub struct CaveCarving {
+    /// How stretched out the tunnels are - smaller values make longer tunnels
+    #[serde(default = "default_tunnel_scale")]
+    pub tunnel_scale: f64,
+    /// How close to 0 the tunnel noise has to be - higher values make wider tunnels
+    #[serde(default = "default_tunnel_thickness")]
+    pub tunnel_thickness: f64,
+    /// How stretched out the caverns are - smaller values make bigger caverns
+    #[serde(default = "default_cavern_scale")]
+    pub cavern_scale: f64,
+    /// Between -1.0 and 1.0 - higher values make fewer caverns. Anything above 1.0 means no caverns.
+    #[serde(default = "default_cavern_threshold")]
+    pub cavern_threshold: f64,
+    /// How many blocks below the average terrain height caves can go
+    #[serde(default = "default_depth")]
+    pub depth: f64,
+    /// How many blocks above the average terrain height caves can go.
+    ///
+    /// Caves that reach into the hills are what make arches & overhangs.
+    #[serde(default = "default_ceiling")]
+    pub ceiling: f64,
+    /// If set, the stone on the floor of caves is replaced with this block
+    #[serde(default)]
+    pub floor: Option<String>,
+}
+
+fn default_tunnel_scale() -> f64 {
+    0.03
+}
+
+fn default_tunnel_thickness() -> f64 {
+    0.08
+}
+
+fn default_cavern_scale() -> f64 {
+    0.02
+}
+
+fn default_cavern_threshold() -> f64 {
+    0.7
+}
+
+fn default_depth() -> f64 {
+    64.0
+}
+
+fn default_ceiling() -> f64 {
+    8.0
+}
+
+#[derive(Debug, Clone)]
+/// A [`CaveCarving`] with its blocks taken from the block registry
+pub struct Caves {
+    carving: CaveCarving,
+    air: Block,
+    floor: Option<Block>,
+}
+
+impl Caves {
+    /// Returns None if any of the blocks this needs are missing
+    pub fn new(carving: &CaveCarving, blocks: &Registry<Block>) -> Option<Self> {
+        let floor = match &carving.floor {
+            Some(floor) => {
+                let Some(block) = blocks.from_id(floor) else {
+                    println!("[Caves] Missing cave floor block {floor}");
+                    return None;
+                };
+
+                Some(block.clone())
+            }
+            None => None,
+        };
+
+        Some(Self {
+            carving: carving.clone(),
+            air: blocks.from_numeric_id(AIR_BLOCK_ID).clone(),
+            floor,
+        })
+    }
+
+    /// Returns true if there should be a cave at these absolute block coordinates
+    fn is_cave(&self, noise: &noise::OpenSimplex, (x, y, z): (f64, f64, f64)) -> bool {
+        let carving = &self.carving;
+
+        let cavern_value = noise.get([
+            x * carving.cavern_scale + CAVERN_NOISE_OFFSET,
+            y * carving.cavern_scale + CAVERN_NOISE_OFFSET,
+            z * carving.cavern_scale + CAVERN_NOISE_OFFSET,
+        ]);
+
+        if cavern_value > carving.cavern_threshold {
+            return true;
+        }
+
+        let first_tunnel_value = noise.get([
+            x * carving.tunnel_scale + FIRST_TUNNEL_NOISE_OFFSET,
+            y * carving.tunnel_scale + FIRST_TUNNEL_NOISE_OFFSET,
+            z * carving.tunnel_scale + FIRST_TUNNEL_NOISE_OFFSET,
+        ]);
+
+        // Most blocks aren't in a tunnel, so this saves sampling the second noise most of the time
+        if first_tunnel_value.abs() >= carving.tunnel_thickness {
+            return false;
+        }
+
+        let second_tunnel_value = noise.get([
+            x * carving.tunnel_scale + SECOND_TUNNEL_NOISE_OFFSET,
+            y * carving.tunnel_scale + SECOND_TUNNEL_NOISE_OFFSET,
+            z * carving.tunnel_scale + SECOND_TUNNEL_NOISE_OFFSET,
+        ]);
+
+        second_tunnel_value.abs() < carving.tunnel_thickness
+    }
+
+    /// Carves the caves out of this already generated chunk.
+    ///
+    /// * `(cx, cy, cz)` The absolute coordinates of this chunk's (0, 0, 0) block
+    /// * `(sx, sy, sz)` The coordinates of this chunk's (0, 0, 0) block within the planet
+    /// * `s_dimensions` How many blocks wide the planet is
+    /// * `middle_air_start` The average height of the terrain
+    /// * `stone` Only this block is replaced by the cave floor
+    /// * `sea` This block is never carved, so seas don't end up with holes in them
+    pub fn carve(
+        &self,
+        chunk: &mut Chunk,
+        noise: &noise::OpenSimplex,
+        (cx, cy, cz): (f64, f64, f64),
+        (sx, sy, sz): (usize, usize, usize),
+        s_dimensions: usize,
+        middle_air_start: usize,
+        stone: &Block,
+        sea: Option<&Block>,
+    ) {
+        let lowest = middle_air_start as f64 - self.carving.depth;
+        let highest = middle_air_start as f64 + self.carving.ceiling;
+
+        // Coordinates are relative to this chunk, and can be outside of it
+        let cave_at = |x: i64, y: i64, z: i64| {
+            let (bx, by, bz) = (sx as i64 + x, sy as i64 + y, sz as i64 + z);
+
+            let in_planet = |c: i64| (0..s_dimensions as i64).contains(&c);
+
+            if !in_planet(bx) || !in_planet(by) || !in_planet(bz) {
+                return false;
+            }
+
+            let (bx, by, bz) = (bx as usize, by as usize, bz as usize);
+
+            let up = Planet::get_planet_face_without_structure(
+                bx,
+                by,
+                bz,
+                s_dimensions,
+                s_dimensions,
+                s_dimensions,
+            );
+
+            let height = height_on_face(up, (bx, by, bz), s_dimensions) as f64;
+
+            height >= lowest
+                && height <= highest
+                && self.is_cave(noise, (cx + x as f64, cy + y as f64, cz + z as f64))
+        };
+
+        let sea_id = sea.map(|sea| sea.id());
+
+        let index =
+            |x: usize, y: usize, z: usize| (z * CHUNK_DIMENSIONS + y) * CHUNK_DIMENSIONS + x;
+
+        let mut carved = vec![false; CHUNK_DIMENSIONS * CHUNK_DIMENSIONS * CHUNK_DIMENSIONS];
+
+        for z in 0..CHUNK_DIMENSIONS {
+            for y in 0..CHUNK_DIMENSIONS {
+                for x in 0..CHUNK_DIMENSIONS {
+                    if !cave_at(x as i64, y as i64, z as i64) {
+                        continue;
+                    }
+
+                    carved[index(x, y, z)] = true;
+
+                    let block = chunk.block_at(x, y, z);
+
+                    if block != AIR_BLOCK_ID && Some(block) != sea_id {
+                        let block_up = chunk.block_rotation(x, y, z);
+
+                        chunk.set_block_at(x, y, z, &self.air, block_up);
+                    }
+                }
+            }
+        }
+
+        let Some(floor) = &self.floor else {
+            return;
+        };
+
+        let in_chunk = |c: i64| (0..CHUNK_DIMENSIONS as i64).contains(&c);
+
+        for z in 0..CHUNK_DIMENSIONS {
+            for y in 0..CHUNK_DIMENSIONS {
+                for x in 0..CHUNK_DIMENSIONS {
+                    // Only stone is replaced, so the surface of the terrain is never covered in cave floor
+                    if carved[index(x, y, z)] || chunk.block_at(x, y, z) != stone.id() {
+                        continue;
+                    }
+
+                    let up = chunk.block_rotation(x, y, z);
+
+                    let (ax, ay, az) = above((x as i64, y as i64, z as i64), up);
+
+                    // Caves are all based off noise, so the block above can be checked even if it's in another chunk
+                    let cave_above = if in_chunk(ax) && in_chunk(ay) && in_chunk(az) {
+                        carved[index(ax as usize, ay as usize, az as usize)]
+                    } else {
+                        cave_at(ax, ay, az)
+                    };
+
+                    if cave_above {
+                        chunk.set_block_at(x, y, z, floor, up);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// How far this block is from the center of the planet, in the direction of this face
+fn height_on_face(up: BlockFace, (x, y, z): (usize, usize, usize), s_dimensions: usize) -> usize {
+    match up {
+        BlockFace::Top => y,
+        BlockFace::Bottom => s_dimensions - y,
+        BlockFace::Front => z,
+        BlockFace::Back => s_dimensions - z,
+        BlockFace::Right => x,
+        BlockFace::Left => s_dimensions - x,
+    }
+}
+
+/// The coordinates of the block on top of this one, if this face is up
+fn above((x, y, z): (i64, i64, i64), up: BlockFace) -> (i64, i64, i64) {
+    match up {
+        BlockFace::Top => (x, y + 1, z),
+        BlockFace::Bottom => (x, y - 1, z),
+        BlockFace::Front => (x, y, z + 1),
+        BlockFace::Back => (x, y, z - 1),
+        BlockFace::Right => (x + 1, y, z),
+        BlockFace::Left => (x - 1, y, z),
+    }
+}
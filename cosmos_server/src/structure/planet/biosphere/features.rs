@@ -0,0 +1,507 @@
+//! Places features such as trees & boulders on top of a biosphere's terrain
+//!
+//! Each chunk places its own features while it is being generated, using random numbers from
+//! [`get_rng_for_chunk`](crate::rng::get_rng_for_chunk), so a chunk always gets the same features.
+//!
+//! Features can be bigger than the chunk they start in. Any blocks that spill into a neighboring chunk are
+//! returned as [`FeatureBlock`]s, and stored in the planet's [`PendingFeatureBlocks`] until that chunk is
+//! generated or loaded. If that chunk is already loaded, the blocks are placed right away.
+
+use std::collections::HashMap;
+
+use bevy::prelude::{
+    App, Commands, Component, Entity, EventWriter, IntoSystemConfig, Query, With, Without,
+};
+use cosmos_core::{
+    block::{blocks::AIR_BLOCK_ID, Block, BlockFace},
+    events::block_events::BlockChangedEvent,
+    registry::{identifiable::Identifiable, Registry},
+    structure::{
+        chunk::{Chunk, CHUNK_DIMENSIONS},
+        planet::Planet,
+        ChunkState, Structure,
+    },
+};
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::{
+    loading::{begin_loading, done_loading, NeedsLoaded},
+    saving::{begin_saving, done_saving, NeedsSaved},
+    SerializedData,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+/// The different shapes of features
+pub enum FeatureKind {
+    /// A trunk of logs with a ball of leaves on top
+    Tree {
+        /// The block the trunk is made of
+        log: String,
+        /// The block the leaves are made of
+        leaves: String,
+        /// The shortest the trunk can be
+        min_height: usize,
+        /// The tallest the trunk can be
+        max_height: usize,
+        /// How far the leaves reach out from the trunk
+        leaf_radius: usize,
+    },
+    /// A ball of blocks, half buried in the ground
+    Boulder {
+        /// The block the boulder is made of
+        block: String,
+        /// The smallest the boulder's radius can be
+        min_radius: usize,
+        /// The biggest the boulder's radius can be
+        max_radius: usize,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// A feature a biosphere places on its surface
+pub struct FeatureDefinition {
+    /// What this feature looks like
+    #[serde(flatten)]
+    pub kind: FeatureKind,
+    /// On average, how many of these each chunk of the surface tries to place.
+    ///
+    /// Some tries will fail, such as ones over water or on steep slopes.
+    pub per_chunk: f32,
+}
+
+#[derive(Debug, Clone)]
+enum FeatureShape {
+    Tree {
+        log: Block,
+        leaves: Block,
+        min_height: usize,
+        max_height: usize,
+        leaf_radius: usize,
+    },
+    Boulder {
+        block: Block,
+        min_radius: usize,
+        max_radius: usize,
+    },
+}
+
+impl FeatureShape {
+    /// The blocks of this feature, as (sideways, up, sideways) offsets from the surface block it is on
+    fn blocks(&self, rng: &mut ChaCha8Rng) -> Vec<((i64, i64, i64), &Block)> {
+        let mut placed = vec![];
+
+        match self {
+            Self::Tree {
+                log,
+                leaves,
+                min_height,
+                max_height,
+                leaf_radius,
+            } => {
+                let height = rng.gen_range(*min_height..=*max_height.max(min_height)) as i64;
+                let radius = *leaf_radius as i64;
+
+                for h in 1..=height {
+                    placed.push(((0, h, 0), log));
+                }
+
+                for dh in -1..=radius {
+                    for a in -radius..=radius {
+                        for b in -radius..=radius {
+                            let dist_sqrd = a * a + b * b + dh * dh;
+
+                            if (a == 0 && b == 0 && dh <= 0) || dist_sqrd > radius * radius + 1 {
+                                continue;
+                            }
+
+                            // Randomly trim the outside of the leaves so every tree looks a bit different
+                            if dist_sqrd >= radius * radius && rng.gen_bool(0.5) {
+                                continue;
+                            }
+
+                            placed.push(((a, height + dh, b), leaves));
+                        }
+                    }
+                }
+            }
+            Self::Boulder {
+                block,
+                min_radius,
+                max_radius,
+            } => {
+                let radius = rng.gen_range(*min_radius..=*max_radius.max(min_radius)) as i64;
+
+                for h in -radius..=radius {
+                    for a in -radius..=radius {
+                        for b in -radius..=radius {
+                            if a * a + b * b + h * h <= radius * radius {
+                                // Sunk into the ground by one block so it doesn't float on slopes
+                                placed.push(((a, h + radius - 1, b), block));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        placed
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Feature {
+    shape: FeatureShape,
+    per_chunk: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+/// The [`FeatureDefinition`]s of a biosphere, with their blocks taken from the block registry
+pub struct Features {
+    features: Vec<Feature>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+/// A single block of a feature that has to be placed in a specific chunk
+pub struct FeatureBlock {
+    /// The coordinates of the chunk this block is in
+    pub chunk: (usize, usize, usize),
+    /// The block's coordinates within that chunk
+    pub coords: (usize, usize, usize),
+    /// The block's numeric id
+    pub block_id: u16,
+    /// The block's rotation
+    pub block_up: BlockFace,
+}
+
+impl FeatureBlock {
+    /// The coordinates of this block within its structure
+    pub fn structure_coords(&self) -> (usize, usize, usize) {
+        let (cx, cy, cz) = self.chunk;
+        let (x, y, z) = self.coords;
+
+        (
+            cx * CHUNK_DIMENSIONS + x,
+            cy * CHUNK_DIMENSIONS + y,
+            cz * CHUNK_DIMENSIONS + z,
+        )
+    }
+}
+
+/// Turns (sideways, up, sideways) offsets on this face into (x, y, z) offsets
+fn offset_on_face((a, h, b): (i64, i64, i64), up: BlockFace) -> (i64, i64, i64) {
+    match up {
+        BlockFace::Top => (a, h, b),
+        BlockFace::Bottom => (a, -h, b),
+        BlockFace::Front => (a, b, h),
+        BlockFace::Back => (a, b, -h),
+        BlockFace::Right => (h, a, b),
+        BlockFace::Left => (-h, a, b),
+    }
+}
+
+impl Features {
+    /// Returns None if any of the blocks these need are missing
+    pub fn new(definitions: &[FeatureDefinition], blocks: &Registry<Block>) -> Option<Self> {
+        let get_block = |id: &str| {
+            let block = blocks.from_id(id).cloned();
+
+            if block.is_none() {
+                println!("[Features] Missing block {id}");
+            }
+
+            block
+        };
+
+        let features = definitions
+            .iter()
+            .map(|definition| {
+                let shape = match &definition.kind {
+                    FeatureKind::Tree {
+                        log,
+                        leaves,
+                        min_height,
+                        max_height,
+                        leaf_radius,
+                    } => FeatureShape::Tree {
+                        log: get_block(log)?,
+                        leaves: get_block(leaves)?,
+                        min_height: *min_height,
+                        max_height: *max_height,
+                        leaf_radius: *leaf_radius,
+                    },
+                    FeatureKind::Boulder {
+                        block,
+                        min_radius,
+                        max_radius,
+                    } => FeatureShape::Boulder {
+                        block: get_block(block)?,
+                        min_radius: *min_radius,
+                        max_radius: *max_radius,
+                    },
+                };
+
+                Some(Feature {
+                    shape,
+                    per_chunk: definition.per_chunk,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self { features })
+    }
+
+    /// Places these features on the surface of this already generated chunk.
+    ///
+    /// Features are only placed on top of the `surface` block, & only if there is air above it within this chunk.
+    ///
+    /// * `(sx, sy, sz)` The coordinates of this chunk's (0, 0, 0) block within the planet
+    /// * `s_dimensions` How many blocks wide the planet is
+    ///
+    /// Returns the blocks that belong in other chunks.
+    pub fn place(
+        &self,
+        chunk: &mut Chunk,
+        rng: &mut ChaCha8Rng,
+        (sx, sy, sz): (usize, usize, usize),
+        s_dimensions: usize,
+        surface: &Block,
+    ) -> Vec<FeatureBlock> {
+        let mut spilled = vec![];
+
+        if self.features.is_empty() {
+            return spilled;
+        }
+
+        let half = CHUNK_DIMENSIONS / 2;
+        let up = Planet::get_planet_face_without_structure(
+            sx + half,
+            sy + half,
+            sz + half,
+            s_dimensions,
+            s_dimensions,
+            s_dimensions,
+        );
+
+        // Chunk coordinates of the block `h` blocks up the column at (a, b)
+        let column = |a: usize, h: usize, b: usize| {
+            let top = CHUNK_DIMENSIONS - 1;
+
+            match up {
+                BlockFace::Top => (a, h, b),
+                BlockFace::Bottom => (a, top - h, b),
+                BlockFace::Front => (a, b, h),
+                BlockFace::Back => (a, b, top - h),
+                BlockFace::Right => (h, a, b),
+                BlockFace::Left => (top - h, a, b),
+            }
+        };
+
+        let chunk_coords = (
+            sx / CHUNK_DIMENSIONS,
+            sy / CHUNK_DIMENSIONS,
+            sz / CHUNK_DIMENSIONS,
+        );
+
+        for feature in self.features.iter() {
+            let mut tries = feature.per_chunk.floor() as usize;
+            if rng.gen::<f32>() < feature.per_chunk.fract() {
+                tries += 1;
+            }
+
+            for _ in 0..tries {
+                let (a, b) = (
+                    rng.gen_range(0..CHUNK_DIMENSIONS),
+                    rng.gen_range(0..CHUNK_DIMENSIONS),
+                );
+
+                // Always generate the shape, even if it isn't placed, so one failed try doesn't change every feature after it
+                let shape = feature.shape.blocks(rng);
+
+                // The highest block in this column, as long as there is air above it in this chunk
+                let Some(surface_h) = (0..CHUNK_DIMENSIONS)
+                    .rev()
+                    .find(|&h| {
+                        let (x, y, z) = column(a, h, b);
+
+                        chunk.has_block_at(x, y, z)
+                    })
+                    .filter(|&h| h + 1 < CHUNK_DIMENSIONS)
+                else {
+                    continue;
+                };
+
+                let (lx, ly, lz) = column(a, surface_h, b);
+
+                // Also skips the edges of the planet, where the surface isn't facing the same way as the chunk
+                if chunk.block_at(lx, ly, lz) != surface.id()
+                    || chunk.block_rotation(lx, ly, lz) != up
+                {
+                    continue;
+                }
+
+                let anchor = ((sx + lx) as i64, (sy + ly) as i64, (sz + lz) as i64);
+
+                for (offset, feature_block) in shape {
+                    let (ox, oy, oz) = offset_on_face(offset, up);
+                    let (x, y, z) = (anchor.0 + ox, anchor.1 + oy, anchor.2 + oz);
+
+                    let in_planet = |c: i64| (0..s_dimensions as i64).contains(&c);
+                    if !in_planet(x) || !in_planet(y) || !in_planet(z) {
+                        continue;
+                    }
+
+                    let (x, y, z) = (x as usize, y as usize, z as usize);
+
+                    let spilled_block = FeatureBlock {
+                        chunk: (
+                            x / CHUNK_DIMENSIONS,
+                            y / CHUNK_DIMENSIONS,
+                            z / CHUNK_DIMENSIONS,
+                        ),
+                        coords: (
+                            x % CHUNK_DIMENSIONS,
+                            y % CHUNK_DIMENSIONS,
+                            z % CHUNK_DIMENSIONS,
+                        ),
+                        block_id: feature_block.id(),
+                        block_up: up,
+                    };
+
+                    if spilled_block.chunk == chunk_coords {
+                        place_in_chunk(chunk, spilled_block.coords, feature_block, up);
+                    } else {
+                        spilled.push(spilled_block);
+                    }
+                }
+            }
+        }
+
+        spilled
+    }
+}
+
+/// Features never replace other blocks, so they don't cut into the terrain or each other
+fn place_in_chunk(
+    chunk: &mut Chunk,
+    (x, y, z): (usize, usize, usize),
+    block: &Block,
+    block_up: BlockFace,
+) {
+    if chunk.block_at(x, y, z) == AIR_BLOCK_ID {
+        chunk.set_block_at(x, y, z, block, block_up);
+    }
+}
+
+#[derive(Component, Debug, Default, Serialize, Deserialize)]
+/// Blocks of features that belong in chunks of this planet that haven't been generated or loaded yet
+pub struct PendingFeatureBlocks {
+    chunks: HashMap<(usize, usize, usize), Vec<FeatureBlock>>,
+}
+
+impl PendingFeatureBlocks {
+    /// Places every block waiting on this chunk into it
+    pub fn apply_to(&mut self, chunk: &mut Chunk, blocks: &Registry<Block>) {
+        let coords = (
+            chunk.structure_x(),
+            chunk.structure_y(),
+            chunk.structure_z(),
+        );
+
+        if let Some(pending) = self.chunks.remove(&coords) {
+            for block in pending.iter() {
+                place_in_chunk(
+                    chunk,
+                    block.coords,
+                    blocks.from_numeric_id(block.block_id),
+                    block.block_up,
+                );
+            }
+        }
+    }
+
+    /// Places blocks that spilled out of a chunk that was just generated.
+    ///
+    /// Blocks in chunks that are already loaded are placed right away, & the rest are saved until their chunk is ready.
+    pub fn add_spilled_blocks(
+        &mut self,
+        spilled: Vec<FeatureBlock>,
+        structure: &mut Structure,
+        blocks: &Registry<Block>,
+        event_writer: &mut EventWriter<BlockChangedEvent>,
+    ) {
+        for block in spilled {
+            let (cx, cy, cz) = block.chunk;
+
+            match structure.get_chunk_state(cx, cy, cz) {
+                ChunkState::Loaded => {
+                    let (x, y, z) = block.structure_coords();
+
+                    if !structure.has_block_at(x, y, z) {
+                        structure.set_block_at(
+                            x,
+                            y,
+                            z,
+                            blocks.from_numeric_id(block.block_id),
+                            block.block_up,
+                            blocks,
+                            Some(&mut *event_writer),
+                        );
+                    }
+                }
+                ChunkState::Loading | ChunkState::Unloaded => {
+                    self.chunks.entry(block.chunk).or_default().push(block);
+                }
+                ChunkState::Invalid => {}
+            }
+        }
+    }
+}
+
+/// Loaded planets get their pending blocks from their save data instead
+fn add_pending_feature_blocks(
+    query: Query<
+        Entity,
+        (
+            With<Planet>,
+            Without<PendingFeatureBlocks>,
+            Without<NeedsLoaded>,
+        ),
+    >,
+    mut commands: Commands,
+) {
+    for entity in query.iter() {
+        commands
+            .entity(entity)
+            .insert(PendingFeatureBlocks::default());
+    }
+}
+
+fn on_save_structure(
+    mut query: Query<(&mut SerializedData, &PendingFeatureBlocks), With<NeedsSaved>>,
+) {
+    for (mut s_data, pending) in query.iter_mut() {
+        s_data.serialize_data("cosmos:pending_feature_blocks", pending);
+    }
+}
+
+fn on_load_structure(
+    query: Query<(Entity, &SerializedData), With<NeedsLoaded>>,
+    mut commands: Commands,
+) {
+    for (entity, s_data) in query.iter() {
+        if let Some(pending) =
+            s_data.deserialize_data::<PendingFeatureBlocks>("cosmos:pending_feature_blocks")
+        {
+            commands.entity(entity).insert(pending);
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_system(add_pending_feature_blocks)
+        .add_system(on_save_structure.after(begin_saving).before(done_saving))
+        .add_system(on_load_structure.after(begin_loading).before(done_loading));
+}
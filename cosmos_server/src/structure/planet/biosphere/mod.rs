@@ -18,6 +18,7 @@ use cosmos_core::{
     },
 };
 use rand::Rng;
+use serde::Deserialize;
 
 use crate::{
     init::init_world::ServerSeed,
@@ -30,9 +31,14 @@ use crate::{
     state::GameState,
 };
 
+use self::features::FeatureBlock;
+
 use super::generation::planet_generator::check_needs_generated_system;
 
-pub mod grass_biosphere;
+pub mod biosphere_definition;
+pub mod biosphere_generation;
+pub mod caves;
+pub mod features;
 pub mod test_all_stone_biosphere;
 
 #[derive(Debug)]
@@ -46,6 +52,12 @@ struct NeedsBiosphereEvent {
 pub trait TGenerateChunkEvent {
     /// Creates the generate chunk event
     fn new(x: usize, y: usize, z: usize, structure_entity: Entity) -> Self;
+
+    /// The structure whose chunk needs generated
+    fn get_structure_entity(&self) -> Entity;
+
+    /// The coordinates of the chunk that needs generated
+    fn get_chunk_coordinates(&self) -> (usize, usize, usize);
 }
 
 /// This has to be redone.
@@ -61,7 +73,9 @@ pub trait TBiosphere<T: Component, E: TGenerateChunkEvent> {
 /// Use this to asynchronously generate chunks
 pub struct GeneratingChunk<T: Component> {
     /// The task responsible for this chunk
-    pub task: Task<(Chunk, Entity)>,
+    ///
+    /// This gives the chunk, its structure, & any blocks of features that spilled out of it
+    pub task: Task<(Chunk, Entity, Vec<FeatureBlock>)>,
     phantom: PhantomData<T>,
 }
 
@@ -76,7 +90,7 @@ impl<T: Component> GeneratingChunk<T> {
     /// Creates a GeneratingChunk instance
     ///
     /// Make sure to add this to an entity & query it to check once it's finished.
-    pub fn new(task: Task<(Chunk, Entity)>) -> Self {
+    pub fn new(task: Task<(Chunk, Entity, Vec<FeatureBlock>)>) -> Self {
         Self {
             task,
             phantom: PhantomData,
@@ -88,52 +102,73 @@ impl<T: Component> GeneratingChunk<T> {
 ///
 /// T: The biosphere's marker component type
 /// E: The biosphere's generate chunk event type
+///
+/// Multiple biospheres can share the same marker component & event, such as every biosphere
+/// created from a [`biosphere_definition::BiosphereDefinition`].
 pub fn register_biosphere<
     T: Component + Default,
     E: Send + Sync + 'static + TGenerateChunkEvent,
 >(
     app: &mut App,
-    biosphere_id: &'static str,
+    biosphere_id: impl Into<String>,
     temperature_range: TemperatureRange,
 ) {
-    app.add_event::<E>()
-        .add_startup_system(move |mut registry: ResMut<BiosphereTemperatureRegistry>| {
-            registry.register(biosphere_id.to_owned(), temperature_range);
-        })
-        .add_systems((
-            // Adds this biosphere's marker component to anything that needs generated
-            (move |mut event_reader: EventReader<NeedsBiosphereEvent>, mut commands: Commands| {
-                for ev in event_reader.iter() {
-                    if ev.biosphere_id == biosphere_id {
-                        commands.entity(ev.entity).insert(T::default());
-                    }
+    let biosphere_id: String = biosphere_id.into();
+
+    // Only add the systems for these types once, even if they are shared by many biospheres
+    if !app.world.contains_resource::<GeneratingChunks<T>>() {
+        app.add_event::<E>()
+            .add_system(
+                // Checks if any blocks need generated for this biosphere
+                check_needs_generated_system::<E, T>.in_set(OnUpdate(GameState::Playing)),
+            )
+            .insert_resource(GeneratingChunks::<T>::default());
+    }
+
+    let registered_id = biosphere_id.clone();
+    let marker_id = biosphere_id.clone();
+    let save_id = biosphere_id.clone();
+    let load_id = biosphere_id;
+
+    app.add_startup_system(move |mut registry: ResMut<BiosphereTemperatureRegistry>| {
+        registry.register(registered_id.clone(), temperature_range);
+    })
+    .add_systems((
+        // Adds this biosphere's marker component to anything that needs generated
+        (move |mut event_reader: EventReader<NeedsBiosphereEvent>, mut commands: Commands| {
+            for ev in event_reader.iter() {
+                if ev.biosphere_id == marker_id {
+                    commands.entity(ev.entity).insert(T::default());
                 }
-            }),
-            // Saves this biosphere when the structure is saved
-            (|mut query: Query<&mut SerializedData, (With<NeedsSaved>, With<T>)>| {
-                for mut sd in query.iter_mut() {
-                    sd.serialize_data(biosphere_id.to_string(), &true);
+            }
+        }),
+        // Saves this biosphere when the structure is saved
+        (move |mut query: Query<
+            (&mut SerializedData, &BiosphereMarker),
+            (With<NeedsSaved>, With<T>),
+        >| {
+            for (mut sd, biosphere) in query.iter_mut() {
+                if biosphere.biosphere_name() == save_id {
+                    sd.serialize_data(save_id.clone(), &true);
                 }
-            })
-            .after(begin_saving)
-            .before(done_saving),
-            // Loads this biosphere when the structure is loaded
-            (move |query: Query<(Entity, &SerializedData), With<NeedsLoaded>>,
-                   mut commands: Commands| {
-                for (entity, sd) in query.iter() {
-                    if sd.deserialize_data::<bool>(biosphere_id).unwrap_or(false) {
-                        commands
-                            .entity(entity)
-                            .insert((T::default(), BiosphereMarker::new(biosphere_id)));
-                    }
+            }
+        })
+        .after(begin_saving)
+        .before(done_saving),
+        // Loads this biosphere when the structure is loaded
+        (move |query: Query<(Entity, &SerializedData), With<NeedsLoaded>>,
+               mut commands: Commands| {
+            for (entity, sd) in query.iter() {
+                if sd.deserialize_data::<bool>(&load_id).unwrap_or(false) {
+                    commands
+                        .entity(entity)
+                        .insert((T::default(), BiosphereMarker::new(load_id.clone())));
                 }
-            })
-            .after(begin_loading)
-            .before(done_loading),
-            // Checks if any blocks need generated for this biosphere
-            check_needs_generated_system::<E, T>.in_set(OnUpdate(GameState::Playing)),
-        ))
-        .insert_resource(GeneratingChunks::<T>::default());
+            }
+        })
+        .after(begin_loading)
+        .before(done_loading),
+    ));
 }
 
 fn add_biosphere(
@@ -165,13 +200,26 @@ fn add_biosphere(
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Deserialize)]
 /// Represents a range of temperatures
 pub struct TemperatureRange {
+    #[serde(default)]
     low: f32,
+    #[serde(default = "max_temperature")]
     high: f32,
 }
 
+fn max_temperature() -> f32 {
+    f32::MAX
+}
+
+impl Default for TemperatureRange {
+    /// Every possible temperature
+    fn default() -> Self {
+        Self::new(0.0, max_temperature())
+    }
+}
+
 impl TemperatureRange {
     /// Creates a new temperature range with the given low + high ranges.
     ///
@@ -217,6 +265,7 @@ pub(super) fn register(app: &mut App) {
         .insert_resource(BiosphereTemperatureRegistry::default())
         .add_system(add_biosphere);
 
-    grass_biosphere::register(app);
+    biosphere_definition::register(app);
+    features::register(app);
     test_all_stone_biosphere::register(app);
 }
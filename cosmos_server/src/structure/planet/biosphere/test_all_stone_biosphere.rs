@@ -1,7 +1,8 @@
 //! Used just for testing, this makes a planet all stone
 
 use bevy::prelude::{
-    App, Component, Entity, EventReader, EventWriter, IntoSystemConfig, OnUpdate, Query, Res,
+    App, Commands, Component, Entity, EventReader, EventWriter, IntoSystemConfigs, OnUpdate, Query,
+    Res, With, Without,
 };
 use cosmos_core::{
     block::{Block, BlockFace},
@@ -14,7 +15,7 @@ use cosmos_core::{
 };
 use rayon::prelude::{IntoParallelRefMutIterator, ParallelIterator};
 
-use crate::GameState;
+use crate::{structure::planet::lod::PlanetLod, GameState};
 
 use super::{register_biosphere, TBiosphere, TGenerateChunkEvent, TemperatureRange};
 
@@ -39,6 +40,14 @@ impl TGenerateChunkEvent for TestStoneChunkNeedsGeneratedEvent {
             structure_entity,
         }
     }
+
+    fn get_structure_entity(&self) -> Entity {
+        self.structure_entity
+    }
+
+    fn get_chunk_coordinates(&self) -> (usize, usize, usize) {
+        (self.x, self.y, self.z)
+    }
 }
 
 #[derive(Default)]
@@ -116,6 +125,20 @@ fn generate_planet(
     }
 }
 
+/// The planet is a solid cube, so every face is as high as the planet is big
+fn generate_lod(
+    query: Query<(Entity, &Structure), (With<TestStoneBiosphereMarker>, Without<PlanetLod>)>,
+    mut commands: Commands,
+) {
+    for (entity, structure) in query.iter() {
+        let s_dimensions = structure.blocks_height();
+
+        commands
+            .entity(entity)
+            .insert(PlanetLod::generate(s_dimensions, |_, _| s_dimensions));
+    }
+}
+
 pub(super) fn register(app: &mut App) {
     register_biosphere::<TestStoneBiosphereMarker, TestStoneChunkNeedsGeneratedEvent>(
         app,
@@ -123,5 +146,5 @@ pub(super) fn register(app: &mut App) {
         TemperatureRange::new(0.0, 0.0),
     );
 
-    app.add_system(generate_planet.in_set(OnUpdate(GameState::Playing)));
+    app.add_systems((generate_planet, generate_lod).in_set(OnUpdate(GameState::Playing)));
 }
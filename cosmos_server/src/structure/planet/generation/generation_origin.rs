@@ -0,0 +1,79 @@
+//! Where a planet's terrain is generated from
+//!
+//! Planets move along their orbits, so their [`Location`] changes every frame. If chunks sampled their noise & rng
+//! from it, chunks generated at different times wouldn't line up with each other. Instead, every planet keeps the
+//! location it was spawned at & generates all of its chunks from that.
+
+use bevy::prelude::{App, Commands, Component, Entity, IntoSystemConfig, Query, With};
+use cosmos_core::physics::location::Location;
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::{
+    loading::{begin_loading, done_loading, NeedsLoaded},
+    saving::{begin_saving, done_saving, NeedsSaved},
+    SerializedData,
+};
+
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+/// The location a planet's terrain is generated from, which never changes even as the planet moves
+pub struct GenerationOrigin(Location);
+
+impl GenerationOrigin {
+    /// Generates the planet as if it were at this location
+    pub fn new(location: Location) -> Self {
+        Self(location)
+    }
+
+    /// The location the planet's terrain is generated from
+    pub fn location(&self) -> &Location {
+        &self.0
+    }
+}
+
+fn on_save_generation_origin(
+    mut query: Query<(&mut SerializedData, &GenerationOrigin), With<NeedsSaved>>,
+) {
+    for (mut s_data, origin) in query.iter_mut() {
+        s_data.serialize_data("cosmos:generation_origin", origin);
+    }
+}
+
+fn on_load_generation_origin(
+    query: Query<(Entity, &SerializedData), With<NeedsLoaded>>,
+    mut commands: Commands,
+) {
+    for (entity, s_data) in query.iter() {
+        if !s_data
+            .deserialize_data::<bool>("cosmos:is_planet")
+            .unwrap_or(false)
+        {
+            continue;
+        }
+
+        // Planets saved before they kept their origin are generated from wherever they were saved
+        let origin = s_data
+            .deserialize_data::<GenerationOrigin>("cosmos:generation_origin")
+            .or_else(|| {
+                s_data
+                    .deserialize_data::<Location>("cosmos:location")
+                    .map(GenerationOrigin::new)
+            });
+
+        if let Some(origin) = origin {
+            commands.entity(entity).insert(origin);
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_system(
+        on_save_generation_origin
+            .after(begin_saving)
+            .before(done_saving),
+    )
+    .add_system(
+        on_load_generation_origin
+            .after(begin_loading)
+            .before(done_loading),
+    );
+}
@@ -2,8 +2,10 @@
 
 use bevy::prelude::App;
 
+pub mod generation_origin;
 pub mod planet_generator;
 
 pub(super) fn register(app: &mut App) {
+    generation_origin::register(app);
     planet_generator::register(app);
 }
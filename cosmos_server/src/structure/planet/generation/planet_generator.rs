@@ -101,7 +101,7 @@ fn bounce_events(
 fn get_requested_chunk(
     mut event_reader: EventReader<RequestChunkEvent>,
     players: Query<&Location, With<Player>>,
-    mut structure: Query<(&mut Structure, &Location), With<Planet>>,
+    mut structure: Query<(&mut Structure, &Location, &Transform), With<Planet>>,
     mut event_writer: EventWriter<RequestChunkBouncer>,
     mut server: ResMut<RenetServer>,
     mut commands: Commands,
@@ -122,10 +122,11 @@ fn get_requested_chunk(
         .collect::<Vec<RequestChunkEvent>>()
         .par_iter()
         .for_each(|ev| {
-            if let Ok((structure, loc)) = structure.get(ev.structure_entity) {
+            if let Ok((structure, loc, transform)) = structure.get(ev.structure_entity) {
                 let (cx, cy, cz) = ev.chunk_coords;
 
-                let cpos = structure.chunk_relative_position(cx, cy, cz);
+                // Planets spin, so the chunk's offset has to be rotated along with the planet
+                let cpos = transform.rotation * structure.chunk_relative_position(cx, cy, cz);
 
                 let chunk_loc = *loc + cpos;
 
@@ -209,7 +210,7 @@ fn get_requested_chunk(
     }
 
     for (entity, (cx, cy, cz), ev) in todo.lock().expect("Failed to lock").take().unwrap() {
-        let Ok((mut structure, _)) = structure.get_mut(entity) else {
+        let Ok((mut structure, _, _)) = structure.get_mut(entity) else {
             continue;
         };
 
@@ -226,32 +227,25 @@ const RENDER_DISTANCE: i32 = 3;
 
 fn generate_chunks_near_players(
     players: Query<&Location, With<Player>>,
-    mut planets: Query<(&Location, &mut Structure, Entity), With<Planet>>,
+    mut planets: Query<(&Location, &Transform, &mut Structure, Entity), With<Planet>>,
     mut commands: Commands,
 ) {
     for player in players.iter() {
         let mut best_planet = None;
         let mut best_dist = f32::INFINITY;
-        for (location, structure, entity) in planets.iter_mut() {
+        for (location, transform, structure, entity) in planets.iter_mut() {
             let dist = location.distance_sqrd(player);
             if dist < best_dist {
                 best_dist = dist;
-                best_planet = Some((location, structure, entity));
+                best_planet = Some((location, transform, structure, entity));
             }
         }
 
-        if let Some((location, mut best_planet, entity)) = best_planet {
-            let player_relative_position: Vec3 = (*player - *location).into();
-            let (px, py, pz) = best_planet.relative_coords_to_local_coords(
-                player_relative_position.x,
-                player_relative_position.y,
-                player_relative_position.z,
-            );
-
-            let (px, py, pz) = (
-                (px as f32 / CHUNK_DIMENSIONSF).floor() as i32,
-                (py as f32 / CHUNK_DIMENSIONSF).floor() as i32,
-                (pz as f32 / CHUNK_DIMENSIONSF).floor() as i32,
+        if let Some((location, transform, mut best_planet, entity)) = best_planet {
+            let (px, py, pz) = Planet::chunk_coords_at(
+                &best_planet,
+                transform.rotation,
+                (*player - *location).into(),
             );
 
             let rd = RENDER_DISTANCE;
@@ -309,11 +303,21 @@ fn mark_chunk_for_generation(
 
 fn unload_chunks_far_from_players(
     players: Query<&Location, With<Player>>,
-    mut planets: Query<(&Location, &mut Structure, Entity, Option<&EntityId>), With<Planet>>,
+    mut planets: Query<
+        (
+            &Location,
+            &Transform,
+            &mut Structure,
+            Entity,
+            Option<&EntityId>,
+            Option<&SaveFileIdentifier>,
+        ),
+        With<Planet>,
+    >,
     mut commands: Commands,
 ) {
     let mut potential_chunks = HashMap::<Entity, HashSet<(usize, usize, usize)>>::new();
-    for (_, planet, entity, _) in planets.iter() {
+    for (_, _, planet, entity, _, _) in planets.iter() {
         let mut set = HashSet::new();
 
         for chunk in planet.all_chunks_iter(false) {
@@ -335,26 +339,19 @@ fn unload_chunks_far_from_players(
     for player in players.iter() {
         let mut best_planet = None;
         let mut best_dist = f32::INFINITY;
-        for (location, structure, entity, entity_id) in planets.iter_mut() {
+        for (location, transform, structure, entity, entity_id, _) in planets.iter_mut() {
             let dist = location.distance_sqrd(player);
             if dist < best_dist {
                 best_dist = dist;
-                best_planet = Some((location, structure, entity, entity_id));
+                best_planet = Some((location, transform, structure, entity, entity_id));
             }
         }
 
-        if let Some((location, best_planet, entity, _)) = best_planet {
-            let player_relative_position: Vec3 = (*player - *location).into();
-            let (px, py, pz) = best_planet.relative_coords_to_local_coords(
-                player_relative_position.x,
-                player_relative_position.y,
-                player_relative_position.z,
-            );
-
-            let (px, py, pz) = (
-                (px as f32 / CHUNK_DIMENSIONSF).floor() as i32,
-                (py as f32 / CHUNK_DIMENSIONSF).floor() as i32,
-                (pz as f32 / CHUNK_DIMENSIONSF).floor() as i32,
+        if let Some((location, transform, best_planet, entity, _)) = best_planet {
+            let (px, py, pz) = Planet::chunk_coords_at(
+                &best_planet,
+                transform.rotation,
+                (*player - *location).into(),
             );
 
             let rd = RENDER_DISTANCE + 1;
@@ -381,7 +378,9 @@ fn unload_chunks_far_from_players(
     }
 
     for (planet, set) in potential_chunks {
-        if let Ok((location, mut structure, _, entity_id)) = planets.get_mut(planet) {
+        if let Ok((location, _, mut structure, _, entity_id, structure_svi)) =
+            planets.get_mut(planet)
+        {
             let mut needs_id = false;
 
             let entity_id = if let Some(x) = entity_id {
@@ -399,8 +398,13 @@ fn unload_chunks_far_from_players(
                         chunk.structure_z(),
                     );
 
-                    commands.spawn((
-                        SaveChunk(chunk),
+                    // Planets move, so the sector they were saved in is used rather than the one they're in now
+                    let svi = if let Some(structure_svi) = structure_svi {
+                        SaveFileIdentifier::as_child(
+                            format!("{cx}_{cy}_{cz}"),
+                            structure_svi.clone(),
+                        )
+                    } else {
                         SaveFileIdentifier::as_child(
                             format!("{cx}_{cy}_{cz}"),
                             SaveFileIdentifier::new(
@@ -408,7 +412,12 @@ fn unload_chunks_far_from_players(
                                 entity_id.clone(),
                                 None,
                             ),
-                        ),
+                        )
+                    };
+
+                    commands.spawn((
+                        SaveChunk(chunk),
+                        svi,
                         NeedsSaved,
                         NeedsUnloaded,
                         NoSendEntity,
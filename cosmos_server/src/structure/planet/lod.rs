@@ -0,0 +1,113 @@
+//! Generates the coarse terrain clients use to draw planets from far away & sends it to them
+
+use bevy::prelude::*;
+use bevy_renet::renet::RenetServer;
+use cosmos_core::{
+    block::BlockFace,
+    netty::{cosmos_encoder, NettyChannel},
+    structure::planet::{
+        lod::{lod_sample_coordinate, LOD_RESOLUTION},
+        planet_netty::PlanetServerMessages,
+        Planet,
+    },
+};
+
+use crate::netty::sync::{entities::RequestedEntityEvent, interest::ClientInterest};
+
+/// How long a request waits for a planet's terrain to be generated before it's given up on
+const MAX_LOD_WAIT_SEC: f32 = 30.0;
+
+#[derive(Component, Debug)]
+/// The coarse terrain of a planet, see [`cosmos_core::structure::planet::lod`]
+///
+/// Each biosphere adds this to its planets based off its own terrain.
+pub struct PlanetLod {
+    /// The heights of every face, indexed by [`BlockFace::index`]
+    faces: [Vec<u16>; 6],
+}
+
+impl PlanetLod {
+    /// Samples the terrain of every face of a planet
+    ///
+    /// * `s_dimensions` The size of the planet in blocks
+    /// * `height_at` Returns the height of the terrain at these coordinates of a face, see [`cosmos_core::structure::planet::lod::lod_face_coordinates`]
+    pub fn generate(
+        s_dimensions: usize,
+        height_at: impl Fn(BlockFace, (usize, usize)) -> usize,
+    ) -> Self {
+        let faces = [0, 1, 2, 3, 4, 5].map(|index| {
+            let face = BlockFace::from_index(index);
+
+            let mut heights = Vec::with_capacity(LOD_RESOLUTION * LOD_RESOLUTION);
+
+            for v in 0..LOD_RESOLUTION {
+                for u in 0..LOD_RESOLUTION {
+                    let coords = (
+                        lod_sample_coordinate(u, s_dimensions),
+                        lod_sample_coordinate(v, s_dimensions),
+                    );
+
+                    heights.push(height_at(face, coords).min(u16::MAX as usize) as u16);
+                }
+            }
+
+            heights
+        });
+
+        Self { faces }
+    }
+}
+
+fn send_lods(
+    mut event_reader: EventReader<RequestedEntityEvent>,
+    mut waiting: Local<Vec<(RequestedEntityEvent, f32)>>,
+    query: Query<Option<&PlanetLod>, With<Planet>>,
+    interest: Res<ClientInterest>,
+    mut server: ResMut<RenetServer>,
+    time: Res<Time>,
+) {
+    let mut requests = std::mem::take(&mut *waiting);
+    requests.extend(event_reader.iter().map(|ev| (*ev, 0.0)));
+
+    for (ev, waited) in requests {
+        // The client disconnected or moved away, so it no longer needs this planet
+        if !interest.is_relevant(ev.client_id, ev.entity) {
+            continue;
+        }
+
+        match query.get(ev.entity) {
+            Ok(Some(lod)) => {
+                for (index, heights) in lod.faces.iter().enumerate() {
+                    server.send_message(
+                        ev.client_id,
+                        NettyChannel::Planets.id(),
+                        cosmos_encoder::serialize(&PlanetServerMessages::LodFace {
+                            entity: ev.entity,
+                            face: BlockFace::from_index(index),
+                            heights: heights.clone(),
+                        }),
+                    );
+                }
+            }
+            // The biosphere hasn't generated this planet's terrain yet
+            Ok(None) => {
+                let waited = waited + time.delta_seconds();
+
+                if waited < MAX_LOD_WAIT_SEC {
+                    waiting.push((ev, waited));
+                } else {
+                    println!(
+                        "Gave up sending the LOD of planet {:?} to client {} - its terrain was never generated",
+                        ev.entity, ev.client_id
+                    );
+                }
+            }
+            // Not a planet, or it was despawned
+            Err(_) => {}
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_system(send_lods);
+}
@@ -5,6 +5,7 @@ use bevy::prelude::*;
 pub mod biosphere;
 mod chunk;
 pub mod generation;
+pub mod lod;
 mod persistence;
 pub mod server_planet_builder;
 mod sync;
@@ -15,4 +16,5 @@ pub(super) fn register(app: &mut App) {
     sync::register(app);
     generation::register(app);
     chunk::register(app);
+    lod::register(app);
 }
@@ -3,8 +3,10 @@ use std::fs;
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::PhysicsWorld;
 use cosmos_core::{
+    block::Block,
     netty::{cosmos_encoder, NoSendEntity},
     physics::location::Location,
+    registry::Registry,
     structure::{
         chunk::{Chunk, ChunkEntity},
         planet::{planet_builder::TPlanetBuilder, Planet},
@@ -20,7 +22,8 @@ use crate::persistence::{
 };
 
 use super::{
-    generation::planet_generator::ChunkNeedsGenerated, server_planet_builder::ServerPlanetBuilder,
+    biosphere::features::PendingFeatureBlocks, generation::planet_generator::ChunkNeedsGenerated,
+    server_planet_builder::ServerPlanetBuilder,
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -171,13 +174,19 @@ fn populate_chunks(
 
 fn load_chunk(
     query: Query<(Entity, &SerializedData, &ChunkEntity), With<NeedsLoaded>>,
-    mut structure_query: Query<&mut Structure>,
+    mut structure_query: Query<(&mut Structure, Option<&mut PendingFeatureBlocks>)>,
     mut chunk_init_event: EventWriter<ChunkInitEvent>,
+    blocks: Res<Registry<Block>>,
     mut commands: Commands,
 ) {
     for (entity, sd, ce) in query.iter() {
-        if let Some(chunk) = sd.deserialize_data::<Chunk>("cosmos:chunk") {
-            if let Ok(mut structure) = structure_query.get_mut(ce.structure_entity) {
+        if let Some(mut chunk) = sd.deserialize_data::<Chunk>("cosmos:chunk") {
+            if let Ok((mut structure, pending)) = structure_query.get_mut(ce.structure_entity) {
+                // Features from chunks generated while this one was unloaded
+                if let Some(mut pending) = pending {
+                    pending.apply_to(&mut chunk, &blocks);
+                }
+
                 let (cx, cy, cz) = (
                     chunk.structure_x(),
                     chunk.structure_y(),
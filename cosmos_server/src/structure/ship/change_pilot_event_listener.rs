@@ -1,10 +1,10 @@
-use bevy::prelude::{App, Entity, EventReader, IntoSystemConfig, OnUpdate, ResMut};
+use bevy::prelude::{App, Entity, EventReader, IntoSystemConfig, OnUpdate, Res, ResMut};
 use bevy_renet::renet::RenetServer;
 use cosmos_core::netty::{
     cosmos_encoder, server_reliable_messages::ServerReliableMessages, NettyChannel,
 };
 
-use crate::state::GameState;
+use crate::{netty::sync::interest::ClientInterest, state::GameState};
 
 /// This event is sent whenever a ship's pilot is changed
 ///
@@ -17,9 +17,12 @@ pub struct ClientChangePilotEvent {
 fn event_listener(
     mut event_reader: EventReader<ClientChangePilotEvent>,
     mut server: ResMut<RenetServer>,
+    interest: Res<ClientInterest>,
 ) {
     for ev in event_reader.iter() {
-        server.broadcast_message(
+        interest.send_to_interested(
+            &mut server,
+            ev.structure_entity,
             NettyChannel::Reliable.id(),
             cosmos_encoder::serialize(&ServerReliableMessages::PilotChange {
                 structure_entity: ev.structure_entity,
@@ -17,7 +17,7 @@ use cosmos_core::{
     },
 };
 
-use crate::state::GameState;
+use crate::{netty::sync::interest::ClientInterest, state::GameState};
 
 const LASER_BASE_VELOCITY: f32 = 200.0;
 const LASER_SHOOT_SECONDS: f32 = 0.2;
@@ -36,6 +36,7 @@ fn update_system(
     time: Res<Time>,
     mut commands: Commands,
     mut server: ResMut<RenetServer>,
+    interest: Res<ClientInterest>,
 ) {
     for (mut cannon_system, system) in query.iter_mut() {
         if let Ok((systems, structure, location, global_transform, ship_velocity, physics_world)) =
@@ -89,7 +90,9 @@ fn update_system(
 
                             let color = Color::rgb(rand::random(), rand::random(), rand::random());
 
-                            server.broadcast_message(
+                            interest.send_to_interested(
+                                &mut server,
+                                system.structure_entity,
                                 NettyChannel::LaserCannonSystem.id(),
                                 cosmos_encoder::serialize(
                                     &ServerLaserCannonSystemMessages::CreateLaser {
@@ -3,7 +3,9 @@
 use bevy::prelude::App;
 
 mod laser_cannon_system;
+pub mod warp_drive_system;
 
 pub(super) fn register(app: &mut App) {
     laser_cannon_system::register(app);
+    warp_drive_system::register(app);
 }
@@ -0,0 +1,477 @@
+//! Charges warp drives & warps ships to far away places
+//!
+//! Once a pilot activates a charged warp drive, it spools up for a few seconds while the server
+//! loads everything around the destination. Then the ship, its pilot & anything inside of it are
+//! moved to the destination and put into a physics world there.
+//!
+//! Ships are never warped into a structure or star - if the destination is inside of one, it's moved
+//! to just outside of it instead.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, time::common_conditions::on_timer};
+use bevy_rapier3d::prelude::{PhysicsWorld, RapierContext, Velocity};
+use bevy_renet::renet::RenetServer;
+use cosmos_core::{
+    entities::player::Player,
+    netty::{
+        cosmos_encoder,
+        warp_drive_messages::{ClientWarpDriveMessages, ServerWarpDriveMessages},
+        NettyChannel,
+    },
+    physics::{
+        location::{Location, Sector, SectorUnit, SYSTEM_SECTORS},
+        player_world::{PlayerWorld, WorldWithin},
+    },
+    structure::{
+        planet::Planet,
+        ship::pilot::Pilot,
+        systems::{
+            energy_storage_system::EnergyStorageSystem, warp_drive_system::WarpDriveSystem,
+            StructureSystem, SystemActive, Systems,
+        },
+        Structure,
+    },
+    universe::star::STAR_RADIUS,
+};
+
+use crate::{
+    init::init_world::ServerSeed,
+    netty::{network_helpers::ServerLobby, validation::send_body_correction},
+    persistence::player_loading::PreloadAround,
+    physics::assign_player_world,
+    state::GameState,
+    universe::generation::{get_star_in_system, star_location_in_system},
+};
+
+/// How long a warp drive takes to spool up before the ship warps
+const SPOOL_UP_SECONDS: f32 = 10.0;
+
+/// How much space is left between a ship & whatever its destination was moved out of
+const DESTINATION_CLEARANCE: f32 = 50.0;
+
+/// How many times a destination can be moved out of something before the warp is cancelled
+const MAX_DESTINATION_NUDGES: usize = 8;
+
+#[derive(Component, Debug, Clone, Copy)]
+/// Where a ship's pilot wants it to warp to.
+///
+/// Without this, the ship will warp one system ahead of wherever it is facing.
+pub struct WarpTarget(pub Location);
+
+#[derive(Component, Debug, Clone, Copy)]
+/// This ship's warp drive is spooling up, and the ship will warp once it's done
+pub struct WarpSpoolUp {
+    destination: Location,
+    remaining: f32,
+}
+
+/// Where this ship would warp to if its warp drive was activated now
+fn warp_destination(
+    location: &Location,
+    transform: &GlobalTransform,
+    target: Option<&WarpTarget>,
+) -> Location {
+    if let Some(target) = target {
+        return target.0;
+    }
+
+    let ahead = (transform.forward() * SYSTEM_SECTORS as f32).round();
+
+    Location::new(
+        location.local,
+        location.sector()
+            + Sector::new(
+                ahead.x as SectorUnit,
+                ahead.y as SectorUnit,
+                ahead.z as SectorUnit,
+            ),
+    )
+}
+
+/// How far from its center a structure's blocks can reach
+fn structure_radius(structure: &Structure) -> f32 {
+    Vec3::new(
+        structure.blocks_width() as f32,
+        structure.blocks_height() as f32,
+        structure.blocks_length() as f32,
+    )
+    .length()
+        / 2.0
+}
+
+/// Everything a ship can't warp into around this destination, as their locations & radii
+///
+/// * `ship_entity` The ship warping, which is never in its own way
+fn warp_obstacles<'a>(
+    destination: &Location,
+    ship_entity: Entity,
+    structures: impl Iterator<Item = (Entity, &'a Location, &'a Structure)>,
+    server_seed: &ServerSeed,
+) -> Vec<(Location, f32)> {
+    let mut obstacles = structures
+        .filter(|(entity, _, _)| *entity != ship_entity)
+        .map(|(_, location, structure)| (*location, structure_radius(structure)))
+        .collect::<Vec<(Location, f32)>>();
+
+    let system = destination.get_system_coordinates();
+
+    if get_star_in_system(&system, server_seed).is_some() {
+        obstacles.push((star_location_in_system(&system), STAR_RADIUS));
+    }
+
+    obstacles
+}
+
+/// Moves the destination just outside of anything the ship would end up inside of.
+///
+/// Returns None if no clear spot was found nearby.
+fn clear_destination(
+    destination: Location,
+    ship_radius: f32,
+    obstacles: &[(Location, f32)],
+) -> Option<Location> {
+    let mut destination = destination;
+
+    for _ in 0..MAX_DESTINATION_NUDGES {
+        let blocking = obstacles.iter().find(|(location, radius)| {
+            let min_distance = radius + ship_radius;
+
+            location.distance_sqrd(&destination) < min_distance * min_distance
+        });
+
+        let Some((location, radius)) = blocking else {
+            return Some(destination);
+        };
+
+        // Pushed straight out from the center of whatever is in the way
+        let direction = location
+            .relative_coords_to(&destination)
+            .try_normalize()
+            .unwrap_or(Vec3::Y);
+
+        destination = *location + direction * (radius + ship_radius + DESTINATION_CLEARANCE);
+    }
+
+    None
+}
+
+fn listen_for_warp_messages(
+    mut server: ResMut<RenetServer>,
+    lobby: Res<ServerLobby>,
+    pilot_query: Query<&Pilot, With<Player>>,
+    mut commands: Commands,
+) {
+    for client_id in server.clients_id().into_iter() {
+        while let Some(message) = server.receive_message(client_id, NettyChannel::WarpDrive.id()) {
+            let Ok(msg) = cosmos_encoder::deserialize::<ClientWarpDriveMessages>(&message) else {
+                println!("Unable to deserialize warp drive message from client {client_id}");
+                continue;
+            };
+
+            let Some(pilot) = lobby
+                .player_from_id(client_id)
+                .and_then(|player_entity| pilot_query.get(player_entity).ok())
+            else {
+                continue;
+            };
+
+            match msg {
+                ClientWarpDriveMessages::SetWarpTarget { target } => {
+                    if let Some(target) = target {
+                        commands.entity(pilot.entity).insert(WarpTarget(target));
+                    } else {
+                        commands.entity(pilot.entity).remove::<WarpTarget>();
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn charge_warp_drives(
+    mut query: Query<(&mut WarpDriveSystem, &StructureSystem)>,
+    mut es_query: Query<&mut EnergyStorageSystem>,
+    systems: Query<&Systems>,
+    time: Res<Time>,
+) {
+    for (mut warp_drive, system) in query.iter_mut() {
+        let Ok(systems) = systems.get(system.structure_entity) else {
+            continue;
+        };
+
+        let Ok(mut energy_storage_system) = systems.query_mut(&mut es_query) else {
+            continue;
+        };
+
+        let wanted = (warp_drive.get_charge_rate() * time.delta_seconds())
+            .min(warp_drive.get_charge_capacity() - warp_drive.get_charge())
+            .min(energy_storage_system.get_energy());
+
+        if wanted > 0.0 {
+            let charged = warp_drive.increase_charge(wanted);
+            energy_storage_system.decrease_energy(charged);
+        }
+    }
+}
+
+fn start_warps(
+    query: Query<(&WarpDriveSystem, &StructureSystem), With<SystemActive>>,
+    ships: Query<
+        (&Location, &GlobalTransform, &Structure, Option<&WarpTarget>),
+        Without<WarpSpoolUp>,
+    >,
+    structures: Query<(Entity, &Location, &Structure)>,
+    server_seed: Res<ServerSeed>,
+    mut commands: Commands,
+) {
+    for (warp_drive, system) in query.iter() {
+        let Ok((location, transform, structure, target)) = ships.get(system.structure_entity)
+        else {
+            continue;
+        };
+
+        let destination = warp_destination(location, transform, target);
+
+        let obstacles = warp_obstacles(
+            &destination,
+            system.structure_entity,
+            structures.iter(),
+            &server_seed,
+        );
+
+        let Some(destination) =
+            clear_destination(destination, structure_radius(structure), &obstacles)
+        else {
+            println!("Warp cancelled - there is no room around {destination}");
+            continue;
+        };
+
+        if !warp_drive.can_warp(location, &destination) {
+            continue;
+        }
+
+        println!("Warp drive spooling up to warp to {destination}");
+
+        commands.entity(system.structure_entity).insert((
+            WarpSpoolUp {
+                destination,
+                remaining: SPOOL_UP_SECONDS,
+            },
+            PreloadAround(destination),
+        ));
+    }
+}
+
+/// Anything within the blocks of a structure is carried along with it when it warps
+fn is_inside(
+    structure: &Structure,
+    structure_location: &Location,
+    structure_transform: &GlobalTransform,
+    location: &Location,
+) -> bool {
+    let relative = structure_transform
+        .to_scale_rotation_translation()
+        .1
+        .inverse()
+        * structure_location.relative_coords_to(location);
+
+    structure
+        .relative_coords_to_local_coords_checked(relative.x, relative.y, relative.z)
+        .is_ok()
+}
+
+fn spool_up_warps(
+    mut ships: Query<(
+        Entity,
+        &mut WarpSpoolUp,
+        &Systems,
+        &Structure,
+        &GlobalTransform,
+        Option<&Pilot>,
+    )>,
+    mut warp_drives: Query<&mut WarpDriveSystem>,
+    mut movable: ParamSet<(
+        Query<&Location, Without<Parent>>,
+        Query<
+            (
+                Entity,
+                &mut Location,
+                Option<&mut Velocity>,
+                Option<&Transform>,
+                Option<&Player>,
+            ),
+            (Without<Parent>, Without<PlayerWorld>, Without<Planet>),
+        >,
+        Query<(&Location, &WorldWithin, &PhysicsWorld), (With<Player>, Without<Parent>)>,
+        Query<(Entity, &Location, &Structure)>,
+    )>,
+    server_seed: Res<ServerSeed>,
+    mut commands: Commands,
+    mut server: ResMut<RenetServer>,
+    mut rapier_context: ResMut<RapierContext>,
+    time: Res<Time>,
+) {
+    for (ship_entity, mut spool_up, systems, structure, global_transform, pilot) in ships.iter_mut()
+    {
+        spool_up.remaining -= time.delta_seconds();
+
+        if spool_up.remaining > 0.0 {
+            continue;
+        }
+
+        commands
+            .entity(ship_entity)
+            .remove::<WarpSpoolUp>()
+            .remove::<PreloadAround>();
+
+        let Some(pilot) = pilot else {
+            println!("Warp cancelled - the ship has no pilot");
+            continue;
+        };
+
+        let Ok(ship_location) = movable.p0().get(ship_entity).copied() else {
+            continue;
+        };
+
+        // Whatever is around the destination has been loaded while spooling up, so it's checked again now
+        let obstacles = warp_obstacles(
+            &spool_up.destination,
+            ship_entity,
+            movable.p3().iter(),
+            &server_seed,
+        );
+
+        let Some(destination) = clear_destination(
+            spool_up.destination,
+            structure_radius(structure),
+            &obstacles,
+        ) else {
+            println!(
+                "Warp cancelled - there is no room around {}",
+                spool_up.destination
+            );
+            continue;
+        };
+
+        let Ok(mut warp_drive) = systems.query_mut(&mut warp_drives) else {
+            continue;
+        };
+
+        // Blocks could have been broken while spooling up
+        if !warp_drive.can_warp(&ship_location, &destination) {
+            println!("Warp cancelled - the warp drive no longer has enough charge");
+            continue;
+        }
+
+        warp_drive.discharge_for_warp(&ship_location, &destination);
+
+        println!("Warping ship from {ship_location} to {destination}");
+
+        // The pilot is moved along with the ship since it's a child of it, so only its world has to be changed
+        let (world_within, physics_world) = assign_player_world(
+            &movable.p2(),
+            pilot.entity,
+            &destination,
+            &mut commands,
+            &mut rapier_context,
+        );
+
+        for (entity, mut location, velocity, transform, player) in movable.p1().iter_mut() {
+            if entity != ship_entity
+                && !is_inside(structure, &ship_location, global_transform, &location)
+            {
+                continue;
+            }
+
+            // Done relative to the ship so everything keeps its exact place inside of it
+            let warped = destination + ship_location.relative_coords_to(&location);
+
+            location.set_from(&warped);
+
+            commands.entity(entity).insert((
+                world_within,
+                PhysicsWorld {
+                    world_id: physics_world.world_id,
+                },
+            ));
+
+            if let (Some(player), Some(transform)) = (player, transform) {
+                if let Some(mut velocity) = velocity {
+                    *velocity = Velocity::zero();
+                }
+
+                // Players control where they are, so they have to be told they were moved
+                send_body_correction(
+                    &mut server,
+                    &mut commands,
+                    player.id(),
+                    entity,
+                    warped,
+                    transform.rotation,
+                    time.elapsed_seconds(),
+                );
+            }
+        }
+    }
+}
+
+fn send_warp_drive_status(
+    ships: Query<(
+        Entity,
+        &Pilot,
+        &Systems,
+        &Location,
+        &GlobalTransform,
+        Option<&WarpTarget>,
+        Option<&WarpSpoolUp>,
+    )>,
+    warp_drives: Query<&WarpDriveSystem>,
+    players: Query<&Player>,
+    mut server: ResMut<RenetServer>,
+) {
+    for (structure_entity, pilot, systems, location, transform, target, spool_up) in ships.iter() {
+        let Ok(player) = players.get(pilot.entity) else {
+            continue;
+        };
+
+        let Ok(warp_drive) = systems.query(&warp_drives) else {
+            continue;
+        };
+
+        if warp_drive.get_charge_capacity() <= 0.0 {
+            continue;
+        }
+
+        let destination = spool_up
+            .map(|spool_up| spool_up.destination)
+            .unwrap_or_else(|| warp_destination(location, transform, target));
+
+        server.send_message(
+            player.id(),
+            NettyChannel::WarpDrive.id(),
+            cosmos_encoder::serialize(&ServerWarpDriveMessages::WarpDriveStatus {
+                structure_entity,
+                charge: warp_drive.get_charge(),
+                charge_capacity: warp_drive.get_charge_capacity(),
+                charge_needed: WarpDriveSystem::charge_needed(location, &destination),
+                destination,
+                spool_remaining: spool_up.map(|spool_up| spool_up.remaining),
+            }),
+        );
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems(
+        (
+            listen_for_warp_messages,
+            charge_warp_drives,
+            start_warps,
+            spool_up_warps,
+            send_warp_drive_status.run_if(on_timer(Duration::from_millis(250))),
+        )
+            .chain()
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
@@ -21,8 +21,13 @@ use cosmos_core::{
 use rand::Rng;
 
 use crate::{
-    init::init_world::ServerSeed, persistence::is_sector_loaded, rng::get_rng_for_sector,
-    state::GameState, structure::asteroid::server_asteroid_builder::ServerAsteroidBuilder,
+    init::init_world::ServerSeed,
+    persistence::{is_entity_saved, EntityId, SectorsCache},
+    rng::get_rng_for_sector,
+    state::GameState,
+    structure::asteroid::{
+        persistence::asteroid_entity_id, server_asteroid_builder::ServerAsteroidBuilder,
+    },
 };
 
 use super::planet_spawner::is_planet_in_sector;
@@ -31,9 +36,10 @@ use super::planet_spawner::is_planet_in_sector;
 struct CachedSectors(HashSet<Sector>);
 
 fn spawn_asteroid(
-    query: Query<&Location, With<Asteroid>>,
+    query: Query<&EntityId, With<Asteroid>>,
     players: Query<&Location, With<Player>>,
     server_seed: Res<ServerSeed>,
+    sectors_cache: Res<SectorsCache>,
     mut cache: ResMut<CachedSectors>,
     mut commands: Commands,
 ) {
@@ -73,17 +79,10 @@ fn spawn_asteroid(
         }
     }
 
-    for loc in query.iter() {
-        let sector = loc.sector();
-        cache.insert(sector);
-        sectors.remove(&sector);
-    }
-
     for sector in sectors {
         cache.insert(sector);
 
-        if is_sector_loaded(sector) || is_planet_in_sector(&sector, &server_seed) {
-            // This sector has already been loaded, don't regenerate stuff
+        if is_planet_in_sector(&sector, &server_seed) {
             continue;
         }
 
@@ -96,7 +95,7 @@ fn spawn_asteroid(
             let multiplier = SECTOR_DIMENSIONS - 600.0;
             let adder = 300.0 + SECTOR_DIMENSIONS / 2.0;
 
-            for _ in 0..n_asteroids {
+            for index in 0..n_asteroids {
                 let size = rng.gen_range(2..=5);
 
                 let loc = Location::new(
@@ -108,13 +107,28 @@ fn spawn_asteroid(
                     sector,
                 );
 
+                let entity_id = asteroid_entity_id(sector, index);
+
+                // Asteroids that are already around or were changed & saved shouldn't be generated again.
+                // All the random numbers for this asteroid have already been used, so the ones after it are unaffected.
+                if query.iter().any(|x| x == &entity_id)
+                    || is_entity_saved(
+                        &sectors_cache,
+                        sector,
+                        &entity_id,
+                        Some(ASTEROID_LOAD_RADIUS),
+                    )
+                {
+                    continue;
+                }
+
                 let mut structure = Structure::new(size, size, size);
                 let builder = ServerAsteroidBuilder::default();
                 let mut entity_cmd = commands.spawn_empty();
 
                 builder.insert_asteroid(&mut entity_cmd, loc, &mut structure);
 
-                entity_cmd.insert((structure, AsteroidNeedsCreated));
+                entity_cmd.insert((structure, entity_id, AsteroidNeedsCreated));
             }
         }
     }
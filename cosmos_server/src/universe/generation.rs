@@ -92,6 +92,21 @@ pub fn get_star_in_system(system: &UniverseSystem, seed: &ServerSeed) -> Option<
     }
 }
 
+/// Gets where the star in this system is - or would be, if the system doesn't have one.
+pub fn star_location_in_system(system: &UniverseSystem) -> Location {
+    /// 0.5 is the center of system
+    const STAR_POS_OFFSET: f32 = 0.5;
+
+    Location::new(
+        Vec3::ZERO,
+        Sector::new(
+            ((system.x() as f32 + STAR_POS_OFFSET) * SYSTEM_SECTORS as f32) as SystemUnit,
+            ((system.y() as f32 + STAR_POS_OFFSET) * SYSTEM_SECTORS as f32) as SystemUnit,
+            ((system.z() as f32 + STAR_POS_OFFSET) * SYSTEM_SECTORS as f32) as SystemUnit,
+        ),
+    )
+}
+
 fn load_stars_near_players(
     players: Query<&Location, With<Player>>,
     seed: Res<ServerSeed>,
@@ -108,25 +123,12 @@ fn load_stars_near_players(
                 }
             }
 
-            /// 0.5 is the center of system
-            const STAR_POS_OFFSET: f32 = 0.5;
-
             commands.spawn((
                 star,
                 PbrBundle {
                     ..Default::default()
                 },
-                Location::new(
-                    Vec3::ZERO,
-                    Sector::new(
-                        ((system.x() as f32 + STAR_POS_OFFSET) * SYSTEM_SECTORS as f32)
-                            as SystemUnit,
-                        ((system.y() as f32 + STAR_POS_OFFSET) * SYSTEM_SECTORS as f32)
-                            as SystemUnit,
-                        ((system.z() as f32 + STAR_POS_OFFSET) * SYSTEM_SECTORS as f32)
-                            as SystemUnit,
-                    ),
-                ),
+                star_location_in_system(&system),
                 Velocity::zero(),
                 LoadingDistance::new(SYSTEM_SECTORS / 2 + 1, SYSTEM_SECTORS / 2 + 1),
             ));
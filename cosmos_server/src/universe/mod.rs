@@ -4,12 +4,22 @@ use bevy::prelude::App;
 
 pub mod asteroid_spawner;
 pub mod generation;
+pub mod orbit;
+pub mod planet_rotation;
 pub mod planet_spawner;
 pub mod star;
+pub mod star_catalog;
+pub mod system_map;
+pub mod world_time;
 
 pub(super) fn register(app: &mut App) {
     star::register(app);
     generation::register(app);
     planet_spawner::register(app);
     asteroid_spawner::register(app);
+    star_catalog::register(app);
+    system_map::register(app);
+    world_time::register(app);
+    orbit::register(app);
+    planet_rotation::register(app);
 }
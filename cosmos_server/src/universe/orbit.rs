@@ -0,0 +1,330 @@
+//! Moves planets around their star along Keplerian orbits
+//!
+//! Each planet's orbit is made when it first appears, and always passes through the spot it was spawned at.
+//! The shape of the orbit (how stretched & tilted it is) comes from the [`ServerSeed`], and where
+//! the planet is along it only depends on the [`WorldTime`], so the same world always has its planets
+//! in the same places.
+//!
+//! Anything near a planet is moved along with it by [`move_with_planets`], so players & ships on a
+//! planet aren't left behind.
+
+use std::f64::consts::TAU;
+
+use bevy::{
+    math::{DQuat, DVec3},
+    prelude::{
+        App, Commands, Component, Entity, IntoSystemConfig, IntoSystemConfigs, OnUpdate, Query,
+        Res, With, Without,
+    },
+};
+use bevy_rapier3d::prelude::RigidBody;
+use cosmos_core::{
+    physics::location::{Location, Sector, SectorUnit, SECTOR_DIMENSIONS},
+    structure::planet::{planet_movement::move_with_planets, Planet},
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    init::init_world::ServerSeed,
+    persistence::{
+        loading::{begin_loading, done_loading, NeedsLoaded},
+        saving::{begin_saving, done_saving, NeedsSaved},
+        SerializedData,
+    },
+    rng::get_rng_for_sector,
+    state::GameState,
+};
+
+use super::{
+    generation::{get_star_in_system, star_location_in_system},
+    world_time::WorldTime,
+};
+
+/// How strongly stars pull on their planets (G * M of the star).
+///
+/// This is far weaker than a real star so that planets move slowly enough for ships to catch up to them.
+/// A planet 1,000 km from its star moves at 10 m/s, and goes around it about once a week.
+const STAR_GRAVITATIONAL_PARAMETER: f64 = 1.0e8;
+
+/// Planets closer than this to their star would move too quickly, so they don't orbit
+const MIN_ORBIT_RADIUS: f64 = 2.0 * SECTOR_DIMENSIONS as f64;
+
+/// How stretched out orbits can be. 0.0 is a circle.
+const MAX_ECCENTRICITY: f64 = 0.2;
+
+/// How far (in radians) orbits can be tilted away from the plane of the galaxy
+const MAX_INCLINATION: f64 = 0.15;
+
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+/// The path a planet follows around its star
+pub struct Orbit {
+    /// The sector the planet was spawned in
+    home_sector: Sector,
+    /// The location of the star being orbited
+    star: Location,
+    /// Points from the star to where the planet is closest to it
+    periapsis_direction: DVec3,
+    /// Which way the planet moves when it is closest to the star
+    motion_direction: DVec3,
+    semi_major_axis: f64,
+    eccentricity: f64,
+    /// Where the planet was along its orbit at `epoch`, as a mean anomaly
+    mean_anomaly_at_epoch: f64,
+    /// The world time this orbit was made at
+    epoch: f64,
+}
+
+impl Orbit {
+    /// Creates an orbit around the star at `star` that passes through `location` at `world_time`.
+    ///
+    /// Returns None if the location is too close to the star to orbit it.
+    pub fn new(
+        location: &Location,
+        star: &Location,
+        server_seed: &ServerSeed,
+        world_time: f64,
+    ) -> Option<Self> {
+        let (from, to) = (star.absolute_coords_f64(), location.absolute_coords_f64());
+        let radial = DVec3::new(to.x - from.x, to.y - from.y, to.z - from.z);
+
+        let distance = radial.length();
+
+        if distance < MIN_ORBIT_RADIUS {
+            return None;
+        }
+
+        let radial = radial / distance;
+
+        let mut rng = get_rng_for_sector(server_seed, &location.sector());
+        // So these numbers aren't the same ones the sector's ores (stream 0) & the planet's rotation (stream 1) were made from
+        rng.set_stream(2);
+
+        // Orbits roughly follow the plane of the galaxy, so start with the galaxy's up & tilt it a bit
+        let flat_normal = (DVec3::Y - radial * radial.dot(DVec3::Y))
+            .try_normalize()
+            .unwrap_or(DVec3::X);
+        let normal =
+            DQuat::from_axis_angle(radial, rng.gen_range(-MAX_INCLINATION..=MAX_INCLINATION))
+                * flat_normal;
+
+        let eccentricity = rng.gen_range(0.0..MAX_ECCENTRICITY);
+        // How far around the orbit from its closest point to the star the planet currently is
+        let true_anomaly = rng.gen_range(0.0..TAU);
+
+        let semi_major_axis = distance * (1.0 + eccentricity * true_anomaly.cos())
+            / (1.0 - eccentricity * eccentricity);
+
+        let periapsis_direction = DQuat::from_axis_angle(normal, -true_anomaly) * radial;
+        let motion_direction = normal.cross(periapsis_direction);
+
+        let eccentric_anomaly = 2.0
+            * ((1.0 - eccentricity).sqrt() * (true_anomaly / 2.0).sin())
+                .atan2((1.0 + eccentricity).sqrt() * (true_anomaly / 2.0).cos());
+
+        Some(Self {
+            home_sector: location.sector(),
+            star: *star,
+            periapsis_direction,
+            motion_direction,
+            semi_major_axis,
+            eccentricity,
+            mean_anomaly_at_epoch: eccentric_anomaly - eccentricity * eccentric_anomaly.sin(),
+            epoch: world_time,
+        })
+    }
+
+    /// The sector the planet was spawned in, even if it has since moved out of it
+    pub fn home_sector(&self) -> Sector {
+        self.home_sector
+    }
+
+    /// How many seconds it takes to go around the star once
+    pub fn period(&self) -> f64 {
+        TAU * (self.semi_major_axis.powi(3) / STAR_GRAVITATIONAL_PARAMETER).sqrt()
+    }
+
+    /// Solves Kepler's equation for where the planet is along its orbit at this world time
+    fn eccentric_anomaly_at(&self, world_time: f64) -> f64 {
+        let mean_anomaly = (self.mean_anomaly_at_epoch
+            + TAU * (world_time - self.epoch) / self.period())
+        .rem_euclid(TAU);
+
+        let e = self.eccentricity;
+        let mut eccentric_anomaly = mean_anomaly;
+
+        // Newton's method converges very quickly for such round orbits
+        for _ in 0..8 {
+            eccentric_anomaly -= (eccentric_anomaly - e * eccentric_anomaly.sin() - mean_anomaly)
+                / (1.0 - e * eccentric_anomaly.cos());
+        }
+
+        eccentric_anomaly
+    }
+
+    /// Where the planet is relative to its star at this world time
+    pub fn offset_at(&self, world_time: f64) -> DVec3 {
+        let eccentric_anomaly = self.eccentric_anomaly_at(world_time);
+
+        let semi_minor_axis =
+            self.semi_major_axis * (1.0 - self.eccentricity * self.eccentricity).sqrt();
+
+        self.periapsis_direction
+            * (self.semi_major_axis * (eccentric_anomaly.cos() - self.eccentricity))
+            + self.motion_direction * (semi_minor_axis * eccentric_anomaly.sin())
+    }
+
+    /// Where the planet is at this world time
+    pub fn location_at(&self, world_time: f64) -> Location {
+        offset_location(&self.star, self.offset_at(world_time))
+    }
+}
+
+/// Moves a location by an offset that may be too large for an f32 to hold precisely
+fn offset_location(origin: &Location, offset: DVec3) -> Location {
+    let sector_dimensions = SECTOR_DIMENSIONS as f64;
+
+    let local = origin.local.as_dvec3() + offset;
+    let sectors = (local / sector_dimensions).round();
+
+    let mut location = Location::new(
+        (local - sectors * sector_dimensions).as_vec3(),
+        origin.sector()
+            + Sector::new(
+                sectors.x as SectorUnit,
+                sectors.y as SectorUnit,
+                sectors.z as SectorUnit,
+            ),
+    );
+
+    location.fix_bounds();
+
+    location
+}
+
+#[derive(Component, Debug)]
+/// This planet has no star to orbit, so it stays still
+struct Stationary;
+
+fn add_orbits(
+    query: Query<
+        (Entity, &Location),
+        (
+            With<Planet>,
+            Without<Orbit>,
+            Without<Stationary>,
+            Without<NeedsLoaded>,
+        ),
+    >,
+    server_seed: Res<ServerSeed>,
+    world_time: Res<WorldTime>,
+    mut commands: Commands,
+) {
+    for (entity, location) in query.iter() {
+        let system = location.get_system_coordinates();
+
+        let orbit = get_star_in_system(&system, &server_seed).and_then(|_| {
+            Orbit::new(
+                location,
+                &star_location_in_system(&system),
+                &server_seed,
+                world_time.as_secs_f64(),
+            )
+        });
+
+        if let Some(orbit) = orbit {
+            commands.entity(entity).insert(orbit);
+        } else {
+            commands.entity(entity).insert(Stationary);
+        }
+    }
+}
+
+fn move_planets_along_orbits(
+    mut query: Query<(&Orbit, &mut Location), With<Planet>>,
+    world_time: Res<WorldTime>,
+) {
+    for (orbit, mut location) in query.iter_mut() {
+        // `set_from` keeps the old `last_transform_loc`, so the transform is moved to match next time they're synced
+        location.set_from(&orbit.location_at(world_time.as_secs_f64()));
+    }
+}
+
+fn on_save_orbit(mut query: Query<(&mut SerializedData, &Orbit), With<NeedsSaved>>) {
+    for (mut s_data, orbit) in query.iter_mut() {
+        s_data.serialize_data("cosmos:orbit", orbit);
+    }
+}
+
+fn on_load_orbit(
+    query: Query<(Entity, &SerializedData), With<NeedsLoaded>>,
+    mut commands: Commands,
+) {
+    for (entity, s_data) in query.iter() {
+        if let Some(orbit) = s_data.deserialize_data::<Orbit>("cosmos:orbit") {
+            commands.entity(entity).insert(orbit);
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems(
+        (
+            add_orbits,
+            move_planets_along_orbits,
+            move_with_planets::<With<RigidBody>>,
+        )
+            .chain()
+            .in_set(OnUpdate(GameState::Playing)),
+    )
+    .add_system(on_save_orbit.after(begin_saving).before(done_saving))
+    .add_system(on_load_orbit.after(begin_loading).before(done_loading));
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Vec3;
+
+    use super::*;
+
+    fn test_orbit(location: &Location, epoch: f64) -> Orbit {
+        let star = Location::new(Vec3::ZERO, Sector::new(0, 0, 0));
+
+        Orbit::new(location, &star, &ServerSeed::new(1234), epoch).expect("Far enough to orbit")
+    }
+
+    #[test]
+    fn test_location_at_epoch_is_spawn_location() {
+        let spawn = Location::new(Vec3::new(1_500.0, -300.0, 4_200.0), Sector::new(12, 1, -7));
+        let epoch = 98_765.4;
+
+        let orbit = test_orbit(&spawn, epoch);
+
+        assert!(orbit.location_at(epoch).distance_sqrd(&spawn) < 0.01);
+        // It should also come back to the same spot after going around once
+        assert!(
+            orbit
+                .location_at(epoch + orbit.period())
+                .distance_sqrd(&spawn)
+                < 0.01
+        );
+    }
+
+    #[test]
+    fn test_period_follows_keplers_third_law() {
+        let orbit = test_orbit(&Location::new(Vec3::ZERO, Sector::new(20, 0, 5)), 0.0);
+
+        // The planet is closest to its star when its mean anomaly is 0, & furthest half an orbit later
+        let periapsis_time = -orbit.mean_anomaly_at_epoch / TAU * orbit.period();
+        let closest = orbit.offset_at(periapsis_time).length();
+        let furthest = orbit
+            .offset_at(periapsis_time + orbit.period() / 2.0)
+            .length();
+
+        // T^2 = 4 * pi^2 * a^3 / (G * M), where a is half the distance between those two points
+        let semi_major_axis = (closest + furthest) / 2.0;
+        let expected = TAU * TAU * semi_major_axis.powi(3) / STAR_GRAVITATIONAL_PARAMETER;
+
+        assert!((orbit.period().powi(2) - expected).abs() / expected < 1.0e-6);
+    }
+}
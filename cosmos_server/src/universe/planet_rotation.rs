@@ -0,0 +1,137 @@
+//! Spins planets around their axis, giving them days & nights
+//!
+//! Like orbits, the axis & length of each planet's day come from the [`ServerSeed`], and how far the planet has
+//! turned only depends on the [`WorldTime`]. The rotation is sent to clients along with the rest of the planet's body.
+
+use std::f64::consts::TAU;
+
+use bevy::prelude::{
+    App, Commands, Component, Entity, IntoSystemConfig, IntoSystemConfigs, OnUpdate, Quat, Query,
+    Res, Transform, Vec3, With, Without,
+};
+use bevy_rapier3d::prelude::RigidBody;
+use cosmos_core::{
+    physics::location::Location,
+    structure::planet::{planet_movement::move_with_planets, Planet},
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    init::init_world::ServerSeed,
+    persistence::{
+        loading::{begin_loading, done_loading, NeedsLoaded},
+        saving::{begin_saving, done_saving, NeedsSaved},
+        SerializedData,
+    },
+    rng::get_rng_for_sector,
+    state::GameState,
+};
+
+use super::world_time::WorldTime;
+
+/// The shortest a day can be, in seconds
+const MIN_DAY_LENGTH: f64 = 20.0 * 60.0;
+/// The longest a day can be, in seconds
+const MAX_DAY_LENGTH: f64 = 40.0 * 60.0;
+
+/// How far (in radians) a planet's axis can be tilted away from straight up
+const MAX_AXIAL_TILT: f32 = 0.4;
+
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+/// How a planet spins
+pub struct PlanetRotation {
+    axis: Vec3,
+    /// How many seconds it takes to spin around once
+    day_length: f64,
+    /// How far through its day the planet was at a world time of 0, from 0.0 to 1.0
+    phase: f64,
+}
+
+impl PlanetRotation {
+    /// Randomly picks how a planet spawned in this location spins
+    pub fn new(location: &Location, server_seed: &ServerSeed) -> Self {
+        let mut rng = get_rng_for_sector(server_seed, &location.sector());
+        // So these numbers aren't the same ones the sector's ores (stream 0) & the planet's orbit (stream 2) were made from
+        rng.set_stream(1);
+
+        let tilt = Quat::from_axis_angle(Vec3::X, rng.gen_range(-MAX_AXIAL_TILT..=MAX_AXIAL_TILT))
+            * Quat::from_axis_angle(Vec3::Z, rng.gen_range(-MAX_AXIAL_TILT..=MAX_AXIAL_TILT));
+
+        Self {
+            axis: (tilt * Vec3::Y).normalize(),
+            day_length: rng.gen_range(MIN_DAY_LENGTH..=MAX_DAY_LENGTH),
+            phase: rng.gen(),
+        }
+    }
+
+    /// How the planet is rotated at this world time
+    pub fn rotation_at(&self, world_time: f64) -> Quat {
+        let turns = (world_time / self.day_length + self.phase).fract();
+
+        Quat::from_axis_angle(self.axis, (turns * TAU) as f32)
+    }
+}
+
+fn add_planet_rotations(
+    query: Query<
+        (Entity, &Location),
+        (With<Planet>, Without<PlanetRotation>, Without<NeedsLoaded>),
+    >,
+    server_seed: Res<ServerSeed>,
+    mut commands: Commands,
+) {
+    for (entity, location) in query.iter() {
+        commands
+            .entity(entity)
+            .insert(PlanetRotation::new(location, &server_seed));
+    }
+}
+
+fn rotate_planets(
+    mut query: Query<(&PlanetRotation, &mut Transform), With<Planet>>,
+    world_time: Res<WorldTime>,
+) {
+    for (rotation, mut transform) in query.iter_mut() {
+        transform.rotation = rotation.rotation_at(world_time.as_secs_f64());
+    }
+}
+
+fn on_save_planet_rotation(
+    mut query: Query<(&mut SerializedData, &PlanetRotation), With<NeedsSaved>>,
+) {
+    for (mut s_data, rotation) in query.iter_mut() {
+        s_data.serialize_data("cosmos:planet_rotation", rotation);
+    }
+}
+
+fn on_load_planet_rotation(
+    query: Query<(Entity, &SerializedData), With<NeedsLoaded>>,
+    mut commands: Commands,
+) {
+    for (entity, s_data) in query.iter() {
+        if let Some(rotation) = s_data.deserialize_data::<PlanetRotation>("cosmos:planet_rotation")
+        {
+            commands.entity(entity).insert(rotation);
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems(
+        (add_planet_rotations, rotate_planets)
+            .chain()
+            .before(move_with_planets::<With<RigidBody>>)
+            .in_set(OnUpdate(GameState::Playing)),
+    )
+    .add_system(
+        on_save_planet_rotation
+            .after(begin_saving)
+            .before(done_saving),
+    )
+    .add_system(
+        on_load_planet_rotation
+            .after(begin_loading)
+            .before(done_loading),
+    );
+}
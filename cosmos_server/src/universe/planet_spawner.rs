@@ -24,16 +24,28 @@ use futures_lite::future;
 use rand::Rng;
 
 use crate::{
-    init::init_world::ServerSeed, persistence::is_sector_loaded, rng::get_rng_for_sector,
-    state::GameState, structure::planet::server_planet_builder::ServerPlanetBuilder,
+    init::init_world::ServerSeed,
+    persistence::{is_sector_loaded, mark_sector_loaded},
+    rng::get_rng_for_sector,
+    state::GameState,
+    structure::planet::{
+        generation::generation_origin::GenerationOrigin, server_planet_builder::ServerPlanetBuilder,
+    },
 };
 
+use super::orbit::Orbit;
+
 #[derive(Debug, Default, Resource, Deref, DerefMut, Clone)]
 struct CachedSectors(HashSet<Sector>);
 
 const BACKGROUND_TEMPERATURE: f32 = 50.0;
 const TEMPERATURE_CONSTANT: f32 = 5.3e9;
 
+/// How hot something this far away (squared) from this star is
+pub fn temperature_from_star(star: &Star, distance_sqrd: f32) -> f32 {
+    (TEMPERATURE_CONSTANT * (star.temperature() / distance_sqrd)).max(BACKGROUND_TEMPERATURE)
+}
+
 #[derive(Component, Debug)]
 struct PlanetSpawnerAsyncTask(Task<(CachedSectors, Vec<PlanetToSpawn>)>);
 
@@ -59,6 +71,9 @@ fn monitor_planets_to_spawn(
         for planet in planets {
             let (size, loc, temperature) = (planet.size, planet.location, planet.temperature);
 
+            // The planet will orbit out of this sector, so nothing may end up saved here to show it was generated
+            mark_sector_loaded(loc.sector());
+
             let mut entity_cmd = commands.spawn_empty();
 
             let mut structure = Structure::new(size, size, size);
@@ -67,7 +82,7 @@ fn monitor_planets_to_spawn(
 
             builder.insert_planet(&mut entity_cmd, &mut structure, Planet::new(temperature));
 
-            entity_cmd.insert((structure, loc));
+            entity_cmd.insert((structure, loc, GenerationOrigin::new(loc)));
         }
 
         *sectors_cache = cache;
@@ -75,7 +90,7 @@ fn monitor_planets_to_spawn(
 }
 
 fn spawn_planet(
-    query: Query<&Location, With<Planet>>,
+    query: Query<(&Location, Option<&Orbit>), With<Planet>>,
     players: Query<&Location, With<Player>>,
     server_seed: Res<ServerSeed>,
     mut commands: Commands,
@@ -94,8 +109,9 @@ fn spawn_planet(
 
     let mut cache = cache.clone();
 
-    query.iter().for_each(|l| {
-        cache.insert(l.sector());
+    query.iter().for_each(|(l, orbit)| {
+        // Planets move, so use the sector they came from rather than the one they're in now
+        cache.insert(orbit.map(|orbit| orbit.home_sector()).unwrap_or(l.sector()));
     });
 
     let server_seed = *server_seed;
@@ -159,9 +175,7 @@ fn spawn_planet(
                         rng.gen_range(200..=500)
                     };
 
-                    let temperature = (TEMPERATURE_CONSTANT
-                        * (star.temperature() / best_dist.unwrap()))
-                    .max(BACKGROUND_TEMPERATURE);
+                    let temperature = temperature_from_star(star, best_dist.unwrap());
 
                     made_stars.push(PlanetToSpawn {
                         size,
@@ -0,0 +1,73 @@
+//! Sends each player the stars around them, so their sky matches the actual universe
+
+use bevy::{
+    prelude::{in_state, App, IntoSystemConfig, Local, Query, Res, ResMut},
+    utils::HashMap,
+};
+use bevy_renet::renet::RenetServer;
+use cosmos_core::{
+    entities::player::Player,
+    netty::{cosmos_encoder, NettyChannel},
+    physics::location::{Location, UniverseSystem},
+    universe::{
+        star_catalog::{CatalogStar, STAR_CATALOG_RADIUS},
+        universe_netty::UniverseServerMessages,
+    },
+};
+
+use crate::{init::init_world::ServerSeed, state::GameState};
+
+use super::generation::get_star_in_system;
+
+/// Finds every star within [`STAR_CATALOG_RADIUS`] systems of the center system
+pub fn stars_around(center: UniverseSystem, seed: &ServerSeed) -> Vec<CatalogStar> {
+    let mut stars = Vec::new();
+
+    for dz in -STAR_CATALOG_RADIUS..=STAR_CATALOG_RADIUS {
+        for dy in -STAR_CATALOG_RADIUS..=STAR_CATALOG_RADIUS {
+            for dx in -STAR_CATALOG_RADIUS..=STAR_CATALOG_RADIUS {
+                let system = UniverseSystem::new(center.x() + dx, center.y() + dy, center.z() + dz);
+
+                if let Some(star) = get_star_in_system(&system, seed) {
+                    stars.push(CatalogStar::new(system, star));
+                }
+            }
+        }
+    }
+
+    stars
+}
+
+/// Sends a new catalog to every player who moved into a different system
+fn send_star_catalogs(
+    players: Query<(&Player, &Location)>,
+    seed: Res<ServerSeed>,
+    mut sent_systems: Local<HashMap<u64, UniverseSystem>>,
+    mut server: ResMut<RenetServer>,
+) {
+    // Players that left should get a new catalog if they come back
+    sent_systems.retain(|id, _| players.iter().any(|(player, _)| player.id() == *id));
+
+    for (player, location) in players.iter() {
+        let system = location.get_system_coordinates();
+
+        if sent_systems.get(&player.id()) == Some(&system) {
+            continue;
+        }
+
+        sent_systems.insert(player.id(), system);
+
+        server.send_message(
+            player.id(),
+            NettyChannel::Universe.id(),
+            cosmos_encoder::serialize(&UniverseServerMessages::StarCatalog {
+                center: system,
+                stars: stars_around(system, &seed),
+            }),
+        );
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_system(send_star_catalogs.run_if(in_state(GameState::Playing)));
+}
@@ -0,0 +1,114 @@
+//! Sends each player a summary of their system, so they can see it on their map
+//!
+//! Planets & asteroids are always sent, since they can be found anyway. Ships & other players are only
+//! sent if the player could already see them (they are relevant to that player's client), or if it's the ship
+//! the player is in, so the map can't be used to track down other players from across the system.
+//! The star is sent separately, as part of the star catalog.
+
+use std::time::Duration;
+
+use bevy::{
+    prelude::{in_state, App, Entity, IntoSystemConfig, Parent, Query, Res, ResMut},
+    time::common_conditions::on_timer,
+};
+use bevy_renet::renet::RenetServer;
+use cosmos_core::{
+    entities::player::Player,
+    netty::{cosmos_encoder, NettyChannel},
+    physics::location::Location,
+    structure::{
+        asteroid::Asteroid,
+        planet::Planet,
+        ship::{pilot::Pilot, Ship},
+        Structure,
+    },
+    universe::{
+        system_map::{SystemBody, SystemBodyKind, MAX_SYSTEM_MAP_BODIES},
+        universe_netty::UniverseServerMessages,
+    },
+};
+
+use crate::{netty::sync::interest::ClientInterest, state::GameState};
+
+fn send_system_maps(
+    players: Query<(Entity, &Player, &Location, Option<&Pilot>, Option<&Parent>)>,
+    structures: Query<(
+        Entity,
+        &Location,
+        &Structure,
+        Option<&Planet>,
+        Option<&Asteroid>,
+        Option<&Ship>,
+    )>,
+    interest: Res<ClientInterest>,
+    mut server: ResMut<RenetServer>,
+) {
+    for (player_entity, player, player_location, pilot, parent) in players.iter() {
+        let system = player_location.get_system_coordinates();
+
+        let in_ship = |entity: Entity| {
+            pilot.map(|pilot| pilot.entity) == Some(entity)
+                || parent.map(|parent| parent.get()) == Some(entity)
+        };
+
+        let mut bodies = structures
+            .iter()
+            .filter(|(_, location, ..)| location.get_system_coordinates() == system)
+            .filter_map(|(entity, location, structure, planet, asteroid, ship)| {
+                let kind = if planet.is_some() {
+                    SystemBodyKind::Planet
+                } else if asteroid.is_some() {
+                    SystemBodyKind::Asteroid
+                } else if ship.is_some()
+                    && (in_ship(entity) || interest.is_relevant(player.id(), entity))
+                {
+                    SystemBodyKind::Ship
+                } else {
+                    return None;
+                };
+
+                let radius = structure.blocks_width() as f32 / 2.0;
+
+                Some(SystemBody::new(kind, *location, radius, None))
+            })
+            .chain(
+                players
+                    .iter()
+                    .filter(|(entity, ..)| {
+                        *entity == player_entity || interest.is_relevant(player.id(), *entity)
+                    })
+                    .filter(|(_, _, location, ..)| location.get_system_coordinates() == system)
+                    .map(|(_, other, location, ..)| {
+                        SystemBody::new(
+                            SystemBodyKind::Player,
+                            *location,
+                            1.0,
+                            Some(other.name().clone()),
+                        )
+                    }),
+            )
+            .collect::<Vec<SystemBody>>();
+
+        // Too many bodies won't fit in one message, so only the closest ones are sent
+        bodies.sort_by(|a, b| {
+            player_location
+                .distance_sqrd(a.location())
+                .total_cmp(&player_location.distance_sqrd(b.location()))
+        });
+        bodies.truncate(MAX_SYSTEM_MAP_BODIES);
+
+        server.send_message(
+            player.id(),
+            NettyChannel::Universe.id(),
+            cosmos_encoder::serialize(&UniverseServerMessages::SystemMap { system, bodies }),
+        );
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_system(
+        send_system_maps
+            .run_if(in_state(GameState::Playing))
+            .run_if(on_timer(Duration::from_secs(2))),
+    );
+}
@@ -0,0 +1,72 @@
+//! Keeps track of how long the world has existed for, which things like planet orbits are based off of
+
+use std::{fs, time::Duration};
+
+use bevy::{
+    prelude::{
+        App, IntoSystemConfig, IntoSystemConfigs, OnUpdate, Query, Res, ResMut, Resource, With,
+    },
+    time::{common_conditions::on_timer, Time},
+};
+use cosmos_core::netty::cosmos_encoder;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    persistence::saving::{begin_saving, done_saving, NeedsSaved},
+    state::GameState,
+};
+
+const WORLD_TIME_PATH: &str = "./world/time.dat";
+
+#[derive(Debug, Resource, Default, Serialize, Deserialize, Clone, Copy)]
+/// How many seconds the world has been running for, counting every time the server has been run
+pub struct WorldTime(f64);
+
+impl WorldTime {
+    /// Gets how many seconds the world has been running for
+    pub fn as_secs_f64(&self) -> f64 {
+        self.0
+    }
+}
+
+fn advance_world_time(mut world_time: ResMut<WorldTime>, time: Res<Time>) {
+    world_time.0 += time.delta_seconds_f64();
+}
+
+fn save_world_time(world_time: Res<WorldTime>) {
+    if let Err(e) = fs::write(WORLD_TIME_PATH, cosmos_encoder::serialize(&*world_time)) {
+        println!("Error writing file '{WORLD_TIME_PATH}': {e}");
+    }
+}
+
+/// Saves the world time whenever entities are saved, so nothing on disk is ever from later than the saved time
+fn save_world_time_with_entities(
+    needs_saved: Query<(), With<NeedsSaved>>,
+    world_time: Res<WorldTime>,
+) {
+    if !needs_saved.is_empty() {
+        save_world_time(world_time);
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    let world_time = fs::read(WORLD_TIME_PATH)
+        .ok()
+        .and_then(|data| cosmos_encoder::deserialize::<WorldTime>(&data).ok())
+        .unwrap_or_default();
+
+    app.insert_resource(world_time)
+        .add_systems(
+            (
+                advance_world_time,
+                // Also saved regularly so time still moves forward on disk while nothing else is being saved
+                save_world_time.run_if(on_timer(Duration::from_secs(10))),
+            )
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_system(
+            save_world_time_with_entities
+                .after(begin_saving)
+                .before(done_saving),
+        );
+}